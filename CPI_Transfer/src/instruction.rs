@@ -0,0 +1,391 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+// Define the instructions this program can handle
+// The token instructions act on a token account or mint controlled by the
+// program's [b"authority"] PDA, which signs the CPI into the SPL Token program
+// The config instructions manage the [b"config"] PDA, and the vesting ones
+// vaults owned by per-beneficiary [b"vesting", beneficiary, mint] PDAs
+// The wrapped SOL ones manage the PDA's associated token account for the
+// native mint, so the PDA can hold SOL as a regular SPL token
+// Deposit and Withdraw use per-user [b"authority", user] PDAs instead, whose
+// vaults only their user can withdraw from
+pub enum TransferInstruction {
+    // Move `amount` tokens from the PDA's account to a destination account
+    Transfer {
+        amount: u64,   // Tokens to move (raw units)
+    },
+    // Move the PDA account's whole balance to a destination account
+    TransferAll,
+    // Let a delegate spend up to `amount` tokens from the PDA's account
+    // (admin only once the config exists)
+    Approve {
+        amount: u64,   // Delegated allowance (raw units)
+    },
+    // Remove the delegate of the PDA's account
+    Revoke,
+    // Destroy `amount` tokens held by the PDA's account (admin only once the
+    // config exists)
+    Burn {
+        amount: u64,   // Tokens to burn (raw units)
+    },
+    // Close the PDA's (empty) token account and send its rent to a destination
+    // (admin only once the config exists)
+    CloseAccount,
+    // Create a mint whose mint authority is the PDA
+    InitializeMint {
+        decimals: u8,  // Decimal places of the new mint
+    },
+    // Mint `amount` new tokens of a PDA-controlled mint into any token account
+    MintTo {
+        amount: u64,   // Tokens to mint (raw units)
+    },
+    // Move the PDA account's remaining balance out, then close it and send
+    // its rent to a recipient (admin only once the config exists)
+    CloseVault,
+    // Move tokens from the PDA's account to several destinations, one
+    // amount per destination account (passed in the same order)
+    BatchTransfer {
+        amounts: Vec<u64>, // Tokens for each destination (raw units)
+    },
+    // Create the config PDA; the signer becomes its admin
+    InitializeConfig {
+        fee_bps: u16,      // Fee skimmed by TransferWithFee, in basis points
+        treasury: Pubkey,  // Token account receiving the fee
+    },
+    // Admin changes the TransferWithFee fee
+    SetFee {
+        fee_bps: u16,      // New fee, in basis points
+    },
+    // Like Transfer, but the configured fee share of `amount` goes to the treasury
+    TransferWithFee {
+        amount: u64,       // Tokens taken from the PDA's account, fee included (raw units)
+    },
+    // Lock `total` tokens in a vault that vests linearly to `beneficiary`
+    CreateVesting {
+        beneficiary: Pubkey, // Wallet that can claim the tokens
+        total: u64,          // Tokens to vest (raw units)
+        start: i64,          // Unix timestamp when vesting starts
+        cliff: i64,          // Seconds after start before anything unlocks
+        period: i64,         // Seconds after start until everything is unlocked
+    },
+    // Beneficiary takes out everything vested so far
+    ClaimVested,
+    // Create the PDA's wrapped SOL (native mint) associated token account
+    CreateWrappedVault,
+    // Deposit `amount` lamports into the wrapped SOL vault and sync its balance
+    WrapSol {
+        amount: u64,       // Lamports to wrap
+    },
+    // Close the wrapped SOL vault, sending all its lamports (balance and
+    // rent) to a recipient as plain SOL (admin only once the config exists)
+    UnwrapSol,
+    // Move `amount` of the signer's tokens into their own vault, creating it
+    Deposit {
+        amount: u64,       // Tokens to deposit (raw units)
+    },
+    // Move `amount` tokens out of the signer's own vault
+    Withdraw {
+        amount: u64,       // Tokens to withdraw (raw units)
+    },
+    // Like Transfer, with an SPL Memo signed by the PDA in the same instruction
+    TransferWithMemo {
+        amount: u64,       // Tokens to move (raw units)
+        memo: String,      // UTF-8 text shown by explorers (invoice id, reason, ...)
+    },
+    // Admin caps how much a single transfer may move (0 removes the cap)
+    SetTransferLimit {
+        max_per_transfer: u64, // New cap (raw units)
+    },
+    // Admin stops or resumes transfers out of the PDA's account
+    SetPaused {
+        paused: bool,      // Whether transfers are stopped
+    },
+    // Admin adds a destination token account to the allowlist, creating it
+    AllowDestination {
+        destination: Pubkey, // Token account transfers may go to
+    },
+    // Admin takes a destination token account off the allowlist
+    DisallowDestination {
+        destination: Pubkey, // Token account transfers may no longer go to
+    },
+    // Admin caps how much all transfers together may move per day (0 removes the cap)
+    SetDailyCap {
+        daily_cap: u64,    // New cap (raw units)
+    },
+    // Like Transfer, but moves the whole balance instead of failing when the
+    // PDA's account holds less than `max_amount`
+    TransferUpTo {
+        max_amount: u64,   // Most tokens to move (raw units)
+    },
+    // Move `amount` tokens from an account owned by an SPL Token multisig
+    // that has the PDA as one of its signers
+    MultisigTransfer {
+        amount: u64,       // Tokens to move (raw units)
+    },
+    // Admin sets how much the faucet hands out per request and how many
+    // slots a wallet waits between requests (amount 0 turns it off)
+    SetFaucet {
+        amount: u64,       // Most tokens per request (raw units)
+        cooldown: u64,     // Slots between requests of the same wallet
+    },
+    // Any wallet draws up to the faucet amount from an account owned by the
+    // separate [b"faucet_vault"] PDA
+    RequestFaucet {
+        amount: u64,       // Tokens to move (raw units)
+    },
+}
+
+// Return data set by Transfer, TransferAll, TransferUpTo, BatchTransfer,
+// TransferWithFee, TransferWithMemo, MultisigTransfer and RequestFaucet
+// Calling programs read it with get_return_data after the CPI, and clients
+// from a simulation, without fetching the token accounts
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct TransferReturnData {
+    pub amount: u64,    // Tokens that left the PDA's account (fees and whole batches included)
+    pub remaining: u64, // PDA account's balance afterwards
+    pub decimals: u8,   // Decimals of the mint, to display the amounts
+    pub fee: u64,       // Withheld by a Token-2022 transfer fee, so destinations got `amount` minus this
+}
+
+// Internal structure for deserializing the Transfer, Approve, Burn, MintTo,
+// TransferWithFee, WrapSol, Deposit, Withdraw, TransferUpTo,
+// MultisigTransfer and RequestFaucet payloads
+#[derive(BorshDeserialize)]
+struct AmountPayload {
+    amount: u64,
+}
+
+// Internal structure for deserializing the BatchTransfer payload
+#[derive(BorshDeserialize)]
+struct BatchTransferPayload {
+    amounts: Vec<u64>,
+}
+
+// Internal structure for deserializing the TransferWithMemo payload
+#[derive(BorshDeserialize)]
+struct TransferWithMemoPayload {
+    amount: u64,
+    memo: String,
+}
+
+// Internal structure for deserializing the InitializeConfig payload
+#[derive(BorshDeserialize)]
+struct InitializeConfigPayload {
+    fee_bps: u16,
+    treasury: Pubkey,
+}
+
+// Internal structure for deserializing the SetFee payload
+#[derive(BorshDeserialize)]
+struct SetFeePayload {
+    fee_bps: u16,
+}
+
+// Internal structure for deserializing the SetTransferLimit payload
+#[derive(BorshDeserialize)]
+struct SetTransferLimitPayload {
+    max_per_transfer: u64,
+}
+
+// Internal structure for deserializing the SetDailyCap payload
+#[derive(BorshDeserialize)]
+struct SetDailyCapPayload {
+    daily_cap: u64,
+}
+
+// Internal structure for deserializing the SetFaucet payload
+#[derive(BorshDeserialize)]
+struct SetFaucetPayload {
+    amount: u64,
+    cooldown: u64,
+}
+
+// Internal structure for deserializing the SetPaused payload
+#[derive(BorshDeserialize)]
+struct SetPausedPayload {
+    paused: bool,
+}
+
+// Internal structure for deserializing the AllowDestination and
+// DisallowDestination payloads
+#[derive(BorshDeserialize)]
+struct DestinationPayload {
+    destination: Pubkey,
+}
+
+// Internal structure for deserializing the CreateVesting payload
+#[derive(BorshDeserialize)]
+struct CreateVestingPayload {
+    beneficiary: Pubkey,
+    total: u64,
+    start: i64,
+    cliff: i64,
+    period: i64,
+}
+
+// Internal structure for deserializing the InitializeMint payload
+#[derive(BorshDeserialize)]
+struct InitializeMintPayload {
+    decimals: u8,
+}
+
+impl TransferInstruction {
+    // Deserialize instruction data from bytes into a TransferInstruction enum
+    // Instruction format: [variant_byte][borsh_serialized_payload]
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        // First byte indicates which instruction variant
+        // (0 = Transfer, 1 = TransferAll, 2 = Approve, 3 = Revoke,
+        // 4 = Burn, 5 = CloseAccount, 6 = InitializeMint, 7 = MintTo,
+        // 8 = CloseVault, 9 = BatchTransfer, 10 = InitializeConfig, 11 = SetFee,
+        // 12 = TransferWithFee, 13 = CreateVesting, 14 = ClaimVested,
+        // 15 = CreateWrappedVault, 16 = WrapSol, 17 = UnwrapSol, 18 = Deposit,
+        // 19 = Withdraw, 20 = TransferWithMemo, 21 = SetTransferLimit,
+        // 22 = SetPaused, 23 = AllowDestination, 24 = DisallowDestination,
+        // 25 = SetDailyCap, 26 = TransferUpTo, 27 = MultisigTransfer,
+        // 28 = SetFaucet, 29 = RequestFaucet)
+        let (&variant, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        Ok(match variant {
+            0 => Self::Transfer {
+                amount: unpack_amount(rest)?,
+            },
+            1 => Self::TransferAll,
+            2 => Self::Approve {
+                amount: unpack_amount(rest)?,
+            },
+            3 => Self::Revoke,
+            4 => Self::Burn {
+                amount: unpack_amount(rest)?,
+            },
+            5 => Self::CloseAccount,
+            6 => {
+                let payload = InitializeMintPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::InitializeMint {
+                    decimals: payload.decimals,
+                }
+            }
+            7 => Self::MintTo {
+                amount: unpack_amount(rest)?,
+            },
+            8 => Self::CloseVault,
+            9 => {
+                let payload = BatchTransferPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::BatchTransfer {
+                    amounts: payload.amounts,
+                }
+            }
+            10 => {
+                let payload = InitializeConfigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::InitializeConfig {
+                    fee_bps: payload.fee_bps,
+                    treasury: payload.treasury,
+                }
+            }
+            11 => {
+                let payload = SetFeePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetFee {
+                    fee_bps: payload.fee_bps,
+                }
+            }
+            12 => Self::TransferWithFee {
+                amount: unpack_amount(rest)?,
+            },
+            13 => {
+                let payload = CreateVestingPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateVesting {
+                    beneficiary: payload.beneficiary,
+                    total: payload.total,
+                    start: payload.start,
+                    cliff: payload.cliff,
+                    period: payload.period,
+                }
+            }
+            14 => Self::ClaimVested,
+            15 => Self::CreateWrappedVault,
+            16 => Self::WrapSol {
+                amount: unpack_amount(rest)?,
+            },
+            17 => Self::UnwrapSol,
+            18 => Self::Deposit {
+                amount: unpack_amount(rest)?,
+            },
+            19 => Self::Withdraw {
+                amount: unpack_amount(rest)?,
+            },
+            20 => {
+                let payload = TransferWithMemoPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::TransferWithMemo {
+                    amount: payload.amount,
+                    memo: payload.memo,
+                }
+            }
+            21 => {
+                let payload = SetTransferLimitPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetTransferLimit {
+                    max_per_transfer: payload.max_per_transfer,
+                }
+            }
+            22 => {
+                let payload = SetPausedPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetPaused {
+                    paused: payload.paused,
+                }
+            }
+            23 => Self::AllowDestination {
+                destination: unpack_destination(rest)?,
+            },
+            24 => Self::DisallowDestination {
+                destination: unpack_destination(rest)?,
+            },
+            25 => {
+                let payload = SetDailyCapPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetDailyCap {
+                    daily_cap: payload.daily_cap,
+                }
+            }
+            26 => Self::TransferUpTo {
+                max_amount: unpack_amount(rest)?,
+            },
+            27 => Self::MultisigTransfer {
+                amount: unpack_amount(rest)?,
+            },
+            28 => {
+                let payload = SetFaucetPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetFaucet {
+                    amount: payload.amount,
+                    cooldown: payload.cooldown,
+                }
+            }
+            29 => Self::RequestFaucet {
+                amount: unpack_amount(rest)?,
+            },
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+}
+
+fn unpack_amount(rest: &[u8]) -> Result<u64, ProgramError> {
+    let payload = AmountPayload::try_from_slice(rest)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    Ok(payload.amount)
+}
+
+fn unpack_destination(rest: &[u8]) -> Result<Pubkey, ProgramError> {
+    let payload = DestinationPayload::try_from_slice(rest)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    Ok(payload.destination)
+}
@@ -1,86 +1,1926 @@
+// Module declarations - organize code into separate files
+pub mod instruction;  // Instruction parsing and types
+pub mod state;        // Account state structures and errors
+
+use crate::instruction::{TransferInstruction, TransferReturnData};
+use crate::state::{Allowlist, FaucetCooldown, TransferConfig, TransferError, Vesting, MAX_FEE_BPS};
+use borsh::{BorshDeserialize, BorshSerialize};
 use {
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
         msg,
-        program::invoke_signed,  // Used for Cross-Program Invocation (CPI) with PDA signing
+        program::{invoke, invoke_signed, set_return_data},  // CPI (invoke_signed for PDA signing) and return data
         program_error::ProgramError,
-        program_pack::Pack,      // Trait for unpacking account data
+        program_pack::{IsInitialized, Pack},
         pubkey::Pubkey,
+        rent::Rent,
+        sysvar::{clock::Clock, Sysvar, SysvarSerialize},
     },
-    spl_token::{
-        instruction::transfer_checked,  // SPL Token instruction builder
-        state::{Account, Mint},         // SPL Token account structures
-    },
+    solana_system_interface::instruction as system_instruction,
+    spl_associated_token_account_interface::instruction::create_associated_token_account_idempotent,
+    spl_generic_token::generic_token::{Account, Mint},  // Token account structures for SPL Token and Token-2022
+    spl_token::instruction::{
+        approve_checked, burn_checked, close_account, initialize_mint2, mint_to_checked, revoke, sync_native,
+        transfer_checked,
+    },  // SPL Token instruction builders
+    spl_token_2022_interface::{
+        extension::{
+            transfer_fee::{instruction::transfer_checked_with_fee, TransferFeeConfig},
+            transfer_hook, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Mint as ExtensionMint,
+    },  // Token-2022 mint extensions (transfer fees and hooks)
+    spl_transfer_hook_interface::onchain::add_extra_accounts_for_execute_cpi,
 };
 
+// SPL Memo program (v2), which checks that every account passed to it signed
+pub const MEMO_PROGRAM_ID: Pubkey = solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
 // Define the program entrypoint - this macro sets up the entry function
 // that the Solana runtime calls when a transaction is sent to this program
+// Skipped with the "no-entrypoint" feature so other programs can depend on this crate
+#[cfg(not(feature = "no-entrypoint"))]
 solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(
     program_id: &Pubkey,         // The program ID of THIS program
     accounts: &[AccountInfo],    // All accounts passed to this instruction
-    _instruction_data: &[u8],    // Instruction data (unused in this program)
+    instruction_data: &[u8],     // Serialized instruction data
 ) -> ProgramResult {
+    // Deserialize instruction data to determine which action to perform
+    let instruction = TransferInstruction::unpack(instruction_data)?;
+
+    // Route to the appropriate handler based on instruction type
+    match instruction {
+        TransferInstruction::Transfer { amount } => transfer(program_id, accounts, TransferAmount::Exact(amount)),
+        TransferInstruction::TransferAll => transfer(program_id, accounts, TransferAmount::All),
+        TransferInstruction::Approve { amount } => approve(program_id, accounts, amount),
+        TransferInstruction::Revoke => revoke_delegate(program_id, accounts),
+        TransferInstruction::Burn { amount } => burn(program_id, accounts, amount),
+        TransferInstruction::CloseAccount => close(program_id, accounts),
+        TransferInstruction::InitializeMint { decimals } => initialize_mint(program_id, accounts, decimals),
+        TransferInstruction::MintTo { amount } => mint_to(program_id, accounts, amount),
+        TransferInstruction::CloseVault => close_vault(program_id, accounts),
+        TransferInstruction::BatchTransfer { amounts } => batch_transfer(program_id, accounts, amounts),
+        TransferInstruction::InitializeConfig { fee_bps, treasury } => {
+            initialize_config(program_id, accounts, fee_bps, treasury)
+        }
+        TransferInstruction::SetFee { fee_bps } => set_fee(program_id, accounts, fee_bps),
+        TransferInstruction::TransferWithFee { amount } => transfer_with_fee(program_id, accounts, amount),
+        TransferInstruction::CreateVesting { beneficiary, total, start, cliff, period } => {
+            create_vesting(program_id, accounts, beneficiary, total, start, cliff, period)
+        }
+        TransferInstruction::ClaimVested => claim_vested(program_id, accounts),
+        TransferInstruction::CreateWrappedVault => create_wrapped_vault(program_id, accounts),
+        TransferInstruction::WrapSol { amount } => wrap_sol(program_id, accounts, amount),
+        TransferInstruction::UnwrapSol => unwrap_sol(program_id, accounts),
+        TransferInstruction::Deposit { amount } => deposit(program_id, accounts, amount),
+        TransferInstruction::Withdraw { amount } => withdraw(program_id, accounts, amount),
+        TransferInstruction::TransferWithMemo { amount, memo } => transfer_with_memo(program_id, accounts, amount, memo),
+        TransferInstruction::SetTransferLimit { max_per_transfer } => {
+            set_transfer_limit(program_id, accounts, max_per_transfer)
+        }
+        TransferInstruction::SetPaused { paused } => set_paused(program_id, accounts, paused),
+        TransferInstruction::SetDailyCap { daily_cap } => set_daily_cap(program_id, accounts, daily_cap),
+        TransferInstruction::TransferUpTo { max_amount } => {
+            transfer(program_id, accounts, TransferAmount::UpTo(max_amount))
+        }
+        TransferInstruction::MultisigTransfer { amount } => multisig_transfer(program_id, accounts, amount),
+        TransferInstruction::SetFaucet { amount, cooldown } => set_faucet(program_id, accounts, amount, cooldown),
+        TransferInstruction::RequestFaucet { amount } => request_faucet(program_id, accounts, amount),
+        TransferInstruction::AllowDestination { destination } => allow_destination(program_id, accounts, destination),
+        TransferInstruction::DisallowDestination { destination } => {
+            disallow_destination(program_id, accounts, destination)
+        }
+    }
+}
+
+// Verify that the authority account is the correct PDA and return its bump
+// We derive the PDA using the same seed that was used to create it
+fn check_authority(program_id: &Pubkey, authority_info: &AccountInfo) -> Result<u8, ProgramError> {
+    let (expected_authority, bump_seed) = Pubkey::find_program_address(&[b"authority"], program_id);
+    if expected_authority != *authority_info.key {
+        return Err(ProgramError::InvalidSeeds);  // Reject if PDA doesn't match
+    }
+    Ok(bump_seed)
+}
+
+// Verify that the authority account is `user`'s own PDA and return its bump
+// Each user's vault is owned by [b"authority", user], so only that user's
+// signature can get the program to sign for it
+fn check_user_authority(program_id: &Pubkey, authority_info: &AccountInfo, user: &Pubkey) -> Result<u8, ProgramError> {
+    let (expected_authority, bump_seed) = Pubkey::find_program_address(&[b"authority", user.as_ref()], program_id);
+    if expected_authority != *authority_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(bump_seed)
+}
+
+// Accept SPL Token or Token-2022, but only the one that actually owns the
+// token account being operated on
+// Every handler calls this (or checks the id itself) before building a CPI,
+// so a look-alike program can't be substituted to receive the PDA's signature
+fn check_token_program(token_program_info: &AccountInfo, source_info: &AccountInfo) -> ProgramResult {
+    if !spl_generic_token::is_known_spl_token_id(token_program_info.key)
+        || source_info.owner != token_program_info.key
+    {
+        msg!("Token account is not owned by token program {}", token_program_info.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Token-2022 encodes these instructions exactly like SPL Token, but the
+// spl_token builders only accept their own program id, so instructions are
+// built for SPL Token and sent to whichever program was passed
+fn for_token_program(mut instruction: Instruction, token_program_info: &AccountInfo) -> Instruction {
+    instruction.program_id = *token_program_info.key;
+    instruction
+}
+
+// How much the transfer handler moves out of the PDA's account
+pub enum TransferAmount {
+    Exact(u64), // Transfer: exactly this much, failing if the balance is short
+    All,        // TransferAll: the whole balance
+    UpTo(u64),  // TransferUpTo: this much, or the whole balance if it's less
+}
+
+// Handler for moving tokens out of the PDA's account
+pub fn transfer(program_id: &Pubkey, accounts: &[AccountInfo], amount: TransferAmount) -> ProgramResult {
     // Create an iterator to safely reference accounts in the slice
     let account_info_iter = &mut accounts.iter();
 
-    // Extract accounts in the order specified by the instruction
-    // These match the order defined in the test file (line 144-148)
+    // Expected accounts in order:
     let source_info = next_account_info(account_info_iter)?;      // 1. Source token account (owned by PDA)
     let mint_info = next_account_info(account_info_iter)?;        // 2. Token mint
     let destination_info = next_account_info(account_info_iter)?; // 3. Destination token account (owned by user)
     let authority_info = next_account_info(account_info_iter)?;   // 4. PDA authority (not a signer, derived)
-    let token_program_info = next_account_info(account_info_iter)?; // 5. SPL Token program (for CPI)
+    let token_program_info = next_account_info(account_info_iter)?; // 5. SPL Token or Token-2022 program (for CPI)
+    let config_info = next_account_info(account_info_iter)?;        // 6. Config PDA (transfer limits, writable)
+    let allowlist_info = next_account_info(account_info_iter)?;     // 7. Allowlist PDA (allowed destinations)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 8. Clock sysvar (for the daily window)
 
-    // Verify that the authority account is the correct PDA
-    // We derive the PDA using the same seed that was used to create it
-    let (expected_authority, bump_seed) = Pubkey::find_program_address(&[b"authority"], program_id);
-    if expected_authority != *authority_info.key {
-        return Err(ProgramError::InvalidSeeds);  // Reject if PDA doesn't match
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+    check_destination(program_id, allowlist_info, destination_info.key)?;
+
+    // With the optional accounts, create the destination as the recipient's
+    // associated token account first if it doesn't exist yet
+    // They start with the payer, which has to sign, telling them apart from
+    // the transfer hook accounts that may follow
+    if account_info_iter.as_slice().first().is_some_and(|info| info.is_signer) {
+        let payer_info = next_account_info(account_info_iter)?;          // 9. Optional: pays to create the destination ATA (signer)
+        let wallet_info = next_account_info(account_info_iter)?;         // 10. Wallet the destination ATA belongs to
+        let ata_program_info = next_account_info(account_info_iter)?;    // 11. Associated token account program
+        let system_program_info = next_account_info(account_info_iter)?; // 12. System program (for the ATA's creation)
+        create_destination_ata(
+            payer_info,
+            destination_info,
+            wallet_info,
+            mint_info,
+            system_program_info,
+            token_program_info,
+            ata_program_info,
+        )?;
     }
+    // ..: Extra accounts of the mint's Token-2022 transfer hook, if it has one
+    let hook_accounts = account_info_iter.as_slice();
 
     // Unpack the source token account to read its data
-    // This deserializes the raw account data into an SPL Token Account struct
-    let source_account = Account::unpack(&source_info.try_borrow_data()?)?;
-    // let amount = source_account.amount;  // Get all tokens from the source account
-    let amount = 100; // homework #2 change to hardcoded value for transfer
+    // This reads the balance through the program that owns the account, so
+    // Token-2022 accounts with extensions work too
+    let source_account = unpack_account(source_info, token_program_info, TransferError::InvalidSourceAccount)?;
+    let amount = match amount {
+        TransferAmount::Exact(amount) => amount,
+        TransferAmount::All => source_account.amount,
+        TransferAmount::UpTo(max_amount) => max_amount.min(source_account.amount),
+    };
+    check_limits(program_id, config_info, clock_sysvar, &[amount])?;
 
     // Unpack the mint account to get the decimal configuration
     // transfer_checked requires decimals to prevent precision errors
-    let mint = Mint::unpack(&mint_info.try_borrow_data()?)?;
+    let mint = unpack_mint(mint_info, token_program_info)?;
     let decimals = mint.decimals;
 
     // Log the transfer attempt (visible in program logs when enabled)
     msg!("Attempting to transfer {} tokens", amount);
-    
+
+    let fee = invoke_transfer(
+        source_info,
+        mint_info,
+        destination_info,
+        authority_info,
+        token_program_info,
+        hook_accounts,
+        amount,
+        decimals,
+        bump_seed,
+    )?;
+
+    set_transfer_return_data(source_info, token_program_info, amount, fee, decimals)
+}
+
+// Handler for a transfer that skims a fee for the treasury
+// The destination gets `amount` minus the configured fee share
+pub fn transfer_with_fee(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Source token account (owned by PDA)
+    let mint_info = next_account_info(account_info_iter)?;          // 2. Token mint
+    let destination_info = next_account_info(account_info_iter)?;   // 3. Destination token account
+    let treasury_info = next_account_info(account_info_iter)?;      // 4. Configured treasury token account
+    let authority_info = next_account_info(account_info_iter)?;     // 5. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 6. SPL Token or Token-2022 program (for CPI)
+    let config_info = next_account_info(account_info_iter)?;        // 7. Config PDA (writable)
+    let allowlist_info = next_account_info(account_info_iter)?;     // 8. Allowlist PDA (allowed destinations)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 9. Clock sysvar (for the daily window)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+
+    let config = load_config(program_id, config_info)?;
+    if config.treasury != *treasury_info.key {
+        return Err(TransferError::InvalidTreasury.into());
+    }
+    // The fee counts towards the limits too: it leaves the PDA's account
+    check_limits(program_id, config_info, clock_sysvar, &[amount])?;
+    // The treasury is the admin's own choice, so only the destination is checked
+    check_destination(program_id, allowlist_info, destination_info.key)?;
+
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+    let fee = config.fee_for(amount);
+
+    msg!("Transferring {} tokens, {} of them to the treasury", amount, fee);
+
+    let mut withheld = invoke_transfer(
+        source_info,
+        mint_info,
+        destination_info,
+        authority_info,
+        token_program_info,
+        &[],
+        amount - fee,
+        decimals,
+        bump_seed,
+    )?;
+    if fee > 0 {
+        withheld += invoke_transfer(
+            source_info,
+            mint_info,
+            treasury_info,
+            authority_info,
+            token_program_info,
+            &[],
+            fee,
+            decimals,
+            bump_seed,
+        )?;
+    }
+
+    set_transfer_return_data(source_info, token_program_info, amount, withheld, decimals)
+}
+
+// Handler for a transfer with a memo attached
+// The PDA signs the memo, so explorers show it as coming from the vault
+pub fn transfer_with_memo(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64, memo: String) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Source token account (owned by PDA)
+    let mint_info = next_account_info(account_info_iter)?;          // 2. Token mint
+    let destination_info = next_account_info(account_info_iter)?;   // 3. Destination token account
+    let authority_info = next_account_info(account_info_iter)?;     // 4. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 5. SPL Token or Token-2022 program (for CPI)
+    let config_info = next_account_info(account_info_iter)?;        // 6. Config PDA (transfer limits, writable)
+    let allowlist_info = next_account_info(account_info_iter)?;     // 7. Allowlist PDA (allowed destinations)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 8. Clock sysvar (for the daily window)
+    let memo_program_info = next_account_info(account_info_iter)?;  // 9. SPL Memo program (for CPI)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+    if *memo_program_info.key != MEMO_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if memo.is_empty() {
+        return Err(TransferError::InvalidMemo.into());
+    }
+    check_limits(program_id, config_info, clock_sysvar, &[amount])?;
+    check_destination(program_id, allowlist_info, destination_info.key)?;
+
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Transferring {} tokens with memo \"{}\"", amount, memo);
+
+    // The memo instruction is just the UTF-8 text, with the signers as accounts
+    invoke_signed(
+        &Instruction::new_with_bytes(
+            MEMO_PROGRAM_ID,
+            memo.as_bytes(),
+            vec![AccountMeta::new_readonly(*authority_info.key, true)],
+        ),
+        &[authority_info.clone(), memo_program_info.clone()],
+        &[&[b"authority", &[bump_seed]]],
+    )?;
+
+    let fee = invoke_transfer(
+        source_info,
+        mint_info,
+        destination_info,
+        authority_info,
+        token_program_info,
+        &[],
+        amount,
+        decimals,
+        bump_seed,
+    )?;
+
+    set_transfer_return_data(source_info, token_program_info, amount, fee, decimals)
+}
+
+// Handler for a transfer out of a token account owned by an SPL Token multisig
+// The PDA must be one of the multisig's signers and signs through
+// invoke_signed; the others sign the transaction, and the token program
+// checks that enough of them did
+pub fn multisig_transfer(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Source token account (owned by the multisig)
+    let mint_info = next_account_info(account_info_iter)?;          // 2. Token mint
+    let destination_info = next_account_info(account_info_iter)?;   // 3. Destination token account
+    let multisig_info = next_account_info(account_info_iter)?;      // 4. SPL Token multisig owning the source
+    let token_program_info = next_account_info(account_info_iter)?; // 5. SPL Token or Token-2022 program (for CPI)
+    let config_info = next_account_info(account_info_iter)?;        // 6. Config PDA (transfer limits, writable)
+    let allowlist_info = next_account_info(account_info_iter)?;     // 7. Allowlist PDA (allowed destinations)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 8. Clock sysvar (for the daily window)
+    let signers = account_info_iter.as_slice();                     // 9.. Multisig signers, including the PDA
+
+    check_token_program(token_program_info, source_info)?;
+    let (authority, bump_seed) = Pubkey::find_program_address(&[b"authority"], program_id);
+    if !signers.iter().any(|signer| *signer.key == authority) {
+        msg!("PDA authority {} is not among the signers", authority);
+        return Err(TransferError::MissingAuthoritySigner.into());
+    }
+    check_limits(program_id, config_info, clock_sysvar, &[amount])?;
+    check_destination(program_id, allowlist_info, destination_info.key)?;
+
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Transferring {} tokens with {} multisig signers", amount, signers.len());
+
+    let (instruction, account_infos, fee) = transfer_instruction(
+        source_info,
+        mint_info,
+        destination_info,
+        multisig_info,
+        token_program_info,
+        signers,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(&instruction, &account_infos, &[&[b"authority", &[bump_seed]]])?;
+
+    set_transfer_return_data(source_info, token_program_info, amount, fee, decimals)
+}
+
+// Handler for paying several destinations from the PDA's account at once
+// The destinations follow the fixed accounts, one per amount
+pub fn batch_transfer(program_id: &Pubkey, accounts: &[AccountInfo], amounts: Vec<u64>) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Source token account (owned by PDA)
+    let mint_info = next_account_info(account_info_iter)?;          // 2. Token mint
+    let authority_info = next_account_info(account_info_iter)?;     // 3. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 4. SPL Token or Token-2022 program (for CPI)
+    let config_info = next_account_info(account_info_iter)?;        // 5. Config PDA (transfer limits, writable)
+    let allowlist_info = next_account_info(account_info_iter)?;     // 6. Allowlist PDA (allowed destinations)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 7. Clock sysvar (for the daily window)
+    let destinations = account_info_iter.as_slice();                // 8.. Destination token accounts
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+
+    if destinations.len() != amounts.len() {
+        msg!("{} destinations for {} amounts", destinations.len(), amounts.len());
+        return Err(TransferError::DestinationCountMismatch.into());
+    }
+    check_limits(program_id, config_info, clock_sysvar, &amounts)?;
+    for destination_info in destinations {
+        check_destination(program_id, allowlist_info, destination_info.key)?;
+    }
+
+    // Check the whole batch up front rather than failing partway through
+    let total = amounts
+        .iter()
+        .try_fold(0u64, |total, amount| total.checked_add(*amount))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let balance = unpack_account(source_info, token_program_info, TransferError::InvalidSourceAccount)?
+        .amount;
+    if total > balance {
+        msg!("Batch of {} tokens exceeds the balance of {}", total, balance);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Transferring {} tokens to {} destinations", total, destinations.len());
+
+    let mut fee = 0;
+    for (destination_info, amount) in destinations.iter().zip(amounts) {
+        fee += invoke_transfer(
+            source_info,
+            mint_info,
+            destination_info,
+            authority_info,
+            token_program_info,
+            &[],
+            amount,
+            decimals,
+            bump_seed,
+        )?;
+    }
+
+    set_transfer_return_data(source_info, token_program_info, total, fee, decimals)
+}
+
+// Move `amount` tokens out of the PDA's account, signed by the PDA
+// `hook_accounts` supplies the extra accounts of the mint's transfer hook,
+// if it has one; returns the tokens withheld by its transfer fee
+#[allow(clippy::too_many_arguments)]
+fn invoke_transfer<'a>(
+    source_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    hook_accounts: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+    bump_seed: u8,
+) -> Result<u64, ProgramError> {
+    // Check both sides hold `mint_info` up front; the token program would
+    // reject a mismatch too, but only with a generic error
+    let source_mint = unpack_account(source_info, token_program_info, TransferError::InvalidSourceAccount)?
+        .mint;
+    let destination_mint = unpack_account(destination_info, token_program_info, TransferError::InvalidDestinationAccount)?
+        .mint;
+    if source_mint != *mint_info.key || destination_mint != *mint_info.key {
+        msg!(
+            "Source holds mint {} and destination {}, not {}",
+            source_mint,
+            destination_mint,
+            mint_info.key
+        );
+        return Err(TransferError::MintMismatch.into());
+    }
+
+    // Build the transfer for the token program, with the accounts it needs
+    let (instruction, account_infos, fee) = transfer_instruction(
+        source_info,
+        mint_info,
+        destination_info,
+        authority_info,
+        token_program_info,
+        &[],  // No multisig signers
+        hook_accounts,
+        amount,
+        decimals,
+    )?;
+
     // Perform a Cross-Program Invocation (CPI) to the SPL Token program
     // invoke_signed allows our PDA to "sign" even though it has no private key
     invoke_signed(
-        // Build the transfer_checked instruction for SPL Token program
-        &transfer_checked(
-            token_program_info.key,   // SPL Token program ID
-            source_info.key,          // Source token account (from)
-            mint_info.key,            // Token mint (for verification)
-            destination_info.key,     // Destination token account (to)
-            authority_info.key,       // Authority (our PDA that owns source account)
-            &[],                      // No multisig signers
-            amount,                   // Amount to transfer
-            decimals,                 // Decimals (prevents precision errors)
-        )
-        .unwrap(),
-        // Accounts required by the SPL Token program for this instruction
-        // Must be in the order expected by transfer_checked
-        &[
-            source_info.clone(),      // Source token account
-            mint_info.clone(),        // Mint account
-            destination_info.clone(), // Destination token account
-            authority_info.clone(),   // Authority (PDA)
-            token_program_info.clone(), // SPL Token program itself (not required, but good practice)
-        ],
+        &instruction,
+        &account_infos,
         // PDA seeds to "sign" the transaction
         // The outer array allows multiple PDAs, inner arrays contain [seed, bump] for each PDA
         &[&[b"authority", &[bump_seed]]],  // Our PDA: seed="authority" + bump_seed
+    )?;
+    Ok(fee)
+}
+
+// Build a transfer_checked of `amount` tokens for the token program, along
+// with the account infos to invoke it with and the fee it withholds
+// Token-2022 mints can carry extensions that change what a transfer needs:
+// - A transfer fee: the destination gets `amount` minus the fee, so the fee
+//   is worked out from the mint's current epoch fee and sent with
+//   TransferCheckedWithFee, which fails if the token program disagrees
+// - A transfer hook: the token program calls the hook program, which needs
+//   the extra accounts its validation account lists, found in `hook_accounts`
+#[allow(clippy::too_many_arguments)]
+fn transfer_instruction<'a>(
+    source_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    signers: &[AccountInfo<'a>],
+    hook_accounts: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+) -> Result<(Instruction, Vec<AccountInfo<'a>>, u64), ProgramError> {
+    // SPL Token mints parse as extension mints without any extensions
+    let (fee, hook_program_id) = {
+        let data = mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<ExtensionMint>::unpack(&data)?;
+        let fee = match mint.get_extension::<TransferFeeConfig>() {
+            Ok(fee_config) => Some(
+                fee_config
+                    .calculate_epoch_fee(Clock::get()?.epoch, amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+            ),
+            Err(_) => None,
+        };
+        (fee, transfer_hook::get_program_id(&mint))
+    };
+
+    let signer_keys: Vec<&Pubkey> = signers.iter().map(|signer| signer.key).collect();
+    let mut instruction = match fee {
+        Some(fee) => {
+            msg!("Mint withholds a transfer fee of {}", fee);
+            transfer_checked_with_fee(
+                token_program_info.key,
+                source_info.key,
+                mint_info.key,
+                destination_info.key,
+                authority_info.key,
+                &signer_keys,
+                amount,
+                decimals,
+                fee,
+            )?
+        }
+        None => for_token_program(
+            transfer_checked(
+                &spl_token::id(),         // Built for SPL Token, see for_token_program
+                source_info.key,          // Source token account (from)
+                mint_info.key,            // Token mint (for verification)
+                destination_info.key,     // Destination token account (to)
+                authority_info.key,       // Authority (our PDA or the multisig owning the source)
+                &signer_keys,             // Multisig signers, if any
+                amount,                   // Amount to transfer
+                decimals,                 // Decimals (prevents precision errors)
+            )?,
+            token_program_info,
+        ),
+    };
+
+    // Accounts required by the token program for this instruction
+    // Must be in the order expected by transfer_checked
+    let mut account_infos = vec![
+        source_info.clone(),      // Source token account
+        mint_info.clone(),        // Mint account
+        destination_info.clone(), // Destination token account
+        authority_info.clone(),   // Authority
+    ];
+    account_infos.extend(signers.iter().cloned());
+    if let Some(hook_program_id) = hook_program_id {
+        add_extra_accounts_for_execute_cpi(
+            &mut instruction,
+            &mut account_infos,
+            &hook_program_id,
+            source_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            amount,
+            hook_accounts,
+        )?;
+    }
+    account_infos.push(token_program_info.clone()); // Token program itself (not required, but good practice)
+
+    Ok((instruction, account_infos, fee.unwrap_or(0)))
+}
+
+// Report a transfer of `amount` tokens out of `source_info` as return data,
+// read by calling programs with get_return_data and by simulating clients
+fn set_transfer_return_data(
+    source_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+    amount: u64,
+    fee: u64,
+    decimals: u8,
+) -> ProgramResult {
+    let remaining = unpack_account(source_info, token_program_info, TransferError::InvalidSourceAccount)?
+        .amount;
+    set_return_data(&borsh::to_vec(&TransferReturnData {
+        amount,
+        remaining,
+        decimals,
+        fee,
+    })?);
+    Ok(())
+}
+
+// Unpack a token account through the token program that owns it, so
+// Token-2022 accounts with extensions work too
+// Fails with `error`, naming the account, if it isn't a token account
+fn unpack_account(
+    account_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+    error: TransferError,
+) -> Result<Account, ProgramError> {
+    let data = account_info.try_borrow_data()?;
+    Account::unpack(&data, token_program_info.key).ok_or_else(|| {
+        msg!("{} is not a token account", account_info.key);
+        error.into()
+    })
+}
+
+// Unpack a mint through the token program that owns it
+fn unpack_mint(mint_info: &AccountInfo, token_program_info: &AccountInfo) -> Result<Mint, ProgramError> {
+    let data = mint_info.try_borrow_data()?;
+    Mint::unpack(&data, token_program_info.key).ok_or_else(|| {
+        msg!("{} is not a mint", mint_info.key);
+        TransferError::InvalidMint.into()
+    })
+}
+
+// Create `wallet_info`'s associated token account for the mint at
+// `destination_info`, paid for by `payer_info`
+// The idempotent variant succeeds without doing anything if the account
+// already exists, and fails if `destination_info` isn't the wallet's ATA
+fn create_destination_ata<'a>(
+    payer_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    wallet_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    ata_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    if !spl_associated_token_account_interface::program::check_id(ata_program_info.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !payer_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    invoke(
+        &create_associated_token_account_idempotent(
+            payer_info.key,
+            wallet_info.key,
+            mint_info.key,
+            token_program_info.key,
+        ),
+        &[
+            payer_info.clone(),
+            destination_info.clone(),
+            wallet_info.clone(),
+            mint_info.clone(),
+            system_program_info.clone(),
+            token_program_info.clone(),
+            ata_program_info.clone(),
+        ],
     )
-}
\ No newline at end of file
+}
+
+// Handler for letting a delegate spend tokens from the PDA's account
+pub fn approve(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Token account (owned by PDA)
+    let mint_info = next_account_info(account_info_iter)?;          // 2. Token mint
+    let delegate_info = next_account_info(account_info_iter)?;      // 3. Delegate being approved
+    let authority_info = next_account_info(account_info_iter)?;     // 4. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 5. SPL Token or Token-2022 program (for CPI)
+    let admin_info = next_account_info(account_info_iter)?;         // 6. Config admin (signer once the config exists)
+    let config_info = next_account_info(account_info_iter)?;        // 7. Config PDA (transfer limits, writable)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 8. Clock sysvar (for the daily window)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+    // The delegate can spend the whole approval, so it counts as leaving now
+    check_admin_limits(program_id, admin_info, config_info, clock_sysvar, &[amount])?;
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Approving {} for {} tokens", delegate_info.key, amount);
+
+    invoke_signed(
+        &for_token_program(
+            approve_checked(
+                &spl_token::id(),
+                source_info.key,
+                mint_info.key,
+                delegate_info.key,
+                authority_info.key,
+                &[],
+                amount,
+                decimals,
+            )?,
+            token_program_info,
+        ),
+        &[
+            source_info.clone(),
+            mint_info.clone(),
+            delegate_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[b"authority", &[bump_seed]]],
+    )
+}
+
+// Handler for removing the delegate of the PDA's account
+pub fn revoke_delegate(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Token account (owned by PDA)
+    let authority_info = next_account_info(account_info_iter)?;     // 2. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 3. SPL Token or Token-2022 program (for CPI)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+
+    msg!("Revoking delegate");
+
+    invoke_signed(
+        &for_token_program(
+            revoke(&spl_token::id(), source_info.key, authority_info.key, &[])?,
+            token_program_info,
+        ),
+        &[
+            source_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[b"authority", &[bump_seed]]],
+    )
+}
+
+// Handler for burning tokens held by the PDA's account
+// Used for deflationary or redemption flows
+pub fn burn(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Token account (owned by PDA)
+    let mint_info = next_account_info(account_info_iter)?;          // 2. Token mint (its supply goes down)
+    let authority_info = next_account_info(account_info_iter)?;     // 3. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 4. SPL Token or Token-2022 program (for CPI)
+    let admin_info = next_account_info(account_info_iter)?;         // 5. Config admin (signer once the config exists)
+    let config_info = next_account_info(account_info_iter)?;        // 6. Config PDA (transfer limits, writable)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 7. Clock sysvar (for the daily window)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+    check_admin_limits(program_id, admin_info, config_info, clock_sysvar, &[amount])?;
+
+    // Burning lowers the mint's supply, so it has to be the source's own mint
+    let source_account = unpack_account(source_info, token_program_info, TransferError::InvalidSourceAccount)?;
+    if source_account.mint != *mint_info.key {
+        msg!("Source holds mint {}, not {}", source_account.mint, mint_info.key);
+        return Err(TransferError::MintMismatch.into());
+    }
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Burning {} tokens", amount);
+
+    invoke_signed(
+        &for_token_program(
+            burn_checked(
+                &spl_token::id(),
+                source_info.key,
+                mint_info.key,
+                authority_info.key,
+                &[],
+                amount,
+                decimals,
+            )?,
+            token_program_info,
+        ),
+        &[
+            source_info.clone(),
+            mint_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[b"authority", &[bump_seed]]],
+    )
+}
+
+// Handler for closing the PDA's token account
+// The SPL Token program only closes accounts with a zero balance
+pub fn close(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Token account to close (owned by PDA)
+    let destination_info = next_account_info(account_info_iter)?;   // 2. Receives the account's rent lamports
+    let authority_info = next_account_info(account_info_iter)?;     // 3. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 4. SPL Token or Token-2022 program (for CPI)
+    let admin_info = next_account_info(account_info_iter)?;         // 5. Config admin (signer once the config exists)
+    let config_info = next_account_info(account_info_iter)?;        // 6. Config PDA (transfer limits, writable)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 7. Clock sysvar (for the daily window)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+    // No tokens leave an empty account, but its rent does, and a paused
+    // config still stops it
+    check_admin_limits(program_id, admin_info, config_info, clock_sysvar, &[0])?;
+
+    msg!("Closing token account {}", source_info.key);
+
+    invoke_close(source_info, destination_info, authority_info, token_program_info, bump_seed)
+}
+
+// Handler for retiring the PDA's token account
+// Unlike CloseAccount, the account doesn't have to be empty: whatever it
+// still holds is transferred out first
+pub fn close_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let source_info = next_account_info(account_info_iter)?;        // 1. Token account to close (owned by PDA)
+    let mint_info = next_account_info(account_info_iter)?;          // 2. Token mint
+    let destination_info = next_account_info(account_info_iter)?;   // 3. Token account receiving the remaining balance
+    let recipient_info = next_account_info(account_info_iter)?;     // 4. Receives the account's rent lamports
+    let authority_info = next_account_info(account_info_iter)?;     // 5. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 6. SPL Token or Token-2022 program (for CPI)
+    let admin_info = next_account_info(account_info_iter)?;         // 7. Config admin (signer once the config exists)
+    let config_info = next_account_info(account_info_iter)?;        // 8. Config PDA (transfer limits, writable)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 9. Clock sysvar (for the daily window)
+    let allowlist_info = next_account_info(account_info_iter)?;     // 10. Allowlist PDA (allowed destinations)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, source_info)?;
+
+    let remaining = unpack_account(source_info, token_program_info, TransferError::InvalidSourceAccount)?
+        .amount;
+    check_admin_limits(program_id, admin_info, config_info, clock_sysvar, &[remaining])?;
+    check_destination(program_id, allowlist_info, destination_info.key)?;
+    if remaining > 0 {
+        let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+        msg!("Moving the remaining {} tokens out", remaining);
+        invoke_transfer(
+            source_info,
+            mint_info,
+            destination_info,
+            authority_info,
+            token_program_info,
+            &[],
+            remaining,
+            decimals,
+            bump_seed,
+        )?;
+    }
+
+    msg!("Closing vault {}, rent to {}", source_info.key, recipient_info.key);
+
+    invoke_close(source_info, recipient_info, authority_info, token_program_info, bump_seed)
+}
+
+// Close the PDA's (empty) token account, sending its rent to `destination_info`
+fn invoke_close<'a>(
+    source_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    bump_seed: u8,
+) -> ProgramResult {
+    invoke_signed(
+        &for_token_program(
+            close_account(
+                &spl_token::id(),
+                source_info.key,
+                destination_info.key,
+                authority_info.key,
+                &[],
+            )?,
+            token_program_info,
+        ),
+        &[
+            source_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[b"authority", &[bump_seed]]],
+    )
+}
+
+// Handler for creating a mint controlled by the PDA
+// The mint is a new keypair account; the payer funds it and both sign
+// Minting is then only possible through MintTo, signed by the PDA
+pub fn initialize_mint(program_id: &Pubkey, accounts: &[AccountInfo], decimals: u8) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let payer_info = next_account_info(account_info_iter)?;          // 1. Pays the mint's rent (signer)
+    let mint_info = next_account_info(account_info_iter)?;           // 2. New mint account (signer)
+    let authority_info = next_account_info(account_info_iter)?;      // 3. PDA authority, becomes the mint authority
+    let token_program_info = next_account_info(account_info_iter)?;  // 4. SPL Token or Token-2022 program (for CPI)
+    let system_program_info = next_account_info(account_info_iter)?; // 5. System program (for the mint's creation)
+
+    check_authority(program_id, authority_info)?;
+    if !spl_generic_token::is_known_spl_token_id(token_program_info.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !payer_info.is_signer || !mint_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    msg!("Creating mint {} with {} decimals", mint_info.key, decimals);
+
+    // A Token-2022 mint without extensions has the same size as an SPL Token one
+    let mint_len = spl_token::state::Mint::LEN;
+    invoke(
+        &system_instruction::create_account(
+            payer_info.key,
+            mint_info.key,
+            Rent::get()?.minimum_balance(mint_len),
+            mint_len as u64,
+            token_program_info.key,
+        ),
+        &[payer_info.clone(), mint_info.clone(), system_program_info.clone()],
+    )?;
+
+    // InitializeMint2 reads rent from the sysvar cache, so no Rent account is needed
+    invoke(
+        &for_token_program(
+            initialize_mint2(&spl_token::id(), mint_info.key, authority_info.key, None, decimals)?,
+            token_program_info,
+        ),
+        &[mint_info.clone(), token_program_info.clone()],
+    )
+}
+
+// Handler for minting new tokens of a PDA-controlled mint
+// Anyone can call it, which makes the mint a faucet; put access control
+// in front of it for reward mints
+pub fn mint_to(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let mint_info = next_account_info(account_info_iter)?;          // 1. Mint whose authority is the PDA
+    let destination_info = next_account_info(account_info_iter)?;   // 2. Token account receiving the new tokens
+    let authority_info = next_account_info(account_info_iter)?;     // 3. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 4. SPL Token or Token-2022 program (for CPI)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_token_program(token_program_info, mint_info)?;
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Minting {} tokens to {}", amount, destination_info.key);
+
+    invoke_signed(
+        &for_token_program(
+            mint_to_checked(
+                &spl_token::id(),
+                mint_info.key,
+                destination_info.key,
+                authority_info.key,
+                &[],
+                amount,
+                decimals,
+            )?,
+            token_program_info,
+        ),
+        &[
+            mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[b"authority", &[bump_seed]]],
+    )
+}
+
+// Handler for creating the config PDA
+// Whoever initializes it first becomes the admin
+pub fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_bps: u16,       // Fee skimmed by TransferWithFee
+    treasury: Pubkey,   // Token account receiving the fee
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;          // 1. Becomes the admin, pays the rent (signer)
+    let config_info = next_account_info(account_info_iter)?;         // 2. Config PDA to create
+    let system_program_info = next_account_info(account_info_iter)?; // 3. System program (for the config's creation)
+
+    if !admin_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if fee_bps > MAX_FEE_BPS {
+        return Err(TransferError::InvalidFee.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+    if pda != *config_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+    if config_info.lamports() > 0 {
+        return Err(TransferError::AlreadyInitialized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin_info.key,
+            config_info.key,
+            Rent::get()?.minimum_balance(TransferConfig::LEN),
+            TransferConfig::LEN as u64,
+            program_id,
+        ),
+        &[admin_info.clone(), config_info.clone(), system_program_info.clone()],
+        &[&[b"config", &[bump_seed]]],
+    )?;
+
+    let config = TransferConfig {
+        is_initialized: true,
+        admin: *admin_info.key,
+        treasury,
+        fee_bps,
+        max_per_transfer: 0,
+        paused: false,
+        daily_cap: 0,
+        window_start: 0,
+        window_total: 0,
+        faucet_amount: 0,
+        faucet_cooldown: 0,
+        bump: bump_seed,
+    };
+    config.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    msg!("Config created with a {} bps fee", fee_bps);
+
+    Ok(())
+}
+
+// Handler for changing the TransferWithFee fee (admin only)
+pub fn set_fee(program_id: &Pubkey, accounts: &[AccountInfo], fee_bps: u16) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;  // 1. Config admin (signer)
+    let config_info = next_account_info(account_info_iter)?; // 2. Config PDA
+
+    if fee_bps > MAX_FEE_BPS {
+        return Err(TransferError::InvalidFee.into());
+    }
+
+    let mut config = load_admin_config(program_id, admin_info, config_info)?;
+    config.fee_bps = fee_bps;
+    config.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    msg!("Fee set to {} bps", fee_bps);
+
+    Ok(())
+}
+
+// Handler for the admin changing the per-transfer cap
+pub fn set_transfer_limit(program_id: &Pubkey, accounts: &[AccountInfo], max_per_transfer: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;  // 1. Config admin (signer)
+    let config_info = next_account_info(account_info_iter)?; // 2. Config PDA
+
+    let mut config = load_admin_config(program_id, admin_info, config_info)?;
+    config.max_per_transfer = max_per_transfer;
+    config.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    msg!("Per-transfer limit set to {}", max_per_transfer);
+
+    Ok(())
+}
+
+// Handler for the admin pausing or resuming transfers
+// Only transfers out of the PDA's account stop; admin instructions keep working
+pub fn set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;  // 1. Config admin (signer)
+    let config_info = next_account_info(account_info_iter)?; // 2. Config PDA
+
+    let mut config = load_admin_config(program_id, admin_info, config_info)?;
+    config.paused = paused;
+    config.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    msg!("Transfers {}", if paused { "paused" } else { "resumed" });
+
+    Ok(())
+}
+
+// Handler for the admin changing the daily cap
+// The current window keeps its total, so lowering the cap can stop
+// transfers until the window rolls over
+pub fn set_daily_cap(program_id: &Pubkey, accounts: &[AccountInfo], daily_cap: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;  // 1. Config admin (signer)
+    let config_info = next_account_info(account_info_iter)?; // 2. Config PDA
+
+    let mut config = load_admin_config(program_id, admin_info, config_info)?;
+    config.daily_cap = daily_cap;
+    config.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    msg!("Daily cap set to {}", daily_cap);
+
+    Ok(())
+}
+
+// Handler for the admin configuring the faucet
+pub fn set_faucet(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64, cooldown: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;  // 1. Config admin (signer)
+    let config_info = next_account_info(account_info_iter)?; // 2. Config PDA
+
+    let mut config = load_admin_config(program_id, admin_info, config_info)?;
+    config.faucet_amount = amount;
+    config.faucet_cooldown = cooldown;
+    config.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+
+    msg!("Faucet set to {} tokens every {} slots", amount, cooldown);
+
+    Ok(())
+}
+
+// Handler for a wallet drawing tokens from the faucet
+// The faucet pays out of token accounts owned by its own [b"faucet_vault"]
+// PDA, which no other instruction signs for, so the cooldown can't be
+// sidestepped with a plain Transfer; the admin funds them like any account
+// Each requester's last request is kept in a [b"faucet", requester] PDA,
+// which they pay for on their first request; the faucet's transfers count
+// towards the config's limits like any other
+pub fn request_faucet(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let requester_info = next_account_info(account_info_iter)?;      // 1. Requesting wallet, pays the cooldown's rent (signer)
+    let cooldown_info = next_account_info(account_info_iter)?;       // 2. Requester's cooldown PDA (created on first use)
+    let source_info = next_account_info(account_info_iter)?;         // 3. Faucet token account (owned by the faucet PDA)
+    let mint_info = next_account_info(account_info_iter)?;           // 4. Token mint
+    let destination_info = next_account_info(account_info_iter)?;    // 5. Destination token account
+    let authority_info = next_account_info(account_info_iter)?;      // 6. Faucet PDA [b"faucet_vault"] (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?;  // 7. SPL Token or Token-2022 program (for CPI)
+    let config_info = next_account_info(account_info_iter)?;         // 8. Config PDA (faucet settings and limits, writable)
+    let allowlist_info = next_account_info(account_info_iter)?;      // 9. Allowlist PDA (allowed destinations)
+    let clock_sysvar = next_account_info(account_info_iter)?;        // 10. Clock sysvar (for the cooldown and daily window)
+    let system_program_info = next_account_info(account_info_iter)?; // 11. System program (for the cooldown's creation)
+
+    if !requester_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (faucet_authority, bump_seed) = Pubkey::find_program_address(&[b"faucet_vault"], program_id);
+    if faucet_authority != *authority_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    check_token_program(token_program_info, source_info)?;
+
+    let config = load_config(program_id, config_info)?;
+    if config.faucet_amount == 0 {
+        return Err(TransferError::FaucetDisabled.into());
+    }
+    if amount == 0 {
+        return Err(TransferError::InvalidAmount.into());
+    }
+    if amount > config.faucet_amount {
+        msg!("Requested {} tokens, the faucet gives at most {}", amount, config.faucet_amount);
+        return Err(TransferError::AboveFaucetLimit.into());
+    }
+
+    let (pda, cooldown_bump) =
+        Pubkey::find_program_address(&[b"faucet", requester_info.key.as_ref()], program_id);
+    if pda != *cooldown_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+
+    let slot = Clock::from_account_info(clock_sysvar)?.slot;
+    if cooldown_info.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                requester_info.key,
+                cooldown_info.key,
+                Rent::get()?.minimum_balance(FaucetCooldown::LEN),
+                FaucetCooldown::LEN as u64,
+                program_id,
+            ),
+            &[requester_info.clone(), cooldown_info.clone(), system_program_info.clone()],
+            &[&[b"faucet", requester_info.key.as_ref(), &[cooldown_bump]]],
+        )?;
+    } else {
+        if cooldown_info.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let cooldown = FaucetCooldown::try_from_slice(&cooldown_info.data.borrow())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if !cooldown.ready_at(slot, config.faucet_cooldown) {
+            msg!(
+                "Last request at slot {}, next one allowed at slot {}",
+                cooldown.last_slot,
+                cooldown.last_slot.saturating_add(config.faucet_cooldown)
+            );
+            return Err(TransferError::FaucetCooldown.into());
+        }
+    }
+    let cooldown = FaucetCooldown {
+        is_initialized: true,
+        requester: *requester_info.key,
+        last_slot: slot,
+        bump: cooldown_bump,
+    };
+    cooldown.serialize(&mut &mut cooldown_info.data.borrow_mut()[..])?;
+
+    check_limits(program_id, config_info, clock_sysvar, &[amount])?;
+    check_destination(program_id, allowlist_info, destination_info.key)?;
+
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Faucet sending {} tokens to {}", amount, requester_info.key);
+
+    let (instruction, account_infos, fee) = transfer_instruction(
+        source_info,
+        mint_info,
+        destination_info,
+        authority_info,
+        token_program_info,
+        &[],
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(&instruction, &account_infos, &[&[b"faucet_vault", &[bump_seed]]])?;
+
+    set_transfer_return_data(source_info, token_program_info, amount, fee, decimals)
+}
+
+// Handler for the admin adding a destination to the allowlist
+// The first destination creates the allowlist, which from then on restricts
+// every transfer out of the PDA's account; the admin pays for each entry
+pub fn allow_destination(program_id: &Pubkey, accounts: &[AccountInfo], destination: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;          // 1. Config admin, pays the rent (signer)
+    let config_info = next_account_info(account_info_iter)?;         // 2. Config PDA
+    let allowlist_info = next_account_info(account_info_iter)?;      // 3. Allowlist PDA (created on first use)
+    let system_program_info = next_account_info(account_info_iter)?; // 4. System program (for creating and growing it)
+
+    load_admin_config(program_id, admin_info, config_info)?;
+
+    let (pda, bump_seed) = Pubkey::find_program_address(&[b"allowlist"], program_id);
+    if pda != *allowlist_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+
+    let rent = Rent::get()?;
+    let mut allowlist = if allowlist_info.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_info.key,
+                allowlist_info.key,
+                rent.minimum_balance(Allowlist::size_for(0)),
+                Allowlist::size_for(0) as u64,
+                program_id,
+            ),
+            &[admin_info.clone(), allowlist_info.clone(), system_program_info.clone()],
+            &[&[b"allowlist", &[bump_seed]]],
+        )?;
+        Allowlist {
+            is_initialized: true,
+            destinations: Vec::new(),
+            bump: bump_seed,
+        }
+    } else {
+        load_allowlist(program_id, allowlist_info)?
+    };
+    if allowlist.destinations.contains(&destination) {
+        msg!("{} is already allowed", destination);
+        return Ok(());
+    }
+    allowlist.destinations.push(destination);
+
+    // Grow the account by one entry, topping up the rent first
+    let new_len = Allowlist::size_for(allowlist.destinations.len());
+    let shortfall = rent.minimum_balance(new_len).saturating_sub(allowlist_info.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(admin_info.key, allowlist_info.key, shortfall),
+            &[admin_info.clone(), allowlist_info.clone(), system_program_info.clone()],
+        )?;
+    }
+    allowlist_info.resize(new_len)?;
+    allowlist.serialize(&mut &mut allowlist_info.data.borrow_mut()[..])?;
+
+    msg!("Allowed destination {}, {} in total", destination, allowlist.destinations.len());
+
+    Ok(())
+}
+
+// Handler for the admin taking a destination off the allowlist
+// The account shrinks and the freed rent goes back to the admin; an empty
+// allowlist still exists, so it then blocks every destination
+pub fn disallow_destination(program_id: &Pubkey, accounts: &[AccountInfo], destination: Pubkey) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin_info = next_account_info(account_info_iter)?;     // 1. Config admin, receives the freed rent (signer)
+    let config_info = next_account_info(account_info_iter)?;    // 2. Config PDA
+    let allowlist_info = next_account_info(account_info_iter)?; // 3. Allowlist PDA
+
+    load_admin_config(program_id, admin_info, config_info)?;
+
+    let mut allowlist = load_allowlist(program_id, allowlist_info)?;
+    let Some(position) = allowlist.destinations.iter().position(|entry| *entry == destination) else {
+        msg!("{} is not on the allowlist", destination);
+        return Ok(());
+    };
+    allowlist.destinations.remove(position);
+
+    let new_len = Allowlist::size_for(allowlist.destinations.len());
+    allowlist_info.resize(new_len)?;
+    allowlist.serialize(&mut &mut allowlist_info.data.borrow_mut()[..])?;
+
+    let excess = allowlist_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(new_len));
+    **allowlist_info.lamports.borrow_mut() -= excess;
+    **admin_info.lamports.borrow_mut() = admin_info
+        .lamports()
+        .checked_add(excess)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    msg!("Disallowed destination {}, {} left", destination, allowlist.destinations.len());
+
+    Ok(())
+}
+
+// Handler for locking tokens in a linear vesting schedule
+// Creates the vesting PDA and moves the tokens into a vault owned by it;
+// one schedule per (beneficiary, mint) pair
+#[allow(clippy::too_many_arguments)]
+pub fn create_vesting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    beneficiary: Pubkey,  // Wallet that can claim the tokens
+    total: u64,           // Tokens to vest
+    start: i64,           // Unix timestamp when vesting starts
+    cliff: i64,           // Seconds after start before anything unlocks
+    period: i64,          // Seconds after start until everything is unlocked
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let funder_info = next_account_info(account_info_iter)?;         // 1. Provides the tokens and pays the rent (signer)
+    let funder_token_info = next_account_info(account_info_iter)?;   // 2. Funder's token account
+    let vault_info = next_account_info(account_info_iter)?;          // 3. Token account owned by the vesting PDA
+    let mint_info = next_account_info(account_info_iter)?;           // 4. Mint of the vested tokens
+    let vesting_info = next_account_info(account_info_iter)?;        // 5. Vesting PDA to create
+    let token_program_info = next_account_info(account_info_iter)?;  // 6. SPL Token or Token-2022 program (for CPI)
+    let system_program_info = next_account_info(account_info_iter)?; // 7. System program (for the vesting PDA's creation)
+
+    if !funder_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    check_token_program(token_program_info, vault_info)?;
+
+    if total == 0 {
+        return Err(TransferError::InvalidAmount.into());
+    }
+    if period <= 0 || !(0..=period).contains(&cliff) {
+        return Err(TransferError::InvalidSchedule.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[b"vesting", beneficiary.as_ref(), mint_info.key.as_ref()],
+        program_id,
+    );
+    if pda != *vesting_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+    if vesting_info.lamports() > 0 {
+        return Err(TransferError::AlreadyInitialized.into());
+    }
+
+    // The vault must hold this mint and be controlled by the vesting PDA,
+    // otherwise the program could never sign the claims
+    let vault = unpack_account(vault_info, token_program_info, TransferError::InvalidVault)?;
+    if vault.owner != pda || vault.mint != *mint_info.key {
+        return Err(TransferError::InvalidVault.into());
+    }
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            funder_info.key,
+            vesting_info.key,
+            Rent::get()?.minimum_balance(Vesting::LEN),
+            Vesting::LEN as u64,
+            program_id,
+        ),
+        &[funder_info.clone(), vesting_info.clone(), system_program_info.clone()],
+        &[&[b"vesting", beneficiary.as_ref(), mint_info.key.as_ref(), &[bump_seed]]],
+    )?;
+
+    // Move the tokens into the vault; the funder signs for their own account
+    invoke(
+        &for_token_program(
+            transfer_checked(
+                &spl_token::id(),
+                funder_token_info.key,
+                mint_info.key,
+                vault_info.key,
+                funder_info.key,
+                &[],
+                total,
+                decimals,
+            )?,
+            token_program_info,
+        ),
+        &[
+            funder_token_info.clone(),
+            mint_info.clone(),
+            vault_info.clone(),
+            funder_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    let vesting = Vesting {
+        is_initialized: true,
+        beneficiary,
+        mint: *mint_info.key,
+        vault: *vault_info.key,
+        total,
+        claimed: 0,
+        start,
+        cliff,
+        period,
+        bump: bump_seed,
+    };
+    vesting.serialize(&mut &mut vesting_info.data.borrow_mut()[..])?;
+
+    msg!("Vesting {} tokens to {} over {} seconds", total, beneficiary, period);
+
+    Ok(())
+}
+
+// Handler for claiming vested tokens
+// Transfers everything unlocked so far that hasn't been claimed yet
+pub fn claim_vested(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let beneficiary_info = next_account_info(account_info_iter)?;   // 1. Beneficiary (signer)
+    let vault_info = next_account_info(account_info_iter)?;         // 2. Vault token account
+    let destination_info = next_account_info(account_info_iter)?;   // 3. Token account receiving the tokens
+    let mint_info = next_account_info(account_info_iter)?;          // 4. Mint of the vested tokens
+    let vesting_info = next_account_info(account_info_iter)?;       // 5. Vesting PDA
+    let token_program_info = next_account_info(account_info_iter)?; // 6. SPL Token or Token-2022 program (for CPI)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 7. Clock sysvar
+
+    if !beneficiary_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if vesting_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    check_token_program(token_program_info, vault_info)?;
+
+    let mut vesting = Vesting::try_from_slice(&vesting_info.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if !vesting.is_initialized() {
+        return Err(TransferError::UninitializedAccount.into());
+    }
+
+    // Re-derive the PDA from the stored bump to prove the signer is the beneficiary
+    let pda = Pubkey::create_program_address(
+        &[b"vesting", beneficiary_info.key.as_ref(), mint_info.key.as_ref(), &[vesting.bump]],
+        program_id,
+    )
+    .map_err(|_| TransferError::InvalidPDA)?;
+    if pda != *vesting_info.key || vesting.beneficiary != *beneficiary_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+    if vesting.vault != *vault_info.key {
+        return Err(TransferError::InvalidVault.into());
+    }
+
+    // The clock is read from the sysvar account for the same reason as rent
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    let amount = vesting.claimable_at(now);
+    if amount == 0 {
+        return Err(TransferError::NothingToClaim.into());
+    }
+
+    // The vesting PDA owns the vault, so it signs the transfer out
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+    invoke_signed(
+        &for_token_program(
+            transfer_checked(
+                &spl_token::id(),
+                vault_info.key,
+                mint_info.key,
+                destination_info.key,
+                vesting_info.key,
+                &[],
+                amount,
+                decimals,
+            )?,
+            token_program_info,
+        ),
+        &[
+            vault_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            vesting_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[b"vesting", beneficiary_info.key.as_ref(), mint_info.key.as_ref(), &[vesting.bump]]],
+    )?;
+
+    vesting.claimed += amount;
+    vesting.serialize(&mut &mut vesting_info.data.borrow_mut()[..])?;
+
+    msg!("Claimed {} tokens, {} of {} so far", amount, vesting.claimed, vesting.total);
+
+    Ok(())
+}
+
+// Handler for creating the PDA's wrapped SOL vault
+// The vault is the PDA's associated token account for the native mint, so
+// it can be found without storing its address anywhere
+// Wrapped SOL is an SPL Token feature here; Token-2022 has its own native mint
+pub fn create_wrapped_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let payer_info = next_account_info(account_info_iter)?;          // 1. Pays the vault's rent (signer)
+    let vault_info = next_account_info(account_info_iter)?;          // 2. PDA's native mint ATA (created)
+    let authority_info = next_account_info(account_info_iter)?;      // 3. PDA authority, owner of the vault
+    let native_mint_info = next_account_info(account_info_iter)?;    // 4. Native mint
+    let token_program_info = next_account_info(account_info_iter)?;  // 5. SPL Token program
+    let ata_program_info = next_account_info(account_info_iter)?;    // 6. Associated token account program
+    let system_program_info = next_account_info(account_info_iter)?; // 7. System program (for the vault's creation)
+
+    check_authority(program_id, authority_info)?;
+    if !spl_token::check_id(token_program_info.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !spl_token::native_mint::check_id(native_mint_info.key) {
+        return Err(TransferError::NotWrappedSol.into());
+    }
+
+    msg!("Creating wrapped SOL vault {}", vault_info.key);
+
+    create_destination_ata(
+        payer_info,
+        vault_info,
+        authority_info,
+        native_mint_info,
+        system_program_info,
+        token_program_info,
+        ata_program_info,
+    )
+}
+
+// Handler for wrapping SOL into the PDA's vault
+// Lamports sent to a native account only show up in its token balance
+// after SyncNative, so both happen in the same instruction
+pub fn wrap_sol(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let depositor_info = next_account_info(account_info_iter)?;      // 1. Wallet sending the SOL (signer)
+    let vault_info = next_account_info(account_info_iter)?;          // 2. PDA's wrapped SOL vault
+    let authority_info = next_account_info(account_info_iter)?;      // 3. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?;  // 4. SPL Token program (for SyncNative)
+    let system_program_info = next_account_info(account_info_iter)?; // 5. System program (for the lamport transfer)
+
+    check_authority(program_id, authority_info)?;
+    check_wrapped_vault(vault_info, authority_info, token_program_info)?;
+    if !depositor_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if amount == 0 {
+        return Err(TransferError::InvalidAmount.into());
+    }
+
+    msg!("Wrapping {} lamports into {}", amount, vault_info.key);
+
+    invoke(
+        &system_instruction::transfer(depositor_info.key, vault_info.key, amount),
+        &[depositor_info.clone(), vault_info.clone(), system_program_info.clone()],
+    )?;
+
+    // SyncNative needs no signer: it only sets the token balance to the
+    // account's lamports above rent
+    invoke(
+        &sync_native(token_program_info.key, vault_info.key)?,
+        &[vault_info.clone(), token_program_info.clone()],
+    )
+}
+
+// Handler for unwrapping the PDA's wrapped SOL
+// Closing a native account releases its whole balance along with the rent,
+// so no transfer is needed first; the vault can be recreated afterwards
+pub fn unwrap_sol(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let vault_info = next_account_info(account_info_iter)?;         // 1. PDA's wrapped SOL vault (closed)
+    let recipient_info = next_account_info(account_info_iter)?;     // 2. Receives all the vault's lamports
+    let authority_info = next_account_info(account_info_iter)?;     // 3. PDA authority (not a signer, derived)
+    let token_program_info = next_account_info(account_info_iter)?; // 4. SPL Token program (for CPI)
+    let admin_info = next_account_info(account_info_iter)?;         // 5. Config admin (signer once the config exists)
+    let config_info = next_account_info(account_info_iter)?;        // 6. Config PDA (transfer limits, writable)
+    let clock_sysvar = next_account_info(account_info_iter)?;       // 7. Clock sysvar (for the daily window)
+    let allowlist_info = next_account_info(account_info_iter)?;     // 8. Allowlist PDA (allowed destinations)
+
+    let bump_seed = check_authority(program_id, authority_info)?;
+    check_wrapped_vault(vault_info, authority_info, token_program_info)?;
+    let wrapped = unpack_account(vault_info, token_program_info, TransferError::NotWrappedSol)?.amount;
+    check_admin_limits(program_id, admin_info, config_info, clock_sysvar, &[wrapped])?;
+    // The SOL goes to a wallet rather than a token account, but it leaves
+    // the PDA all the same, so the recipient has to be listed too
+    check_destination(program_id, allowlist_info, recipient_info.key)?;
+
+    msg!("Unwrapping {} lamports to {}", vault_info.lamports(), recipient_info.key);
+
+    invoke_close(vault_info, recipient_info, authority_info, token_program_info, bump_seed)
+}
+
+// Check that `vault_info` is an SPL Token native account owned by the PDA
+fn check_wrapped_vault(
+    vault_info: &AccountInfo,
+    authority_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+) -> ProgramResult {
+    if !spl_token::check_id(token_program_info.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    check_token_program(token_program_info, vault_info)?;
+    let vault = unpack_account(vault_info, token_program_info, TransferError::NotWrappedSol)?;
+    if !spl_token::native_mint::check_id(&vault.mint) || vault.owner != *authority_info.key {
+        msg!("{} is not the PDA's wrapped SOL account", vault_info.key);
+        return Err(TransferError::NotWrappedSol.into());
+    }
+    Ok(())
+}
+
+// Handler for a user funding their own vault
+// The vault is the user authority's associated token account for the mint,
+// created on the first deposit at the user's expense
+pub fn deposit(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let user_info = next_account_info(account_info_iter)?;           // 1. Depositing user (signer)
+    let source_info = next_account_info(account_info_iter)?;         // 2. User's token account to deposit from
+    let mint_info = next_account_info(account_info_iter)?;           // 3. Token mint
+    let vault_info = next_account_info(account_info_iter)?;          // 4. User's vault (ATA of the user authority)
+    let authority_info = next_account_info(account_info_iter)?;      // 5. User authority PDA [b"authority", user]
+    let token_program_info = next_account_info(account_info_iter)?;  // 6. SPL Token or Token-2022 program (for CPI)
+    let ata_program_info = next_account_info(account_info_iter)?;    // 7. Associated token account program
+    let system_program_info = next_account_info(account_info_iter)?; // 8. System program (for the vault's creation)
+
+    check_user_authority(program_id, authority_info, user_info.key)?;
+    check_token_program(token_program_info, source_info)?;
+    if amount == 0 {
+        return Err(TransferError::InvalidAmount.into());
+    }
+
+    // Also checks the user's signature, as the user pays for the vault
+    create_destination_ata(
+        user_info,
+        vault_info,
+        authority_info,
+        mint_info,
+        system_program_info,
+        token_program_info,
+        ata_program_info,
+    )?;
+
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Depositing {} tokens into {}", amount, vault_info.key);
+
+    // The user owns the source account and signed this transaction, so a
+    // plain invoke carries their signature into the token program
+    invoke(
+        &for_token_program(
+            transfer_checked(
+                &spl_token::id(),
+                source_info.key,
+                mint_info.key,
+                vault_info.key,
+                user_info.key,
+                &[],
+                amount,
+                decimals,
+            )?,
+            token_program_info,
+        ),
+        &[
+            source_info.clone(),
+            mint_info.clone(),
+            vault_info.clone(),
+            user_info.clone(),
+            token_program_info.clone(),
+        ],
+    )
+}
+
+// Handler for a user taking tokens out of their own vault
+// The PDA is derived from the signer, so nobody else can withdraw
+pub fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let user_info = next_account_info(account_info_iter)?;          // 1. Withdrawing user (signer)
+    let vault_info = next_account_info(account_info_iter)?;         // 2. User's vault (owned by the user authority)
+    let mint_info = next_account_info(account_info_iter)?;          // 3. Token mint
+    let destination_info = next_account_info(account_info_iter)?;   // 4. Token account receiving the tokens
+    let authority_info = next_account_info(account_info_iter)?;     // 5. User authority PDA [b"authority", user]
+    let token_program_info = next_account_info(account_info_iter)?; // 6. SPL Token or Token-2022 program (for CPI)
+
+    if !user_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let bump_seed = check_user_authority(program_id, authority_info, user_info.key)?;
+    check_token_program(token_program_info, vault_info)?;
+    if amount == 0 {
+        return Err(TransferError::InvalidAmount.into());
+    }
+
+    let decimals = unpack_mint(mint_info, token_program_info)?.decimals;
+
+    msg!("Withdrawing {} tokens from {}", amount, vault_info.key);
+
+    invoke_signed(
+        &for_token_program(
+            transfer_checked(
+                &spl_token::id(),
+                vault_info.key,
+                mint_info.key,
+                destination_info.key,
+                authority_info.key,
+                &[],
+                amount,
+                decimals,
+            )?,
+            token_program_info,
+        ),
+        &[
+            vault_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[b"authority", user_info.key.as_ref(), &[bump_seed]]],
+    )
+}
+
+// Read the config PDA, checking it is this program's initialized config
+fn load_config(program_id: &Pubkey, config_info: &AccountInfo) -> Result<TransferConfig, ProgramError> {
+    if config_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let config = TransferConfig::try_from_slice(&config_info.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if !config.is_initialized() {
+        return Err(TransferError::UninitializedAccount.into());
+    }
+    let pda = Pubkey::create_program_address(&[b"config", &[config.bump]], program_id)
+        .map_err(|_| TransferError::InvalidPDA)?;
+    if pda != *config_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+    Ok(config)
+}
+
+// Read the config PDA for an admin instruction, checking the signer is its admin
+fn load_admin_config(
+    program_id: &Pubkey,
+    admin_info: &AccountInfo,
+    config_info: &AccountInfo,
+) -> Result<TransferConfig, ProgramError> {
+    if !admin_info.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let config = load_config(program_id, config_info)?;
+    if config.admin != *admin_info.key {
+        return Err(TransferError::NotAdmin.into());
+    }
+    Ok(config)
+}
+
+// Enforce the config's pause flag, per-transfer cap and daily cap on the
+// transfers of `amounts` out of the PDA's account, and add them to the
+// current daily window
+// Before InitializeConfig there is no config and so no limits, but the
+// address is checked either way so another account can't stand in for it
+fn check_limits(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    amounts: &[u64],
+) -> ProgramResult {
+    let (pda, _) = Pubkey::find_program_address(&[b"config"], program_id);
+    if pda != *config_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+    if config_info.data_is_empty() {
+        return Ok(());
+    }
+
+    let mut config = load_config(program_id, config_info)?;
+    let mut total = 0u64;
+    for amount in amounts {
+        config.check_transfer(*amount)?;
+        total = total.checked_add(*amount).ok_or(ProgramError::ArithmeticOverflow)?;
+    }
+
+    // The clock is read from the sysvar account for the same reason as rent
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    config.record_outflow(total, now)?;
+    config.serialize(&mut &mut config_info.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// check_limits for the instructions that can empty the PDA's account or let
+// someone else spend from it (Approve, Burn, CloseAccount, CloseVault,
+// UnwrapSol): once the config exists they also need its admin's signature
+// Before that they stay open like everything else, since anyone could
+// create the config and make themselves admin anyway
+fn check_admin_limits(
+    program_id: &Pubkey,
+    admin_info: &AccountInfo,
+    config_info: &AccountInfo,
+    clock_sysvar: &AccountInfo,
+    amounts: &[u64],
+) -> ProgramResult {
+    if !config_info.data_is_empty() {
+        load_admin_config(program_id, admin_info, config_info)?;
+    }
+    // Also checks the config's address when it doesn't exist yet
+    check_limits(program_id, config_info, clock_sysvar, amounts)
+}
+
+// Read the allowlist PDA, checking it is this program's initialized allowlist
+fn load_allowlist(program_id: &Pubkey, allowlist_info: &AccountInfo) -> Result<Allowlist, ProgramError> {
+    if allowlist_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let allowlist = Allowlist::try_from_slice(&allowlist_info.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if !allowlist.is_initialized() {
+        return Err(TransferError::UninitializedAccount.into());
+    }
+    let pda = Pubkey::create_program_address(&[b"allowlist", &[allowlist.bump]], program_id)
+        .map_err(|_| TransferError::InvalidPDA)?;
+    if pda != *allowlist_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+    Ok(allowlist)
+}
+
+// Reject a transfer to `destination` if the allowlist exists and doesn't list it
+// As with the config, the address is checked even before the allowlist exists
+fn check_destination(program_id: &Pubkey, allowlist_info: &AccountInfo, destination: &Pubkey) -> ProgramResult {
+    let (pda, _) = Pubkey::find_program_address(&[b"allowlist"], program_id);
+    if pda != *allowlist_info.key {
+        msg!("Invalid seeds for PDA");
+        return Err(TransferError::InvalidPDA.into());
+    }
+    if allowlist_info.data_is_empty() {
+        return Ok(());
+    }
+    if !load_allowlist(program_id, allowlist_info)?.destinations.contains(destination) {
+        msg!("Destination {} is not on the allowlist", destination);
+        return Err(TransferError::DestinationNotAllowed.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,268 @@
+// Import Borsh traits for serializing/deserializing data to store on-chain
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;  // For creating custom error types with descriptions
+
+// Fees are in basis points: 10_000 bps = 100%
+pub const MAX_FEE_BPS: u16 = 10_000;
+
+// Length of the window the daily cap applies to
+pub const DAY_SECONDS: i64 = 24 * 60 * 60;
+
+// The program config stored in the PDA derived from [b"config"]
+// Set up once by InitializeConfig; the signer becomes the admin
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct TransferConfig {
+    pub is_initialized: bool,  // Whether this account has been initialized
+    pub admin: Pubkey,         // Wallet allowed to change the config
+    pub treasury: Pubkey,      // Token account receiving the fee of TransferWithFee
+    pub fee_bps: u16,          // Fee skimmed by TransferWithFee, in basis points
+    pub max_per_transfer: u64, // Largest amount a single transfer may move (0 = no cap)
+    pub paused: bool,          // Whether transfers out of the PDA's account are stopped
+    pub daily_cap: u64,        // Most tokens all transfers may move per window (0 = no cap)
+    pub window_start: i64,     // Unix timestamp the current daily window started at
+    pub window_total: u64,     // Tokens moved so far in the current window
+    pub faucet_amount: u64,    // Most tokens a RequestFaucet may move (0 = faucet off)
+    pub faucet_cooldown: u64,  // Slots a requester has to wait between requests
+    pub bump: u8,              // Bump seed of this PDA
+}
+
+impl TransferConfig {
+    // Serialized size: bool + 2 pubkeys + u16 + u64 + bool + u64 + i64 + 3 * u64 + u8
+    pub const LEN: usize = 1 + 32 * 2 + 2 + 8 + 1 + 8 + 8 + 8 * 3 + 1;
+
+    // Reject a transfer of `amount` while paused or above the cap
+    pub fn check_transfer(&self, amount: u64) -> Result<(), TransferError> {
+        if self.paused {
+            return Err(TransferError::Paused);
+        }
+        if self.max_per_transfer > 0 && amount > self.max_per_transfer {
+            return Err(TransferError::AboveTransferLimit);
+        }
+        Ok(())
+    }
+
+    // Add `amount` to the daily window, first starting a new window at `now`
+    // if the current one is a day old; fails if it would go over the daily cap
+    pub fn record_outflow(&mut self, amount: u64, now: i64) -> Result<(), TransferError> {
+        if now.saturating_sub(self.window_start) >= DAY_SECONDS {
+            self.window_start = now;
+            self.window_total = 0;
+        }
+        let total = self.window_total.saturating_add(amount);
+        if self.daily_cap > 0 && total > self.daily_cap {
+            return Err(TransferError::DailyCapExceeded);
+        }
+        self.window_total = total;
+        Ok(())
+    }
+
+    // The treasury's share of `amount`, rounded down
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_bps as u128 / MAX_FEE_BPS as u128) as u64
+    }
+}
+
+// The destination token accounts transfers out of the PDA's account may go to
+// Stored in the PDA derived from [b"allowlist"]; the account is resized to fit
+// the list as destinations are added and removed
+// Until the first destination is added there is no allowlist and any
+// destination is accepted; afterwards only listed ones are
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Allowlist {
+    pub is_initialized: bool,      // Whether this account has been initialized
+    pub destinations: Vec<Pubkey>, // Allowed destination token accounts
+    pub bump: u8,                  // Bump seed of this PDA
+}
+
+impl Allowlist {
+    // Account size for an allowlist of `destinations` entries
+    // is_initialized (1) + destinations (4 + 32 * n) + bump (1)
+    pub fn size_for(destinations: usize) -> usize {
+        1 + 4 + 32 * destinations + 1
+    }
+}
+
+impl Sealed for Allowlist {}
+
+impl IsInitialized for Allowlist {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// When a wallet last drew from the faucet, stored in the PDA derived from
+// [b"faucet", requester] and created on its first request
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct FaucetCooldown {
+    pub is_initialized: bool, // Whether this account has been initialized
+    pub requester: Pubkey,    // Wallet the cooldown applies to
+    pub last_slot: u64,       // Slot of the wallet's last request
+    pub bump: u8,             // Bump seed of this PDA
+}
+
+impl FaucetCooldown {
+    // Serialized size: bool + pubkey + u64 + u8
+    pub const LEN: usize = 1 + 32 + 8 + 1;
+
+    // Whether the requester may draw again at `slot`
+    pub fn ready_at(&self, slot: u64, cooldown: u64) -> bool {
+        slot >= self.last_slot.saturating_add(cooldown)
+    }
+}
+
+impl Sealed for FaucetCooldown {}
+
+impl IsInitialized for FaucetCooldown {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A linear vesting schedule stored in the PDA derived from
+// [b"vesting", beneficiary, mint]
+// Nothing unlocks before the cliff; after it the unlocked amount grows
+// linearly from `start` until everything is unlocked at `start + period`
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Vesting {
+    pub is_initialized: bool,  // Whether this account has been initialized
+    pub beneficiary: Pubkey,   // Wallet that can claim the vested tokens
+    pub mint: Pubkey,          // Mint of the vested tokens
+    pub vault: Pubkey,         // Token account holding the tokens (owned by this PDA)
+    pub total: u64,            // Tokens vested over the whole schedule
+    pub claimed: u64,          // Tokens already claimed
+    pub start: i64,            // Unix timestamp when vesting starts
+    pub cliff: i64,            // Seconds after `start` before anything unlocks
+    pub period: i64,           // Seconds after `start` until everything is unlocked
+    pub bump: u8,              // Bump seed of this PDA
+}
+
+impl Vesting {
+    // Serialized size: bool + 3 pubkeys + 2 * u64 + 3 * i64 + u8
+    pub const LEN: usize = 1 + 32 * 3 + 8 * 2 + 8 * 3 + 1;
+
+    // Tokens that can be claimed at `now`, on top of what was claimed already
+    pub fn claimable_at(&self, now: i64) -> u64 {
+        unlocked(self.total, now.saturating_sub(self.start), self.cliff, self.period)
+            .saturating_sub(self.claimed)
+    }
+}
+
+// Tokens of a `total` unlocked `elapsed` seconds into a schedule with the
+// given cliff and period
+pub fn unlocked(total: u64, elapsed: i64, cliff: i64, period: i64) -> u64 {
+    if elapsed < cliff || elapsed <= 0 {
+        return 0;
+    }
+    if elapsed >= period {
+        return total;
+    }
+    (total as u128 * elapsed as u128 / period as u128) as u64
+}
+
+// Sealed trait implementation - required by Solana's Pack trait
+impl Sealed for TransferConfig {}
+impl Sealed for Vesting {}
+
+// Implement IsInitialized trait to check if account is ready to use
+impl IsInitialized for TransferConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl IsInitialized for Vesting {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Custom error types for this program
+// The #[error(...)] attributes provide user-friendly error messages
+#[derive(Debug, Error)]
+pub enum TransferError {
+    #[error("Token account does not hold the given mint")]
+    MintMismatch,
+
+    #[error("Number of destination accounts does not match the number of amounts")]
+    DestinationCountMismatch,
+
+    #[error("Account not initialized yet")]
+    UninitializedAccount,
+
+    #[error("PDA derived does not equal PDA passed in")]
+    InvalidPDA,
+
+    #[error("Config account already exists")]
+    AlreadyInitialized,
+
+    #[error("Signer is not the config admin")]
+    NotAdmin,
+
+    #[error("Fee must be at most 10,000 basis points")]
+    InvalidFee,
+
+    #[error("Treasury does not match the configured treasury")]
+    InvalidTreasury,
+
+    #[error("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[error("Vesting period must be positive and no shorter than the cliff")]
+    InvalidSchedule,
+
+    #[error("Vault is not a token account owned by the vesting PDA")]
+    InvalidVault,
+
+    #[error("No vested tokens to claim yet")]
+    NothingToClaim,
+
+    #[error("Token account is not the PDA's wrapped SOL account")]
+    NotWrappedSol,
+
+    #[error("Memo must not be empty")]
+    InvalidMemo,
+
+    #[error("Transfers are paused")]
+    Paused,
+
+    #[error("Amount is above the per-transfer limit")]
+    AboveTransferLimit,
+
+    #[error("Destination is not on the allowlist")]
+    DestinationNotAllowed,
+
+    #[error("Transfer would exceed the daily cap")]
+    DailyCapExceeded,
+
+    #[error("Source is not a token account")]
+    InvalidSourceAccount,
+
+    #[error("Destination is not a token account")]
+    InvalidDestinationAccount,
+
+    #[error("Mint account is not a mint")]
+    InvalidMint,
+
+    #[error("PDA authority is not among the multisig signers")]
+    MissingAuthoritySigner,
+
+    #[error("Faucet is turned off")]
+    FaucetDisabled,
+
+    #[error("Amount is above the faucet limit")]
+    AboveFaucetLimit,
+
+    #[error("Requester must wait for the faucet cooldown to pass")]
+    FaucetCooldown,
+}
+
+// Convert our custom errors into Solana's ProgramError type
+// This allows our errors to be returned from instruction handlers
+impl From<TransferError> for ProgramError {
+    fn from(e: TransferError) -> Self {
+        ProgramError::Custom(e as u32)  // Convert enum variant to error code
+    }
+}
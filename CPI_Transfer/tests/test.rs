@@ -1,19 +1,44 @@
-use transfer::process_instruction;
+use transfer::instruction::TransferReturnData;
+use transfer::{process_instruction, MEMO_PROGRAM_ID};
+use transfer::state::{unlocked, Allowlist, FaucetCooldown, TransferConfig, TransferError, Vesting, DAY_SECONDS};
 
 use {
+    borsh::BorshDeserialize,
     solana_program::{
-        instruction::{AccountMeta, Instruction},
+        clock::Clock,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction, InstructionError},
         program_pack::Pack,
         pubkey::Pubkey,
         rent::Rent,
     },
-    solana_program_test::{processor, tokio, ProgramTest},
-    solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction},
+    solana_program_test::{processor, tokio, BanksClient, ProgramTest},
+    solana_sdk::{
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
     solana_system_interface::instruction as system_instruction,
-    spl_token::state::{Account, Mint},
+    spl_associated_token_account_interface::address::get_associated_token_address_with_program_id,
+    spl_token::state::{Account, Mint, Multisig},
+    spl_token_2022_interface::{
+        extension::{
+            transfer_fee::{instruction::initialize_transfer_fee_config, TransferFeeAmount},
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
+        state::{Account as ExtensionAccount, Mint as ExtensionMint},
+    },
     std::str::FromStr,
 };
 
+// Helper to build instruction data for variants carrying an amount
+// (0 = Transfer, 2 = Approve, 4 = Burn, 7 = MintTo)
+fn amount_instruction_data(variant: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![variant];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
 #[tokio::test]
 async fn success() {
     // Enable Solana runtime logging to see msg!() output from the program
@@ -26,6 +51,10 @@ async fn success() {
     let destination = Keypair::new();  // Token account that will receive tokens (owned by payer)
     // Derive the PDA that will be the authority over the source account
     let (authority_pubkey, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    // Derive the config PDA holding the transfer limits (none until InitializeConfig)
+    let (config_pubkey, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    // Derive the allowlist PDA (any destination is allowed until it is created)
+    let (allowlist_pubkey, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
 
     // Add the program to the test framework
     // This registers our program so when transactions are sent to program_id, 
@@ -41,7 +70,7 @@ async fn success() {
 
     // Start the program test - creates a local test validator
     // Returns: banks_client (for interacting with accounts), payer (funded test account), recent_blockhash
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
 
     // STEP 1: Create and initialize the token mint
     // This calls the System Program and SPL Token Program (NOT our program)
@@ -148,15 +177,18 @@ async fn success() {
     // STEP 5: Call OUR program to transfer tokens from source to destination
     // THIS is the only transaction that calls our program!
     let transaction = Transaction::new_signed_with_payer(
-        &[Instruction::new_with_bincode(
+        &[Instruction::new_with_bytes(
             program_id,  // Our program's ID - routes to our process_instruction
-            &(),         // Empty instruction data (our program doesn't use it)
+            &amount_instruction_data(0, 100), // Transfer { amount: 100 }
             vec![
                 AccountMeta::new(source.pubkey(), false),          // Writable, not signer
                 AccountMeta::new_readonly(mint.pubkey(), false),   // Read-only, not signer
                 AccountMeta::new(destination.pubkey(), false),     // Writable, not signer
                 AccountMeta::new_readonly(authority_pubkey, false), // Read-only PDA, not signer (derived in program)
                 AccountMeta::new_readonly(spl_token::id(), false), // SPL Token program to CPI into
+                AccountMeta::new(config_pubkey, false),            // Config PDA, checked for transfer limits
+                AccountMeta::new_readonly(allowlist_pubkey, false), // Allowlist PDA, checked for the destination
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar, for the daily cap window
             ],
         )],
         Some(&payer.pubkey()),  // Transaction fee payer
@@ -174,5 +206,2186 @@ async fn success() {
         .unwrap()
         .unwrap();
     let token_account = Account::unpack(&account.data).unwrap();
-    assert_eq!(token_account.amount, 100);  // Should have all tokens from source
-}
\ No newline at end of file
+    assert_eq!(token_account.amount, 100);  // Should have the requested amount
+}
+// Create a mint (payer is the mint authority), a source token account owned
+// by the PDA holding `amount` tokens, and a destination owned by the payer,
+// all under `token_program` (SPL Token or Token-2022)
+async fn setup_accounts(
+    banks_client: &BanksClient,
+    payer: &Keypair,
+    recent_blockhash: Hash,
+    token_program: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> (Keypair, Keypair, Keypair) {
+    let mint = Keypair::new();
+    let source = Keypair::new();
+    let destination = Keypair::new();
+    let rent = Rent::default();
+
+    // Token-2022 takes the same instructions as SPL Token for these calls;
+    // the spl_token builders just need pointing at the other program
+    let via = |mut instruction: Instruction| {
+        instruction.program_id = *token_program;
+        instruction
+    };
+
+    let mut instructions = vec![
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            token_program,
+        ),
+        via(spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer.pubkey(), None, 9).unwrap()),
+    ];
+    for (account, owner) in [(&source, authority), (&destination, &payer.pubkey())] {
+        instructions.push(system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(Account::LEN),
+            Account::LEN as u64,
+            token_program,
+        ));
+        instructions.push(via(
+            spl_token::instruction::initialize_account(&spl_token::id(), &account.pubkey(), &mint.pubkey(), owner)
+                .unwrap(),
+        ));
+    }
+    instructions.push(via(
+        spl_token::instruction::mint_to(&spl_token::id(), &mint.pubkey(), &source.pubkey(), &payer.pubkey(), &[], amount)
+            .unwrap(),
+    ));
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[payer, &mint, &source, &destination],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    (mint, source, destination)
+}
+
+async fn token_account(banks_client: &BanksClient, address: Pubkey) -> Account {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+    Account::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn approve_revoke_burn_transfer_all_and_close() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+    let delegate = Keypair::new();
+
+    // STEP 1: Approve a delegate for 500 tokens
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(2, 500),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new_readonly(delegate.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = token_account(&banks_client, source.pubkey()).await;
+    assert_eq!(account.delegate, Some(delegate.pubkey()).into());
+    assert_eq!(account.delegated_amount, 500);
+
+    // STEP 2: Revoke it again
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &[3],
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = token_account(&banks_client, source.pubkey()).await;
+    assert_eq!(account.delegate, None.into());
+
+    // STEP 3: Burn 1,000 tokens, which only works against the source's own mint
+    let burn = |mint: &Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(4, 1_000),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+    let (other_mint, _, _) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 1).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[burn(&other_mint.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TransferError::MintMismatch as u32)
+        )
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[burn(&mint.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, source.pubkey()).await.amount, 9_000);
+    let account = banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+    assert_eq!(Mint::unpack(&account.data).unwrap().supply, 9_000);
+
+    // STEP 4: Transfer everything that is left, then close the empty account
+    let rent_lamports = banks_client.get_balance(source.pubkey()).await.unwrap();
+    let recipient = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            Instruction::new_with_bytes(
+                program_id,
+                &[1],
+                vec![
+                    AccountMeta::new(source.pubkey(), false),
+                    AccountMeta::new_readonly(mint.pubkey(), false),
+                    AccountMeta::new(destination.pubkey(), false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(allowlist, false),
+                    AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[5],
+                vec![
+                    AccountMeta::new(source.pubkey(), false),
+                    AccountMeta::new(recipient, false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new_readonly(payer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                ],
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 9_000);
+    assert!(banks_client.get_account(source.pubkey()).await.unwrap().is_none());
+    assert_eq!(banks_client.get_balance(recipient).await.unwrap(), rent_lamports);
+}
+
+#[tokio::test]
+async fn transfer_token_2022() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let token_2022 = spl_generic_token::token_2022::id();
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &token_2022, &authority, 10_000).await;
+
+    let transfer = |token_program: &Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(0, 2_500),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(*token_program, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+
+    // STEP 1: Naming SPL Token for a Token-2022 account is rejected
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&spl_token::id())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+
+    // STEP 2: Any other program is rejected before the PDA signs anything
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&Pubkey::new_unique())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+
+    // STEP 3: Transferring through Token-2022 works
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&token_2022)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 2_500);
+    assert_eq!(token_account(&banks_client, source.pubkey()).await.amount, 7_500);
+}
+
+#[tokio::test]
+async fn transfer_token_2022_with_transfer_fee() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let token_2022 = spl_generic_token::token_2022::id();
+    let rent = Rent::default();
+
+    // A Token-2022 mint charging 1% per transfer; its token accounts need
+    // room for the withheld fee
+    let mint = Keypair::new();
+    let source = Keypair::new();
+    let destination = Keypair::new();
+    let mint_len = ExtensionType::try_calculate_account_len::<ExtensionMint>(&[ExtensionType::TransferFeeConfig]).unwrap();
+    let account_len =
+        ExtensionType::try_calculate_account_len::<ExtensionAccount>(&[ExtensionType::TransferFeeAmount]).unwrap();
+    let mut instructions = vec![
+        system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(mint_len),
+            mint_len as u64,
+            &token_2022,
+        ),
+        initialize_transfer_fee_config(&token_2022, &mint.pubkey(), None, None, 100, u64::MAX).unwrap(),
+        spl_token_2022_interface::instruction::initialize_mint2(&token_2022, &mint.pubkey(), &payer.pubkey(), None, 9)
+            .unwrap(),
+    ];
+    for (account, owner) in [(&source, &authority), (&destination, &payer.pubkey())] {
+        instructions.push(system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(account_len),
+            account_len as u64,
+            &token_2022,
+        ));
+        instructions.push(
+            spl_token_2022_interface::instruction::initialize_account3(&token_2022, &account.pubkey(), &mint.pubkey(), owner)
+                .unwrap(),
+        );
+    }
+    instructions.push(
+        spl_token_2022_interface::instruction::mint_to(&token_2022, &mint.pubkey(), &source.pubkey(), &payer.pubkey(), &[], 10_000)
+            .unwrap(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer, &mint, &source, &destination],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // The destination is credited the amount less the fee, and the return
+    // data tells the caller how much was withheld
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(0, 5_000),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(token_2022, false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(
+        TransferReturnData::try_from_slice(&return_data.data).unwrap(),
+        TransferReturnData { amount: 5_000, remaining: 5_000, decimals: 9, fee: 50 }
+    );
+    let account = banks_client.get_account(destination.pubkey()).await.unwrap().unwrap();
+    let account = StateWithExtensions::<ExtensionAccount>::unpack(&account.data).unwrap();
+    assert_eq!(account.base.amount, 4_950);
+    assert_eq!(u64::from(account.get_extension::<TransferFeeAmount>().unwrap().withheld_amount), 50);
+}
+
+#[tokio::test]
+async fn transfer_creates_destination_ata() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, _) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+
+    // The recipient has no token account for this mint yet
+    let recipient = Pubkey::new_unique();
+    let recipient_ata = get_associated_token_address_with_program_id(&recipient, &mint.pubkey(), &spl_token::id());
+    assert!(banks_client.get_account(recipient_ata).await.unwrap().is_none());
+
+    let transfer = |amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(0, amount),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(recipient_ata, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(recipient, false),
+                AccountMeta::new_readonly(spl_associated_token_account_interface::program::id(), false),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )
+    };
+
+    // The first transfer creates the ATA, the second finds it already there
+    for (amount, expected) in [(1_000, 1_000), (500, 1_500)] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[transfer(amount)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let account = token_account(&banks_client, recipient_ata).await;
+        assert_eq!((account.owner, account.amount), (recipient, expected));
+    }
+}
+
+#[tokio::test]
+async fn mint_with_pda_authority() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let mint = Keypair::new();
+    let destination = Keypair::new();
+
+    // STEP 1: Create the mint through the program, then a token account for it
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            Instruction::new_with_bytes(
+                program_id,
+                &[6, 6], // InitializeMint { decimals: 6 }
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(mint.pubkey(), true),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &destination.pubkey(),
+                Rent::default().minimum_balance(Account::LEN),
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &destination.pubkey(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &mint, &destination],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+    let mint_state = Mint::unpack(&account.data).unwrap();
+    assert_eq!(mint_state.mint_authority, Some(authority).into());
+    assert_eq!(mint_state.decimals, 6);
+
+    // STEP 2: Mint through the PDA
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(7, 42_000),
+            vec![
+                AccountMeta::new(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 42_000);
+    let account = banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+    assert_eq!(Mint::unpack(&account.data).unwrap().supply, 42_000);
+}
+
+#[tokio::test]
+async fn transfer_rejects_mint_mismatch() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, _) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+    // A destination for an unrelated mint
+    let (_, _, other_destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 1).await;
+
+    let transfer = |mint: Pubkey, destination: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(0, 100),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new(destination, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+
+    // Each wrong account fails early with its own error, before any CPI
+    for (instruction, error) in [
+        (transfer(mint.pubkey(), other_destination.pubkey()), TransferError::MintMismatch),
+        (transfer(other_destination.pubkey(), other_destination.pubkey()), TransferError::InvalidMint),
+        (transfer(mint.pubkey(), mint.pubkey()), TransferError::InvalidDestinationAccount),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+    assert_eq!(token_account(&banks_client, source.pubkey()).await.amount, 10_000);
+}
+
+#[tokio::test]
+async fn transfer_return_data_and_up_to() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+
+    let transfer = |data: &[u8]| {
+        Instruction::new_with_bytes(
+            program_id,
+            data,
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+
+    // STEP 1: Transfer returns the amount moved and what the source has left
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&amount_instruction_data(0, 2_500))],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, program_id);
+    assert_eq!(
+        TransferReturnData::try_from_slice(&return_data.data).unwrap(),
+        TransferReturnData { amount: 2_500, remaining: 7_500, decimals: 9, fee: 0 }
+    );
+
+    // STEP 2: A simulated TransferAll reports that it would empty the source
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&[1])],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+    simulation.result.unwrap().unwrap();
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(
+        TransferReturnData::try_from_slice(&return_data.data).unwrap(),
+        TransferReturnData { amount: 7_500, remaining: 0, decimals: 9, fee: 0 }
+    );
+
+    // STEP 3: TransferUpTo moves the full amount while the balance covers it,
+    // then drains what is left instead of failing
+    for (max_amount, expected) in [
+        (1_000, TransferReturnData { amount: 1_000, remaining: 6_500, decimals: 9, fee: 0 }),
+        (100_000, TransferReturnData { amount: 6_500, remaining: 0, decimals: 9, fee: 0 }),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[transfer(&amount_instruction_data(26, max_amount))],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+        result.result.unwrap();
+        let return_data = result.metadata.unwrap().return_data.unwrap();
+        assert_eq!(TransferReturnData::try_from_slice(&return_data.data).unwrap(), expected);
+    }
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 10_000);
+}
+
+#[tokio::test]
+async fn close_vault_with_balance() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 3_000).await;
+
+    // The vault still holds tokens, so CloseVault moves them out before closing
+    let rent_lamports = banks_client.get_balance(source.pubkey()).await.unwrap();
+    let recipient = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &[8],
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new(recipient, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new_readonly(allowlist, false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 3_000);
+    assert!(banks_client.get_account(source.pubkey()).await.unwrap().is_none());
+    assert_eq!(banks_client.get_balance(recipient).await.unwrap(), rent_lamports);
+}
+
+#[tokio::test]
+async fn batch_transfer() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, first) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 1_000).await;
+
+    // STEP 1: Two more destinations for the same mint
+    let second = Keypair::new();
+    let third = Keypair::new();
+    let mut instructions = vec![];
+    for account in [&second, &third] {
+        instructions.push(system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            Rent::default().minimum_balance(Account::LEN),
+            Account::LEN as u64,
+            &spl_token::id(),
+        ));
+        instructions.push(
+            spl_token::instruction::initialize_account(&spl_token::id(), &account.pubkey(), &mint.pubkey(), &payer.pubkey())
+                .unwrap(),
+        );
+    }
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer, &second, &third],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // BatchTransfer data: variant 9, then the Borsh Vec<u64> (u32 length + items)
+    let batch = |amounts: &[u64], destinations: &[&Keypair]| {
+        let mut data = vec![9];
+        data.extend_from_slice(&(amounts.len() as u32).to_le_bytes());
+        for amount in amounts {
+            data.extend_from_slice(&amount.to_le_bytes());
+        }
+        let mut accounts = vec![
+            AccountMeta::new(source.pubkey(), false),
+            AccountMeta::new_readonly(mint.pubkey(), false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(allowlist, false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+        ];
+        accounts.extend(destinations.iter().map(|account| AccountMeta::new(account.pubkey(), false)));
+        Instruction::new_with_bytes(program_id, &data, accounts)
+    };
+
+    // STEP 2: A batch larger than the balance, or with a missing destination, is rejected
+    for (instruction, error) in [
+        (batch(&[600, 300, 200], &[&first, &second, &third]), InstructionError::InsufficientFunds),
+        (
+            batch(&[100, 200, 300], &[&first, &second]),
+            InstructionError::Custom(TransferError::DestinationCountMismatch as u32),
+        ),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(result.unwrap_err().unwrap(), TransactionError::InstructionError(0, error));
+    }
+
+    // STEP 3: Pay all three
+    let transaction = Transaction::new_signed_with_payer(
+        &[batch(&[100, 200, 300], &[&first, &second, &third])],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    for (account, expected) in [(&first, 100), (&second, 200), (&third, 300), (&source, 400)] {
+        assert_eq!(token_account(&banks_client, account.pubkey()).await.amount, expected);
+    }
+}
+
+#[tokio::test]
+async fn transfer_with_fee() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 100_000).await;
+
+    // STEP 1: A treasury token account, and a config with a 2.5% fee
+    let treasury = Keypair::new();
+    let mut data = vec![10];
+    data.extend_from_slice(&250u16.to_le_bytes());
+    data.extend_from_slice(treasury.pubkey().as_ref());
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &treasury.pubkey(),
+                Rent::default().minimum_balance(Account::LEN),
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(&spl_token::id(), &treasury.pubkey(), &mint.pubkey(), &payer.pubkey())
+                .unwrap(),
+            Instruction::new_with_bytes(
+                program_id,
+                &data,
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &treasury],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let transfer_with_fee = |amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(12, amount),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new(treasury.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+
+    // STEP 2: 2.5% of 10,000 goes to the treasury
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer_with_fee(10_000)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 9_750);
+    assert_eq!(token_account(&banks_client, treasury.pubkey()).await.amount, 250);
+
+    // STEP 3: Only the admin can change the fee
+    let set_fee = |admin: &Pubkey, fee_bps: u16| {
+        let mut data = vec![11];
+        data.extend_from_slice(&fee_bps.to_le_bytes());
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![AccountMeta::new_readonly(*admin, true), AccountMeta::new(config, false)],
+        )
+    };
+    let stranger = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_fee(&stranger.pubkey(), 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(TransferError::NotAdmin as u32))
+    );
+
+    // STEP 4: At 10%, 1,000 of the next 10,000 goes to the treasury
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_fee(&payer.pubkey(), 1_000), transfer_with_fee(10_000)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 18_750);
+    assert_eq!(token_account(&banks_client, treasury.pubkey()).await.amount, 1_250);
+}
+
+#[tokio::test]
+async fn transfer_limits_and_pause() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+
+    let transfer = |amount: u64, config: Pubkey| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(0, amount),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+    let set_limit = |max_per_transfer: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(21, max_per_transfer),
+            vec![AccountMeta::new_readonly(payer.pubkey(), true), AccountMeta::new(config, false)],
+        )
+    };
+    let set_paused = |admin: &Pubkey, paused: bool| {
+        Instruction::new_with_bytes(
+            program_id,
+            &[22, paused as u8],
+            vec![AccountMeta::new_readonly(*admin, true), AccountMeta::new(config, false)],
+        )
+    };
+
+    // STEP 1: Create the config (no fee) and cap transfers at 1,000
+    let mut data = vec![10];
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(destination.pubkey().as_ref());
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            Instruction::new_with_bytes(
+                program_id,
+                &data,
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+            set_limit(1_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Above the cap, or with some other config account, is rejected
+    for (instruction, error) in [
+        (transfer(1_500, config), TransferError::AboveTransferLimit),
+        (transfer(500, Pubkey::new_unique()), TransferError::InvalidPDA),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+
+    // STEP 3: Up to the cap goes through
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(1_000, config)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 1_000);
+
+    // STEP 4: Only the admin can pause, and nothing moves while paused
+    let stranger = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_paused(&stranger.pubkey(), true)],
+        Some(&payer.pubkey()),
+        &[&payer, &stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(TransferError::NotAdmin as u32))
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_paused(&payer.pubkey(), true), transfer(200, config)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, InstructionError::Custom(TransferError::Paused as u32))
+    );
+
+    // STEP 5: Once resumed, transfers work again
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_paused(&payer.pubkey(), false), transfer(200, config)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 1_200);
+}
+
+#[tokio::test]
+async fn paused_vault_cannot_be_drained() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+    let native_mint = spl_token::native_mint::id();
+    let vault = get_associated_token_address_with_program_id(&authority, &native_mint, &spl_token::id());
+    let stranger = Keypair::new();
+
+    // STEP 1: A wrapped SOL vault holding 1 SOL, and a paused config
+    let mut data = vec![10];
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(destination.pubkey().as_ref());
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            Instruction::new_with_bytes(
+                program_id,
+                &[15],
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(native_mint, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new_readonly(spl_associated_token_account_interface::program::id(), false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &amount_instruction_data(16, 1_000_000_000),
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &data,
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[22, 1],
+                vec![AccountMeta::new_readonly(payer.pubkey(), true), AccountMeta::new(config, false)],
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // Approve, Burn, CloseAccount, CloseVault and UnwrapSol, signed by `admin`
+    let drains = |admin: &Pubkey| {
+        let gate = vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+        ];
+        let with_gate = |mut accounts: Vec<AccountMeta>| {
+            accounts.extend(gate.iter().cloned());
+            accounts
+        };
+        vec![
+            Instruction::new_with_bytes(
+                program_id,
+                &amount_instruction_data(2, u64::MAX),
+                with_gate(vec![
+                    AccountMeta::new(source.pubkey(), false),
+                    AccountMeta::new_readonly(mint.pubkey(), false),
+                    AccountMeta::new_readonly(*admin, false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ]),
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &amount_instruction_data(4, 10_000),
+                with_gate(vec![
+                    AccountMeta::new(source.pubkey(), false),
+                    AccountMeta::new(mint.pubkey(), false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ]),
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[5],
+                with_gate(vec![
+                    AccountMeta::new(source.pubkey(), false),
+                    AccountMeta::new(*admin, false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ]),
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[8],
+                with_gate(vec![
+                    AccountMeta::new(source.pubkey(), false),
+                    AccountMeta::new_readonly(mint.pubkey(), false),
+                    AccountMeta::new(destination.pubkey(), false),
+                    AccountMeta::new(*admin, false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ])
+                .into_iter()
+                .chain([AccountMeta::new_readonly(allowlist, false)])
+                .collect(),
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[17],
+                with_gate(vec![
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new(*admin, false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ])
+                .into_iter()
+                .chain([AccountMeta::new_readonly(allowlist, false)])
+                .collect(),
+            ),
+        ]
+    };
+
+    // STEP 2: Someone other than the admin can't use them at all, and even
+    // the admin can't while transfers are paused
+    for (admin, error) in [(&stranger, TransferError::NotAdmin), (&payer, TransferError::Paused)] {
+        for instruction in drains(&admin.pubkey()) {
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&payer.pubkey()),
+                &[&payer, admin],
+                recent_blockhash,
+            );
+            let result = banks_client.process_transaction(transaction).await;
+            assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+            );
+        }
+    }
+
+    let account = token_account(&banks_client, source.pubkey()).await;
+    assert_eq!((account.amount, account.delegate), (10_000, None.into()));
+    assert_eq!(token_account(&banks_client, vault).await.amount, 1_000_000_000);
+}
+
+#[tokio::test]
+async fn daily_cap_rolls_over() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let (mint, source, destination) = setup_accounts(
+        &context.banks_client,
+        &payer,
+        context.last_blockhash,
+        &spl_token::id(),
+        &authority,
+        10_000,
+    )
+    .await;
+
+    let transfer = |amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(0, amount),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+
+    // STEP 1: A config (no fee) allowing 1,000 tokens a day
+    let mut data = vec![10];
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(destination.pubkey().as_ref());
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            Instruction::new_with_bytes(
+                program_id,
+                &data,
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &amount_instruction_data(25, 1_000),
+                vec![AccountMeta::new_readonly(payer.pubkey(), true), AccountMeta::new(config, false)],
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: 600 fits, 500 more would go over, 400 fills the day exactly
+    for (amount, error) in [
+        (600, None),
+        (500, Some(TransferError::DailyCapExceeded)),
+        (400, None),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[transfer(amount)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            context.last_blockhash,
+        );
+        let result = context.banks_client.process_transaction(transaction).await;
+        match error {
+            None => result.unwrap(),
+            Some(error) => assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+            ),
+        }
+    }
+
+    // STEP 3: A day later the window starts over
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += DAY_SECONDS;
+    context.set_sysvar(&clock);
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(500)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = context.banks_client.get_account(config).await.unwrap().unwrap();
+    let state = TransferConfig::try_from_slice(&account.data).unwrap();
+    assert_eq!((state.window_start, state.window_total), (clock.unix_timestamp, 500));
+    assert_eq!(token_account(&context.banks_client, destination.pubkey()).await.amount, 1_500);
+}
+
+#[tokio::test]
+async fn destination_allowlist() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+
+    // STEP 1: A second destination for the same mint, and a config (no fee)
+    let listed = Keypair::new();
+    let mut data = vec![10];
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(destination.pubkey().as_ref());
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &listed.pubkey(),
+                Rent::default().minimum_balance(Account::LEN),
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(&spl_token::id(), &listed.pubkey(), &mint.pubkey(), &payer.pubkey())
+                .unwrap(),
+            Instruction::new_with_bytes(
+                program_id,
+                &data,
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &listed],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let transfer = |destination: &Pubkey, amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(0, amount),
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(*destination, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            ],
+        )
+    };
+    // AllowDestination (23) and DisallowDestination (24) data: variant, then the destination
+    let allow = |admin: &Pubkey, destination: &Pubkey| {
+        let mut data = vec![23];
+        data.extend_from_slice(destination.as_ref());
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(*admin, true),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new(allowlist, false),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )
+    };
+    let disallow = |destination: &Pubkey| {
+        let mut data = vec![24];
+        data.extend_from_slice(destination.as_ref());
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new(allowlist, false),
+            ],
+        )
+    };
+    let not_allowed = TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(TransferError::DestinationNotAllowed as u32),
+    );
+
+    // STEP 2: Without an allowlist any destination works
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&destination.pubkey(), 500)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 3: Only the admin can add destinations
+    let stranger = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[allow(&stranger.pubkey(), &stranger.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(TransferError::NotAdmin as u32))
+    );
+
+    // STEP 4: Once one destination is listed, the others are rejected
+    let transaction = Transaction::new_signed_with_payer(
+        &[allow(&payer.pubkey(), &listed.pubkey()), transfer(&listed.pubkey(), 300)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&destination.pubkey(), 600)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(result.unwrap_err().unwrap(), not_allowed);
+
+    // STEP 5: Listing and then removing the first destination again
+    let transaction = Transaction::new_signed_with_payer(
+        &[allow(&payer.pubkey(), &destination.pubkey()), transfer(&destination.pubkey(), 700)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[disallow(&destination.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(&destination.pubkey(), 800)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(result.unwrap_err().unwrap(), not_allowed);
+
+    // The allowlist shrank back to its single entry
+    let account = banks_client.get_account(allowlist).await.unwrap().unwrap();
+    let list = Allowlist::try_from_slice(&account.data).unwrap();
+    assert_eq!(list.destinations, vec![listed.pubkey()]);
+    assert_eq!(account.data.len(), Allowlist::size_for(1));
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 1_200);
+    assert_eq!(token_account(&banks_client, listed.pubkey()).await.amount, 300);
+
+    // STEP 6: CloseVault can't send the remaining balance around the list either
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &[8],
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new(payer.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new_readonly(allowlist, false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(result.unwrap_err().unwrap(), not_allowed);
+}
+
+#[tokio::test]
+async fn vesting_create_and_claim() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    // STEP 1: The payer holds the tokens and is also the beneficiary
+    // `funder_token` is the payer's funded account, `claim_to` an empty one
+    let (mint, funder_token, claim_to) = setup_accounts(
+        &context.banks_client,
+        &payer,
+        context.last_blockhash,
+        &spl_token::id(),
+        &payer.pubkey(),
+        10_000,
+    )
+    .await;
+    let (vesting_pda, _) = Pubkey::find_program_address(
+        &[b"vesting", payer.pubkey().as_ref(), mint.pubkey().as_ref()],
+        &program_id,
+    );
+    let vault = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &vault.pubkey(),
+                Rent::default().minimum_balance(Account::LEN),
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(&spl_token::id(), &vault.pubkey(), &mint.pubkey(), &vesting_pda)
+                .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &vault],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Vest 10,000 tokens over 1,000 seconds with a 100 second cliff
+    let start = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let mut data = vec![13];
+    data.extend_from_slice(payer.pubkey().as_ref());
+    data.extend_from_slice(&10_000u64.to_le_bytes());
+    data.extend_from_slice(&start.to_le_bytes());
+    data.extend_from_slice(&100i64.to_le_bytes());
+    data.extend_from_slice(&1_000i64.to_le_bytes());
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(funder_token.pubkey(), false),
+                AccountMeta::new(vault.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(vesting_pda, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&context.banks_client, vault.pubkey()).await.amount, 10_000);
+
+    let claim = Instruction::new_with_bytes(
+        program_id,
+        &[14],
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(vault.pubkey(), false),
+            AccountMeta::new(claim_to.pubkey(), false),
+            AccountMeta::new_readonly(mint.pubkey(), false),
+            AccountMeta::new(vesting_pda, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+        ],
+    );
+
+    // STEP 3: Nothing is claimable before the cliff
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&claim),
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(TransferError::NothingToClaim as u32))
+    );
+
+    // STEP 4: Halfway through, half has vested; at the end, the rest
+    for (elapsed, expected) in [(500, 5_000), (2_000, 10_000)] {
+        let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp = start + elapsed;
+        context.set_sysvar(&clock);
+
+        let blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            std::slice::from_ref(&claim),
+            Some(&payer.pubkey()),
+            &[&payer],
+            blockhash,
+        );
+        context.banks_client.process_transaction(transaction).await.unwrap();
+        assert_eq!(token_account(&context.banks_client, claim_to.pubkey()).await.amount, expected);
+    }
+
+    let account = context.banks_client.get_account(vesting_pda).await.unwrap().unwrap();
+    let vesting = Vesting::try_from_slice(&account.data).unwrap();
+    assert_eq!((vesting.claimed, vesting.total), (10_000, 10_000));
+}
+
+/// Nothing unlocks before the cliff, then the amount grows linearly
+#[test]
+fn vesting_schedule() {
+    assert_eq!(unlocked(1_000, 0, 100, 1_000), 0);
+    assert_eq!(unlocked(1_000, 99, 100, 1_000), 0);
+    // Past the cliff, what vested during it is released at once
+    assert_eq!(unlocked(1_000, 100, 100, 1_000), 100);
+    assert_eq!(unlocked(1_000, 500, 100, 1_000), 500);
+    assert_eq!(unlocked(1_000, 1_000, 100, 1_000), 1_000);
+    assert_eq!(unlocked(1_000, 5_000, 100, 1_000), 1_000);
+    assert_eq!(unlocked(1_000, -5, 0, 1_000), 0);
+}
+
+#[tokio::test]
+async fn wrap_and_unwrap_sol() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let native_mint = spl_token::native_mint::id();
+    let vault = get_associated_token_address_with_program_id(&authority, &native_mint, &spl_token::id());
+    let wrap = |amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(16, amount),
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )
+    };
+
+    // Create the vault and wrap 2 SOL in one transaction, then 1 more SOL
+    let create = Instruction::new_with_bytes(
+        program_id,
+        &[15],
+        vec![
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(native_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account_interface::program::id(), false),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+    );
+    for (instructions, expected) in [
+        (vec![create, wrap(2_000_000_000)], 2_000_000_000),
+        (vec![wrap(1_000_000_000)], 3_000_000_000),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let account = token_account(&banks_client, vault).await;
+        assert_eq!((account.owner, account.amount), (authority, expected));
+    }
+
+    // Unwrapping closes the vault; the recipient gets the SOL and the rent
+    let vault_lamports = banks_client.get_balance(vault).await.unwrap();
+    assert_eq!(vault_lamports, 3_000_000_000 + Rent::default().minimum_balance(Account::LEN));
+    let recipient = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &[17],
+            vec![
+                AccountMeta::new(vault, false),
+                AccountMeta::new(recipient, false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(payer.pubkey(), true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new_readonly(allowlist, false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert!(banks_client.get_account(vault).await.unwrap().is_none());
+    assert_eq!(banks_client.get_balance(recipient).await.unwrap(), vault_lamports);
+}
+
+#[tokio::test]
+async fn per_user_vault_deposit_and_withdraw() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    // The payer is the user here: both token accounts are theirs
+    let (mint, wallet, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &payer.pubkey(), 10_000).await;
+    let (user_authority, _) = Pubkey::find_program_address(&[b"authority", payer.pubkey().as_ref()], &program_id);
+    let vault = get_associated_token_address_with_program_id(&user_authority, &mint.pubkey(), &spl_token::id());
+
+    // STEP 1: The first deposit creates the vault, the second reuses it
+    for (amount, expected) in [(4_000, 4_000), (1_000, 5_000)] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &amount_instruction_data(18, amount),
+                vec![
+                    AccountMeta::new(payer.pubkey(), true),
+                    AccountMeta::new(wallet.pubkey(), false),
+                    AccountMeta::new_readonly(mint.pubkey(), false),
+                    AccountMeta::new(vault, false),
+                    AccountMeta::new_readonly(user_authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new_readonly(spl_associated_token_account_interface::program::id(), false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let account = token_account(&banks_client, vault).await;
+        assert_eq!((account.owner, account.amount), (user_authority, expected));
+    }
+
+    let withdraw = |user: &Pubkey, amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(19, amount),
+            vec![
+                AccountMeta::new_readonly(*user, true),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(user_authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        )
+    };
+
+    // STEP 2: Someone else can't withdraw from the payer's vault
+    let other_user = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[withdraw(&other_user.pubkey(), 1_500)],
+        Some(&payer.pubkey()),
+        &[&payer, &other_user],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+
+    // STEP 3: The payer withdraws with their own signature
+    let transaction = Transaction::new_signed_with_payer(
+        &[withdraw(&payer.pubkey(), 1_500)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(token_account(&banks_client, vault).await.amount, 3_500);
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 1_500);
+    assert_eq!(token_account(&banks_client, wallet.pubkey()).await.amount, 5_000);
+}
+
+#[tokio::test]
+async fn transfer_with_memo() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &authority, 10_000).await;
+
+    // Format: [20][amount: u64 LE][memo length: u32 LE][memo bytes]
+    let transfer = |memo: &str, memo_program: Pubkey| {
+        let mut data = amount_instruction_data(20, 1_000);
+        data.extend_from_slice(&(memo.len() as u32).to_le_bytes());
+        data.extend_from_slice(memo.as_bytes());
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new_readonly(memo_program, false),
+            ],
+        )
+    };
+
+    // STEP 1: An empty memo and a look-alike memo program are rejected
+    for (instruction, expected) in [
+        (
+            transfer("", MEMO_PROGRAM_ID),
+            InstructionError::Custom(TransferError::InvalidMemo as u32),
+        ),
+        (transfer("invoice #42", Pubkey::new_unique()), InstructionError::IncorrectProgramId),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(result.unwrap_err().unwrap(), TransactionError::InstructionError(0, expected));
+    }
+
+    // STEP 2: With a memo the PDA signs, the transfer goes through
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer("invoice #42", MEMO_PROGRAM_ID)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 1_000);
+}
+
+#[tokio::test]
+async fn multisig_transfer() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // A 2-of-2 multisig between the PDA and a cosigner owns the source
+    let multisig = Keypair::new();
+    let cosigner = Keypair::new();
+    let (mint, source, destination) =
+        setup_accounts(&banks_client, &payer, recent_blockhash, &spl_token::id(), &multisig.pubkey(), 10_000).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &multisig.pubkey(),
+                Rent::default().minimum_balance(Multisig::LEN),
+                Multisig::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_multisig(
+                &spl_token::id(),
+                &multisig.pubkey(),
+                &[&authority, &cosigner.pubkey()],
+                2,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &multisig],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let transfer = |signers: Vec<AccountMeta>| {
+        let mut accounts = vec![
+            AccountMeta::new(source.pubkey(), false),
+            AccountMeta::new_readonly(mint.pubkey(), false),
+            AccountMeta::new(destination.pubkey(), false),
+            AccountMeta::new_readonly(multisig.pubkey(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(allowlist, false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+        ];
+        accounts.extend(signers);
+        Instruction::new_with_bytes(program_id, &amount_instruction_data(27, 4_000), accounts)
+    };
+
+    // STEP 1: The program refuses to relay a transfer the PDA does not sign,
+    // and the token program refuses one the cosigner has not signed
+    for (signers, keypairs, expected) in [
+        (
+            vec![AccountMeta::new_readonly(cosigner.pubkey(), true)],
+            vec![&payer, &cosigner],
+            InstructionError::Custom(TransferError::MissingAuthoritySigner as u32),
+        ),
+        (
+            vec![AccountMeta::new_readonly(authority, false)],
+            vec![&payer],
+            InstructionError::MissingRequiredSignature,
+        ),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[transfer(signers)],
+            Some(&payer.pubkey()),
+            &keypairs,
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(result.unwrap_err().unwrap(), TransactionError::InstructionError(0, expected));
+    }
+
+    // STEP 2: With the cosigner's signature alongside the PDA's, it goes through
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer(vec![
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(cosigner.pubkey(), true),
+        ])],
+        Some(&payer.pubkey()),
+        &[&payer, &cosigner],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(
+        TransferReturnData::try_from_slice(&return_data.data).unwrap(),
+        TransferReturnData { amount: 4_000, remaining: 6_000, decimals: 9, fee: 0 }
+    );
+    assert_eq!(token_account(&banks_client, destination.pubkey()).await.amount, 4_000);
+}
+
+#[tokio::test]
+async fn faucet_cooldown() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("TransferTokens11111111111111111111111111111").unwrap();
+    let (authority, _) = Pubkey::find_program_address(&[b"authority"], &program_id);
+    let (faucet_authority, _) = Pubkey::find_program_address(&[b"faucet_vault"], &program_id);
+    let (config, _) = Pubkey::find_program_address(&[b"config"], &program_id);
+    let (allowlist, _) = Pubkey::find_program_address(&[b"allowlist"], &program_id);
+    let program_test = ProgramTest::new(
+        "spl_example_transfer_tokens",
+        program_id,
+        processor!(process_instruction),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let (mint, source, destination) = setup_accounts(
+        &context.banks_client,
+        &payer,
+        context.last_blockhash,
+        &spl_token::id(),
+        &faucet_authority,
+        10_000,
+    )
+    .await;
+
+    // The payer requests for itself, so the destination is its own account
+    let (cooldown, _) = Pubkey::find_program_address(&[b"faucet", payer.pubkey().as_ref()], &program_id);
+    let request = |amount: u64| {
+        Instruction::new_with_bytes(
+            program_id,
+            &amount_instruction_data(29, amount),
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(cooldown, false),
+                AccountMeta::new(source.pubkey(), false),
+                AccountMeta::new_readonly(mint.pubkey(), false),
+                AccountMeta::new(destination.pubkey(), false),
+                AccountMeta::new_readonly(faucet_authority, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(allowlist, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )
+    };
+    let set_faucet = |amount: u64, cooldown: u64| {
+        let mut data = amount_instruction_data(28, amount);
+        data.extend_from_slice(&cooldown.to_le_bytes());
+        Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![AccountMeta::new_readonly(payer.pubkey(), true), AccountMeta::new(config, false)],
+        )
+    };
+    let expect_error = |result: Result<(), solana_program_test::BanksClientError>, error: TransferError| {
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    };
+
+    // STEP 1: Until the admin turns it on, the faucet gives nothing
+    let mut data = vec![10];
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(destination.pubkey().as_ref());
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(payer.pubkey(), true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[request(100)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    expect_error(context.banks_client.process_transaction(transaction).await, TransferError::FaucetDisabled);
+
+    // STEP 2: At 500 tokens every 100 slots, a larger request is refused
+    // and the first one in range goes through
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_faucet(500, 100)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    for (amount, error) in [(501, Some(TransferError::AboveFaucetLimit)), (500, None)] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[request(amount)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            context.last_blockhash,
+        );
+        let result = context.banks_client.process_transaction(transaction).await;
+        match error {
+            None => result.unwrap(),
+            Some(error) => expect_error(result, error),
+        }
+    }
+    assert_eq!(token_account(&context.banks_client, destination.pubkey()).await.amount, 500);
+
+    // STEP 3: Asking again before the cooldown is over fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[request(200)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    expect_error(context.banks_client.process_transaction(transaction).await, TransferError::FaucetCooldown);
+
+    // Nor can a plain Transfer get around it: the faucet's account isn't the
+    // program authority's, and the program won't sign as the faucet for it
+    for (authority, expected) in [
+        (authority, InstructionError::Custom(spl_token::error::TokenError::OwnerMismatch as u32)),
+        (faucet_authority, InstructionError::InvalidSeeds),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[Instruction::new_with_bytes(
+                program_id,
+                &[1],
+                vec![
+                    AccountMeta::new(source.pubkey(), false),
+                    AccountMeta::new_readonly(mint.pubkey(), false),
+                    AccountMeta::new(destination.pubkey(), false),
+                    AccountMeta::new_readonly(authority, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new(config, false),
+                    AccountMeta::new_readonly(allowlist, false),
+                    AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                ],
+            )],
+            Some(&payer.pubkey()),
+            &[&payer],
+            context.last_blockhash,
+        );
+        let result = context.banks_client.process_transaction(transaction).await;
+        assert_eq!(result.unwrap_err().unwrap(), TransactionError::InstructionError(0, expected));
+    }
+
+    // STEP 4: 100 slots later the wallet can draw again
+    let account = context.banks_client.get_account(cooldown).await.unwrap().unwrap();
+    let state = FaucetCooldown::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.requester, payer.pubkey());
+    context.warp_to_slot(state.last_slot + 100).unwrap();
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[request(200)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(token_account(&context.banks_client, destination.pubkey()).await.amount, 700);
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+use counter::instructions::CounterInstructions;
+use libfuzzer_sys::fuzz_target;
+
+// Any byte string must either parse or return an error, never panic
+fuzz_target!(|data: &[u8]| {
+    let _ = CounterInstructions::unpack(data);
+});
@@ -19,10 +19,16 @@ impl CounterInstructions {
             .split_first()
             .ok_or(ProgramError::InvalidInstructionData)?;
 
+        // Malformed payloads are the caller's mistake, so they come back as
+        // InvalidInstructionData instead of panicking the program
+        let args = || {
+            CounterArgs::try_from_slice(rest).map_err(|_| ProgramError::InvalidInstructionData)
+        };
+
         Ok(match variant {
-            0 => Self::Increment(CounterArgs::try_from_slice(rest).unwrap()),
-            1 => Self::Decrement(CounterArgs::try_from_slice(rest).unwrap()),
-            2 => Self::Update(CounterArgs::try_from_slice(rest).unwrap()),
+            0 => Self::Increment(args()?),
+            1 => Self::Decrement(args()?),
+            2 => Self::Update(args()?),
             3 => Self::Reset,
             _ => return Err(ProgramError::InvalidInstructionData),
         })
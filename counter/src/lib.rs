@@ -1,7 +1,6 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
     entrypoint::ProgramResult,
     msg,
     pubkey::Pubkey,
@@ -16,7 +15,9 @@ pub struct CounterAccount {
     pub counter: u32,
 }
 
-entrypoint!(process_instruction);
+// Skipped with the "no-entrypoint" feature so other programs can depend on this crate
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
 pub fn process_instruction(
     _program_id: &Pubkey,
@@ -59,7 +60,8 @@ pub fn process_instruction(
 #[cfg(test)]
 mod test {
     use super::*;
-    use solana_program::{clock::Epoch, pubkey::Pubkey};
+    use solana_program::program_error::ProgramError;
+    use solana_program::pubkey::Pubkey;
     use std::mem;
 
     #[test]
@@ -131,4 +133,15 @@ mod test {
             0
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_unpack_malformed_instruction() {
+        // Empty input, a truncated u32, and an unknown variant are all rejected
+        for data in [&[][..], &[0, 1, 2][..], &[2][..], &[4][..]] {
+            assert_eq!(
+                CounterInstructions::unpack(data).err(),
+                Some(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+}
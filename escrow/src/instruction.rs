@@ -0,0 +1,49 @@
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+
+// Define the instructions this program can handle
+// Each instruction represents an action users can perform
+pub enum EscrowInstruction {
+    // Maker deposits `amount_a` of token A and asks `amount_b` of token B for it
+    InitEscrow {
+        amount_a: u64, // Token A deposited into the vault (raw units)
+        amount_b: u64, // Token B the maker wants in return (raw units)
+    },
+    // Taker pays the maker's price in token B and receives the token A deposit
+    Exchange,
+    // Maker takes back the token A deposit and closes the escrow
+    Cancel,
+}
+
+// Internal structure for deserializing InitEscrow instruction data
+#[derive(BorshDeserialize)]
+struct InitEscrowPayload {
+    amount_a: u64,
+    amount_b: u64,
+}
+
+impl EscrowInstruction {
+    // Deserialize instruction data from bytes into an EscrowInstruction enum
+    // Instruction format: [variant_byte][borsh_serialized_payload]
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        // First byte indicates which instruction variant
+        // (0 = InitEscrow, 1 = Exchange, 2 = Cancel)
+        let (&variant, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        Ok(match variant {
+            0 => {
+                let payload = InitEscrowPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::InitEscrow {
+                    amount_a: payload.amount_a,
+                    amount_b: payload.amount_b,
+                }
+            }
+            1 => Self::Exchange,
+            2 => Self::Cancel,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+}
@@ -0,0 +1,402 @@
+// Module declarations - organize code into separate files
+pub mod instruction;  // Instruction parsing and types
+pub mod state;        // Account state structures and errors
+
+use crate::instruction::EscrowInstruction;
+use crate::state::{Escrow, EscrowError};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,  // Macro for logging on-chain
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::rent::Rent,
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token::{
+    instruction::{close_account, transfer_checked},
+    state::{Account, Mint},
+};
+
+// Define the program entrypoint
+// Skipped with the "no-entrypoint" feature so other programs can depend on this crate
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// Main entry point for all instructions sent to this program
+pub fn process_instruction(
+    program_id: &Pubkey,         // This program's ID
+    accounts: &[AccountInfo],    // Accounts required by the instruction
+    instruction_data: &[u8],     // Serialized instruction data
+) -> ProgramResult {
+    // Deserialize instruction data to determine which action to perform
+    let instruction = EscrowInstruction::unpack(instruction_data)?;
+
+    // Route to the appropriate handler based on instruction type
+    match instruction {
+        EscrowInstruction::InitEscrow { amount_a, amount_b } => {
+            init_escrow(program_id, accounts, amount_a, amount_b)
+        }
+        EscrowInstruction::Exchange => exchange(program_id, accounts),
+        EscrowInstruction::Cancel => cancel(program_id, accounts),
+    }
+}
+
+// Handler for making an offer
+// Creates the escrow PDA and moves the maker's token A into a vault owned by it
+pub fn init_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount_a: u64,   // Token A to deposit
+    amount_b: u64,   // Token B asked in return
+) -> ProgramResult {
+    msg!("Creating escrow: {} token A for {} token B", amount_a, amount_b);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let maker = next_account_info(account_info_iter)?;          // Maker (signer, pays rent)
+    let maker_token_a = next_account_info(account_info_iter)?;  // Maker's token A account to deposit from
+    let maker_token_b = next_account_info(account_info_iter)?;  // Maker's token B account to be paid into
+    let vault = next_account_info(account_info_iter)?;          // Token A account owned by the escrow PDA
+    let mint_a = next_account_info(account_info_iter)?;         // Mint of the deposited token
+    let mint_b = next_account_info(account_info_iter)?;         // Mint of the token asked in return
+    let escrow_account = next_account_info(account_info_iter)?; // Escrow PDA to create
+    let token_program = next_account_info(account_info_iter)?;  // SPL Token program
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+
+    // Verify the maker has signed the transaction
+    if !maker.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !spl_token::check_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if amount_a == 0 || amount_b == 0 {
+        return Err(EscrowError::InvalidAmount.into());
+    }
+
+    // One open escrow per (maker, mint A, mint B)
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[b"escrow", maker.key.as_ref(), mint_a.key.as_ref(), mint_b.key.as_ref()],
+        program_id,
+    );
+    if pda != *escrow_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(EscrowError::InvalidPDA.into());
+    }
+    if escrow_account.lamports() > 0 {
+        return Err(EscrowError::AlreadyInitialized.into());
+    }
+
+    // The vault must hold token A and be controlled by the escrow PDA,
+    // otherwise the program could never sign the deposit out
+    let vault_state = Account::unpack(&vault.try_borrow_data()?)?;
+    if vault_state.owner != pda || vault_state.mint != *mint_a.key {
+        return Err(EscrowError::InvalidVault.into());
+    }
+    // The payment account must take token B, or no taker could ever pay
+    let maker_token_b_state = Account::unpack(&maker_token_b.try_borrow_data()?)?;
+    if maker_token_b_state.mint != *mint_b.key {
+        return Err(EscrowError::InvalidTokenAccount.into());
+    }
+    let decimals = Mint::unpack(&mint_a.try_borrow_data()?)?.decimals;
+
+    // Create the escrow PDA via CPI to System Program
+    // Note: In tests, Rent::get() may fail with UnsupportedSysvar
+    // Using Rent::default() provides standard rent parameters
+    let rent_lamports = Rent::default().minimum_balance(Escrow::LEN);
+    invoke_signed(
+        &system_instruction::create_account(
+            maker.key,
+            escrow_account.key,
+            rent_lamports,
+            Escrow::LEN as u64,
+            program_id,
+        ),
+        &[maker.clone(), escrow_account.clone(), system_program.clone()],
+        &[&[
+            b"escrow",
+            maker.key.as_ref(),
+            mint_a.key.as_ref(),
+            mint_b.key.as_ref(),
+            &[bump_seed],
+        ]],
+    )?;
+
+    // Move the deposit into the vault; the maker signs for their own account
+    invoke(
+        &transfer_checked(
+            token_program.key,
+            maker_token_a.key,
+            mint_a.key,
+            vault.key,
+            maker.key,
+            &[],
+            amount_a,
+            decimals,
+        )?,
+        &[
+            maker_token_a.clone(),
+            mint_a.clone(),
+            vault.clone(),
+            maker.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    let escrow = Escrow {
+        is_initialized: true,
+        maker: *maker.key,
+        mint_a: *mint_a.key,
+        mint_b: *mint_b.key,
+        vault: *vault.key,
+        maker_receive: *maker_token_b.key,
+        amount_a,
+        amount_b,
+        bump: bump_seed,
+    };
+    escrow.serialize(&mut &mut escrow_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for taking an offer
+// The taker pays token B to the maker and receives the token A deposit in
+// the same instruction, so either both legs happen or neither does
+pub fn exchange(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Exchanging escrow...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let taker = next_account_info(account_info_iter)?;          // Taker (signer)
+    let taker_token_b = next_account_info(account_info_iter)?;  // Taker's token B account to pay from
+    let taker_token_a = next_account_info(account_info_iter)?;  // Taker's token A account to receive the deposit
+    let maker = next_account_info(account_info_iter)?;          // Maker (receives the rent)
+    let maker_token_b = next_account_info(account_info_iter)?;  // Maker's token B account recorded in the escrow
+    let vault = next_account_info(account_info_iter)?;          // Vault holding the deposit
+    let mint_a = next_account_info(account_info_iter)?;         // Mint of the deposited token
+    let mint_b = next_account_info(account_info_iter)?;         // Mint of the token asked in return
+    let escrow_account = next_account_info(account_info_iter)?; // Escrow PDA
+    let token_program = next_account_info(account_info_iter)?;  // SPL Token program
+
+    if !taker.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !spl_token::check_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let escrow = load_escrow(program_id, escrow_account, maker, mint_a, mint_b)?;
+    if escrow.vault != *vault.key {
+        return Err(EscrowError::InvalidVault.into());
+    }
+    if escrow.maker_receive != *maker_token_b.key {
+        return Err(EscrowError::InvalidTokenAccount.into());
+    }
+
+    // Leg 1: the taker pays the maker in token B
+    let decimals_b = Mint::unpack(&mint_b.try_borrow_data()?)?.decimals;
+    invoke(
+        &transfer_checked(
+            token_program.key,
+            taker_token_b.key,
+            mint_b.key,
+            maker_token_b.key,
+            taker.key,
+            &[],
+            escrow.amount_b,
+            decimals_b,
+        )?,
+        &[
+            taker_token_b.clone(),
+            mint_b.clone(),
+            maker_token_b.clone(),
+            taker.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    // Leg 2: the escrow PDA releases the deposit to the taker
+    // The whole vault goes, so the vault can be closed even if someone sent
+    // extra tokens to it
+    let deposit = Account::unpack(&vault.try_borrow_data()?)?.amount;
+    let decimals_a = Mint::unpack(&mint_a.try_borrow_data()?)?.decimals;
+    let seeds: &[&[u8]] = &[
+        b"escrow",
+        maker.key.as_ref(),
+        mint_a.key.as_ref(),
+        mint_b.key.as_ref(),
+        &[escrow.bump],
+    ];
+    invoke_signed(
+        &transfer_checked(
+            token_program.key,
+            vault.key,
+            mint_a.key,
+            taker_token_a.key,
+            escrow_account.key,
+            &[],
+            deposit,
+            decimals_a,
+        )?,
+        &[
+            vault.clone(),
+            mint_a.clone(),
+            taker_token_a.clone(),
+            escrow_account.clone(),
+            token_program.clone(),
+        ],
+        &[seeds],
+    )?;
+
+    close_escrow(escrow_account, vault, maker, token_program, seeds)?;
+
+    msg!("Swapped {} token A for {} token B", deposit, escrow.amount_b);
+
+    Ok(())
+}
+
+// Handler for withdrawing an offer nobody took
+// Returns the deposit to the maker and closes the escrow
+pub fn cancel(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Cancelling escrow...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let maker = next_account_info(account_info_iter)?;          // Maker (signer, receives the rent)
+    let maker_token_a = next_account_info(account_info_iter)?;  // Maker's token A account to refund into
+    let vault = next_account_info(account_info_iter)?;          // Vault holding the deposit
+    let mint_a = next_account_info(account_info_iter)?;         // Mint of the deposited token
+    let mint_b = next_account_info(account_info_iter)?;         // Mint of the token asked in return
+    let escrow_account = next_account_info(account_info_iter)?; // Escrow PDA
+    let token_program = next_account_info(account_info_iter)?;  // SPL Token program
+
+    if !maker.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !spl_token::check_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // The maker's key is part of the seeds, so only the maker can cancel
+    let escrow = load_escrow(program_id, escrow_account, maker, mint_a, mint_b)?;
+    if escrow.vault != *vault.key {
+        return Err(EscrowError::InvalidVault.into());
+    }
+
+    let deposit = Account::unpack(&vault.try_borrow_data()?)?.amount;
+    let decimals = Mint::unpack(&mint_a.try_borrow_data()?)?.decimals;
+    let seeds: &[&[u8]] = &[
+        b"escrow",
+        maker.key.as_ref(),
+        mint_a.key.as_ref(),
+        mint_b.key.as_ref(),
+        &[escrow.bump],
+    ];
+    invoke_signed(
+        &transfer_checked(
+            token_program.key,
+            vault.key,
+            mint_a.key,
+            maker_token_a.key,
+            escrow_account.key,
+            &[],
+            deposit,
+            decimals,
+        )?,
+        &[
+            vault.clone(),
+            mint_a.clone(),
+            maker_token_a.clone(),
+            escrow_account.clone(),
+            token_program.clone(),
+        ],
+        &[seeds],
+    )?;
+
+    close_escrow(escrow_account, vault, maker, token_program, seeds)?;
+
+    msg!("Returned {} token A to the maker", deposit);
+
+    Ok(())
+}
+
+// Read an escrow, checking it is the initialized escrow PDA of this maker
+// and mint pair
+fn load_escrow(
+    program_id: &Pubkey,
+    escrow_account: &AccountInfo,
+    maker: &AccountInfo,
+    mint_a: &AccountInfo,
+    mint_b: &AccountInfo,
+) -> Result<Escrow, ProgramError> {
+    if escrow_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let escrow = Escrow::try_from_slice(&escrow_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if !escrow.is_initialized() {
+        return Err(EscrowError::UninitializedAccount.into());
+    }
+
+    // Re-derive the PDA from the stored bump
+    let pda = Pubkey::create_program_address(
+        &[
+            b"escrow",
+            maker.key.as_ref(),
+            mint_a.key.as_ref(),
+            mint_b.key.as_ref(),
+            &[escrow.bump],
+        ],
+        program_id,
+    )
+    .map_err(|_| EscrowError::InvalidPDA)?;
+    if pda != *escrow_account.key || escrow.maker != *maker.key {
+        msg!("Invalid seeds for PDA");
+        return Err(EscrowError::InvalidPDA.into());
+    }
+
+    Ok(escrow)
+}
+
+// Close the emptied vault and the escrow PDA, refunding both rents to the maker
+fn close_escrow<'a>(
+    escrow_account: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    maker: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &close_account(token_program.key, vault.key, maker.key, escrow_account.key, &[])?,
+        &[
+            vault.clone(),
+            maker.clone(),
+            escrow_account.clone(),
+            token_program.clone(),
+        ],
+        &[seeds],
+    )?;
+
+    let refund = escrow_account.lamports();
+    **maker.try_borrow_mut_lamports()? = maker
+        .lamports()
+        .checked_add(refund)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **escrow_account.try_borrow_mut_lamports()? = 0;
+    escrow_account.data.borrow_mut().fill(0);
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+// Import Borsh traits for serializing/deserializing data to store on-chain
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;  // For creating custom error types with descriptions
+
+// The escrow account stored in the PDA derived from
+// [b"escrow", maker, mint_a, mint_b]
+// Records the maker's offer; the deposit itself sits in the vault
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Escrow {
+    pub is_initialized: bool,  // Whether this account has been initialized
+    pub maker: Pubkey,         // Wallet that made the offer
+    pub mint_a: Pubkey,        // Mint of the deposited token
+    pub mint_b: Pubkey,        // Mint of the token asked in return
+    pub vault: Pubkey,         // Token A account holding the deposit (owned by this PDA)
+    pub maker_receive: Pubkey, // Maker's token B account that receives the payment
+    pub amount_a: u64,         // Token A deposited
+    pub amount_b: u64,         // Token B asked in return
+    pub bump: u8,              // Bump seed of this PDA
+}
+
+impl Escrow {
+    // Serialized size: bool + 5 pubkeys + 2 * u64 + u8
+    pub const LEN: usize = 1 + 32 * 5 + 8 * 2 + 1;
+}
+
+// Sealed trait implementation - required by Solana's Pack trait
+impl Sealed for Escrow {}
+
+// Implement IsInitialized trait to check if account is ready to use
+impl IsInitialized for Escrow {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Custom error types for this program
+// The #[error(...)] attributes provide user-friendly error messages
+#[derive(Debug, Error)]
+pub enum EscrowError {
+    #[error("Account not initialized yet")]
+    UninitializedAccount,
+
+    #[error("PDA derived does not equal PDA passed in")]
+    InvalidPDA,
+
+    #[error("Amounts must be greater than zero")]
+    InvalidAmount,
+
+    #[error("Vault is not a token A account owned by the escrow PDA")]
+    InvalidVault,
+
+    #[error("Token account does not match the escrow")]
+    InvalidTokenAccount,
+
+    #[error("Escrow account already exists")]
+    AlreadyInitialized,
+}
+
+// Convert our custom errors into Solana's ProgramError type
+// This allows our errors to be returned from instruction handlers
+impl From<EscrowError> for ProgramError {
+    fn from(e: EscrowError) -> Self {
+        ProgramError::Custom(e as u32)  // Convert enum variant to error code
+    }
+}
@@ -0,0 +1,283 @@
+// Integration tests for the two-party token escrow program
+// These tests use solana-program-test to simulate on-chain behavior
+
+use borsh::BorshDeserialize;
+use escrow::state::{Escrow, EscrowError};
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program_test::{processor, tokio, BanksClient, ProgramTest};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_system_interface::instruction as system_instruction;
+use spl_token::state::{Account, Mint};
+
+/// Helper function to create instruction data for InitEscrow
+/// Format: [variant_byte: 0][amount_a: u64 LE][amount_b: u64 LE]
+fn init_escrow_instruction_data(amount_a: u64, amount_b: u64) -> Vec<u8> {
+    let mut data = vec![0u8]; // Variant 0 for InitEscrow
+    data.extend_from_slice(&amount_a.to_le_bytes());
+    data.extend_from_slice(&amount_b.to_le_bytes());
+    data
+}
+
+/// Mints, token accounts, and the escrow PDA shared by the tests
+struct Setup {
+    program_id: Pubkey,
+    maker: Keypair,
+    taker: Keypair,
+    mint_a: Keypair,
+    mint_b: Keypair,
+    maker_token_a: Keypair,
+    maker_token_b: Keypair,
+    taker_token_a: Keypair,
+    taker_token_b: Keypair,
+    vault: Keypair,
+    escrow_pda: Pubkey,
+}
+
+/// Create both mints and every token account, give the maker 1,000 token A
+/// and the taker 500 token B, then open an escrow offering 100 A for 40 B
+async fn setup(banks_client: &BanksClient, payer: &Keypair, recent_blockhash: Hash, program_id: Pubkey) -> Setup {
+    let setup = Setup {
+        program_id,
+        maker: Keypair::new(),
+        taker: Keypair::new(),
+        mint_a: Keypair::new(),
+        mint_b: Keypair::new(),
+        maker_token_a: Keypair::new(),
+        maker_token_b: Keypair::new(),
+        taker_token_a: Keypair::new(),
+        taker_token_b: Keypair::new(),
+        vault: Keypair::new(),
+        escrow_pda: Pubkey::default(),
+    };
+    let (escrow_pda, _) = Pubkey::find_program_address(
+        &[
+            b"escrow",
+            setup.maker.pubkey().as_ref(),
+            setup.mint_a.pubkey().as_ref(),
+            setup.mint_b.pubkey().as_ref(),
+        ],
+        &program_id,
+    );
+    let setup = Setup { escrow_pda, ..setup };
+    let rent = Rent::default();
+
+    // STEP 1: Mints (payer is the mint authority), token accounts, balances
+    let mut instructions = vec![
+        system_instruction::transfer(&payer.pubkey(), &setup.maker.pubkey(), 100_000_000),
+        system_instruction::transfer(&payer.pubkey(), &setup.taker.pubkey(), 100_000_000),
+    ];
+    for mint in [&setup.mint_a, &setup.mint_b] {
+        instructions.push(system_instruction::create_account(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ));
+        instructions.push(
+            spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer.pubkey(), None, 6).unwrap(),
+        );
+    }
+    for (account, mint, owner) in [
+        (&setup.maker_token_a, &setup.mint_a, setup.maker.pubkey()),
+        (&setup.maker_token_b, &setup.mint_b, setup.maker.pubkey()),
+        (&setup.taker_token_a, &setup.mint_a, setup.taker.pubkey()),
+        (&setup.taker_token_b, &setup.mint_b, setup.taker.pubkey()),
+        (&setup.vault, &setup.mint_a, escrow_pda), // Vault is controlled by the escrow PDA
+    ] {
+        instructions.push(system_instruction::create_account(
+            &payer.pubkey(),
+            &account.pubkey(),
+            rent.minimum_balance(Account::LEN),
+            Account::LEN as u64,
+            &spl_token::id(),
+        ));
+        instructions.push(
+            spl_token::instruction::initialize_account(&spl_token::id(), &account.pubkey(), &mint.pubkey(), &owner)
+                .unwrap(),
+        );
+    }
+    for (mint, account, amount) in [
+        (&setup.mint_a, &setup.maker_token_a, 1_000),
+        (&setup.mint_b, &setup.taker_token_b, 500),
+    ] {
+        instructions.push(
+            spl_token::instruction::mint_to(&spl_token::id(), &mint.pubkey(), &account.pubkey(), &payer.pubkey(), &[], amount)
+                .unwrap(),
+        );
+    }
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[
+            payer,
+            &setup.mint_a,
+            &setup.mint_b,
+            &setup.maker_token_a,
+            &setup.maker_token_b,
+            &setup.taker_token_a,
+            &setup.taker_token_b,
+            &setup.vault,
+        ],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: The maker offers 100 token A for 40 token B
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &init_escrow_instruction_data(100, 40),
+        vec![
+            AccountMeta::new(setup.maker.pubkey(), true),              // Maker (signer)
+            AccountMeta::new(setup.maker_token_a.pubkey(), false),     // Maker token A account
+            AccountMeta::new_readonly(setup.maker_token_b.pubkey(), false), // Maker token B account
+            AccountMeta::new(setup.vault.pubkey(), false),             // Vault
+            AccountMeta::new_readonly(setup.mint_a.pubkey(), false),   // Mint A
+            AccountMeta::new_readonly(setup.mint_b.pubkey(), false),   // Mint B
+            AccountMeta::new(escrow_pda, false),                       // Escrow PDA
+            AccountMeta::new_readonly(spl_token::id(), false),         // Token program
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false), // System program
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer, &setup.maker],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    setup
+}
+
+async fn token_balance(banks_client: &BanksClient, address: Pubkey) -> u64 {
+    let account = banks_client.get_account(address).await.unwrap().unwrap();
+    Account::unpack(&account.data).unwrap().amount
+}
+
+/// Open an escrow, then let a taker swap against it
+///
+/// 1. Checks the deposit landed in the vault and the offer was recorded
+/// 2. Swaps, checking both legs moved and both accounts were closed
+#[tokio::test]
+async fn test_init_and_exchange() {
+    // Enable Solana runtime logging to see msg!() output from the program
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "escrow",
+        program_id,
+        processor!(escrow::process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let setup = setup(&banks_client, &payer, recent_blockhash, program_id).await;
+
+    let account = banks_client.get_account(setup.escrow_pda).await.unwrap().unwrap();
+    let escrow = Escrow::try_from_slice(&account.data).unwrap();
+    assert_eq!((escrow.amount_a, escrow.amount_b), (100, 40));
+    assert_eq!(escrow.maker_receive, setup.maker_token_b.pubkey());
+    assert_eq!(token_balance(&banks_client, setup.vault.pubkey()).await, 100);
+    assert_eq!(token_balance(&banks_client, setup.maker_token_a.pubkey()).await, 900);
+
+    // STEP 3: The taker swaps
+    let maker_lamports = banks_client.get_balance(setup.maker.pubkey()).await.unwrap();
+    let instruction = Instruction::new_with_bytes(
+        setup.program_id,
+        &[1u8], // Variant 1 for Exchange
+        vec![
+            AccountMeta::new(setup.taker.pubkey(), true),            // Taker (signer)
+            AccountMeta::new(setup.taker_token_b.pubkey(), false),   // Taker token B account
+            AccountMeta::new(setup.taker_token_a.pubkey(), false),   // Taker token A account
+            AccountMeta::new(setup.maker.pubkey(), false),           // Maker
+            AccountMeta::new(setup.maker_token_b.pubkey(), false),   // Maker token B account
+            AccountMeta::new(setup.vault.pubkey(), false),           // Vault
+            AccountMeta::new_readonly(setup.mint_a.pubkey(), false), // Mint A
+            AccountMeta::new_readonly(setup.mint_b.pubkey(), false), // Mint B
+            AccountMeta::new(setup.escrow_pda, false),               // Escrow PDA
+            AccountMeta::new_readonly(spl_token::id(), false),       // Token program
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &setup.taker],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(token_balance(&banks_client, setup.taker_token_a.pubkey()).await, 100);
+    assert_eq!(token_balance(&banks_client, setup.taker_token_b.pubkey()).await, 460);
+    assert_eq!(token_balance(&banks_client, setup.maker_token_b.pubkey()).await, 40);
+
+    // The vault and the escrow PDA were closed and their rent went to the maker
+    assert!(banks_client.get_account(setup.vault.pubkey()).await.unwrap().is_none());
+    assert!(banks_client.get_account(setup.escrow_pda).await.unwrap().is_none());
+    let rent = Rent::default();
+    assert_eq!(
+        banks_client.get_balance(setup.maker.pubkey()).await.unwrap(),
+        maker_lamports + rent.minimum_balance(Account::LEN) + rent.minimum_balance(Escrow::LEN)
+    );
+}
+
+/// Only the maker can cancel, and cancelling returns the deposit
+#[tokio::test]
+async fn test_cancel() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "escrow",
+        program_id,
+        processor!(escrow::process_instruction),
+    );
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let setup = setup(&banks_client, &payer, recent_blockhash, program_id).await;
+
+    let cancel = |signer: &Pubkey, refund_to: &Pubkey| {
+        Instruction::new_with_bytes(
+            setup.program_id,
+            &[2u8], // Variant 2 for Cancel
+            vec![
+                AccountMeta::new(*signer, true),                         // Maker (signer)
+                AccountMeta::new(*refund_to, false),                     // Maker token A account
+                AccountMeta::new(setup.vault.pubkey(), false),           // Vault
+                AccountMeta::new_readonly(setup.mint_a.pubkey(), false), // Mint A
+                AccountMeta::new_readonly(setup.mint_b.pubkey(), false), // Mint B
+                AccountMeta::new(setup.escrow_pda, false),               // Escrow PDA
+                AccountMeta::new_readonly(spl_token::id(), false),       // Token program
+            ],
+        )
+    };
+
+    // STEP 3: The taker can't cancel the maker's escrow
+    let transaction = Transaction::new_signed_with_payer(
+        &[cancel(&setup.taker.pubkey(), &setup.taker_token_a.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &setup.taker],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(EscrowError::InvalidPDA as u32))
+    );
+
+    // STEP 4: The maker cancels and gets the deposit back
+    let transaction = Transaction::new_signed_with_payer(
+        &[cancel(&setup.maker.pubkey(), &setup.maker_token_a.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &setup.maker],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(token_balance(&banks_client, setup.maker_token_a.pubkey()).await, 1_000);
+    assert!(banks_client.get_account(setup.vault.pubkey()).await.unwrap().is_none());
+    assert!(banks_client.get_account(setup.escrow_pda).await.unwrap().is_none());
+}
@@ -0,0 +1,62 @@
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+// Define the instructions this program can handle
+// Each instruction represents an action users can perform
+pub enum InvoiceInstruction {
+    // Payee issues a new invoice
+    CreateInvoice {
+        invoice_id: u64,  // Payee-chosen id, part of the PDA seeds
+        payer: Pubkey,    // Wallet expected to pay
+        mint: Pubkey,     // SPL Token mint, or Pubkey::default() for SOL
+        amount: u64,      // Amount due
+        due_date: i64,    // Unix timestamp the invoice is due by
+        memo: String,     // Reference text
+    },
+    // Payer settles an open invoice
+    PayInvoice,
+    // Payee cancels an overdue, unpaid invoice
+    CancelInvoice,
+}
+
+// Internal structure for deserializing CreateInvoice instruction data
+#[derive(BorshDeserialize)]
+struct CreateInvoicePayload {
+    invoice_id: u64,
+    payer: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+    due_date: i64,
+    memo: String,
+}
+
+impl InvoiceInstruction {
+    // Deserialize instruction data from bytes into an InvoiceInstruction enum
+    // Instruction format: [variant_byte][borsh_serialized_payload]
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        // First byte indicates which instruction variant
+        // (0 = CreateInvoice, 1 = PayInvoice, 2 = CancelInvoice)
+        let (&variant, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        Ok(match variant {
+            0 => {
+                let payload = CreateInvoicePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateInvoice {
+                    invoice_id: payload.invoice_id,
+                    payer: payload.payer,
+                    mint: payload.mint,
+                    amount: payload.amount,
+                    due_date: payload.due_date,
+                    memo: payload.memo,
+                }
+            }
+            1 => Self::PayInvoice,
+            2 => Self::CancelInvoice,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+}
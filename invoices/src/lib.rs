@@ -0,0 +1,322 @@
+// Module declarations - organize code into separate files
+pub mod instruction;  // Instruction parsing and types
+pub mod state;        // Account state structures and errors
+
+use crate::instruction::InvoiceInstruction;
+use crate::state::{Invoice, InvoiceError, InvoiceStatus, MAX_MEMO_LEN};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,  // Macro for logging on-chain
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, rent::Rent, Sysvar, SysvarSerialize},
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token::{
+    instruction::transfer_checked,
+    state::{Account, Mint},
+};
+
+// Define the program entrypoint
+// Skipped with the "no-entrypoint" feature so other programs can depend on this crate
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// Main entry point for all instructions sent to this program
+pub fn process_instruction(
+    program_id: &Pubkey,         // This program's ID
+    accounts: &[AccountInfo],    // Accounts required by the instruction
+    instruction_data: &[u8],     // Serialized instruction data
+) -> ProgramResult {
+    // Deserialize instruction data to determine which action to perform
+    let instruction = InvoiceInstruction::unpack(instruction_data)?;
+
+    // Route to the appropriate handler based on instruction type
+    match instruction {
+        InvoiceInstruction::CreateInvoice {
+            invoice_id,
+            payer,
+            mint,
+            amount,
+            due_date,
+            memo,
+        } => create_invoice(program_id, accounts, invoice_id, payer, mint, amount, due_date, memo),
+        InvoiceInstruction::PayInvoice => pay_invoice(program_id, accounts),
+        InvoiceInstruction::CancelInvoice => cancel_invoice(program_id, accounts),
+    }
+}
+
+// Handler for issuing a new invoice
+// Creates the invoice PDA, funded by the payee
+// Token invoices must use an SPL Token mint: PayInvoice only moves tokens
+// through the SPL Token program, so a Token-2022 invoice could never be paid
+#[allow(clippy::too_many_arguments)]
+pub fn create_invoice(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    invoice_id: u64,   // Payee-chosen id
+    payer: Pubkey,     // Wallet expected to pay
+    mint: Pubkey,      // Token mint, or default for SOL
+    amount: u64,       // Amount due
+    due_date: i64,     // Due date (unix timestamp)
+    memo: String,      // Reference text
+) -> ProgramResult {
+    msg!("Creating invoice {} for {}", invoice_id, amount);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let payee = next_account_info(account_info_iter)?;          // Invoice issuer (signer, pays rent)
+    let invoice_account = next_account_info(account_info_iter)?; // Invoice PDA to create
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let clock_sysvar = next_account_info(account_info_iter)?;   // Clock sysvar to validate the due date
+    // Token invoices only: the invoice mint
+    let mint_info = if mint == Pubkey::default() { None } else { Some(next_account_info(account_info_iter)?) };
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    if !payee.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if amount == 0 {
+        return Err(InvoiceError::InvalidAmount.into());
+    }
+    if memo.len() > MAX_MEMO_LEN {
+        return Err(InvoiceError::MemoTooLong.into());
+    }
+    if let Some(mint_info) = mint_info {
+        if *mint_info.key != mint {
+            return Err(InvoiceError::InvalidTokenAccount.into());
+        }
+        if !spl_token::check_id(mint_info.owner) {
+            msg!("Mint {} is owned by {}, not the SPL Token program", mint_info.key, mint_info.owner);
+            return Err(InvoiceError::UnsupportedTokenProgram.into());
+        }
+        Mint::unpack(&mint_info.try_borrow_data()?)?;
+    }
+    // The clock is read without Clock::get(), which may fail with
+    // UnsupportedSysvar in tests
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    if due_date <= now {
+        return Err(InvoiceError::InvalidDueDate.into());
+    }
+
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[b"invoice", payee.key.as_ref(), &invoice_id.to_le_bytes()],
+        program_id,
+    );
+    if pda != *invoice_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(InvoiceError::InvalidPDA.into());
+    }
+
+    let rent_lamports = load_rent(rent_sysvar)?.minimum_balance(Invoice::LEN);
+    invoke_signed(
+        &system_instruction::create_account(
+            payee.key,
+            invoice_account.key,
+            rent_lamports,
+            Invoice::LEN as u64,
+            program_id,
+        ),
+        &[payee.clone(), invoice_account.clone(), system_program.clone()],
+        &[&[b"invoice", payee.key.as_ref(), &invoice_id.to_le_bytes(), &[bump_seed]]],
+    )?;
+
+    let invoice = Invoice {
+        is_initialized: true,
+        invoice_id,
+        payee: *payee.key,
+        payer,
+        mint,
+        amount,
+        due_date,
+        status: InvoiceStatus::Open,
+        paid_at: 0,
+        bump: bump_seed,
+        memo,
+    };
+    invoice.serialize(&mut &mut invoice_account.data.borrow_mut()[..])?;
+
+    msg!("Invoice created: {}", pda);
+
+    Ok(())
+}
+
+// Handler for settling an invoice
+// Moves SOL or tokens from the payer to the payee and marks the invoice paid
+// in the same instruction, so the invoice can never be paid twice
+pub fn pay_invoice(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Paying invoice...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts:
+    let payer = next_account_info(account_info_iter)?;           // Invoiced payer (signer)
+    let payee = next_account_info(account_info_iter)?;           // Invoice issuer (receives SOL)
+    let invoice_account = next_account_info(account_info_iter)?; // Invoice PDA
+    let clock_sysvar = next_account_info(account_info_iter)?;    // Clock sysvar for the payment time
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if invoice_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut invoice = load_invoice(program_id, invoice_account)?;
+    if invoice.status != InvoiceStatus::Open {
+        return Err(InvoiceError::InvoiceNotOpen.into());
+    }
+    if invoice.payer != *payer.key {
+        return Err(InvoiceError::WrongPayer.into());
+    }
+    if invoice.payee != *payee.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if invoice.is_sol() {
+        // SOL invoices: system transfer from payer to payee
+        let system_program = next_account_info(account_info_iter)?;
+        invoke(
+            &system_instruction::transfer(payer.key, payee.key, invoice.amount),
+            &[payer.clone(), payee.clone(), system_program.clone()],
+        )?;
+    } else {
+        // Token invoices: transfer_checked between the two token accounts
+        let payer_token = next_account_info(account_info_iter)?;   // Payer's token account
+        let payee_token = next_account_info(account_info_iter)?;   // Payee's token account
+        let mint_info = next_account_info(account_info_iter)?;     // Invoice mint
+        let token_program = next_account_info(account_info_iter)?; // SPL Token program
+
+        if !spl_token::check_id(token_program.key) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if *mint_info.key != invoice.mint {
+            return Err(InvoiceError::InvalidTokenAccount.into());
+        }
+        let payee_token_state = Account::unpack(&payee_token.try_borrow_data()?)?;
+        if payee_token_state.owner != invoice.payee || payee_token_state.mint != invoice.mint {
+            return Err(InvoiceError::InvalidTokenAccount.into());
+        }
+        let decimals = Mint::unpack(&mint_info.try_borrow_data()?)?.decimals;
+
+        invoke(
+            &transfer_checked(
+                token_program.key,
+                payer_token.key,
+                mint_info.key,
+                payee_token.key,
+                payer.key,
+                &[],
+                invoice.amount,
+                decimals,
+            )?,
+            &[
+                payer_token.clone(),
+                mint_info.clone(),
+                payee_token.clone(),
+                payer.clone(),
+                token_program.clone(),
+            ],
+        )?;
+    }
+
+    invoice.status = InvoiceStatus::Paid;
+    invoice.paid_at = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    invoice.serialize(&mut &mut invoice_account.data.borrow_mut()[..])?;
+
+    msg!("Invoice {} paid", invoice.invoice_id);
+
+    Ok(())
+}
+
+// Handler for cancelling an overdue invoice
+// The account is kept so both parties can still see the invoice history
+pub fn cancel_invoice(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Cancelling invoice...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts:
+    let payee = next_account_info(account_info_iter)?;           // Invoice issuer (signer)
+    let invoice_account = next_account_info(account_info_iter)?; // Invoice PDA
+    let clock_sysvar = next_account_info(account_info_iter)?;    // Clock sysvar to check the due date
+
+    if !payee.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if invoice_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut invoice = load_invoice(program_id, invoice_account)?;
+    if invoice.payee != *payee.key {
+        return Err(InvoiceError::InvalidPDA.into());
+    }
+    if invoice.status != InvoiceStatus::Open {
+        return Err(InvoiceError::InvoiceNotOpen.into());
+    }
+    if Clock::from_account_info(clock_sysvar)?.unix_timestamp <= invoice.due_date {
+        return Err(InvoiceError::NotOverdue.into());
+    }
+
+    invoice.status = InvoiceStatus::Cancelled;
+    invoice.serialize(&mut &mut invoice_account.data.borrow_mut()[..])?;
+
+    msg!("Invoice {} cancelled", invoice.invoice_id);
+
+    Ok(())
+}
+
+// Deserialize an invoice and check it lives at its canonical PDA
+fn load_invoice(program_id: &Pubkey, invoice_account: &AccountInfo) -> Result<Invoice, ProgramError> {
+    let invoice = Invoice::deserialize(&mut &invoice_account.data.borrow()[..])
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if !invoice.is_initialized() {
+        return Err(InvoiceError::UninitializedAccount.into());
+    }
+
+    let pda = Pubkey::create_program_address(
+        &[
+            b"invoice",
+            invoice.payee.as_ref(),
+            &invoice.invoice_id.to_le_bytes(),
+            &[invoice.bump],
+        ],
+        program_id,
+    )
+    .map_err(|_| InvoiceError::InvalidPDA)?;
+    if pda != *invoice_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(InvoiceError::InvalidPDA.into());
+    }
+
+    Ok(invoice)
+}
+
+// Rent parameters for sizing new accounts
+// Prefers the runtime's Rent sysvar via Rent::get(); where the syscall is
+// unavailable (some program-test setups fail with UnsupportedSysvar) the
+// caller can pass the Rent sysvar account instead
+fn load_rent(rent_sysvar: Option<&AccountInfo>) -> Result<Rent, ProgramError> {
+    match Rent::get() {
+        Ok(rent) => Ok(rent),
+        Err(err) => match rent_sysvar {
+            Some(account) if *account.key == solana_program::sysvar::rent::ID => {
+                Rent::from_account_info(account)
+            }
+            _ => {
+                msg!("Rent sysvar unavailable and no Rent sysvar account passed");
+                Err(err)
+            }
+        },
+    }
+}
@@ -0,0 +1,97 @@
+// Import Borsh traits for serializing/deserializing data to store on-chain
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;  // For creating custom error types with descriptions
+
+// Longest memo accepted, in bytes
+pub const MAX_MEMO_LEN: usize = 64;
+
+// Lifecycle of an invoice
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvoiceStatus {
+    Open,
+    Paid,
+    Cancelled,
+}
+
+// The invoice account stored in the PDA derived from [b"invoice", payee, invoice_id]
+// A default (all zeros) mint means the invoice is payable in SOL
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Invoice {
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub invoice_id: u64,        // Payee-chosen id, part of the PDA seeds
+    pub payee: Pubkey,          // Wallet that issued the invoice and gets paid
+    pub payer: Pubkey,          // Wallet expected to settle the invoice
+    pub mint: Pubkey,           // Token mint to pay in, or Pubkey::default() for SOL
+    pub amount: u64,            // Amount due (lamports or raw token units)
+    pub due_date: i64,          // Unix timestamp after which the invoice is overdue
+    pub status: InvoiceStatus,  // Open, Paid or Cancelled
+    pub paid_at: i64,           // Unix timestamp of settlement (0 while unpaid)
+    pub bump: u8,               // Bump seed of this PDA
+    pub memo: String,           // Free-text reference shown to the payer
+}
+
+impl Invoice {
+    // Serialized size with a memo of MAX_MEMO_LEN bytes
+    pub const LEN: usize = 1 + 8 + 32 * 3 + 8 + 8 + 1 + 8 + 1 + (4 + MAX_MEMO_LEN);
+
+    // Invoices paid in SOL have no mint
+    pub fn is_sol(&self) -> bool {
+        self.mint == Pubkey::default()
+    }
+}
+
+// Sealed trait implementation - required by Solana's Pack trait
+impl Sealed for Invoice {}
+
+// Implement IsInitialized trait to check if account is ready to use
+impl IsInitialized for Invoice {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Custom error types for this program
+// The #[error(...)] attributes provide user-friendly error messages
+#[derive(Debug, Error)]
+pub enum InvoiceError {
+    #[error("Account not initialized yet")]
+    UninitializedAccount,
+
+    #[error("PDA derived does not equal PDA passed in")]
+    InvalidPDA,
+
+    #[error("Invoice amount must be greater than zero")]
+    InvalidAmount,
+
+    #[error("Memo longer than the maximum length")]
+    MemoTooLong,
+
+    #[error("Due date is in the past")]
+    InvalidDueDate,
+
+    #[error("Invoice is not open")]
+    InvoiceNotOpen,
+
+    #[error("Signer is not the invoiced payer")]
+    WrongPayer,
+
+    #[error("Token account does not match the invoice")]
+    InvalidTokenAccount,
+
+    #[error("Invoice is not overdue yet")]
+    NotOverdue,
+
+    #[error("Invoice mint must belong to the SPL Token program; Token-2022 mints are not supported")]
+    UnsupportedTokenProgram,
+}
+
+// Convert our custom errors into Solana's ProgramError type
+// This allows our errors to be returned from instruction handlers
+impl From<InvoiceError> for ProgramError {
+    fn from(e: InvoiceError) -> Self {
+        ProgramError::Custom(e as u32)  // Convert enum variant to error code
+    }
+}
@@ -0,0 +1,320 @@
+// Integration tests for the invoices program
+// These tests use solana-program-test to simulate on-chain behavior
+
+use borsh::BorshDeserialize;
+use invoices::state::{Invoice, InvoiceError, InvoiceStatus, MAX_MEMO_LEN};
+use solana_program::clock::Clock;
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext};
+use solana_sdk::account::AccountSharedData;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::str::FromStr;
+use solana_system_interface::instruction as system_instruction;
+
+/// Payload structure for CreateInvoice instruction
+/// Must match the structure expected by InvoiceInstruction::unpack in instruction.rs
+#[derive(borsh::BorshSerialize)]
+struct CreateInvoicePayload {
+    invoice_id: u64,
+    payer: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+    due_date: i64,
+    memo: String,
+}
+
+/// Helper function to create a SOL CreateInvoice instruction
+fn create_sol_invoice_instruction(
+    program_id: Pubkey,
+    payee: &Pubkey,
+    payer: &Pubkey,
+    invoice_id: u64,
+    amount: u64,
+    due_date: i64,
+) -> (Instruction, Pubkey) {
+    let memo = format!("INV-{}", invoice_id);
+    create_invoice_instruction(program_id, payee, payer, invoice_id, Pubkey::default(), amount, due_date, &memo)
+}
+
+/// Helper function to create a CreateInvoice instruction in SOL or, with a
+/// non-default mint, in tokens
+/// Format: [variant_byte: 0][borsh_serialized_payload]
+#[allow(clippy::too_many_arguments)]
+fn create_invoice_instruction(
+    program_id: Pubkey,
+    payee: &Pubkey,
+    payer: &Pubkey,
+    invoice_id: u64,
+    mint: Pubkey,
+    amount: u64,
+    due_date: i64,
+    memo: &str,
+) -> (Instruction, Pubkey) {
+    let (invoice_pda, _bump) = Pubkey::find_program_address(
+        &[b"invoice", payee.as_ref(), &invoice_id.to_le_bytes()],
+        &program_id,
+    );
+
+    let mut data = vec![0u8]; // Variant 0 for CreateInvoice
+    let payload = CreateInvoicePayload {
+        invoice_id,
+        payer: *payer,
+        mint, // Default mint = payable in SOL
+        amount,
+        due_date,
+        memo: memo.to_string(),
+    };
+    data.extend_from_slice(&borsh::to_vec(&payload).unwrap());
+
+    let mut accounts = vec![
+        AccountMeta::new(*payee, true),       // Payee (signer, pays rent)
+        AccountMeta::new(invoice_pda, false), // Invoice PDA to create
+        AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+    ];
+    if mint != Pubkey::default() {
+        accounts.push(AccountMeta::new_readonly(mint, false)); // Invoice mint
+    }
+    accounts.push(AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false));
+    (Instruction::new_with_bytes(program_id, &data, accounts), invoice_pda)
+}
+
+/// Sets up the program and a funded payee wallet
+async fn setup() -> (ProgramTestContext, Pubkey, Keypair) {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "invoices",
+        program_id,
+        processor!(invoices::process_instruction),
+    );
+    let context = program_test.start_with_context().await;
+
+    let payee = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &context.payer.pubkey(),
+            &payee.pubkey(),
+            10_000_000,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    (context, program_id, payee)
+}
+
+/// Create a SOL invoice and pay it
+///
+/// The payer's lamports move to the payee and the invoice is marked Paid
+/// in the same instruction; paying a second time is rejected.
+#[tokio::test]
+async fn test_create_and_pay_sol_invoice() {
+    let (mut context, program_id, payee) = setup().await;
+    let payer = context.payer.insecure_clone();
+    let amount = 5_000_000;
+
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let (create_instruction, invoice_pda) =
+        create_sol_invoice_instruction(program_id, &payee.pubkey(), &payer.pubkey(), 1, amount, now + 3600);
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &payee],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let payee_before = context.banks_client.get_balance(payee.pubkey()).await.unwrap();
+
+    let pay_instruction = Instruction::new_with_bytes(
+        program_id,
+        &[1u8], // Variant 1 for PayInvoice
+        vec![
+            AccountMeta::new(payer.pubkey(), true),  // Payer (signer)
+            AccountMeta::new(payee.pubkey(), false), // Payee receives the SOL
+            AccountMeta::new(invoice_pda, false),    // Invoice PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&pay_instruction),
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let payee_after = context.banks_client.get_balance(payee.pubkey()).await.unwrap();
+    assert_eq!(payee_after - payee_before, amount);
+
+    let account = context.banks_client.get_account(invoice_pda).await.unwrap().unwrap();
+    let invoice = Invoice::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Paid);
+    assert_eq!(invoice.memo, "INV-1");
+
+    // A paid invoice can't be paid again
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[pay_instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Invoice should not be payable twice");
+}
+
+/// Only overdue invoices can be cancelled
+#[tokio::test]
+async fn test_cancel_overdue_invoice() {
+    let (mut context, program_id, payee) = setup().await;
+    let payer = context.payer.insecure_clone();
+
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let due_date = now + 3600;
+    let (create_instruction, invoice_pda) =
+        create_sol_invoice_instruction(program_id, &payee.pubkey(), &payer.pubkey(), 7, 1_000, due_date);
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &payee],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let cancel_instruction = Instruction::new_with_bytes(
+        program_id,
+        &[2u8], // Variant 2 for CancelInvoice
+        vec![
+            AccountMeta::new(payee.pubkey(), true), // Payee (signer)
+            AccountMeta::new(invoice_pda, false),   // Invoice PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+        ],
+    );
+
+    // Not overdue yet: cancellation is rejected
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&cancel_instruction),
+        Some(&payer.pubkey()),
+        &[&payer, &payee],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Invoice should not be cancellable before its due date");
+
+    // Move the clock past the due date and cancel
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = due_date + 1;
+    context.set_sysvar(&clock);
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[cancel_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &payee],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = context.banks_client.get_account(invoice_pda).await.unwrap().unwrap();
+    let invoice = Invoice::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Cancelled);
+}
+
+/// A memo of exactly MAX_MEMO_LEN bytes fits the account
+///
+/// The invoice account is sized for the longest memo, so one at the limit
+/// fills it exactly and reads back intact; one byte more is rejected.
+#[tokio::test]
+async fn test_memo_at_max_length() {
+    let (context, program_id, payee) = setup().await;
+    let payer = context.payer.insecure_clone();
+
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let memo = "m".repeat(MAX_MEMO_LEN);
+    let (create_instruction, invoice_pda) = create_invoice_instruction(
+        program_id,
+        &payee.pubkey(),
+        &payer.pubkey(),
+        3,
+        Pubkey::default(),
+        1_000,
+        now + 3600,
+        &memo,
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &payee],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = context.banks_client.get_account(invoice_pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), Invoice::LEN);
+    assert_eq!(Invoice::try_from_slice(&account.data).unwrap().memo, memo);
+
+    // One byte over the limit is rejected
+    let (create_instruction, _) = create_invoice_instruction(
+        program_id,
+        &payee.pubkey(),
+        &payer.pubkey(),
+        4,
+        Pubkey::default(),
+        1_000,
+        now + 3600,
+        &"m".repeat(MAX_MEMO_LEN + 1),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &payee],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(InvoiceError::MemoTooLong as u32))
+    );
+}
+
+/// Token-2022 mints are rejected when the invoice is created
+///
+/// PayInvoice only transfers through the SPL Token program, so an invoice
+/// in a Token-2022 mint could never be paid; it fails up front instead.
+#[tokio::test]
+async fn test_token_2022_mint_rejected() {
+    let (mut context, program_id, payee) = setup().await;
+    let payer = context.payer.insecure_clone();
+
+    // Only the owner is checked before rejecting, so an empty mint-sized account will do
+    let token_2022 = Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").unwrap();
+    let mint = Pubkey::new_unique();
+    context.set_account(&mint, &AccountSharedData::new(Rent::default().minimum_balance(82), 82, &token_2022));
+
+    let now = context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let (create_instruction, _) =
+        create_invoice_instruction(program_id, &payee.pubkey(), &payer.pubkey(), 5, mint, 1_000, now + 3600, "INV-5");
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &payee],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(InvoiceError::UnsupportedTokenProgram as u32)
+        )
+    );
+}
@@ -0,0 +1,49 @@
+use borsh::BorshDeserialize;
+use solana_program::program_error::ProgramError;
+
+// Define the instructions this program can handle
+// Each instruction represents an action users can perform
+pub enum LockupInstruction {
+    // Lock governance tokens in a PDA-owned vault for `duration` seconds
+    CreateLockup {
+        amount: u64,   // Tokens to lock (raw units)
+        duration: i64, // Lock duration in seconds
+    },
+    // Return the locked tokens to the owner once the lock has expired
+    Withdraw,
+    // Report the current voting power through return data, for CPI callers
+    GetVotingPower,
+}
+
+// Internal structure for deserializing CreateLockup instruction data
+#[derive(BorshDeserialize)]
+struct CreateLockupPayload {
+    amount: u64,
+    duration: i64,
+}
+
+impl LockupInstruction {
+    // Deserialize instruction data from bytes into a LockupInstruction enum
+    // Instruction format: [variant_byte][borsh_serialized_payload]
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        // First byte indicates which instruction variant
+        // (0 = CreateLockup, 1 = Withdraw, 2 = GetVotingPower)
+        let (&variant, rest) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        Ok(match variant {
+            0 => {
+                let payload = CreateLockupPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateLockup {
+                    amount: payload.amount,
+                    duration: payload.duration,
+                }
+            }
+            1 => Self::Withdraw,
+            2 => Self::GetVotingPower,
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+}
@@ -0,0 +1,297 @@
+// Module declarations - organize code into separate files
+pub mod instruction;  // Instruction parsing and types
+pub mod state;        // Account state structures and errors
+
+use crate::instruction::LockupInstruction;
+use crate::state::{Lockup, LockupError, MAX_LOCK_DURATION, MIN_LOCK_DURATION};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,  // Macro for logging on-chain
+    program::{invoke, invoke_signed, set_return_data},
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, rent::Rent, Sysvar, SysvarSerialize},
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token::{
+    instruction::transfer_checked,
+    state::{Account, Mint},
+};
+
+// Define the program entrypoint
+// Skipped with the "no-entrypoint" feature so other programs can depend on this crate
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// Main entry point for all instructions sent to this program
+pub fn process_instruction(
+    program_id: &Pubkey,         // This program's ID
+    accounts: &[AccountInfo],    // Accounts required by the instruction
+    instruction_data: &[u8],     // Serialized instruction data
+) -> ProgramResult {
+    // Deserialize instruction data to determine which action to perform
+    let instruction = LockupInstruction::unpack(instruction_data)?;
+
+    // Route to the appropriate handler based on instruction type
+    match instruction {
+        LockupInstruction::CreateLockup { amount, duration } => {
+            create_lockup(program_id, accounts, amount, duration)
+        }
+        LockupInstruction::Withdraw => withdraw(program_id, accounts),
+        LockupInstruction::GetVotingPower => get_voting_power(program_id, accounts),
+    }
+}
+
+// Handler for locking governance tokens
+// Creates the lockup PDA and moves the tokens into a vault owned by that PDA
+pub fn create_lockup(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,     // Tokens to lock
+    duration: i64,   // Lock duration in seconds
+) -> ProgramResult {
+    msg!("Creating lockup of {} tokens for {} seconds", amount, duration);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;          // Token holder (signer, pays rent)
+    let owner_token = next_account_info(account_info_iter)?;    // Owner's token account to lock from
+    let vault = next_account_info(account_info_iter)?;          // Token account owned by the lockup PDA
+    let mint_info = next_account_info(account_info_iter)?;      // Governance token mint
+    let lockup_account = next_account_info(account_info_iter)?; // Lockup PDA to create
+    let token_program = next_account_info(account_info_iter)?;  // SPL Token program
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let clock_sysvar = next_account_info(account_info_iter)?;   // Clock sysvar for the lock start time
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    // Verify the owner has signed the transaction
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if !spl_token::check_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if amount == 0 {
+        return Err(LockupError::InvalidAmount.into());
+    }
+    if !(MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&duration) {
+        return Err(LockupError::InvalidDuration.into());
+    }
+
+    // One lockup per (owner, mint) pair
+    let (pda, bump_seed) = Pubkey::find_program_address(
+        &[b"lockup", owner.key.as_ref(), mint_info.key.as_ref()],
+        program_id,
+    );
+    if pda != *lockup_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(LockupError::InvalidPDA.into());
+    }
+    if lockup_account.lamports() > 0 {
+        return Err(LockupError::AlreadyInitialized.into());
+    }
+
+    // The vault must hold this mint and be controlled by the lockup PDA,
+    // otherwise the program could never sign the withdrawal
+    let vault_state = Account::unpack(&vault.try_borrow_data()?)?;
+    if vault_state.owner != pda || vault_state.mint != *mint_info.key {
+        return Err(LockupError::InvalidVault.into());
+    }
+    let decimals = Mint::unpack(&mint_info.try_borrow_data()?)?.decimals;
+
+    // Create the lockup PDA via CPI to System Program
+    let rent_lamports = load_rent(rent_sysvar)?.minimum_balance(Lockup::LEN);
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            lockup_account.key,
+            rent_lamports,
+            Lockup::LEN as u64,
+            program_id,
+        ),
+        &[owner.clone(), lockup_account.clone(), system_program.clone()],
+        &[&[b"lockup", owner.key.as_ref(), mint_info.key.as_ref(), &[bump_seed]]],
+    )?;
+
+    // Move the tokens into the vault; the owner signs for their own account
+    invoke(
+        &transfer_checked(
+            token_program.key,
+            owner_token.key,
+            mint_info.key,
+            vault.key,
+            owner.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            owner_token.clone(),
+            mint_info.clone(),
+            vault.clone(),
+            owner.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    // The clock is read from the sysvar account, since Clock::get() may
+    // fail with UnsupportedSysvar in tests
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    let lockup = Lockup {
+        is_initialized: true,
+        owner: *owner.key,
+        mint: *mint_info.key,
+        vault: *vault.key,
+        amount,
+        start_ts: now,
+        end_ts: now + duration,
+        voting_power: state::voting_power(amount, duration),
+        bump: bump_seed,
+    };
+    msg!("Voting power: {}", lockup.voting_power);
+
+    lockup.serialize(&mut &mut lockup_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for withdrawing tokens once the lock has expired
+// Returns the tokens to the owner and closes the lockup PDA
+pub fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Withdrawing lockup...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts:
+    let owner = next_account_info(account_info_iter)?;          // Lockup owner (signer, receives rent)
+    let vault = next_account_info(account_info_iter)?;          // Vault token account
+    let destination = next_account_info(account_info_iter)?;    // Token account receiving the tokens
+    let mint_info = next_account_info(account_info_iter)?;      // Governance token mint
+    let lockup_account = next_account_info(account_info_iter)?; // Existing lockup PDA
+    let token_program = next_account_info(account_info_iter)?;  // SPL Token program
+    let clock_sysvar = next_account_info(account_info_iter)?;   // Clock sysvar
+
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if lockup_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if !spl_token::check_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let lockup = Lockup::try_from_slice(&lockup_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if !lockup.is_initialized() {
+        return Err(LockupError::UninitializedAccount.into());
+    }
+
+    // Re-derive the PDA from the stored bump to prove the signer owns it
+    let pda = Pubkey::create_program_address(
+        &[b"lockup", owner.key.as_ref(), mint_info.key.as_ref(), &[lockup.bump]],
+        program_id,
+    )
+    .map_err(|_| LockupError::InvalidPDA)?;
+    if pda != *lockup_account.key || lockup.owner != *owner.key {
+        msg!("Invalid seeds for PDA");
+        return Err(LockupError::InvalidPDA.into());
+    }
+    if lockup.vault != *vault.key {
+        return Err(LockupError::InvalidVault.into());
+    }
+
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    if now < lockup.end_ts {
+        msg!("Tokens unlock at {}", lockup.end_ts);
+        return Err(LockupError::StillLocked.into());
+    }
+
+    // The lockup PDA owns the vault, so it signs the transfer out
+    let decimals = Mint::unpack(&mint_info.try_borrow_data()?)?.decimals;
+    invoke_signed(
+        &transfer_checked(
+            token_program.key,
+            vault.key,
+            mint_info.key,
+            destination.key,
+            lockup_account.key,
+            &[],
+            lockup.amount,
+            decimals,
+        )?,
+        &[
+            vault.clone(),
+            mint_info.clone(),
+            destination.clone(),
+            lockup_account.clone(),
+            token_program.clone(),
+        ],
+        &[&[b"lockup", owner.key.as_ref(), mint_info.key.as_ref(), &[lockup.bump]]],
+    )?;
+
+    // Close the lockup PDA: refund its rent to the owner and wipe the data
+    let refund = lockup_account.lamports();
+    **owner.try_borrow_mut_lamports()? = owner
+        .lamports()
+        .checked_add(refund)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **lockup_account.try_borrow_mut_lamports()? = 0;
+    lockup_account.data.borrow_mut().fill(0);
+
+    msg!("Returned {} tokens", lockup.amount);
+
+    Ok(())
+}
+
+// Handler that reports the current (decayed) voting power of a lockup
+// Governance programs can CPI into this and read the u64 from return data,
+// or read the lockup account directly and call Lockup::voting_power_at
+pub fn get_voting_power(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let lockup_account = next_account_info(account_info_iter)?; // Lockup PDA to read
+    let clock_sysvar = next_account_info(account_info_iter)?;   // Clock sysvar
+
+    if lockup_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let lockup = Lockup::try_from_slice(&lockup_account.data.borrow())
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    if !lockup.is_initialized() {
+        return Err(LockupError::UninitializedAccount.into());
+    }
+
+    let power = lockup.voting_power_at(Clock::from_account_info(clock_sysvar)?.unix_timestamp);
+    msg!("Voting power: {}", power);
+    set_return_data(&power.to_le_bytes());
+
+    Ok(())
+}
+
+// Rent parameters for sizing new accounts
+// Prefers the runtime's Rent sysvar via Rent::get(); where the syscall is
+// unavailable (some program-test setups fail with UnsupportedSysvar) the
+// caller can pass the Rent sysvar account instead
+fn load_rent(rent_sysvar: Option<&AccountInfo>) -> Result<Rent, ProgramError> {
+    match Rent::get() {
+        Ok(rent) => Ok(rent),
+        Err(err) => match rent_sysvar {
+            Some(account) if *account.key == solana_program::sysvar::rent::ID => {
+                Rent::from_account_info(account)
+            }
+            _ => {
+                msg!("Rent sysvar unavailable and no Rent sysvar account passed");
+                Err(err)
+            }
+        },
+    }
+}
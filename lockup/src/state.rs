@@ -0,0 +1,89 @@
+// Import Borsh traits for serializing/deserializing data to store on-chain
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::program_error::ProgramError;
+use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;  // For creating custom error types with descriptions
+
+// Shortest and longest allowed lock durations, in seconds
+pub const MIN_LOCK_DURATION: i64 = 24 * 60 * 60;             // 1 day
+pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;   // ~4 years
+
+// The lockup account stored in the PDA derived from [b"lockup", owner, mint]
+// Records how many governance tokens are locked and until when
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Lockup {
+    pub is_initialized: bool,  // Whether this account has been initialized
+    pub owner: Pubkey,         // Wallet that locked the tokens
+    pub mint: Pubkey,          // Governance token mint
+    pub vault: Pubkey,         // Token account holding the locked tokens (owned by this PDA)
+    pub amount: u64,           // Number of tokens locked
+    pub start_ts: i64,         // Unix timestamp when the lock was created
+    pub end_ts: i64,           // Unix timestamp when the tokens can be withdrawn
+    pub voting_power: u64,     // Voting power at creation time
+    pub bump: u8,              // Bump seed of this PDA
+}
+
+impl Lockup {
+    // Serialized size: bool + 3 pubkeys + u64 + 2 * i64 + u64 + u8
+    pub const LEN: usize = 1 + 32 * 3 + 8 + 8 + 8 + 8 + 1;
+
+    // Voting power decays linearly to zero as the unlock time approaches
+    // power = amount * remaining_lock_time / MAX_LOCK_DURATION
+    pub fn voting_power_at(&self, now: i64) -> u64 {
+        voting_power(self.amount, self.end_ts.saturating_sub(now))
+    }
+}
+
+// Time-weighted voting power for `amount` tokens locked for `remaining` seconds
+pub fn voting_power(amount: u64, remaining: i64) -> u64 {
+    if remaining <= 0 {
+        return 0;
+    }
+    let remaining = remaining.min(MAX_LOCK_DURATION) as u128;
+    (amount as u128 * remaining / MAX_LOCK_DURATION as u128) as u64
+}
+
+// Sealed trait implementation - required by Solana's Pack trait
+impl Sealed for Lockup {}
+
+// Implement IsInitialized trait to check if account is ready to use
+impl IsInitialized for Lockup {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Custom error types for this program
+// The #[error(...)] attributes provide user-friendly error messages
+#[derive(Debug, Error)]
+pub enum LockupError {
+    #[error("Account not initialized yet")]
+    UninitializedAccount,
+
+    #[error("PDA derived does not equal PDA passed in")]
+    InvalidPDA,
+
+    #[error("Lock amount must be greater than zero")]
+    InvalidAmount,
+
+    #[error("Lock duration outside the allowed range")]
+    InvalidDuration,
+
+    #[error("Vault is not a token account owned by the lockup PDA")]
+    InvalidVault,
+
+    #[error("Tokens are still locked")]
+    StillLocked,
+
+    #[error("Lockup account already exists")]
+    AlreadyInitialized,
+}
+
+// Convert our custom errors into Solana's ProgramError type
+// This allows our errors to be returned from instruction handlers
+impl From<LockupError> for ProgramError {
+    fn from(e: LockupError) -> Self {
+        ProgramError::Custom(e as u32)  // Convert enum variant to error code
+    }
+}
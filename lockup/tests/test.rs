@@ -0,0 +1,211 @@
+// Integration tests for the governance token lockup program
+// These tests use solana-program-test to simulate on-chain behavior
+
+use borsh::BorshDeserialize;
+use lockup::state::{voting_power, Lockup};
+use solana_program::clock::Clock;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::program_pack::Pack;
+use solana_program::pubkey::Pubkey;
+use solana_program::rent::Rent;
+use solana_program_test::{processor, tokio, ProgramTest};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_system_interface::instruction as system_instruction;
+use spl_token::state::{Account, Mint};
+
+const LOCK_DURATION: i64 = 30 * 24 * 60 * 60; // 30 days
+
+/// Helper function to create instruction data for CreateLockup
+/// Format: [variant_byte: 0][amount: u64 LE][duration: i64 LE]
+fn create_lockup_instruction_data(amount: u64, duration: i64) -> Vec<u8> {
+    let mut data = vec![0u8]; // Variant 0 for CreateLockup
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&duration.to_le_bytes());
+    data
+}
+
+/// Lock tokens, fail to withdraw early, then withdraw after the lock expires
+///
+/// 1. Creates a mint, the owner's token account, and a vault owned by the lockup PDA
+/// 2. Locks tokens and checks the recorded voting power
+/// 3. Verifies an early withdrawal is rejected
+/// 4. Moves the clock past the unlock time and withdraws
+#[tokio::test]
+async fn test_lock_and_withdraw() {
+    // Enable Solana runtime logging to see msg!() output from the program
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "lockup",
+        program_id,
+        processor!(lockup::process_instruction),
+    );
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let rent = Rent::default();
+
+    let mint = Keypair::new();
+    let owner_token = Keypair::new();
+    let vault = Keypair::new();
+    let decimals = 6;
+    let amount = 1_000_000u64;
+
+    // The lockup PDA is unique per (owner, mint) and owns the vault
+    let (lockup_pda, _bump) = Pubkey::find_program_address(
+        &[b"lockup", payer.pubkey().as_ref(), mint.pubkey().as_ref()],
+        &program_id,
+    );
+
+    // STEP 1: Create the mint and both token accounts, then fund the owner
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &owner_token.pubkey(),
+                rent.minimum_balance(Account::LEN),
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &owner_token.pubkey(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &vault.pubkey(),
+                rent.minimum_balance(Account::LEN),
+                Account::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &vault.pubkey(),
+                &mint.pubkey(),
+                &lockup_pda, // Vault is controlled by the lockup PDA
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &owner_token.pubkey(),
+                &payer.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &mint, &owner_token, &vault],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Lock all tokens for 30 days
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_lockup_instruction_data(amount, LOCK_DURATION),
+        vec![
+            AccountMeta::new(payer.pubkey(), true),               // Owner (signer)
+            AccountMeta::new(owner_token.pubkey(), false),        // Owner token account
+            AccountMeta::new(vault.pubkey(), false),              // Vault
+            AccountMeta::new_readonly(mint.pubkey(), false),      // Mint
+            AccountMeta::new(lockup_pda, false),                  // Lockup PDA
+            AccountMeta::new_readonly(spl_token::id(), false),    // Token program
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false), // System program
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),        // Clock sysvar
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),         // Rent sysvar
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = context.banks_client.get_account(lockup_pda).await.unwrap().unwrap();
+    let lockup = Lockup::try_from_slice(&account.data).unwrap();
+    assert_eq!(lockup.amount, amount);
+    assert_eq!(lockup.end_ts - lockup.start_ts, LOCK_DURATION);
+    assert_eq!(lockup.voting_power, voting_power(amount, LOCK_DURATION));
+
+    let vault_account = context.banks_client.get_account(vault.pubkey()).await.unwrap().unwrap();
+    assert_eq!(Account::unpack(&vault_account.data).unwrap().amount, amount);
+
+    // STEP 3: Withdrawing before the lock expires must fail
+    let withdraw_instruction = Instruction::new_with_bytes(
+        program_id,
+        &[1u8], // Variant 1 for Withdraw
+        vec![
+            AccountMeta::new(payer.pubkey(), true),            // Owner (signer)
+            AccountMeta::new(vault.pubkey(), false),           // Vault
+            AccountMeta::new(owner_token.pubkey(), false),     // Destination
+            AccountMeta::new_readonly(mint.pubkey(), false),   // Mint
+            AccountMeta::new(lockup_pda, false),               // Lockup PDA
+            AccountMeta::new_readonly(spl_token::id(), false), // Token program
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&withdraw_instruction),
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Withdraw should fail while tokens are locked");
+
+    // STEP 4: Move the clock past the unlock time and withdraw
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = lockup.end_ts + 1;
+    context.set_sysvar(&clock);
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[withdraw_instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let owner_account = context.banks_client.get_account(owner_token.pubkey()).await.unwrap().unwrap();
+    assert_eq!(Account::unpack(&owner_account.data).unwrap().amount, amount);
+
+    // The lockup PDA was closed and its rent refunded
+    let closed = context.banks_client.get_account(lockup_pda).await.unwrap();
+    assert!(closed.is_none(), "Lockup account should be closed");
+}
+
+/// Voting power scales with the remaining lock time and decays to zero
+#[test]
+fn test_voting_power_decay() {
+    let max = lockup::state::MAX_LOCK_DURATION;
+    assert_eq!(voting_power(1_000, max), 1_000);
+    assert_eq!(voting_power(1_000, max / 2), 500);
+    assert_eq!(voting_power(1_000, 0), 0);
+    assert_eq!(voting_power(1_000, -5), 0);
+    // Locks longer than the maximum don't earn extra power
+    assert_eq!(voting_power(1_000, max * 2), 1_000);
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use review::instruction::ReviewInstruction;
+
+// Any byte string must either parse or return an error, never panic
+fuzz_target!(|data: &[u8]| {
+    let _ = ReviewInstruction::unpack(data);
+});
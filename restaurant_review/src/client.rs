@@ -0,0 +1,1423 @@
+// Off-chain helpers for building instructions and deriving PDAs
+// Enabled with the "client" feature so on-chain builds don't carry them
+//
+// Instruction data is [variant_byte][borsh_serialized_payload], matching
+// ReviewInstruction::unpack. Borsh serializes a tuple exactly like a struct
+// with the same fields in the same order, so the payloads are built from tuples
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    sysvar,
+};
+use solana_system_interface::program as system_program;
+
+use crate::compression::{ACCOUNT_COMPRESSION_ID, NOOP_ID};
+use crate::events::ReviewEvent;
+use crate::state::{normalize_title, review_commitment, review_seed, title_seed, SubRatings};
+
+// Variant bytes, see ReviewInstruction::unpack
+const ADD_REVIEW: u8 = 0;
+const UPDATE_REVIEW: u8 = 1;
+const DELETE_REVIEW: u8 = 2;
+const ADD_COMMENT: u8 = 3;
+const INITIALIZE_STATS: u8 = 4;
+const TIP_REVIEWER: u8 = 5;
+const TIP_REVIEWER_TOKEN: u8 = 6;
+const VOTE_REVIEW: u8 = 7;
+const TRANSFER_REVIEW_OWNERSHIP: u8 = 8;
+const INITIALIZE_CONFIG: u8 = 9;
+const FLAG_REVIEW: u8 = 10;
+const RESOLVE_FLAG: u8 = 11;
+const VERIFY_RESTAURANT_OWNER: u8 = 12;
+const RESPOND_TO_REVIEW: u8 = 13;
+const REGISTER_RESTAURANT: u8 = 14;
+const SET_STRICT_REGISTRY: u8 = 15;
+const SET_UPDATE_COOLDOWN: u8 = 16;
+const ARCHIVE_REVIEW: u8 = 17;
+const MIGRATE_REVIEW: u8 = 18;
+const MIGRATE_ACCOUNT: u8 = 19;
+const CLAIM_BADGE: u8 = 20;
+const CREATE_MULTISIG: u8 = 21;
+const CREATE_PROFILE: u8 = 22;
+const UPDATE_PROFILE: u8 = 23;
+const CREATE_REVIEW_TREE: u8 = 24;
+const ADD_COMPRESSED_REVIEW: u8 = 25;
+const UPDATE_COMPRESSED_REVIEW: u8 = 26;
+const UPDATE_CONFIG: u8 = 27;
+const ADD_ANONYMOUS_REVIEW: u8 = 28;
+const REVEAL_REVIEW: u8 = 29;
+const REPORT_REVIEW: u8 = 30;
+const SET_REPORT_THRESHOLD: u8 = 31;
+const ADD_REVIEW_FOR_RESTAURANT: u8 = 32;
+const SET_REVIEW_STAKE: u8 = 33;
+const SLASH_REVIEW: u8 = 34;
+const SET_REVIEW_FEE: u8 = 35;
+const WITHDRAW_TREASURY: u8 = 36;
+const NOMINATE_ADMIN: u8 = 37;
+const ACCEPT_ADMIN: u8 = 38;
+const SET_PAUSED: u8 = 39;
+const SET_DISPLAY_NAME: u8 = 40;
+const ADD_REVIEW_IF_NOT_EXISTS: u8 = 41;
+const UPSERT_REVIEW: u8 = 42;
+const ADD_FOLLOW_UP_REVIEW: u8 = 43;
+const FOLLOW: u8 = 44;
+const UNFOLLOW: u8 = 45;
+const BOOKMARK_RESTAURANT: u8 = 46;
+const REMOVE_BOOKMARK: u8 = 47;
+const SET_CLAIM_FEE: u8 = 48;
+const CLAIM_RESTAURANT: u8 = 49;
+const APPROVE_CLAIM: u8 = 50;
+const REJECT_CLAIM: u8 = 51;
+const REMOVE_REVIEW: u8 = 52;
+const RECOMPUTE_AGGREGATE: u8 = 53;
+
+fn instruction_data<T: BorshSerialize>(variant: u8, payload: &T) -> Vec<u8> {
+    let mut data = vec![variant];
+    data.extend_from_slice(&borsh::to_vec(payload).expect("payload serializes"));
+    data
+}
+
+// ---- PDA derivation ----
+
+// Review PDA: [reviewer, review_seed(title)]
+pub fn review_pda(reviewer: &Pubkey, title: &str, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[reviewer.as_ref(), &review_seed(title)], program_id).0
+}
+
+// Comment counter created with each review: [review_pda, "comment"]
+pub fn comment_counter_pda(review_pda: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[review_pda.as_ref(), b"comment"], program_id).0
+}
+
+// Comment at a given index: [review_pda, index as big-endian u64]
+pub fn comment_pda(review_pda: &Pubkey, index: u64, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[review_pda.as_ref(), &index.to_be_bytes()], program_id).0
+}
+
+// Singleton program stats: ["stats"]
+pub fn stats_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"stats"], program_id).0
+}
+
+// Singleton program config: ["config"]
+pub fn config_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], program_id).0
+}
+
+// Follow-up review: [reviewer, review_seed(title), sequence as u32 LE]
+pub fn follow_up_review_pda(reviewer: &Pubkey, title: &str, sequence: u32, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[reviewer.as_ref(), &review_seed(title), &sequence.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+// Follow-up counter of a wallet and title: ["followups", reviewer, review_seed(title)]
+pub fn followups_pda(reviewer: &Pubkey, title: &str, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"followups", reviewer.as_ref(), &review_seed(title)], program_id).0
+}
+
+// Per-wallet reviewer record: ["reviewer", reviewer]
+pub fn reviewer_record_pda(reviewer: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reviewer", reviewer.as_ref()], program_id).0
+}
+
+// Review of a registered restaurant: [reviewer, restaurant_pda(name)]
+pub fn restaurant_review_pda(reviewer: &Pubkey, name: &str, program_id: &Pubkey) -> Pubkey {
+    let restaurant = restaurant_pda(name, program_id);
+    Pubkey::find_program_address(&[reviewer.as_ref(), restaurant.as_ref()], program_id).0
+}
+
+// Stake escrow of a review: ["stake", review]
+pub fn review_stake_pda(review_pda: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"stake", review_pda.as_ref()], program_id).0
+}
+
+// Program treasury collecting review fees: ["treasury"]
+pub fn treasury_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], program_id).0
+}
+
+// Reviewer's display name profile: ["reviewer_profile", reviewer]
+pub fn reviewer_profile_pda(reviewer: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reviewer_profile", reviewer.as_ref()], program_id).0
+}
+
+// `follower` following `followee`: ["follow", follower, followee]
+pub fn follow_pda(follower: &Pubkey, followee: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"follow", follower.as_ref(), followee.as_ref()], program_id).0
+}
+
+// Per-wallet list of bookmarked restaurants: ["bookmarks", owner]
+pub fn bookmarks_pda(owner: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bookmarks", owner.as_ref()], program_id).0
+}
+
+// Per-wallet list of review PDAs: ["reviewer_index", reviewer]
+pub fn reviewer_index_pda(reviewer: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reviewer_index", reviewer.as_ref()], program_id).0
+}
+
+// Restaurant aggregate: ["aggregate", title_seed(normalize_title(title))]
+pub fn aggregate_pda(title: &str, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"aggregate", &title_seed(&normalize_title(title))], program_id).0
+}
+
+// Restaurant registry entry: ["restaurant", title_seed(normalize_title(name))]
+pub fn restaurant_pda(name: &str, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"restaurant", &title_seed(&normalize_title(name))], program_id).0
+}
+
+// Saved revision of a review: [review_pda, "revision", index as little-endian u64]
+pub fn revision_pda(review_pda: &Pubkey, revision: u64, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[review_pda.as_ref(), b"revision", &revision.to_le_bytes()],
+        program_id,
+    )
+    .0
+}
+
+// A wallet's vote on a review: [review_pda, voter]
+pub fn vote_pda(review_pda: &Pubkey, voter: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[review_pda.as_ref(), voter.as_ref()], program_id).0
+}
+
+// Token tip ledger: [review_pda, "tips", mint]
+pub fn tip_ledger_pda(review_pda: &Pubkey, mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[review_pda.as_ref(), b"tips", mint.as_ref()], program_id).0
+}
+
+// Directory profile of a registered restaurant: ["profile", restaurant_pda(name)]
+pub fn restaurant_profile_pda(name: &str, program_id: &Pubkey) -> Pubkey {
+    let restaurant = restaurant_pda(name, program_id);
+    Pubkey::find_program_address(&[b"profile", restaurant.as_ref()], program_id).0
+}
+
+// Badge mint for a review-count milestone: ["badge", reviewer, milestone]
+pub fn badge_mint_pda(reviewer: &Pubkey, milestone: u64, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"badge", reviewer.as_ref(), &milestone.to_le_bytes()], program_id).0
+}
+
+// Token account holding a badge: ["badge_account", badge_mint]
+pub fn badge_account_pda(badge_mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"badge_account", badge_mint.as_ref()], program_id).0
+}
+
+// Mint and freeze authority of every badge: ["badge_authority"]
+pub fn badge_authority_pda(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"badge_authority"], program_id).0
+}
+
+// Abuse report by one wallet: ["report", review_pda, reporter]
+pub fn report_pda(review_pda: &Pubkey, reporter: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"report", review_pda.as_ref(), reporter.as_ref()], program_id).0
+}
+
+// Number of reports on a review: ["reports", review_pda]
+pub fn report_count_pda(review_pda: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"reports", review_pda.as_ref()], program_id).0
+}
+
+// Commitment to a hidden review: ["commitment", author, commitment]
+pub fn commitment_pda(author: &Pubkey, commitment: &[u8; 32], program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"commitment", author.as_ref(), commitment], program_id).0
+}
+
+// Authority of a compressed review tree: ["tree_authority", merkle_tree]
+pub fn tree_authority_pda(merkle_tree: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"tree_authority", merkle_tree.as_ref()], program_id).0
+}
+
+// Pending or settled claim on a restaurant: ["claim", title_seed(normalize_title(title))]
+pub fn claim_pda(title: &str, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"claim", &title_seed(&normalize_title(title))], program_id).0
+}
+
+// Verified owner registry entry for a restaurant: ["verified_owner", title_seed(normalize_title(title))]
+pub fn verified_owner_pda(title: &str, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"verified_owner", &title_seed(&normalize_title(title))],
+        program_id,
+    )
+    .0
+}
+
+// Owner response to a review: ["response", review_pda]
+pub fn response_pda(review_pda: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"response", review_pda.as_ref()], program_id).0
+}
+
+// ---- Events ----
+
+// Decode a "Program data: ..." line from a transaction's log messages
+// Returns None for any other log line, including events of other programs
+pub fn decode_event_log(line: &str) -> Option<ReviewEvent> {
+    let fields = line
+        .strip_prefix("Program data: ")?
+        .split(' ')
+        .map(|field| BASE64_STANDARD.decode(field).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let fields: Vec<&[u8]> = fields.iter().map(Vec::as_slice).collect();
+    ReviewEvent::decode(&fields)
+}
+
+// ---- Instruction builders ----
+
+// AddReview signed by `reviewer`, who also pays rent for every new account
+// `content_uri` may be empty when the review has no off-chain content;
+// `visited_at` is a Unix timestamp no later than the current time
+#[allow(clippy::too_many_arguments)]
+pub fn add_review_ix(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    tags: &[&str],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+) -> Instruction {
+    let review = review_pda(reviewer, title, program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(
+            ADD_REVIEW,
+            &(title, rating, description, tags, content_uri, sub_ratings, visited_at, price_range),
+        ),
+        vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(comment_counter_pda(&review, program_id), false),
+            AccountMeta::new(stats_pda(program_id), false),
+            AccountMeta::new(reviewer_record_pda(reviewer, program_id), false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, program_id), false),
+            AccountMeta::new(reviewer_index_pda(reviewer, program_id), false),
+            AccountMeta::new(review_stake_pda(&review, program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// AddReviewIfNotExists: add_review_ix that is safe to resend, succeeding
+// without changes if the review already exists with this content
+#[allow(clippy::too_many_arguments)]
+pub fn add_review_if_not_exists_ix(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    tags: &[&str],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+) -> Instruction {
+    let mut instruction = add_review_ix(
+        program_id,
+        reviewer,
+        title,
+        rating,
+        description,
+        tags,
+        content_uri,
+        sub_ratings,
+        visited_at,
+        price_range,
+    );
+    instruction.data[0] = ADD_REVIEW_IF_NOT_EXISTS;
+    instruction
+}
+
+// AddFollowUpReview by `reviewer` of a restaurant they reviewed before
+// `sequence` is one past the follow-up counter's count, 1 for the first
+// follow-up
+#[allow(clippy::too_many_arguments)]
+pub fn add_follow_up_review_ix(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    tags: &[&str],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+    sequence: u32,
+) -> Instruction {
+    let review = follow_up_review_pda(reviewer, title, sequence, program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(
+            ADD_FOLLOW_UP_REVIEW,
+            &(title, rating, description, tags, content_uri, sub_ratings, visited_at, price_range),
+        ),
+        vec![
+            AccountMeta::new(followups_pda(reviewer, title, program_id), false),
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(comment_counter_pda(&review, program_id), false),
+            AccountMeta::new(stats_pda(program_id), false),
+            AccountMeta::new(reviewer_record_pda(reviewer, program_id), false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, program_id), false),
+            AccountMeta::new(reviewer_index_pda(reviewer, program_id), false),
+            AccountMeta::new(review_stake_pda(&review, program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// UpsertReview by `reviewer`: AddReview if their review of `title` doesn't
+// exist, UpdateReview if it does
+// `revision` is the review's current revision_count, where an update saves
+// the old version; it is 0 for a review that was never updated
+#[allow(clippy::too_many_arguments)]
+pub fn upsert_review_ix(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    tags: &[&str],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+    revision: u64,
+) -> Instruction {
+    let review = review_pda(reviewer, title, program_id);
+    let mut instruction = add_review_ix(
+        program_id,
+        reviewer,
+        title,
+        rating,
+        description,
+        tags,
+        content_uri,
+        sub_ratings,
+        visited_at,
+        price_range,
+    );
+    instruction.data[0] = UPSERT_REVIEW;
+    // The revision PDA goes between the treasury and the Rent sysvar
+    let rent_sysvar = instruction.accounts.pop().expect("add_review_ix passes the Rent sysvar");
+    instruction.accounts.push(AccountMeta::new(revision_pda(&review, revision, program_id), false));
+    instruction.accounts.push(rent_sysvar);
+    instruction
+}
+
+// AddReviewForRestaurant of the restaurant registered as `name`
+#[allow(clippy::too_many_arguments)]
+pub fn add_review_for_restaurant_ix(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    name: &str,
+    rating: u8,
+    description: &str,
+    tags: &[&str],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+) -> Instruction {
+    let review = restaurant_review_pda(reviewer, name, program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(
+            ADD_REVIEW_FOR_RESTAURANT,
+            &(rating, description, tags, content_uri, sub_ratings, visited_at, price_range),
+        ),
+        vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(comment_counter_pda(&review, program_id), false),
+            AccountMeta::new(stats_pda(program_id), false),
+            AccountMeta::new(reviewer_record_pda(reviewer, program_id), false),
+            AccountMeta::new(aggregate_pda(name, program_id), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(name, program_id), false),
+            AccountMeta::new(reviewer_index_pda(reviewer, program_id), false),
+            AccountMeta::new(review_stake_pda(&review, program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// AddReview written by `reviewer` with a sponsor, `payer`, paying the rent
+// The review is still the reviewer's; the rent goes back to `payer` on delete
+#[allow(clippy::too_many_arguments)]
+pub fn sponsored_add_review_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    tags: &[&str],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+) -> Instruction {
+    let mut instruction = add_review_ix(
+        program_id,
+        reviewer,
+        title,
+        rating,
+        description,
+        tags,
+        content_uri,
+        sub_ratings,
+        visited_at,
+        price_range,
+    );
+    instruction.accounts.push(AccountMeta::new(*payer, true));
+    instruction
+}
+
+// UpdateReview signed by `reviewer`, who wrote the review and still owns it
+// `revision` is the review's current revision_count; the old version is saved there
+#[allow(clippy::too_many_arguments)]
+pub fn update_review_ix(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    tags: &[&str],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+    revision: u64,
+) -> Instruction {
+    let review = review_pda(reviewer, title, program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(
+            UPDATE_REVIEW,
+            &(title, rating, description, tags, content_uri, sub_ratings, visited_at, price_range),
+        ),
+        vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(stats_pda(program_id), false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new(revision_pda(&review, revision, program_id), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// DeleteReview signed by `reviewer`, who wrote the review and still owns it; they receive the rent
+pub fn delete_review_ix(program_id: &Pubkey, reviewer: &Pubkey, title: &str) -> Instruction {
+    let review = review_pda(reviewer, title, program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(DELETE_REVIEW, &(title,)),
+        vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new(comment_counter_pda(&review, program_id), false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new(reviewer_index_pda(reviewer, program_id), false),
+            AccountMeta::new(review_stake_pda(&review, program_id), false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// DeleteReview of a sponsored review; its rent goes back to `payer`
+pub fn delete_sponsored_review_ix(
+    program_id: &Pubkey,
+    reviewer: &Pubkey,
+    title: &str,
+    payer: &Pubkey,
+) -> Instruction {
+    let mut instruction = delete_review_ix(program_id, reviewer, title);
+    instruction.accounts.push(AccountMeta::new(*payer, false));
+    instruction
+}
+
+// AddComment on `review`; `index` is the counter's current value
+pub fn add_comment_ix(
+    program_id: &Pubkey,
+    commenter: &Pubkey,
+    review: &Pubkey,
+    index: u64,
+    comment: &str,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(ADD_COMMENT, &(comment,)),
+        vec![
+            AccountMeta::new(*commenter, true),
+            AccountMeta::new_readonly(*review, false),
+            AccountMeta::new(comment_counter_pda(review, program_id), false),
+            AccountMeta::new(comment_pda(review, index, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// InitializeStats paid for by `payer`
+pub fn initialize_stats_ix(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[INITIALIZE_STATS],
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(stats_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// TipReviewer sending `lamports` from `tipper` to the owner of `review`
+pub fn tip_reviewer_ix(
+    program_id: &Pubkey,
+    tipper: &Pubkey,
+    reviewer: &Pubkey,
+    review: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(TIP_REVIEWER, &lamports),
+        vec![
+            AccountMeta::new(*tipper, true),
+            AccountMeta::new(*reviewer, false),
+            AccountMeta::new(*review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// TipReviewerToken sending `amount` of `mint` between two token accounts
+// `token_program` is the program that owns the mint, SPL Token or Token-2022
+#[allow(clippy::too_many_arguments)]
+pub fn tip_reviewer_token_ix(
+    program_id: &Pubkey,
+    tipper: &Pubkey,
+    reviewer: &Pubkey,
+    review: &Pubkey,
+    tipper_token: &Pubkey,
+    reviewer_token: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(TIP_REVIEWER_TOKEN, &amount),
+        vec![
+            AccountMeta::new(*tipper, true),
+            AccountMeta::new_readonly(*reviewer, false),
+            AccountMeta::new_readonly(*review, false),
+            AccountMeta::new(*tipper_token, false),
+            AccountMeta::new(*reviewer_token, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(tip_ledger_pda(review, mint, program_id), false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// VoteReview by `voter` on `review`, a review of the restaurant `title`
+pub fn vote_review_ix(program_id: &Pubkey, voter: &Pubkey, review: &Pubkey, title: &str, up: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(VOTE_REVIEW, &up),
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new(*review, false),
+            AccountMeta::new(vote_pda(review, voter, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// TransferReviewOwnership handing `review` from `owner` to `new_owner`
+pub fn transfer_review_ownership_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    review: &Pubkey,
+    new_owner: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(TRANSFER_REVIEW_OWNERSHIP, new_owner),
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(*review, false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+        ],
+    )
+}
+
+// InitializeConfig making `admin` the admin, with extra `moderators`
+pub fn initialize_config_ix(program_id: &Pubkey, admin: &Pubkey, moderators: &[Pubkey]) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(INITIALIZE_CONFIG, &moderators.to_vec()),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// FlagReview on `review` reported by `flagger`
+pub fn flag_review_ix(program_id: &Pubkey, flagger: &Pubkey, review: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[FLAG_REVIEW],
+        vec![
+            AccountMeta::new_readonly(*flagger, true),
+            AccountMeta::new(*review, false),
+        ],
+    )
+}
+
+// ReportReview of `review` by `reporter`
+pub fn report_review_ix(program_id: &Pubkey, reporter: &Pubkey, review: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[REPORT_REVIEW],
+        vec![
+            AccountMeta::new(*reporter, true),
+            AccountMeta::new(*review, false),
+            AccountMeta::new(report_pda(review, reporter, program_id), false),
+            AccountMeta::new(report_count_pda(review, program_id), false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// SetReportThreshold by the config `admin`
+pub fn set_report_threshold_ix(program_id: &Pubkey, admin: &Pubkey, reports: u32) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_REPORT_THRESHOLD, &reports),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+        ],
+    )
+}
+
+// SetReviewStake by the config `admin`
+pub fn set_review_stake_ix(program_id: &Pubkey, admin: &Pubkey, lamports: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_REVIEW_STAKE, &lamports),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+        ],
+    )
+}
+
+// SlashReview of `review` by `moderator`, sending its stake to the treasury PDA
+pub fn slash_review_ix(program_id: &Pubkey, moderator: &Pubkey, review: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[SLASH_REVIEW],
+        vec![
+            AccountMeta::new(*moderator, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(*review, false),
+            AccountMeta::new(review_stake_pda(review, program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// RemoveReview of `review`, `author`'s review of `title`, by a moderator,
+// sending the rent to the treasury PDA
+pub fn remove_review_ix(
+    program_id: &Pubkey,
+    moderator: &Pubkey,
+    review: &Pubkey,
+    author: &Pubkey,
+    title: &str,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[REMOVE_REVIEW],
+        vec![
+            AccountMeta::new(*moderator, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*review, false),
+            AccountMeta::new(comment_counter_pda(review, program_id), false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new(reviewer_index_pda(author, program_id), false),
+            AccountMeta::new(review_stake_pda(review, program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// RecomputeAggregate of restaurant `title`; anyone can send it
+pub fn recompute_aggregate_ix(program_id: &Pubkey, title: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(RECOMPUTE_AGGREGATE, &(title,)),
+        vec![
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+        ],
+    )
+}
+
+// SetReviewFee by the config `admin`, who pays the treasury's rent the first time
+pub fn set_review_fee_ix(program_id: &Pubkey, admin: &Pubkey, lamports: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_REVIEW_FEE, &lamports),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// SetClaimFee by the config `admin`, creating the treasury if needed
+pub fn set_claim_fee_ix(program_id: &Pubkey, admin: &Pubkey, lamports: u64) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_CLAIM_FEE, &lamports),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// WithdrawTreasury by the config `admin`, sending `lamports` to `destination`
+pub fn withdraw_treasury_ix(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    destination: &Pubkey,
+    lamports: u64,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(WITHDRAW_TREASURY, &lamports),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// NominateAdmin by the config `admin`, naming `new_admin` as its successor
+pub fn nominate_admin_ix(program_id: &Pubkey, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(NOMINATE_ADMIN, new_admin),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+        ],
+    )
+}
+
+// AcceptAdmin signed by the wallet the admin nominated
+pub fn accept_admin_ix(program_id: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[ACCEPT_ADMIN],
+        vec![
+            AccountMeta::new_readonly(*new_admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+        ],
+    )
+}
+
+// SetPaused by the config `admin`
+pub fn set_paused_ix(program_id: &Pubkey, admin: &Pubkey, paused: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_PAUSED, &paused),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+        ],
+    )
+}
+
+// SetDisplayName for `reviewer`, creating their profile on first use
+pub fn set_display_name_ix(program_id: &Pubkey, reviewer: &Pubkey, name: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_DISPLAY_NAME, &(name,)),
+        vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new(reviewer_profile_pda(reviewer, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// Follow of `followee` by `follower`, who pays for the follow and any
+// profile it creates
+pub fn follow_ix(program_id: &Pubkey, follower: &Pubkey, followee: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[FOLLOW],
+        vec![
+            AccountMeta::new(*follower, true),
+            AccountMeta::new_readonly(*followee, false),
+            AccountMeta::new(follow_pda(follower, followee, program_id), false),
+            AccountMeta::new(reviewer_profile_pda(follower, program_id), false),
+            AccountMeta::new(reviewer_profile_pda(followee, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// Unfollow of `followee` by `follower`, who gets the follow's rent back
+pub fn unfollow_ix(program_id: &Pubkey, follower: &Pubkey, followee: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[UNFOLLOW],
+        vec![
+            AccountMeta::new(*follower, true),
+            AccountMeta::new_readonly(*followee, false),
+            AccountMeta::new(follow_pda(follower, followee, program_id), false),
+            AccountMeta::new(reviewer_profile_pda(follower, program_id), false),
+            AccountMeta::new(reviewer_profile_pda(followee, program_id), false),
+        ],
+    )
+}
+
+// BookmarkRestaurant of the registered restaurant `name` by `owner`
+pub fn bookmark_restaurant_ix(program_id: &Pubkey, owner: &Pubkey, name: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[BOOKMARK_RESTAURANT],
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(restaurant_pda(name, program_id), false),
+            AccountMeta::new(bookmarks_pda(owner, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// RemoveBookmark of the restaurant `name` from `owner`'s bookmarks
+pub fn remove_bookmark_ix(program_id: &Pubkey, owner: &Pubkey, name: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[REMOVE_BOOKMARK],
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(restaurant_pda(name, program_id), false),
+            AccountMeta::new(bookmarks_pda(owner, program_id), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// ResolveFlag on `review` by `moderator`, hiding it or making it visible
+pub fn resolve_flag_ix(program_id: &Pubkey, moderator: &Pubkey, review: &Pubkey, hide: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(RESOLVE_FLAG, &hide),
+        vec![
+            AccountMeta::new_readonly(*moderator, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(*review, false),
+        ],
+    )
+}
+
+// VerifyRestaurantOwner by the config `admin`, registering `owner` for `title`
+pub fn verify_restaurant_owner_ix(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    title: &str,
+    owner: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(VERIFY_RESTAURANT_OWNER, &(title, owner)),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(verified_owner_pda(title, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// ClaimRestaurant of the registered restaurant `name` by `claimant`
+pub fn claim_restaurant_ix(program_id: &Pubkey, claimant: &Pubkey, name: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[CLAIM_RESTAURANT],
+        vec![
+            AccountMeta::new(*claimant, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(name, program_id), false),
+            AccountMeta::new_readonly(verified_owner_pda(name, program_id), false),
+            AccountMeta::new(claim_pda(name, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// ApproveClaim by the config `admin` of the pending claim on `name`
+pub fn approve_claim_ix(program_id: &Pubkey, admin: &Pubkey, name: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[APPROVE_CLAIM],
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(restaurant_pda(name, program_id), false),
+            AccountMeta::new(verified_owner_pda(name, program_id), false),
+            AccountMeta::new(claim_pda(name, program_id), false),
+            AccountMeta::new(treasury_pda(program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// RejectClaim by the config `admin` of `claimant`'s pending claim on `name`
+pub fn reject_claim_ix(program_id: &Pubkey, admin: &Pubkey, name: &str, claimant: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[REJECT_CLAIM],
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(claim_pda(name, program_id), false),
+            AccountMeta::new(*claimant, false),
+        ],
+    )
+}
+
+// RespondToReview by `owner` on `review`, a review of restaurant `title`
+pub fn respond_to_review_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    review: &Pubkey,
+    title: &str,
+    response: &str,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(RESPOND_TO_REVIEW, &response),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*review, false),
+            AccountMeta::new_readonly(verified_owner_pda(title, program_id), false),
+            AccountMeta::new(response_pda(review, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// RegisterRestaurant by the config `admin`
+pub fn register_restaurant_ix(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    name: &str,
+    location: &str,
+    owner: &Pubkey,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(REGISTER_RESTAURANT, &(name, location, owner)),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new(restaurant_pda(name, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// SetStrictRegistry by the config `admin`
+pub fn set_strict_registry_ix(program_id: &Pubkey, admin: &Pubkey, strict: bool) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_STRICT_REGISTRY, &strict),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+        ],
+    )
+}
+
+// SetUpdateCooldown by the config `admin`
+pub fn set_update_cooldown_ix(program_id: &Pubkey, admin: &Pubkey, seconds: u32) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(SET_UPDATE_COOLDOWN, &seconds),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+        ],
+    )
+}
+
+// UpdateConfig by the config `admin`, setting the rating scale
+pub fn update_config_ix(program_id: &Pubkey, admin: &Pubkey, min_rating: u8, max_rating: u8) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(UPDATE_CONFIG, &(min_rating, max_rating)),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(program_id), false),
+            AccountMeta::new_readonly(stats_pda(program_id), false),
+        ],
+    )
+}
+
+// ArchiveReview of `review` (about restaurant `title`) by its `owner`
+pub fn archive_review_ix(program_id: &Pubkey, owner: &Pubkey, review: &Pubkey, title: &str) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[ARCHIVE_REVIEW],
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(*review, false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+        ],
+    )
+}
+
+// MigrateReview of `author`'s review of `title` to `new_title`, signed by
+// its `owner`, who pays for the new accounts
+// For a sponsored review, push the sponsor as a final writable account so
+// it gets its rent back
+pub fn migrate_review_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    author: &Pubkey,
+    title: &str,
+    new_title: &str,
+) -> Instruction {
+    let old_review = review_pda(author, title, program_id);
+    let new_review = review_pda(author, new_title, program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(MIGRATE_REVIEW, &(new_title,)),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(old_review, false),
+            AccountMeta::new(new_review, false),
+            AccountMeta::new(comment_counter_pda(&old_review, program_id), false),
+            AccountMeta::new(comment_counter_pda(&new_review, program_id), false),
+            AccountMeta::new(aggregate_pda(title, program_id), false),
+            AccountMeta::new(aggregate_pda(new_title, program_id), false),
+            AccountMeta::new_readonly(config_pda(program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(new_title, program_id), false),
+            AccountMeta::new(reviewer_index_pda(author, program_id), false),
+            AccountMeta::new(review_stake_pda(&old_review, program_id), false),
+            AccountMeta::new(review_stake_pda(&new_review, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// MigrateAccount of `review`, with `payer` covering any extra rent
+pub fn migrate_account_ix(program_id: &Pubkey, payer: &Pubkey, review: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &[MIGRATE_ACCOUNT],
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// ClaimBadge for the `milestone` review count, signed and paid for by `reviewer`
+pub fn claim_badge_ix(program_id: &Pubkey, reviewer: &Pubkey, milestone: u64) -> Instruction {
+    let mint = badge_mint_pda(reviewer, milestone, program_id);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(CLAIM_BADGE, &milestone),
+        vec![
+            AccountMeta::new(*reviewer, true),
+            AccountMeta::new_readonly(reviewer_index_pda(reviewer, program_id), false),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(badge_account_pda(&mint, program_id), false),
+            AccountMeta::new_readonly(badge_authority_pda(program_id), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// CreateMultisig at the new keypair account `multisig`, paid for by `payer`
+pub fn create_multisig_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    multisig: &Pubkey,
+    threshold: u8,
+    signers: &[Pubkey],
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(CREATE_MULTISIG, &(threshold, signers)),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*multisig, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// Turn an update, delete, or transfer built for a wallet owner into one
+// approved by a multisig owner: the multisig takes the owner's place
+// (unsigned) and the signing `members` are appended
+// For updates the first member pays for the revision account; deletes of
+// a multisig-owned review must use delete_sponsored_review_ix with the
+// review's rent payer
+pub fn signed_by_multisig(mut instruction: Instruction, multisig: &Pubkey, members: &[Pubkey]) -> Instruction {
+    instruction.accounts[0] = AccountMeta::new(*multisig, false);
+    instruction
+        .accounts
+        .extend(members.iter().map(|member| AccountMeta::new(*member, true)));
+    instruction
+}
+
+// CreateProfile for the registered restaurant `name`, signed by its `owner`
+pub fn create_profile_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    name: &str,
+    cuisine: &str,
+    address: &str,
+    website: &str,
+    price_range: u8,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(CREATE_PROFILE, &(cuisine, address, website, price_range)),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(restaurant_pda(name, program_id), false),
+            AccountMeta::new(restaurant_profile_pda(name, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// UpdateProfile for the registered restaurant `name`, signed by its `owner`
+pub fn update_profile_ix(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    name: &str,
+    cuisine: &str,
+    address: &str,
+    website: &str,
+    price_range: u8,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(UPDATE_PROFILE, &(cuisine, address, website, price_range)),
+        vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(restaurant_pda(name, program_id), false),
+            AccountMeta::new(restaurant_profile_pda(name, program_id), false),
+        ],
+    )
+}
+
+// Accounts shared by the compressed review instructions
+fn tree_accounts(program_id: &Pubkey, signer: &Pubkey, merkle_tree: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*signer, true),
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(tree_authority_pda(merkle_tree, program_id), false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_ID, false),
+        AccountMeta::new_readonly(NOOP_ID, false),
+    ]
+}
+
+// Tree accounts plus the config PDA, whose rating scale compressed reviews
+// are checked against
+fn compressed_review_accounts(program_id: &Pubkey, author: &Pubkey, merkle_tree: &Pubkey) -> Vec<AccountMeta> {
+    let mut accounts = tree_accounts(program_id, author, merkle_tree);
+    accounts.push(AccountMeta::new_readonly(config_pda(program_id), false));
+    accounts
+}
+
+// CreateReviewTree, after `merkle_tree` has been allocated for the
+// compression program
+pub fn create_review_tree_ix(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    merkle_tree: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(CREATE_REVIEW_TREE, &(max_depth, max_buffer_size)),
+        tree_accounts(program_id, payer, merkle_tree),
+    )
+}
+
+// AddCompressedReview appending `author`'s review to `merkle_tree`
+pub fn add_compressed_review_ix(
+    program_id: &Pubkey,
+    author: &Pubkey,
+    merkle_tree: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(ADD_COMPRESSED_REVIEW, &(title, rating, description)),
+        compressed_review_accounts(program_id, author, merkle_tree),
+    )
+}
+
+// UpdateCompressedReview replacing leaf `index`; `proof` lists the sibling
+// nodes from the leaf up, as returned by an indexer
+#[allow(clippy::too_many_arguments)]
+pub fn update_compressed_review_ix(
+    program_id: &Pubkey,
+    author: &Pubkey,
+    merkle_tree: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    new_rating: u8,
+    new_description: &str,
+    root: [u8; 32],
+    index: u32,
+    proof: &[Pubkey],
+) -> Instruction {
+    let mut accounts = compressed_review_accounts(program_id, author, merkle_tree);
+    accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(*node, false)));
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(
+            UPDATE_COMPRESSED_REVIEW,
+            &(title, rating, description, new_rating, new_description, root, index),
+        ),
+        accounts,
+    )
+}
+
+// AddAnonymousReview committing `author` to a review they reveal later
+// with the same title, rating, description, and salt
+pub fn add_anonymous_review_ix(
+    program_id: &Pubkey,
+    author: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    salt: &[u8; 32],
+) -> Instruction {
+    let commitment = review_commitment(author, title, rating, description, salt);
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(ADD_ANONYMOUS_REVIEW, &commitment),
+        vec![
+            AccountMeta::new(*author, true),
+            AccountMeta::new(commitment_pda(author, &commitment, program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// RevealReview of a review committed with add_anonymous_review_ix
+// The visit details are given only now; they aren't part of the commitment
+#[allow(clippy::too_many_arguments)]
+pub fn reveal_review_ix(
+    program_id: &Pubkey,
+    author: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    salt: &[u8; 32],
+    visited_at: i64,
+    price_range: u8,
+) -> Instruction {
+    let commitment = review_commitment(author, title, rating, description, salt);
+    // The commitment, then the accounts of AddReview
+    let mut accounts = vec![AccountMeta::new(commitment_pda(author, &commitment, program_id), false)];
+    accounts.extend(
+        add_review_ix(program_id, author, title, rating, description, &[], "", None, visited_at, price_range)
+            .accounts,
+    );
+    Instruction::new_with_bytes(
+        *program_id,
+        &instruction_data(REVEAL_REVIEW, &(title, rating, description, salt, visited_at, price_range)),
+        accounts,
+    )
+}
@@ -0,0 +1,94 @@
+// CPIs into the spl-account-compression program, for reviews stored as
+// leaves of a concurrent Merkle tree instead of one PDA each
+//
+// spl-account-compression is an Anchor program, so its instructions are an
+// 8-byte sighash followed by the Borsh-encoded arguments. They are encoded by
+// hand here rather than pulling in the Anchor crates.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+};
+
+// spl-account-compression program
+pub const ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+// spl-noop program, which the compression program logs tree changes through
+pub const NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+// Leaf of a compressed review: sha256 of the Borsh-encoded
+// (author, title, rating, description)
+// The author is part of the leaf, so only the author can produce the
+// previous leaf an update has to prove
+pub fn review_leaf(author: &Pubkey, title: &str, rating: u8, description: &str) -> [u8; 32] {
+    let data = borsh::to_vec(&(author, title, rating, description)).unwrap();
+    hashv(&[&data]).to_bytes()
+}
+
+// Anchor instruction data: sha256("global:<name>")[..8] ++ Borsh args
+fn anchor_data<T: BorshSerialize>(name: &str, args: &T) -> Vec<u8> {
+    let sighash = hashv(&[b"global:", name.as_bytes()]);
+    let mut data = sighash.to_bytes()[..8].to_vec();
+    data.extend(borsh::to_vec(args).unwrap());
+    data
+}
+
+// init_empty_merkle_tree(max_depth, max_buffer_size)
+// The tree account must already be allocated and owned by the compression
+// program; `authority` becomes the only account allowed to modify it
+pub fn init_empty_merkle_tree(
+    merkle_tree: &Pubkey,
+    authority: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        ACCOUNT_COMPRESSION_ID,
+        &anchor_data("init_empty_merkle_tree", &(max_depth, max_buffer_size)),
+        vec![
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(NOOP_ID, false),
+        ],
+    )
+}
+
+// append(leaf)
+pub fn append(merkle_tree: &Pubkey, authority: &Pubkey, leaf: [u8; 32]) -> Instruction {
+    Instruction::new_with_bytes(
+        ACCOUNT_COMPRESSION_ID,
+        &anchor_data("append", &leaf),
+        vec![
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(NOOP_ID, false),
+        ],
+    )
+}
+
+// replace_leaf(root, previous_leaf, new_leaf, index)
+// The proof nodes are passed as trailing read-only accounts
+pub fn replace_leaf(
+    merkle_tree: &Pubkey,
+    authority: &Pubkey,
+    root: [u8; 32],
+    previous_leaf: [u8; 32],
+    new_leaf: [u8; 32],
+    index: u32,
+    proof: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(NOOP_ID, false),
+    ];
+    accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(*node, false)));
+    Instruction::new_with_bytes(
+        ACCOUNT_COMPRESSION_ID,
+        &anchor_data("replace_leaf", &(root, previous_leaf, new_leaf, index)),
+        accounts,
+    )
+}
@@ -0,0 +1,145 @@
+// Events emitted with sol_log_data so indexers can follow reviews from the
+// program logs instead of fetching and decoding accounts
+//
+// Each event is logged as two data fields: its 8-byte discriminator, then
+// the Borsh-encoded event. They show up in the transaction logs as
+// "Program data: <base64 field> <base64 field>"
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey};
+
+pub trait Event: BorshSerialize + BorshDeserialize {
+    // Tag logged ahead of the event, one per event type
+    const DISCRIMINATOR: [u8; 8];
+
+    // Log the event for indexers
+    fn emit(&self) -> ProgramResult {
+        let data = borsh::to_vec(self)?;
+        sol_log_data(&[&Self::DISCRIMINATOR, &data]);
+        Ok(())
+    }
+}
+
+// A new review was created
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ReviewAdded {
+    pub review: Pubkey,  // Review PDA
+    pub author: Pubkey,  // Wallet that wrote it
+    pub title: String,   // Restaurant name
+    pub rating: u8,      // Rating on the configured scale
+    pub created_at: i64, // Unix timestamp
+}
+
+impl Event for ReviewAdded {
+    const DISCRIMINATOR: [u8; 8] = *b"ev_added";
+}
+
+// A review's rating, description, tags, or content URI changed
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ReviewUpdated {
+    pub review: Pubkey,      // Review PDA
+    pub owner: Pubkey,       // Owner who made the change
+    pub rating: u8,          // New rating
+    pub revision_count: u64, // Saved revisions, including the one just made
+    pub updated_at: i64,     // Unix timestamp
+}
+
+impl Event for ReviewUpdated {
+    const DISCRIMINATOR: [u8; 8] = *b"ev_updtd";
+}
+
+// A review was closed by its owner
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ReviewDeleted {
+    pub review: Pubkey, // Review PDA, now closed
+    pub owner: Pubkey,  // Owner who deleted it and got the rent back
+    pub title: String,  // Restaurant name
+}
+
+impl Event for ReviewDeleted {
+    const DISCRIMINATOR: [u8; 8] = *b"ev_deltd";
+}
+
+// A review was taken down by a moderator
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ReviewRemoved {
+    pub review: Pubkey,    // Review PDA, now closed
+    pub author: Pubkey,    // Wallet that wrote it
+    pub moderator: Pubkey, // Admin or moderator who removed it
+    pub title: String,     // Restaurant name
+}
+
+impl Event for ReviewRemoved {
+    const DISCRIMINATOR: [u8; 8] = *b"ev_remvd";
+}
+
+// A review was appended to a Merkle tree
+// Compressed reviews have no account, so the event carries everything
+// needed to rebuild the leaf (see compression::review_leaf)
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct CompressedReviewAdded {
+    pub merkle_tree: Pubkey, // Tree the leaf was appended to
+    pub author: Pubkey,      // Wallet that wrote it
+    pub title: String,       // Restaurant name
+    pub rating: u8,          // Rating (1-10)
+    pub description: String, // Review text
+}
+
+impl Event for CompressedReviewAdded {
+    const DISCRIMINATOR: [u8; 8] = *b"ev_cadd\0";
+}
+
+// A compressed review's leaf was replaced
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct CompressedReviewUpdated {
+    pub merkle_tree: Pubkey, // Tree holding the leaf
+    pub index: u32,          // Leaf index
+    pub author: Pubkey,      // Wallet that wrote it
+    pub title: String,       // Restaurant name
+    pub rating: u8,          // New rating
+    pub description: String, // New review text
+}
+
+impl Event for CompressedReviewUpdated {
+    const DISCRIMINATOR: [u8; 8] = *b"ev_cupd\0";
+}
+
+// Any event this program emits, for decoding logs
+#[derive(Debug, PartialEq)]
+pub enum ReviewEvent {
+    Added(ReviewAdded),
+    Updated(ReviewUpdated),
+    Deleted(ReviewDeleted),
+    Removed(ReviewRemoved),
+    CompressedAdded(CompressedReviewAdded),
+    CompressedUpdated(CompressedReviewUpdated),
+}
+
+impl ReviewEvent {
+    // Decode the data fields of one sol_log_data call
+    // Returns None for logs that aren't events from this program
+    pub fn decode(fields: &[&[u8]]) -> Option<Self> {
+        let [discriminator, data] = fields else {
+            return None;
+        };
+        match <[u8; 8]>::try_from(*discriminator).ok()? {
+            ReviewAdded::DISCRIMINATOR => ReviewAdded::try_from_slice(data).ok().map(Self::Added),
+            ReviewUpdated::DISCRIMINATOR => {
+                ReviewUpdated::try_from_slice(data).ok().map(Self::Updated)
+            }
+            ReviewDeleted::DISCRIMINATOR => {
+                ReviewDeleted::try_from_slice(data).ok().map(Self::Deleted)
+            }
+            ReviewRemoved::DISCRIMINATOR => {
+                ReviewRemoved::try_from_slice(data).ok().map(Self::Removed)
+            }
+            CompressedReviewAdded::DISCRIMINATOR => {
+                CompressedReviewAdded::try_from_slice(data).ok().map(Self::CompressedAdded)
+            }
+            CompressedReviewUpdated::DISCRIMINATOR => {
+                CompressedReviewUpdated::try_from_slice(data).ok().map(Self::CompressedUpdated)
+            }
+            _ => None,
+        }
+    }
+}
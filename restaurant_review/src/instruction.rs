@@ -1,5 +1,8 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
+
+use crate::state::SubRatings;
 
 // Define the instructions this program can handle
 // Each instruction represents an action users can perform
@@ -7,15 +10,270 @@ pub enum ReviewInstruction {
     // Create a new restaurant review
     AddReview {
         title: String,       // Restaurant name
-        rating: u8,          // Rating from 1-10
+        rating: u8,          // Rating on the configured scale (1-10 by default)
         description: String, // Review text
+        tags: Vec<String>,   // Cuisine/category tags
+        content_uri: String, // Off-chain content URI, empty if none
+        sub_ratings: Option<SubRatings>, // Food/service/ambiance, each 1-10
+        visited_at: i64,     // Unix timestamp of the visit, not in the future
+        price_range: u8,     // 1 ($) to 4 ($$$$)
     },
     // Update an existing restaurant review
     UpdateReview {
         title: String,       // Restaurant name (used to find the PDA)
         rating: u8,          // New rating
         description: String, // New review text
+        tags: Vec<String>,   // New tags, replacing the old ones
+        content_uri: String, // New off-chain content URI, empty to clear it
+        sub_ratings: Option<SubRatings>, // New sub-ratings, None to clear them
+        visited_at: i64,     // New visit timestamp, not in the future
+        price_range: u8,     // New price range, 1 ($) to 4 ($$$$)
+    },
+    // Delete a review and return its rent to the reviewer
+    DeleteReview {
+        title: String,       // Restaurant name (used to find the PDA)
+    },
+    // Comment on an existing review
+    AddComment {
+        comment: String,     // Comment text
+    },
+    // Create the program-wide statistics account (once)
+    InitializeStats,
+    // Send SOL to a review's author
+    TipReviewer {
+        lamports: u64,       // Tip amount
+    },
+    // Send SPL tokens to a review's author
+    TipReviewerToken {
+        amount: u64,         // Tip amount in the mint's base units
+    },
+    // Up- or downvote a review, once per wallet
+    VoteReview {
+        up: bool,            // true = upvote, false = downvote
+    },
+    // Hand a review to another wallet, which can then update or delete it
+    TransferReviewOwnership {
+        new_owner: Pubkey,   // Wallet receiving the review
+    },
+    // Create the program config with the signer as admin (once)
+    InitializeConfig {
+        moderators: Vec<Pubkey>, // Moderators besides the admin
+    },
+    // Report a visible review for moderation
+    FlagReview,
+    // Moderator decision on a review: hide it or make it visible again
+    ResolveFlag {
+        hide: bool,          // true = Hidden, false = Visible
+    },
+    // Admin registers (or replaces) the verified owner of a restaurant
+    VerifyRestaurantOwner {
+        title: String,       // Restaurant name, normalized for the registry seed
+        owner: Pubkey,       // Wallet allowed to respond to its reviews
+    },
+    // Restaurant owner's reply to a review, replacing any earlier reply
+    RespondToReview {
+        response: String,    // Reply text
+    },
+    // Admin adds a restaurant to the registry
+    RegisterRestaurant {
+        name: String,        // Canonical restaurant name
+        location: String,    // Address or city
+        owner: Pubkey,       // Wallet the restaurant is registered to
+    },
+    // Admin turns strict mode on or off
+    SetStrictRegistry {
+        strict: bool,        // true = only registered restaurants can be reviewed
+    },
+    // Admin sets the minimum time between updates of the same review
+    SetUpdateCooldown {
+        seconds: u32,        // 0 disables the cooldown
+    },
+    // Owner soft-deletes a review, keeping the account for indexers
+    ArchiveReview,
+    // Owner renames a review, moving it to the PDA for the new title
+    MigrateReview {
+        new_title: String,   // Restaurant name the review moves to
+    },
+    // Upgrade a review written in an older layout to the current one
+    MigrateAccount,
+    // Reviewer mints the badge NFT for a milestone of live reviews they reached
+    ClaimBadge {
+        milestone: u64,      // One of BADGE_MILESTONES
+    },
+    // Create an M-of-N multisig that reviews can be transferred to
+    CreateMultisig {
+        threshold: u8,        // Signatures required
+        signers: Vec<Pubkey>, // Members
+    },
+    // Registered owner creates the directory profile of their restaurant
+    CreateProfile {
+        cuisine: String,     // Kind of food served
+        address: String,     // Street address
+        website: String,     // Website URL, empty if none
+        price_range: u8,     // 1 ($) to 4 ($$$$)
+    },
+    // Registered owner replaces every field of their restaurant's profile
+    UpdateProfile {
+        cuisine: String,     // Kind of food served
+        address: String,     // Street address
+        website: String,     // Website URL, empty if none
+        price_range: u8,     // 1 ($) to 4 ($$$$)
+    },
+    // Set up a concurrent Merkle tree for compressed reviews, with a program
+    // PDA as its authority
+    CreateReviewTree {
+        max_depth: u32,       // Tree holds up to 2^max_depth reviews
+        max_buffer_size: u32, // Concurrent changes per slot
+    },
+    // Append a review to a tree as a leaf instead of creating a PDA for it
+    AddCompressedReview {
+        title: String,       // Restaurant name
+        rating: u8,          // Rating 1-10
+        description: String, // Review text
+    },
+    // Replace a compressed review's leaf, proving its current contents
+    UpdateCompressedReview {
+        title: String,           // Restaurant name (unchanged)
+        rating: u8,              // Current rating
+        description: String,     // Current review text
+        new_rating: u8,          // New rating 1-10
+        new_description: String, // New review text
+        root: [u8; 32],          // Tree root the proof was built against
+        index: u32,              // Leaf index of the review
+    },
+    // Commit to a review by its hash, to reveal it later
+    AddAnonymousReview {
+        commitment: [u8; 32], // state::review_commitment of the review
+    },
+    // Reveal a committed review, creating it as AddReview would
+    RevealReview {
+        title: String,       // Restaurant name
+        rating: u8,          // Rating on the configured scale
+        description: String, // Review text
+        salt: [u8; 32],      // Salt used in the commitment
+        visited_at: i64,     // Unix timestamp of the visit, not committed to
+        price_range: u8,     // 1 ($) to 4 ($$$$), not committed to
+    },
+    // Report a review as abusive; enough reports hide it for moderators
+    ReportReview,
+    // Admin sets how many reports hide a review, 0 to turn auto-hiding off
+    SetReportThreshold {
+        reports: u32,        // Reports needed
+    },
+    // Admin sets the rating scale, before the first review is added
+    UpdateConfig {
+        min_rating: u8,      // Lowest rating accepted, at least 1
+        max_rating: u8,      // Highest rating accepted
+    },
+    // Add a review of a registered restaurant, addressed by its registry
+    // account instead of a free-text title
+    AddReviewForRestaurant {
+        rating: u8,          // Rating on the configured scale
+        description: String, // Review text
+        tags: Vec<String>,   // Cuisine/category tags
+        content_uri: String, // Off-chain content URI, empty if none
+        sub_ratings: Option<SubRatings>, // Food/service/ambiance, each 1-10
+        visited_at: i64,     // Unix timestamp of the visit, not in the future
+        price_range: u8,     // 1 ($) to 4 ($$$$)
+    },
+    // Admin sets the stake new reviews lock
+    SetReviewStake {
+        lamports: u64,       // Stake per new review, 0 for none
+    },
+    // Moderator confiscates a spam review's stake to the treasury PDA
+    SlashReview,
+    // Admin sets the fee new reviews pay into the program treasury
+    SetReviewFee {
+        lamports: u64,       // Fee per new review, 0 for none
+    },
+    // Admin withdraws collected fees from the program treasury
+    WithdrawTreasury {
+        lamports: u64,       // Amount to withdraw, at most the fees held
+    },
+    // Admin nominates a successor, who takes over once it accepts
+    NominateAdmin {
+        new_admin: Pubkey,   // Wallet that may accept the admin role
+    },
+    // Nominated wallet accepts and becomes the admin
+    AcceptAdmin,
+    // Admin freezes or unfreezes adding, editing, and deleting reviews and comments
+    SetPaused {
+        paused: bool,        // true to pause
+    },
+    // Set the handle shown next to the signer's reviews
+    SetDisplayName {
+        name: String,        // At most MAX_DISPLAY_NAME_LEN bytes of [A-Za-z0-9_.-]
     },
+    // AddReview that succeeds without changes if the signer's review of
+    // this title already exists with exactly this content
+    AddReviewIfNotExists {
+        title: String,       // Restaurant name
+        rating: u8,          // Rating on the configured scale (1-10 by default)
+        description: String, // Review text
+        tags: Vec<String>,   // Cuisine/category tags
+        content_uri: String, // Off-chain content URI, empty if none
+        sub_ratings: Option<SubRatings>, // Food/service/ambiance, each 1-10
+        visited_at: i64,     // Unix timestamp of the visit, not in the future
+        price_range: u8,     // 1 ($) to 4 ($$$$)
+    },
+    // AddReview if the signer's review of this title doesn't exist yet,
+    // UpdateReview if it does
+    UpsertReview {
+        title: String,       // Restaurant name
+        rating: u8,          // Rating on the configured scale (1-10 by default)
+        description: String, // Review text
+        tags: Vec<String>,   // Cuisine/category tags
+        content_uri: String, // Off-chain content URI, empty if none
+        sub_ratings: Option<SubRatings>, // Food/service/ambiance, each 1-10
+        visited_at: i64,     // Unix timestamp of the visit, not in the future
+        price_range: u8,     // 1 ($) to 4 ($$$$)
+    },
+    // Post another review of a restaurant after a repeat visit, leaving
+    // the earlier ones as they are
+    AddFollowUpReview {
+        title: String,       // Restaurant name
+        rating: u8,          // Rating on the configured scale (1-10 by default)
+        description: String, // Review text
+        tags: Vec<String>,   // Cuisine/category tags
+        content_uri: String, // Off-chain content URI, empty if none
+        sub_ratings: Option<SubRatings>, // Food/service/ambiance, each 1-10
+        visited_at: i64,     // Unix timestamp of the visit, not in the future
+        price_range: u8,     // 1 ($) to 4 ($$$$)
+    },
+    // Follow a reviewer; the followee is passed as an account
+    Follow,
+    // Stop following a reviewer
+    Unfollow,
+    // Add a registered restaurant to the signer's bookmarks
+    BookmarkRestaurant,
+    // Take a restaurant out of the signer's bookmarks
+    RemoveBookmark,
+    // Admin sets the fee a restaurant claim escrows
+    SetClaimFee {
+        lamports: u64,       // Fee per claim, 0 for none
+    },
+    // Ask to become the verified owner of a registered restaurant
+    ClaimRestaurant,
+    // Admin makes the claimant the restaurant's owner, keeping the fee
+    ApproveClaim,
+    // Admin turns a claim down, refunding the fee
+    RejectClaim,
+    // Moderator closes an abusive review, sending its rent to the treasury PDA
+    RemoveReview,
+    // Anyone brings a restaurant's time-decayed rating sums up to date
+    RecomputeAggregate {
+        title: String,       // Restaurant name (used to find the aggregate PDA)
+    },
+}
+
+// Return data set by AddReview and UpdateReview
+// Calling programs read it with get_return_data after the CPI, and clients
+// from a simulation, without fetching the review account
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub struct ReviewReturnData {
+    pub review: Pubkey,      // Review PDA
+    pub rating: u8,          // Rating after the instruction
+    pub revision_count: u64, // Saved revisions, 0 for a new review
 }
 
 // Internal structure for deserializing instruction data
@@ -25,33 +283,599 @@ struct ReviewPayload {
     title: String,
     rating: u8,
     description: String,
+    tags: Vec<String>,
+    content_uri: String,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+}
+
+// Payload for AddReviewForRestaurant - the title comes from the registry
+#[derive(BorshDeserialize)]
+struct RestaurantReviewPayload {
+    rating: u8,
+    description: String,
+    tags: Vec<String>,
+    content_uri: String,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+}
+
+// Payload for DeleteReview and RecomputeAggregate - only the title is needed to find the PDA
+#[derive(BorshDeserialize)]
+struct DeleteReviewPayload {
+    title: String,
+}
+
+// Payload for TipReviewer
+#[derive(BorshDeserialize)]
+struct TipPayload {
+    lamports: u64,
+}
+
+// Payload for TipReviewerToken
+#[derive(BorshDeserialize)]
+struct TokenTipPayload {
+    amount: u64,
+}
+
+// Payload for VoteReview
+#[derive(BorshDeserialize)]
+struct VotePayload {
+    up: bool,
+}
+
+// Payload for TransferReviewOwnership
+#[derive(BorshDeserialize)]
+struct TransferOwnershipPayload {
+    new_owner: Pubkey,
+}
+
+// Payload for InitializeConfig
+#[derive(BorshDeserialize)]
+struct ConfigPayload {
+    moderators: Vec<Pubkey>,
+}
+
+// Payload for ResolveFlag
+#[derive(BorshDeserialize)]
+struct ResolveFlagPayload {
+    hide: bool,
+}
+
+// Payload for VerifyRestaurantOwner
+#[derive(BorshDeserialize)]
+struct VerifyOwnerPayload {
+    title: String,
+    owner: Pubkey,
+}
+
+// Payload for RespondToReview
+#[derive(BorshDeserialize)]
+struct ResponsePayload {
+    response: String,
+}
+
+// Payload for RegisterRestaurant
+#[derive(BorshDeserialize)]
+struct RegisterRestaurantPayload {
+    name: String,
+    location: String,
+    owner: Pubkey,
+}
+
+// Payload for SetStrictRegistry
+#[derive(BorshDeserialize)]
+struct StrictRegistryPayload {
+    strict: bool,
+}
+
+// Payload for SetUpdateCooldown
+#[derive(BorshDeserialize)]
+struct UpdateCooldownPayload {
+    seconds: u32,
+}
+
+// Payload for UpdateConfig
+#[derive(BorshDeserialize)]
+struct UpdateConfigPayload {
+    min_rating: u8,
+    max_rating: u8,
+}
+
+// Payload for AddAnonymousReview
+#[derive(BorshDeserialize)]
+struct CommitmentPayload {
+    commitment: [u8; 32],
+}
+
+// Payload for RevealReview
+#[derive(BorshDeserialize)]
+struct RevealReviewPayload {
+    title: String,
+    rating: u8,
+    description: String,
+    salt: [u8; 32],
+    visited_at: i64,
+    price_range: u8,
+}
+
+// Payload for SetReviewStake
+#[derive(BorshDeserialize)]
+struct ReviewStakePayload {
+    lamports: u64,
+}
+
+// Payload for SetReviewFee, WithdrawTreasury, and SetClaimFee
+#[derive(BorshDeserialize)]
+struct LamportsPayload {
+    lamports: u64,
+}
+
+// Payload for NominateAdmin
+#[derive(BorshDeserialize)]
+struct NominateAdminPayload {
+    new_admin: Pubkey,
+}
+
+// Payload for SetPaused
+#[derive(BorshDeserialize)]
+struct PausedPayload {
+    paused: bool,
+}
+
+// Payload for SetDisplayName
+#[derive(BorshDeserialize)]
+struct DisplayNamePayload {
+    name: String,
+}
+
+// Payload for SetReportThreshold
+#[derive(BorshDeserialize)]
+struct ReportThresholdPayload {
+    reports: u32,
+}
+
+// Payload for MigrateReview
+#[derive(BorshDeserialize)]
+struct MigrateReviewPayload {
+    new_title: String,
+}
+
+// Payload for ClaimBadge
+#[derive(BorshDeserialize)]
+struct ClaimBadgePayload {
+    milestone: u64,
+}
+
+// Payload for CreateMultisig
+#[derive(BorshDeserialize)]
+struct CreateMultisigPayload {
+    threshold: u8,
+    signers: Vec<Pubkey>,
+}
+
+// Payload for CreateProfile and UpdateProfile
+#[derive(BorshDeserialize)]
+struct ProfilePayload {
+    cuisine: String,
+    address: String,
+    website: String,
+    price_range: u8,
+}
+
+// Payload for CreateReviewTree
+#[derive(BorshDeserialize)]
+struct CreateReviewTreePayload {
+    max_depth: u32,
+    max_buffer_size: u32,
+}
+
+// Payload for AddCompressedReview
+#[derive(BorshDeserialize)]
+struct CompressedReviewPayload {
+    title: String,
+    rating: u8,
+    description: String,
+}
+
+// Payload for UpdateCompressedReview
+#[derive(BorshDeserialize)]
+struct UpdateCompressedReviewPayload {
+    title: String,
+    rating: u8,
+    description: String,
+    new_rating: u8,
+    new_description: String,
+    root: [u8; 32],
+    index: u32,
+}
+
+// Payload for AddComment - the review is identified by the accounts passed in
+#[derive(BorshDeserialize)]
+struct CommentPayload {
+    comment: String,
 }
 
 impl ReviewInstruction {
     // Deserialize instruction data from bytes into a ReviewInstruction enum
     // Instruction format: [variant_byte][borsh_serialized_payload]
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        // First byte indicates which instruction variant (0 = AddReview, 1 = UpdateReview)
+        // First byte indicates which instruction variant
+        // (0 = AddReview, 1 = UpdateReview, 2 = DeleteReview, 3 = AddComment,
+        // 4 = InitializeStats, 5 = TipReviewer,
+        // 6 = TipReviewerToken, 7 = VoteReview, 8 = TransferReviewOwnership,
+        // 9 = InitializeConfig, 10 = FlagReview, 11 = ResolveFlag,
+        // 12 = VerifyRestaurantOwner, 13 = RespondToReview,
+        // 14 = RegisterRestaurant, 15 = SetStrictRegistry, 16 = SetUpdateCooldown,
+        // 17 = ArchiveReview, 18 = MigrateReview, 19 = MigrateAccount,
+        // 20 = ClaimBadge, 21 = CreateMultisig, 22 = CreateProfile,
+        // 23 = UpdateProfile, 24 = CreateReviewTree, 25 = AddCompressedReview,
+        // 26 = UpdateCompressedReview, 27 = UpdateConfig, 28 = AddAnonymousReview,
+        // 29 = RevealReview, 30 = ReportReview, 31 = SetReportThreshold,
+        // 32 = AddReviewForRestaurant, 33 = SetReviewStake, 34 = SlashReview,
+        // 35 = SetReviewFee, 36 = WithdrawTreasury, 37 = NominateAdmin,
+        // 38 = AcceptAdmin, 39 = SetPaused, 40 = SetDisplayName,
+        // 41 = AddReviewIfNotExists, 42 = UpsertReview, 43 = AddFollowUpReview,
+        // 44 = Follow, 45 = Unfollow, 46 = BookmarkRestaurant, 47 = RemoveBookmark,
+        // 48 = SetClaimFee, 49 = ClaimRestaurant, 50 = ApproveClaim, 51 = RejectClaim,
+        // 52 = RemoveReview, 53 = RecomputeAggregate)
         let (&variant, rest) = input
             .split_first()
             .ok_or(ProgramError::InvalidInstructionData)?;
-        
-        // Remaining bytes contain the instruction data (title, rating, description)
-        let payload = ReviewPayload::try_from_slice(rest)
-            .map_err(|_| ProgramError::InvalidInstructionData)?;
-        
+
+        // Remaining bytes contain the instruction data for that variant
         // Match on variant to create the appropriate instruction
         Ok(match variant {
-            0 => Self::AddReview {
-                title: payload.title,
-                rating: payload.rating,
-                description: payload.description,
-            },
-            1 => Self::UpdateReview {
-                title: payload.title,
-                rating: payload.rating,
-                description: payload.description,
-            },
+            0 => {
+                let payload = ReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    tags: payload.tags,
+                    content_uri: payload.content_uri,
+                    sub_ratings: payload.sub_ratings,
+                    visited_at: payload.visited_at,
+                    price_range: payload.price_range,
+                }
+            }
+            1 => {
+                let payload = ReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpdateReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    tags: payload.tags,
+                    content_uri: payload.content_uri,
+                    sub_ratings: payload.sub_ratings,
+                    visited_at: payload.visited_at,
+                    price_range: payload.price_range,
+                }
+            }
+            2 => {
+                let payload = DeleteReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::DeleteReview {
+                    title: payload.title,
+                }
+            }
+            3 => {
+                let payload = CommentPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddComment {
+                    comment: payload.comment,
+                }
+            }
+            4 => Self::InitializeStats,
+            5 => {
+                let payload = TipPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::TipReviewer {
+                    lamports: payload.lamports,
+                }
+            }
+            6 => {
+                let payload = TokenTipPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::TipReviewerToken {
+                    amount: payload.amount,
+                }
+            }
+            7 => {
+                let payload = VotePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::VoteReview { up: payload.up }
+            }
+            8 => {
+                let payload = TransferOwnershipPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::TransferReviewOwnership {
+                    new_owner: payload.new_owner,
+                }
+            }
+            9 => {
+                let payload = ConfigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::InitializeConfig {
+                    moderators: payload.moderators,
+                }
+            }
+            10 => Self::FlagReview,
+            11 => {
+                let payload = ResolveFlagPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ResolveFlag { hide: payload.hide }
+            }
+            12 => {
+                let payload = VerifyOwnerPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::VerifyRestaurantOwner {
+                    title: payload.title,
+                    owner: payload.owner,
+                }
+            }
+            13 => {
+                let payload = ResponsePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RespondToReview {
+                    response: payload.response,
+                }
+            }
+            14 => {
+                let payload = RegisterRestaurantPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RegisterRestaurant {
+                    name: payload.name,
+                    location: payload.location,
+                    owner: payload.owner,
+                }
+            }
+            15 => {
+                let payload = StrictRegistryPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetStrictRegistry {
+                    strict: payload.strict,
+                }
+            }
+            16 => {
+                let payload = UpdateCooldownPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetUpdateCooldown {
+                    seconds: payload.seconds,
+                }
+            }
+            17 => Self::ArchiveReview,
+            18 => {
+                let payload = MigrateReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::MigrateReview {
+                    new_title: payload.new_title,
+                }
+            }
+            19 => Self::MigrateAccount,
+            20 => {
+                let payload = ClaimBadgePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::ClaimBadge {
+                    milestone: payload.milestone,
+                }
+            }
+            21 => {
+                let payload = CreateMultisigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateMultisig {
+                    threshold: payload.threshold,
+                    signers: payload.signers,
+                }
+            }
+            22 => {
+                let payload = ProfilePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateProfile {
+                    cuisine: payload.cuisine,
+                    address: payload.address,
+                    website: payload.website,
+                    price_range: payload.price_range,
+                }
+            }
+            23 => {
+                let payload = ProfilePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpdateProfile {
+                    cuisine: payload.cuisine,
+                    address: payload.address,
+                    website: payload.website,
+                    price_range: payload.price_range,
+                }
+            }
+            24 => {
+                let payload = CreateReviewTreePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::CreateReviewTree {
+                    max_depth: payload.max_depth,
+                    max_buffer_size: payload.max_buffer_size,
+                }
+            }
+            25 => {
+                let payload = CompressedReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddCompressedReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                }
+            }
+            26 => {
+                let payload = UpdateCompressedReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpdateCompressedReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    new_rating: payload.new_rating,
+                    new_description: payload.new_description,
+                    root: payload.root,
+                    index: payload.index,
+                }
+            }
+            27 => {
+                let payload = UpdateConfigPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpdateConfig {
+                    min_rating: payload.min_rating,
+                    max_rating: payload.max_rating,
+                }
+            }
+            28 => {
+                let payload = CommitmentPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddAnonymousReview {
+                    commitment: payload.commitment,
+                }
+            }
+            29 => {
+                let payload = RevealReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RevealReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    salt: payload.salt,
+                    visited_at: payload.visited_at,
+                    price_range: payload.price_range,
+                }
+            }
+            30 => Self::ReportReview,
+            31 => {
+                let payload = ReportThresholdPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetReportThreshold {
+                    reports: payload.reports,
+                }
+            }
+            32 => {
+                let payload = RestaurantReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddReviewForRestaurant {
+                    rating: payload.rating,
+                    description: payload.description,
+                    tags: payload.tags,
+                    content_uri: payload.content_uri,
+                    sub_ratings: payload.sub_ratings,
+                    visited_at: payload.visited_at,
+                    price_range: payload.price_range,
+                }
+            }
+            33 => {
+                let payload = ReviewStakePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetReviewStake {
+                    lamports: payload.lamports,
+                }
+            }
+            34 => Self::SlashReview,
+            35 => {
+                let payload = LamportsPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetReviewFee {
+                    lamports: payload.lamports,
+                }
+            }
+            36 => {
+                let payload = LamportsPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::WithdrawTreasury {
+                    lamports: payload.lamports,
+                }
+            }
+            37 => {
+                let payload = NominateAdminPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::NominateAdmin {
+                    new_admin: payload.new_admin,
+                }
+            }
+            38 => Self::AcceptAdmin,
+            39 => {
+                let payload = PausedPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetPaused {
+                    paused: payload.paused,
+                }
+            }
+            40 => {
+                let payload = DisplayNamePayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetDisplayName { name: payload.name }
+            }
+            41 => {
+                let payload = ReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddReviewIfNotExists {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    tags: payload.tags,
+                    content_uri: payload.content_uri,
+                    sub_ratings: payload.sub_ratings,
+                    visited_at: payload.visited_at,
+                    price_range: payload.price_range,
+                }
+            }
+            42 => {
+                let payload = ReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::UpsertReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    tags: payload.tags,
+                    content_uri: payload.content_uri,
+                    sub_ratings: payload.sub_ratings,
+                    visited_at: payload.visited_at,
+                    price_range: payload.price_range,
+                }
+            }
+            43 => {
+                let payload = ReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::AddFollowUpReview {
+                    title: payload.title,
+                    rating: payload.rating,
+                    description: payload.description,
+                    tags: payload.tags,
+                    content_uri: payload.content_uri,
+                    sub_ratings: payload.sub_ratings,
+                    visited_at: payload.visited_at,
+                    price_range: payload.price_range,
+                }
+            }
+            44 => Self::Follow,
+            45 => Self::Unfollow,
+            46 => Self::BookmarkRestaurant,
+            47 => Self::RemoveBookmark,
+            48 => {
+                let payload = LamportsPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::SetClaimFee {
+                    lamports: payload.lamports,
+                }
+            }
+            49 => Self::ClaimRestaurant,
+            50 => Self::ApproveClaim,
+            51 => Self::RejectClaim,
+            52 => Self::RemoveReview,
+            53 => {
+                let payload = DeleteReviewPayload::try_from_slice(rest)
+                    .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Self::RecomputeAggregate {
+                    title: payload.title,
+                }
+            }
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
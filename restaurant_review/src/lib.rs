@@ -1,27 +1,59 @@
 // Module declarations - organize code into separate files
+pub mod compression;  // CPIs into spl-account-compression for compressed reviews
+pub mod events;       // Events logged for indexers, also decoded by clients
 pub mod instruction;  // Instruction parsing and types
 pub mod state;        // Account state structures and errors
+#[cfg(all(feature = "client", not(target_os = "solana")))]
+pub mod client;       // Off-chain instruction builders and PDA helpers
 
-use crate::instruction::ReviewInstruction;
-use crate::state::AccountState;
+use crate::compression::{review_leaf, ACCOUNT_COMPRESSION_ID, NOOP_ID};
+use crate::events::{
+    CompressedReviewAdded, CompressedReviewUpdated, Event, ReviewAdded, ReviewDeleted, ReviewRemoved, ReviewUpdated,
+};
+use crate::instruction::{ReviewInstruction, ReviewReturnData};
+use crate::state::{
+    grapheme_count, helpfulness, is_printable_text, normalize_title, review_commitment, review_seed, title_seed, AccountState, ProgramConfig, ProgramStats, RestaurantAggregate, ReviewComment,
+    Restaurant, RestaurantProfile, ReviewCommentCounter, ReviewCommitment, ReviewResponse, ReviewRevision, ReviewStatus, ReviewVote,
+    ReviewMultisig, ReviewReport, ReviewReportCount, ReviewStake, ReviewZeroCopy, SubRatings, ProgramTreasury, FollowUpCounter, ReviewerFollow, ReviewerIndex, RestaurantBookmarks, RestaurantClaim, ClaimStatus, ReviewerProfile, ReviewerRecord, TipLedger, VerifiedOwner, MAX_ACCOUNT_LEN, MAX_BOOKMARKS, MAX_DESCRIPTION_LEN,
+    DECAY_SCALE, DEFAULT_MAX_RATING, DEFAULT_MIN_RATING, MAX_CUISINE_LEN, MAX_LOCATION_LEN, MAX_MODERATORS, MAX_PRICE_RANGE, MAX_WEBSITE_LEN, MAX_MULTISIG_SIGNERS, BADGE_MILESTONES, MAX_RESPONSE_LEN, MAX_TAGS, MAX_TAG_LEN, MAX_TITLE_LEN, MAX_DESCRIPTION_GRAPHEMES, MAX_TITLE_GRAPHEMES,
+    CONTENT_URI_PREFIXES, MAX_CONTENT_URI_LEN, MAX_DISPLAY_NAME_LEN, MAX_SUB_RATING, MIN_SUB_RATING,
+};
 use crate::state::ReviewError;
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::BorshSerialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
     entrypoint::ProgramResult,
     msg,  // Macro for logging on-chain
-    program::invoke_signed,  // For CPI with PDA signing
+    program::{invoke, invoke_signed, set_return_data},  // For CPI (invoke_signed lets a PDA sign)
     program_error::ProgramError,
-    program_pack::IsInitialized,
+    program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},  // For calculating rent exemption
+    sysvar::{clock::Clock, rent::Rent, Sysvar, SysvarSerialize},  // Clock for timestamps, Rent for rent exemption
 };
 use solana_system_interface::instruction as system_instruction;
+use spl_generic_token::generic_token::{Account, Mint};
+use spl_token::instruction::{
+    freeze_account, initialize_account3, initialize_mint2, mint_to, set_authority, transfer_checked,
+    AuthorityType,
+};
 use std::convert::TryInto;
+use std::ops::RangeInclusive;
+
+// Like msg!, but only with the "debug-logs" feature
+// For logs that echo user-provided strings (titles, descriptions, comments):
+// formatting and logging them costs compute units on every instruction, so
+// production builds leave them out
+macro_rules! debug_msg {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "debug-logs")]
+        msg!($($arg)*);
+    };
+}
 
 // Define the program entrypoint
-entrypoint!(process_instruction);
+// Skipped with the "no-entrypoint" feature so other programs can depend on this crate
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
 // Main entry point for all instructions sent to this program
 pub fn process_instruction(
@@ -38,28 +70,615 @@ pub fn process_instruction(
             title,
             rating,
             description,
-        } => add_review(program_id, accounts, title, rating, description),
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        } => add_review(
+            program_id,
+            accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        ),
+        ReviewInstruction::AddReviewIfNotExists {
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        } => add_review_if_not_exists(
+            program_id,
+            accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        ),
+        ReviewInstruction::UpsertReview {
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        } => upsert_review(
+            program_id,
+            accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        ),
+        ReviewInstruction::AddFollowUpReview {
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        } => add_follow_up_review(
+            program_id,
+            accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        ),
+        ReviewInstruction::AddReviewForRestaurant {
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        } => add_review_for_restaurant(
+            program_id,
+            accounts,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        ),
         ReviewInstruction::UpdateReview {
             title,
             rating,
             description,
-        } => update_review(program_id, accounts, title, rating, description),
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        } => update_review(
+            program_id,
+            accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        ),
+        ReviewInstruction::DeleteReview { title } => delete_review(program_id, accounts, title),
+        ReviewInstruction::AddComment { comment } => add_comment(program_id, accounts, comment),
+        ReviewInstruction::InitializeStats => initialize_stats(program_id, accounts),
+        ReviewInstruction::TipReviewer { lamports } => tip_reviewer(program_id, accounts, lamports),
+        ReviewInstruction::TipReviewerToken { amount } => {
+            tip_reviewer_token(program_id, accounts, amount)
+        }
+        ReviewInstruction::VoteReview { up } => vote_review(program_id, accounts, up),
+        ReviewInstruction::TransferReviewOwnership { new_owner } => {
+            transfer_review_ownership(program_id, accounts, new_owner)
+        }
+        ReviewInstruction::InitializeConfig { moderators } => {
+            initialize_config(program_id, accounts, moderators)
+        }
+        ReviewInstruction::FlagReview => flag_review(program_id, accounts),
+        ReviewInstruction::ResolveFlag { hide } => resolve_flag(program_id, accounts, hide),
+        ReviewInstruction::VerifyRestaurantOwner { title, owner } => {
+            verify_restaurant_owner(program_id, accounts, title, owner)
+        }
+        ReviewInstruction::RespondToReview { response } => {
+            respond_to_review(program_id, accounts, response)
+        }
+        ReviewInstruction::RegisterRestaurant {
+            name,
+            location,
+            owner,
+        } => register_restaurant(program_id, accounts, name, location, owner),
+        ReviewInstruction::SetStrictRegistry { strict } => {
+            set_strict_registry(program_id, accounts, strict)
+        }
+        ReviewInstruction::SetUpdateCooldown { seconds } => {
+            set_update_cooldown(program_id, accounts, seconds)
+        }
+        ReviewInstruction::UpdateConfig {
+            min_rating,
+            max_rating,
+        } => update_config(program_id, accounts, min_rating, max_rating),
+        ReviewInstruction::AddAnonymousReview { commitment } => {
+            add_anonymous_review(program_id, accounts, commitment)
+        }
+        ReviewInstruction::RevealReview {
+            title,
+            rating,
+            description,
+            salt,
+            visited_at,
+            price_range,
+        } => reveal_review(program_id, accounts, title, rating, description, salt, visited_at, price_range),
+        ReviewInstruction::ReportReview => report_review(program_id, accounts),
+        ReviewInstruction::SetReportThreshold { reports } => {
+            set_report_threshold(program_id, accounts, reports)
+        }
+        ReviewInstruction::SetReviewStake { lamports } => set_review_stake(program_id, accounts, lamports),
+        ReviewInstruction::SlashReview => slash_review(program_id, accounts),
+        ReviewInstruction::SetReviewFee { lamports } => set_review_fee(program_id, accounts, lamports),
+        ReviewInstruction::WithdrawTreasury { lamports } => {
+            withdraw_treasury(program_id, accounts, lamports)
+        }
+        ReviewInstruction::NominateAdmin { new_admin } => nominate_admin(program_id, accounts, new_admin),
+        ReviewInstruction::AcceptAdmin => accept_admin(program_id, accounts),
+        ReviewInstruction::SetPaused { paused } => set_paused(program_id, accounts, paused),
+        ReviewInstruction::SetDisplayName { name } => set_display_name(program_id, accounts, name),
+        ReviewInstruction::Follow => follow(program_id, accounts),
+        ReviewInstruction::Unfollow => unfollow(program_id, accounts),
+        ReviewInstruction::BookmarkRestaurant => bookmark_restaurant(program_id, accounts),
+        ReviewInstruction::RemoveBookmark => remove_bookmark(program_id, accounts),
+        ReviewInstruction::SetClaimFee { lamports } => set_claim_fee(program_id, accounts, lamports),
+        ReviewInstruction::ClaimRestaurant => claim_restaurant(program_id, accounts),
+        ReviewInstruction::ApproveClaim => approve_claim(program_id, accounts),
+        ReviewInstruction::RejectClaim => reject_claim(program_id, accounts),
+        ReviewInstruction::RemoveReview => remove_review(program_id, accounts),
+        ReviewInstruction::RecomputeAggregate { title } => recompute_aggregate(program_id, accounts, title),
+        ReviewInstruction::ArchiveReview => archive_review(program_id, accounts),
+        ReviewInstruction::MigrateReview { new_title } => {
+            migrate_review(program_id, accounts, new_title)
+        }
+        ReviewInstruction::MigrateAccount => migrate_account(program_id, accounts),
+        ReviewInstruction::ClaimBadge { milestone } => claim_badge(program_id, accounts, milestone),
+        ReviewInstruction::CreateMultisig { threshold, signers } => {
+            create_multisig(program_id, accounts, threshold, signers)
+        }
+        ReviewInstruction::CreateProfile {
+            cuisine,
+            address,
+            website,
+            price_range,
+        } => create_profile(program_id, accounts, cuisine, address, website, price_range),
+        ReviewInstruction::UpdateProfile {
+            cuisine,
+            address,
+            website,
+            price_range,
+        } => update_profile(program_id, accounts, cuisine, address, website, price_range),
+        ReviewInstruction::CreateReviewTree {
+            max_depth,
+            max_buffer_size,
+        } => create_review_tree(program_id, accounts, max_depth, max_buffer_size),
+        ReviewInstruction::AddCompressedReview {
+            title,
+            rating,
+            description,
+        } => add_compressed_review(program_id, accounts, title, rating, description),
+        ReviewInstruction::UpdateCompressedReview {
+            title,
+            rating,
+            description,
+            new_rating,
+            new_description,
+            root,
+            index,
+        } => update_compressed_review(
+            program_id,
+            accounts,
+            title,
+            rating,
+            description,
+            new_rating,
+            new_description,
+            root,
+            index,
+        ),
     }
 }
 
 // Handler for adding a new restaurant review
 // Creates a PDA account to store the review data
+#[allow(clippy::too_many_arguments)]
 pub fn add_review(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     title: String,         // Restaurant name
-    rating: u8,            // Rating 1-10
+    rating: u8,            // Rating on the configured scale
+    description: String,   // Review text
+    tags: Vec<String>,     // Cuisine/category tags
+    content_uri: String,   // Off-chain content URI, empty if none
+    sub_ratings: Option<SubRatings>, // Food/service/ambiance breakdown
+    visited_at: i64,       // When the reviewer visited
+    price_range: u8,       // What the visit cost, 1 ($) to 4 ($$$$)
+) -> ProgramResult {
+    create_review(
+        program_id,
+        accounts,
+        Some(title),
+        rating,
+        description,
+        tags,
+        content_uri,
+        sub_ratings,
+        visited_at,
+        price_range,
+        0,
+    )
+}
+
+// Handler for AddReviewIfNotExists, which takes the same accounts as AddReview
+// A client that resends an AddReview after a timeout can't tell whether the
+// first one landed; this succeeds without touching anything when the review
+// is already there with the same content, and fails like AddReview when the
+// existing review differs
+#[allow(clippy::too_many_arguments)]
+pub fn add_review_if_not_exists(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,         // Restaurant name
+    rating: u8,            // Rating on the configured scale
+    description: String,   // Review text
+    tags: Vec<String>,     // Cuisine/category tags
+    content_uri: String,   // Off-chain content URI, empty if none
+    sub_ratings: Option<SubRatings>, // Food/service/ambiance breakdown
+    visited_at: i64,       // When the reviewer visited
+    price_range: u8,       // What the visit cost, 1 ($) to 4 ($$$$)
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts: as for AddReview; only the first two are read here
+    let initializer = next_account_info(account_info_iter)?; // User creating the review (signer)
+    let pda_account = next_account_info(account_info_iter)?; // PDA to store review data
+
+    if pda_account.data_is_empty() {
+        return add_review(
+            program_id,
+            accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        );
+    }
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[initializer.key.as_ref(), &review_seed(&title)],
+        program_id,
+    );
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let review = load_review(program_id, pda_account)?;
+    let identical = review.author == *initializer.key
+        && review.title == title
+        && review.rating == rating
+        && review.description == description
+        && review.tags == tags
+        && review.content_uri == content_uri
+        && review.sub_ratings == sub_ratings
+        && review.visited_at == visited_at
+        && review.price_range == price_range;
+    if !identical {
+        msg!("Review {} already exists with different content", pda);
+        return Err(ReviewError::AccountAlreadyInitialized.into());
+    }
+
+    msg!("Review {} already exists, nothing to do", pda);
+    set_return_data(&borsh::to_vec(&ReviewReturnData {
+        review: pda,
+        rating,
+        revision_count: review.revision_count,
+    })?);
+
+    Ok(())
+}
+
+// Handler for UpsertReview
+// Adds the signer's review of `title` if its PDA is empty and updates it
+// otherwise, so a client doesn't have to fetch the account to choose
+// between AddReview and UpdateReview. Either way the signer must own the
+// review: AddReview makes them its owner, and UpdateReview checks they
+// still are. A review owned by a multisig is updated with UpdateReview
+#[allow(clippy::too_many_arguments)]
+pub fn upsert_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,         // Restaurant name
+    rating: u8,            // Rating on the configured scale
+    description: String,   // Review text
+    tags: Vec<String>,     // Cuisine/category tags
+    content_uri: String,   // Off-chain content URI, empty if none
+    sub_ratings: Option<SubRatings>, // Food/service/ambiance breakdown
+    visited_at: i64,       // When the reviewer visited
+    price_range: u8,       // What the visit cost, 1 ($) to 4 ($$$$)
+) -> ProgramResult {
+    msg!("Upserting review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let initializer = next_account_info(account_info_iter)?;    // Reviewer (signer)
+    let pda_account = next_account_info(account_info_iter)?;    // Review PDA, created if empty
+    let system_program = next_account_info(account_info_iter)?; // System program
+    let pda_counter = next_account_info(account_info_iter)?;    // Comment counter PDA (add only)
+    let pda_stats = next_account_info(account_info_iter)?;      // Program stats PDA
+    let pda_reviewer = next_account_info(account_info_iter)?;   // Reviewer record PDA (add only)
+    let pda_aggregate = next_account_info(account_info_iter)?;  // Restaurant aggregate PDA
+    let clock_sysvar = next_account_info(account_info_iter)?;   // Clock sysvar for the timestamps
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA (may be uninitialized)
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant registry PDA (add only)
+    let pda_index = next_account_info(account_info_iter)?;      // Reviewer index PDA (add only)
+    let pda_stake = next_account_info(account_info_iter)?;      // Stake escrow PDA (add only)
+    let pda_treasury = next_account_info(account_info_iter)?;   // Program treasury PDA (add only)
+    let pda_revision = next_account_info(account_info_iter)?;   // Revision PDA for the version being replaced (update only)
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    // The review must be the signer's own for this title, whichever way
+    // it goes
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[initializer.key.as_ref(), &review_seed(&title)],
+        program_id,
+    );
+    if pda != *pda_account.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    if pda_account.data_is_empty() {
+        let mut add_accounts = vec![
+            initializer.clone(),
+            pda_account.clone(),
+            system_program.clone(),
+            pda_counter.clone(),
+            pda_stats.clone(),
+            pda_reviewer.clone(),
+            pda_aggregate.clone(),
+            clock_sysvar.clone(),
+            pda_config.clone(),
+            pda_restaurant.clone(),
+            pda_index.clone(),
+            pda_stake.clone(),
+            pda_treasury.clone(),
+        ];
+        add_accounts.extend(rent_sysvar.cloned());
+        add_review(
+            program_id,
+            &add_accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        )
+    } else {
+        // No multisig members follow, so update_review only accepts the
+        // owner's own signature
+        let update_accounts = [
+            initializer.clone(),
+            pda_account.clone(),
+            system_program.clone(),
+            pda_stats.clone(),
+            pda_aggregate.clone(),
+            pda_revision.clone(),
+            clock_sysvar.clone(),
+            pda_config.clone(),
+        ];
+        update_review(
+            program_id,
+            &update_accounts,
+            title,
+            rating,
+            description,
+            tags,
+            content_uri,
+            sub_ratings,
+            visited_at,
+            price_range,
+        )
+    }
+}
+
+// Review a registered restaurant: takes the same accounts as AddReview, but
+// the registry entry must exist and the review is seeded by its address
+// ([reviewer, restaurant]) instead of by a free-text title
+pub fn add_review_for_restaurant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rating: u8,            // Rating on the configured scale
+    description: String,   // Review text
+    tags: Vec<String>,     // Cuisine/category tags
+    content_uri: String,   // Off-chain content URI, empty if none
+    sub_ratings: Option<SubRatings>, // Food/service/ambiance breakdown
+    visited_at: i64,       // When the reviewer visited
+    price_range: u8,       // What the visit cost, 1 ($) to 4 ($$$$)
+) -> ProgramResult {
+    create_review(
+        program_id,
+        accounts,
+        None,
+        rating,
+        description,
+        tags,
+        content_uri,
+        sub_ratings,
+        visited_at,
+        price_range,
+        0,
+    )
+}
+
+// Handler for adding a follow-up review after a repeat visit
+// Takes AddReview's accounts preceded by the follow-up counter PDA. The
+// follow-up is a new review at [author, review_seed(title), sequence], with
+// sequence one past the last follow-up, so the earlier reviews of the
+// restaurant stay as they were; follow-ups themselves can't be edited
+#[allow(clippy::too_many_arguments)]
+pub fn add_follow_up_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,         // Restaurant name
+    rating: u8,            // Rating on the configured scale
     description: String,   // Review text
+    tags: Vec<String>,     // Cuisine/category tags
+    content_uri: String,   // Off-chain content URI, empty if none
+    sub_ratings: Option<SubRatings>, // Food/service/ambiance breakdown
+    visited_at: i64,       // When the reviewer visited
+    price_range: u8,       // What the visit cost, 1 ($) to 4 ($$$$)
+) -> ProgramResult {
+    msg!("Adding follow-up review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let pda_followups = next_account_info(account_info_iter)?; // Follow-up counter PDA (created with the first follow-up)
+    // Remaining accounts: as for AddReview
+    let review_accounts = account_info_iter.as_slice();
+    let initializer = next_account_info(account_info_iter)?;    // User creating the review (signer)
+    next_account_info(account_info_iter)?;                      // Review PDA, checked by create_review
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    // AddReview's optional Rent sysvar follows its 13 fixed accounts
+    let rent_sysvar = review_accounts.get(13);
+
+    check_system_program(system_program)?;
+
+    if !initializer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // The counter lives at ["followups", author, review_seed(title)]
+    let seed = review_seed(&title);
+    let (followups_pda, followups_bump) = Pubkey::find_program_address(
+        &[b"followups", initializer.key.as_ref(), &seed],
+        program_id,
+    );
+    if followups_pda != *pda_followups.key {
+        msg!("Invalid seeds for follow-up counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let mut followups = if pda_followups.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                initializer.key,
+                pda_followups.key,
+                load_rent(rent_sysvar)?.minimum_balance(FollowUpCounter::SIZE),
+                FollowUpCounter::SIZE as u64,
+                program_id,
+            ),
+            &[initializer.clone(), pda_followups.clone(), system_program.clone()],
+            &[&[b"followups", initializer.key.as_ref(), &seed, &[followups_bump]]],
+        )?;
+        FollowUpCounter {
+            discriminator: FollowUpCounter::DISCRIMINATOR,
+            is_initialized: true,
+            author: *initializer.key,
+            count: 0,
+        }
+    } else {
+        if pda_followups.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let followups = FollowUpCounter::unpack_checked(&pda_followups.data.borrow())?;
+        if !followups.is_initialized() {
+            return Err(ReviewError::UninitializedAccount.into());
+        }
+        followups
+    };
+
+    // Sequence numbers are never reused, even once a follow-up is deleted
+    followups.count = followups
+        .count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    followups.serialize(&mut &mut pda_followups.data.borrow_mut()[..])?;
+
+    create_review(
+        program_id,
+        review_accounts,
+        Some(title),
+        rating,
+        description,
+        tags,
+        content_uri,
+        sub_ratings,
+        visited_at,
+        price_range,
+        followups.count,
+    )
+}
+
+// Shared body of AddReview, AddReviewForRestaurant, and AddFollowUpReview
+// `title` is None for a review of the registry entry passed in; `sequence`
+// is 0 except for a follow-up
+#[allow(clippy::too_many_arguments)]
+fn create_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: Option<String>,
+    rating: u8,
+    description: String,
+    tags: Vec<String>,
+    content_uri: String,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+    sequence: u32,
 ) -> ProgramResult {
     msg!("Adding  review...");
-    msg!("Title: {}", title);
-    msg!("Rating: {}", rating);
-    msg!("Description: {}", description);
+    debug_msg!("Rating: {}", rating);
+    debug_msg!("Description: {}", description);
 
     let account_info_iter = &mut accounts.iter();
 
@@ -67,19 +686,85 @@ pub fn add_review(
     let initializer = next_account_info(account_info_iter)?;   // User creating the review (signer)
     let pda_account = next_account_info(account_info_iter)?;   // PDA to store review data
     let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let pda_counter = next_account_info(account_info_iter)?;    // Comment counter PDA created with the review
+    let pda_stats = next_account_info(account_info_iter)?;      // Program stats PDA
+    let pda_reviewer = next_account_info(account_info_iter)?;   // Reviewer record PDA (created on first review)
+    let pda_aggregate = next_account_info(account_info_iter)?;  // Restaurant aggregate PDA (created on first review)
+    let clock_sysvar = next_account_info(account_info_iter)?;   // Clock sysvar for the timestamps
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA (may be uninitialized)
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant registry PDA (may be uninitialized)
+    let pda_index = next_account_info(account_info_iter)?;      // Reviewer index PDA (created on first review)
+    let pda_stake = next_account_info(account_info_iter)?;      // Stake escrow PDA (created if the config asks for a stake)
+    let pda_treasury = next_account_info(account_info_iter)?;   // Program treasury PDA (receives the fee, if the config sets one)
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+    // Optional sponsor (signer) paying the rent in the reviewer's place
+    // Comes after the Rent sysvar, so a sponsored review passes both
+    let payer = next_account_info(account_info_iter).unwrap_or(initializer);
+
+    check_system_program(system_program)?;
 
     // Verify the user has signed the transaction
-    if !initializer.is_signer {
+    if !initializer.is_signer || !payer.is_signer {
         msg!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
+    check_not_paused(program_id, pda_config)?;
 
-    // Derive the PDA address using initializer pubkey and title as seeds
-    // This ensures each user can only have one review per restaurant title
-    let (pda, bump_seed) = Pubkey::find_program_address(
-        &[initializer.key.as_ref(), title.as_bytes().as_ref()],
-        program_id,
-    );
+    // Derive the PDA seed from the title, or for a registered restaurant
+    // take the registered name as the title and seed by the entry's address
+    // The normalized title is hashed so titles longer than a 32-byte seed
+    // still work, and so each user has one review per restaurant title
+    let (title, seed) = match title {
+        Some(title) => {
+            let seed = review_seed(&title);
+            (title, seed)
+        }
+        None => {
+            let restaurant = load_restaurant(program_id, pda_restaurant)?;
+            (restaurant.name, pda_restaurant.key.to_bytes())
+        }
+    };
+    debug_msg!("Title: {}", title);
+
+    // Reject oversized input before any accounts are created
+    if title.len() > MAX_TITLE_LEN {
+        msg!("Title is {} bytes, maximum is {}", title.len(), MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    let title_graphemes = grapheme_count(&title);
+    if title_graphemes > MAX_TITLE_GRAPHEMES {
+        msg!("Title is {} characters, maximum is {}", title_graphemes, MAX_TITLE_GRAPHEMES);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    validate_text("Title", &title)?;
+    if description.len() > MAX_DESCRIPTION_LEN {
+        msg!("Description is {} bytes, maximum is {}", description.len(), MAX_DESCRIPTION_LEN);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+    let description_graphemes = grapheme_count(&description);
+    if description_graphemes > MAX_DESCRIPTION_GRAPHEMES {
+        msg!("Description is {} characters, maximum is {}", description_graphemes, MAX_DESCRIPTION_GRAPHEMES);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+    validate_text("Description", &description)?;
+    validate_tags(&tags)?;
+    validate_content_uri(&content_uri)?;
+    validate_sub_ratings(sub_ratings)?;
+
+    // Note: In tests, Clock::get() may fail with UnsupportedSysvar
+    // Reading the clock sysvar account works everywhere
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    validate_visit(visited_at, price_range, now)?;
+    check_registry(program_id, pda_config, pda_restaurant, &title)?;
+
+    // Derive the PDA address using initializer pubkey and the seed above;
+    // a follow-up adds its sequence number as a third seed
+    let sequence_seed = sequence.to_le_bytes();
+    let mut seeds: Vec<&[u8]> = vec![initializer.key.as_ref(), &seed];
+    if sequence > 0 {
+        seeds.push(&sequence_seed);
+    }
+    let (pda, bump_seed) = Pubkey::find_program_address(&seeds, program_id);
     
     // Verify the PDA account passed in matches our derived address
     if pda != *pda_account.key {
@@ -87,146 +772,4597 @@ pub fn add_review(
         return Err(ProgramError::InvalidArgument);
     }
 
-    // Validate rating is within acceptable range
-    if rating > 10 || rating < 1 {
+    // The system program would refuse to create an account that is already
+    // funded, but with a generic error; say why instead
+    if pda_account.lamports() > 0 || !pda_account.data_is_empty() {
+        msg!("Review {} already exists", pda);
+        return Err(ReviewError::AccountAlreadyInitialized.into());
+    }
+
+    // Validate rating is within the configured scale
+    let rating_range = load_rating_range(program_id, pda_config)?;
+    if !rating_range.contains(&rating) {
         return Err(ReviewError::InvalidRating.into());
     }
 
-    // Fixed account size for storing review data
-    // In production, you'd calculate this based on actual data size
-    let account_len: usize = 1000;
+    // Reviews have a fixed layout, so every review account is the same size
+    // and later edits always fit
+    let account_len = AccountState::LEN;
 
     // Calculate rent-exempt minimum balance required
-    // Note: In tests, Rent::get() may fail with UnsupportedSysvar
-    // Using Rent::default() provides standard rent parameters
-    let rent = Rent::default();
+    let rent = load_rent(rent_sysvar)?;
     let rent_lamports = rent.minimum_balance(account_len);
 
+    // PDA seeds for signing: [user_pubkey, seed, (sequence,) bump_seed]
+    let bump = [bump_seed];
+    let mut signer_seeds = seeds.clone();
+    signer_seeds.push(&bump);
+
     // Create the PDA account via CPI to System Program
     // invoke_signed allows our PDA to "sign" the transaction
     invoke_signed(
         &system_instruction::create_account(
-            initializer.key,     // Funding account
+            payer.key,           // Funding account
             pda_account.key,     // New account to create
             rent_lamports,       // Lamports for rent exemption
             account_len.try_into().unwrap(),  // Account size in bytes
             program_id,          // Owner of the new account (this program)
         ),
         &[
-            initializer.clone(),
+            payer.clone(),
             pda_account.clone(),
             system_program.clone(),
         ],
-        // PDA seeds for signing: [user_pubkey, title, bump_seed]
-        &[&[
-            initializer.key.as_ref(),
-            title.as_bytes().as_ref(),
-            &[bump_seed],
-        ]],
+        &[signer_seeds.as_slice()],
     )?;
 
     msg!("PDA created: {}", pda);
 
+    // The account must be able to live forever without being garbage collected
+    if !rent.is_exempt(pda_account.lamports(), pda_account.data_len()) {
+        msg!("Review account is not rent exempt");
+        return Err(ReviewError::NotRentExempt.into());
+    }
+
     // Create a new AccountState with the review data
     // For a newly created account, we start fresh rather than deserializing zeros
     msg!("Creating account state");
-    let mut account_data = AccountState {
+
+    // Fold the rating into the restaurant's aggregate, creating it for the
+    // restaurant's first review
+    let mut aggregate = load_or_create_aggregate(
+        program_id,
+        payer,
+        pda_aggregate,
+        system_program,
+        &rent,
+        &title,
+        &rating_range,
+    )?;
+    aggregate.add_rating(rating, helpfulness(0, 0), now)?;
+    aggregate.add_sub_ratings(sub_ratings)?;
+    aggregate.serialize(&mut &mut pda_aggregate.data.borrow_mut()[..])?;
+
+    let account_data = AccountState {
+        discriminator: AccountState::DISCRIMINATOR,
+        version: AccountState::VERSION,
         title,
         rating,
+        tip_total: 0,
+        upvotes: 0,
+        downvotes: 0,
+        revision_count: 0,
+        created_at: now,
+        updated_at: now,
+        bump: bump_seed,
+        author: *initializer.key,
+        owner: *initializer.key,
+        payer: *payer.key,
+        status: ReviewStatus::Visible,
+        tags,
+        content_uri,
         description,
+        sub_ratings,
+        visited_at,
+        price_range,
+        sequence,
         is_initialized: true,
     };
 
+    let event = ReviewAdded {
+        review: pda,
+        author: account_data.author,
+        title: account_data.title.clone(),
+        rating,
+        created_at: now,
+    };
+
     msg!("serializing account");
-    // Serialize the updated state back to the account
-    account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
+    // Pack the review into its fixed layout
+    AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
     msg!("state account serialized");
+    event.emit()?;
+
+    // Create the comment counter alongside the review so the comment flow
+    // and indexers can rely on it existing for every review
+    create_comment_counter(program_id, payer, pda_counter, system_program, &rent, &pda)?;
+
+    // Lock the configured stake, which comes back when the review is deleted
+    let stake = load_review_stake(program_id, pda_config)?;
+    if stake > 0 {
+        create_stake_escrow(program_id, payer, pda_stake, system_program, &rent, &pda, stake)?;
+    }
+
+    // Pay the configured fee, which unlike the stake is never returned
+    let fee = load_review_fee(program_id, pda_config)?;
+    if fee > 0 {
+        collect_review_fee(program_id, payer, pda_treasury, system_program, fee)?;
+    }
+
+    // Track the new review in the program stats
+    let mut stats = load_stats(program_id, pda_stats)?;
+    stats.total_reviews = stats
+        .total_reviews
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // The reviewer record lives at ["reviewer", reviewer_pubkey]
+    // If it doesn't exist yet this is the wallet's first review
+    let (reviewer_pda, reviewer_bump) =
+        Pubkey::find_program_address(&[b"reviewer", initializer.key.as_ref()], program_id);
+    if reviewer_pda != *pda_reviewer.key {
+        msg!("Invalid seeds for reviewer PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let mut reviewer_data = if pda_reviewer.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_reviewer.key,
+                rent.minimum_balance(ReviewerRecord::SIZE),
+                ReviewerRecord::SIZE as u64,
+                program_id,
+            ),
+            &[payer.clone(), pda_reviewer.clone(), system_program.clone()],
+            &[&[b"reviewer", initializer.key.as_ref(), &[reviewer_bump]]],
+        )?;
+        msg!("reviewer record created");
+
+        stats.total_unique_reviewers = stats
+            .total_unique_reviewers
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+        ReviewerRecord {
+            discriminator: ReviewerRecord::DISCRIMINATOR,
+            is_initialized: true,
+            reviewer: *initializer.key,
+            review_count: 0,
+        }
+    } else {
+        if pda_reviewer.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        ReviewerRecord::unpack_checked(&pda_reviewer.data.borrow())?
+    };
+    reviewer_data.review_count = reviewer_data
+        .review_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    reviewer_data.serialize(&mut &mut pda_reviewer.data.borrow_mut()[..])?;
+    stats.serialize(&mut &mut pda_stats.data.borrow_mut()[..])?;
+
+    add_to_reviewer_index(program_id, payer, pda_index, system_program, &rent, initializer.key, &pda)?;
+
+    // Set last, since every CPI above resets the return data
+    set_return_data(&borsh::to_vec(&ReviewReturnData {
+        review: pda,
+        rating,
+        revision_count: 0,
+    })?);
 
     Ok(())
 }
 
-// Handler for updating an existing restaurant review
-// Only allows the original reviewer to update their review
-pub fn update_review(
+// Handler for committing to a review without revealing it
+// Only the hash is stored; the review is created when it is revealed
+pub fn add_anonymous_review(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _title: String,        // Title is used to derive PDA, but not changed
-    rating: u8,            // New rating
-    description: String,   // New description
+    commitment: [u8; 32],
 ) -> ProgramResult {
-    msg!("Updating  review...");
+    msg!("Adding review commitment...");
 
     let account_info_iter = &mut accounts.iter();
 
-    // Expected accounts:
-    let initializer = next_account_info(account_info_iter)?;  // Original reviewer (signer)
-    let pda_account = next_account_info(account_info_iter)?;  // Existing review PDA
+    // Expected accounts in order:
+    let author = next_account_info(account_info_iter)?;         // Review author (signer, pays rent)
+    let pda_commitment = next_account_info(account_info_iter)?; // Commitment PDA to create
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
 
-    // Verify the PDA is owned by this program
-    if pda_account.owner != program_id {
-        return Err(ProgramError::IllegalOwner);
-    }
+    check_system_program(system_program)?;
 
-    // Verify the original reviewer is signing
-    if !initializer.is_signer {
+    if !author.is_signer {
         msg!("Missing required signature");
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    msg!("unpacking state account");
-    // Deserialize existing review data
-    // Use deserialize to handle accounts larger than the serialized data
-    let mut account_data =
-        AccountState::deserialize(&mut &pda_account.data.borrow()[..])
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-    msg!("review title: {}", account_data.title);
-
-    // Re-derive the PDA to verify the signer is the original reviewer
-    // Uses the stored title and the signer's pubkey
-    let (pda, _bump_seed) = Pubkey::find_program_address(
-        &[
-            initializer.key.as_ref(),
-            account_data.title.as_bytes().as_ref(),
-        ],
+    // The commitment lives at ["commitment", author, commitment]
+    let (commitment_pda, commitment_bump) = Pubkey::find_program_address(
+        &[b"commitment", author.key.as_ref(), &commitment],
         program_id,
     );
-    
-    // Ensure the PDA matches (proves this user created the review)
-    if pda != *pda_account.key {
-        msg!("Invalid seeds for PDA");
+    if commitment_pda != *pda_commitment.key {
+        msg!("Invalid seeds for commitment PDA");
         return Err(ReviewError::InvalidPDA.into());
     }
-
-    msg!("checking if  account is initialized");
-    // Can't update a review that doesn't exist
-    if !account_data.is_initialized() {
-        msg!("Account is not initialized");
-        return Err(ReviewError::UninitializedAccount.into());
+    if !pda_commitment.data_is_empty() {
+        return Err(ReviewError::AlreadyInitialized.into());
     }
 
-    // Validate new rating
-    if rating > 10 || rating < 1 {
-        return Err(ReviewError::InvalidRating.into());
-    }
+    invoke_signed(
+        &system_instruction::create_account(
+            author.key,
+            pda_commitment.key,
+            load_rent(rent_sysvar)?.minimum_balance(ReviewCommitment::SIZE),
+            ReviewCommitment::SIZE as u64,
+            program_id,
+        ),
+        &[author.clone(), pda_commitment.clone(), system_program.clone()],
+        &[&[b"commitment", author.key.as_ref(), &commitment, &[commitment_bump]]],
+    )?;
 
-    msg!("Review before update:");
-    msg!("Title: {}", account_data.title);
-    msg!("Rating: {}", account_data.rating);
-    msg!("Description: {}", account_data.description);
+    let commitment_data = ReviewCommitment {
+        discriminator: ReviewCommitment::DISCRIMINATOR,
+        is_initialized: true,
+        author: *author.key,
+        commitment,
+    };
+    commitment_data.serialize(&mut &mut pda_commitment.data.borrow_mut()[..])?;
 
-    // Update only the rating and description (title stays the same)
-    account_data.rating = rating;
-    account_data.description = description;
+    msg!("Commitment created: {}", commitment_pda);
 
-    msg!("Review after update:");
-    msg!("Title: {}", account_data.title);
-    msg!("Rating: {}", account_data.rating);
-    msg!("Description: {}", account_data.description);
+    Ok(())
+}
 
-    msg!("serializing account");
-    // Save the updated state back to the account
-    account_data.serialize(&mut &mut pda_account.data.borrow_mut()[..])?;
-    msg!("state account serialized");
+// Handler for revealing a committed review
+// Checks the review against the commitment, creates it exactly as AddReview
+// would (without tags, a content URI, or sub-ratings), and closes the commitment
+#[allow(clippy::too_many_arguments)]
+pub fn reveal_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    rating: u8,
+    description: String,
+    salt: [u8; 32],
+    visited_at: i64,
+    price_range: u8,
+) -> ProgramResult {
+    msg!("Revealing review...");
+
+    // Expected accounts in order:
+    // Commitment PDA to close, then the accounts of AddReview, starting
+    // with the author (signer), who gets the commitment's rent back
+    let (pda_commitment, review_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let author = review_accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    if !author.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pda_commitment.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let commitment = ReviewCommitment::unpack_checked(&pda_commitment.data.borrow())?;
+    if !commitment.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    if commitment.author != *author.key {
+        msg!("Signer did not make this commitment");
+        return Err(ReviewError::NotReviewOwner.into());
+    }
+    if review_commitment(author.key, &title, rating, &description, &salt) != commitment.commitment {
+        return Err(ReviewError::CommitmentMismatch.into());
+    }
+
+    add_review(
+        program_id,
+        review_accounts,
+        title,
+        rating,
+        description,
+        vec![],
+        String::new(),
+        None,
+        visited_at,
+        price_range,
+    )?;
+    close_account(pda_commitment, author)
+}
+
+// Handler for updating an existing restaurant review
+// Only allows the review's current owner to update it
+#[allow(clippy::too_many_arguments)]
+pub fn update_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _title: String,        // Title is used to derive PDA, but not changed
+    rating: u8,            // New rating
+    description: String,   // New description
+    tags: Vec<String>,     // New tags
+    content_uri: String,   // New off-chain content URI
+    sub_ratings: Option<SubRatings>, // New sub-ratings, None to clear them
+    visited_at: i64,       // New visit timestamp
+    price_range: u8,       // New price range
+) -> ProgramResult {
+    msg!("Updating  review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts:
+    let initializer = next_account_info(account_info_iter)?;  // Review owner (signer, or a ReviewMultisig)
+    let pda_account = next_account_info(account_info_iter)?;  // Existing review PDA
+    let system_program = next_account_info(account_info_iter)?; // System program for rent top-ups
+    let pda_stats = next_account_info(account_info_iter)?;      // Program stats PDA
+    let pda_aggregate = next_account_info(account_info_iter)?;  // Restaurant aggregate PDA
+    let pda_revision = next_account_info(account_info_iter)?;   // Revision PDA for the version being replaced
+    let clock_sysvar = next_account_info(account_info_iter)?;   // Clock sysvar for updated_at
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA (may be uninitialized)
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+    // Remaining accounts: multisig members signing for a multisig owner
+
+    check_system_program(system_program)?;
+    check_not_paused(program_id, pda_config)?;
+
+    // Verify the PDA is owned by this program
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Catch malformed account lists with specific errors instead of a
+    // failed write or deserialization further down
+    if !pda_account.is_writable {
+        msg!("Review account is not writable");
+        return Err(ReviewError::ReviewNotWritable.into());
+    }
+    // Reviews in an older layout are shorter and are reported as
+    // OutdatedAccount when loaded
+    if pda_account.data_len() < AccountState::LEN_V0 {
+        msg!("Review account is {} bytes, expected {}", pda_account.data_len(), AccountState::LEN);
+        return Err(ReviewError::ReviewAccountTooSmall.into());
+    }
+    if initializer.executable {
+        msg!("Review owner {} is a program", initializer.key);
+        return Err(ReviewError::ExecutableOwner.into());
+    }
+
+    // Verify the owner (or enough of its multisig members) is signing
+    // Whoever signs pays for the revision account
+    let payer = review_authority(program_id, initializer, account_info_iter.as_slice())?;
+
+    // Read what the update needs straight from the account's fixed layout;
+    // only the old description is copied out, for the revision
+    let (title, old_rating, old_sub_ratings, weight, old_description, revision_index, created_at, last_update) = {
+        let data = pda_account.data.borrow();
+        let review = ReviewZeroCopy::load(&data)?;
+
+        // Can't update a review that doesn't exist
+        if !review.is_initialized() {
+            msg!("Account is not initialized");
+            return Err(ReviewError::UninitializedAccount.into());
+        }
+
+        // Re-derive the PDA from the stored author, title, and bump to
+        // prove this is the review it claims to be
+        let title = review.title.as_str()?.to_string();
+        check_review_address(program_id, &review.author, &title, review.bump, review.sequence(), pda_account.key)?;
+
+        // A follow-up records one visit as it was written
+        if review.sequence() > 0 {
+            msg!("Follow-up reviews can't be edited");
+            return Err(ReviewError::FollowUpReadOnly.into());
+        }
+
+        // Only the current owner may edit, which is the author unless the
+        // review has been transferred
+        if review.owner() != *initializer.key {
+            msg!("Signer is not the owner of this review");
+            return Err(ReviewError::NotReviewOwner.into());
+        }
+
+        // An archived review is read-only
+        if review.status == ReviewStatus::Deleted as u8 {
+            msg!("Review has been deleted");
+            return Err(ReviewError::ReviewArchived.into());
+        }
+
+        (
+            title,
+            review.rating,
+            review.sub_ratings(),
+            review.helpfulness(),
+            review.description.as_str()?.to_string(),
+            review.revision_count(),
+            review.created_at(),
+            review.updated_at(),
+        )
+    };
+
+    // Validate new rating
+    if !load_rating_range(program_id, pda_config)?.contains(&rating) {
+        return Err(ReviewError::InvalidRating.into());
+    }
+
+    if description.len() > MAX_DESCRIPTION_LEN {
+        msg!("Description is {} bytes, maximum is {}", description.len(), MAX_DESCRIPTION_LEN);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+    let description_graphemes = grapheme_count(&description);
+    if description_graphemes > MAX_DESCRIPTION_GRAPHEMES {
+        msg!("Description is {} characters, maximum is {}", description_graphemes, MAX_DESCRIPTION_GRAPHEMES);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+    validate_text("Description", &description)?;
+    validate_tags(&tags)?;
+    validate_content_uri(&content_uri)?;
+    validate_sub_ratings(sub_ratings)?;
+
+    // Note: In tests, Clock::get() may fail with UnsupportedSysvar
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    validate_visit(visited_at, price_range, now)?;
+
+    // Rate-limit edits so a rating can't be flipped back and forth
+    let cooldown = load_update_cooldown(program_id, pda_config)?;
+    let next_update = last_update.saturating_add(cooldown);
+    if now < next_update {
+        msg!("Review can be updated again in {} seconds", next_update - now);
+        return Err(ReviewError::UpdateTooSoon.into());
+    }
+
+    msg!("Review before update:");
+    debug_msg!("Title: {}", title);
+    debug_msg!("Rating: {}", old_rating);
+    debug_msg!("Description: {}", old_description);
+
+    // Move this review's rating from its old to its new histogram bucket,
+    // keeping its helpfulness weight, and swap its sub-ratings in the running sums
+    let mut aggregate = load_aggregate(program_id, pda_aggregate, &title)?;
+    aggregate.remove_rating(old_rating, weight, created_at)?;
+    aggregate.add_rating(rating, weight, created_at)?;
+    aggregate.remove_sub_ratings(old_sub_ratings)?;
+    aggregate.add_sub_ratings(sub_ratings)?;
+    aggregate.serialize(&mut &mut pda_aggregate.data.borrow_mut()[..])?;
+
+    // Save the current version before overwriting it
+    // The revision lives at [review_pda, "revision", revision_count]
+    let (revision_pda, revision_bump) = Pubkey::find_program_address(
+        &[pda_account.key.as_ref(), b"revision", &revision_index.to_le_bytes()],
+        program_id,
+    );
+    if revision_pda != *pda_revision.key {
+        msg!("Invalid seeds for revision PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let revision_len = ReviewRevision::get_account_size(&old_description);
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_revision.key,
+            load_rent(rent_sysvar)?.minimum_balance(revision_len),
+            revision_len as u64,
+            program_id,
+        ),
+        &[payer.clone(), pda_revision.clone(), system_program.clone()],
+        &[&[
+            pda_account.key.as_ref(),
+            b"revision",
+            &revision_index.to_le_bytes(),
+            &[revision_bump],
+        ]],
+    )?;
+
+    let revision = ReviewRevision {
+        discriminator: ReviewRevision::DISCRIMINATOR,
+        is_initialized: true,
+        review: *pda_account.key,
+        revision: revision_index,
+        rating: old_rating,
+        description: old_description,
+    };
+    revision.serialize(&mut &mut pda_revision.data.borrow_mut()[..])?;
+    msg!("Saved revision {}", revision_index);
+
+    // Patch the changed fields in place (title stays the same); the rest
+    // of the account is left untouched
+    let mut data = pda_account.data.borrow_mut();
+    let review = ReviewZeroCopy::load_mut(&mut data)?;
+    review.rating = rating;
+    review.set_sub_ratings(sub_ratings);
+    review.set_visited_at(visited_at);
+    review.price_range = price_range;
+    review.description.set(&description);
+    review.set_tags(&tags);
+    review.content_uri.set(&content_uri);
+    review.set_updated_at(now);
+    let revision_count = revision_index
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    review.set_revision_count(revision_count);
+    drop(data);
+
+    ReviewUpdated {
+        review: *pda_account.key,
+        owner: *initializer.key,
+        rating,
+        revision_count,
+        updated_at: now,
+    }
+    .emit()?;
+
+    msg!("Review after update:");
+    debug_msg!("Title: {}", title);
+    debug_msg!("Rating: {}", rating);
+    debug_msg!("Description: {}", description);
+
+    let mut stats = load_stats(program_id, pda_stats)?;
+    stats.total_updates = stats
+        .total_updates
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    stats.serialize(&mut &mut pda_stats.data.borrow_mut()[..])?;
+
+    // Set last, since every CPI above resets the return data
+    set_return_data(&borsh::to_vec(&ReviewReturnData {
+        review: *pda_account.key,
+        rating,
+        revision_count,
+    })?);
+
+    Ok(())
+}
+
+// Seconds a review must wait between updates
+// Like check_registry, an uninitialized config means no restriction
+fn load_update_cooldown(program_id: &Pubkey, pda_config: &AccountInfo) -> Result<i64, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_config.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(load_config(program_id, pda_config)?.update_cooldown as i64)
+}
+
+// Ratings accepted for account-backed reviews
+// The config may not exist yet, in which case the default scale applies
+fn load_rating_range(program_id: &Pubkey, pda_config: &AccountInfo) -> Result<RangeInclusive<u8>, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_config.data_is_empty() {
+        return Ok(DEFAULT_MIN_RATING..=DEFAULT_MAX_RATING);
+    }
+    Ok(load_config(program_id, pda_config)?.rating_range())
+}
+
+// Enforce strict registry mode for a new review
+// The config may not exist yet, in which case any restaurant can be
+// reviewed; in strict mode the restaurant must be in the registry
+fn check_registry(
+    program_id: &Pubkey,
+    pda_config: &AccountInfo,
+    pda_restaurant: &AccountInfo,
+    title: &str,
+) -> ProgramResult {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_config.data_is_empty() || !load_config(program_id, pda_config)?.strict_registry {
+        return Ok(());
+    }
+
+    let normalized = normalize_title(title);
+    let (restaurant_pda, _restaurant_bump) =
+        Pubkey::find_program_address(&[b"restaurant", &title_seed(&normalized)], program_id);
+    if restaurant_pda != *pda_restaurant.key {
+        msg!("Invalid seeds for restaurant PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_restaurant.data_is_empty() {
+        msg!("{} is not a registered restaurant", normalized);
+        return Err(ReviewError::RestaurantNotRegistered.into());
+    }
+    if pda_restaurant.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let restaurant = Restaurant::unpack_checked(&pda_restaurant.data.borrow())?;
+    if !restaurant.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    Ok(())
+}
+
+// Titles and descriptions end up in logs, events, and frontends, so they
+// can't carry characters that would forge log lines or reorder the display
+fn validate_text(field: &str, text: &str) -> ProgramResult {
+    if !is_printable_text(text) {
+        msg!("{} contains control or bidirectional formatting characters", field);
+        return Err(ReviewError::InvalidCharacters.into());
+    }
+    Ok(())
+}
+
+// Tags must fit the fixed slots in a review account
+fn validate_tags(tags: &[String]) -> ProgramResult {
+    if tags.len() > MAX_TAGS {
+        msg!("{} tags, maximum is {}", tags.len(), MAX_TAGS);
+        return Err(ReviewError::TooManyTags.into());
+    }
+    if let Some(tag) = tags.iter().find(|tag| tag.is_empty() || tag.len() > MAX_TAG_LEN) {
+        msg!("Invalid tag: {:?}, tags must be 1 to {} bytes", tag, MAX_TAG_LEN);
+        return Err(ReviewError::InvalidTag.into());
+    }
+    Ok(())
+}
+
+// Sub-ratings are optional, but each one given must be on the 1-10 scale
+fn validate_sub_ratings(sub_ratings: Option<SubRatings>) -> ProgramResult {
+    if sub_ratings.is_some_and(|ratings| !ratings.is_valid()) {
+        msg!("Sub-ratings must be {} to {}", MIN_SUB_RATING, MAX_SUB_RATING);
+        return Err(ReviewError::InvalidSubRating.into());
+    }
+    Ok(())
+}
+
+// A review can't be of a visit that hasn't happened yet, so analytics can
+// trust visited_at when weighing recent visits
+fn validate_visit(visited_at: i64, price_range: u8, now: i64) -> ProgramResult {
+    if visited_at > now {
+        msg!("Visit date {} is after the current time {}", visited_at, now);
+        return Err(ReviewError::VisitInFuture.into());
+    }
+    if !(1..=MAX_PRICE_RANGE).contains(&price_range) {
+        return Err(ReviewError::InvalidPriceRange.into());
+    }
+    Ok(())
+}
+
+// The content URI is optional, but when given it must fit its slot and
+// point at content-addressed storage
+fn validate_content_uri(content_uri: &str) -> ProgramResult {
+    if content_uri.is_empty() {
+        return Ok(());
+    }
+    if content_uri.len() > MAX_CONTENT_URI_LEN {
+        msg!("Content URI is {} bytes, maximum is {}", content_uri.len(), MAX_CONTENT_URI_LEN);
+        return Err(ReviewError::ContentUriTooLong.into());
+    }
+    if !CONTENT_URI_PREFIXES
+        .iter()
+        .any(|prefix| content_uri.len() > prefix.len() && content_uri.starts_with(prefix))
+    {
+        msg!("Content URI must start with one of {:?}", CONTENT_URI_PREFIXES);
+        return Err(ReviewError::InvalidContentUri.into());
+    }
+    Ok(())
+}
+
+// Check that `key` is the review PDA for the author, title, and bump stored
+// in the review. create_program_address is much cheaper than
+// find_program_address, and because the bump was saved from
+// find_program_address at creation it is always the canonical one
+// Reviews created before titles were normalized are seeded by the title as
+// typed; MigrateReview moves them to the normalized address
+// A review added with AddReviewForRestaurant is seeded by the registry
+// entry's address, which is derived from its title too; that is checked
+// last since it needs a find_program_address
+// A follow-up review has its sequence number as an extra seed
+fn check_review_address(
+    program_id: &Pubkey,
+    author: &[u8],
+    title: &str,
+    bump: u8,
+    sequence: u32,
+    key: &Pubkey,
+) -> ProgramResult {
+    let sequence_seed = sequence.to_le_bytes();
+    let matches = |seed: [u8; 32]| {
+        let pda = if sequence == 0 {
+            Pubkey::create_program_address(&[author, &seed, &[bump]], program_id)
+        } else {
+            Pubkey::create_program_address(&[author, &seed, &sequence_seed, &[bump]], program_id)
+        };
+        pda.is_ok_and(|pda| pda == *key)
+    };
+    if !matches(review_seed(title))
+        && !matches(title_seed(title))
+        && !matches(restaurant_address(program_id, title).to_bytes())
+    {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    Ok(())
+}
+
+// check_review_address for an unpacked review
+fn review_address(program_id: &Pubkey, review: &AccountState, key: &Pubkey) -> ProgramResult {
+    check_review_address(program_id, review.author.as_ref(), &review.title, review.bump, review.sequence, key)
+}
+
+// Account that gets a review's rent back when it is closed
+// That is whoever paid it: the owner's own wallet unless the review was
+// sponsored, in which case the sponsor must be passed as `payer_account`
+fn rent_destination<'a, 'b>(
+    review: &AccountState,
+    owner: &'b AccountInfo<'a>,
+    payer_account: Option<&'b AccountInfo<'a>>,
+) -> Result<&'b AccountInfo<'a>, ProgramError> {
+    if review.payer == *owner.key {
+        return Ok(owner);
+    }
+    match payer_account {
+        Some(account) if *account.key == review.payer => Ok(account),
+        _ => {
+            msg!("Rent payer {} must be passed to receive the rent", review.payer);
+            Err(ReviewError::InvalidRentPayer.into())
+        }
+    }
+}
+
+// Handler for deleting a restaurant review
+// Closes the PDA and returns its rent lamports to whoever paid for it
+pub fn delete_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _title: String,        // Title is used to derive PDA client-side
+) -> ProgramResult {
+    msg!("Deleting review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts:
+    let initializer = next_account_info(account_info_iter)?;  // Review owner (signer or a ReviewMultisig, receives rent)
+    let pda_account = next_account_info(account_info_iter)?;  // Review PDA to close
+    let pda_counter = next_account_info(account_info_iter)?;  // Comment counter PDA to close
+    let pda_aggregate = next_account_info(account_info_iter)?; // Restaurant aggregate PDA
+    let pda_index = next_account_info(account_info_iter)?;     // Author's reviewer index PDA
+    let pda_stake = next_account_info(account_info_iter)?;     // Stake escrow PDA, closed if the review was staked
+    let pda_config = next_account_info(account_info_iter)?;    // Program config PDA (may be uninitialized)
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+    let payer_account = next_account_info(account_info_iter).ok(); // Rent payer, if the review was sponsored
+    // Remaining accounts: multisig members signing for a multisig owner
+    // (a multisig never pays rent itself, so its reviews always pass the payer)
+
+    check_not_paused(program_id, pda_config)?;
+
+    // Verify the PDA is owned by this program
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Verify the owner (or enough of its multisig members) is signing
+    review_authority(program_id, initializer, account_info_iter.as_slice())?;
+
+    let account_data = AccountState::unpack_checked(&pda_account.data.borrow())?;
+
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // Re-derive the PDA from the stored author, title, and bump
+    review_address(program_id, &account_data, pda_account.key)?;
+    let pda = *pda_account.key;
+
+    // Only the current owner may delete the review and collect its rent
+    if account_data.owner != *initializer.key {
+        msg!("Signer is not the owner of this review");
+        return Err(ReviewError::NotReviewOwner.into());
+    }
+
+    let rent_destination = rent_destination(&account_data, initializer, payer_account)?;
+
+    // The counter is tied to the review, so it goes too; otherwise
+    // re-adding the same review would collide with the old counter
+    let (counter_pda, _counter_bump) =
+        Pubkey::find_program_address(&[pda.as_ref(), b"comment"], program_id);
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // A deleted review no longer counts towards the restaurant's rating;
+    // an archived one was already taken out when it was archived
+    if account_data.status != ReviewStatus::Deleted {
+        let mut aggregate = load_aggregate(program_id, pda_aggregate, &account_data.title)?;
+        aggregate.remove_rating(account_data.rating, account_data.helpfulness(), account_data.created_at)?;
+        aggregate.remove_sub_ratings(account_data.sub_ratings)?;
+        aggregate.serialize(&mut &mut pda_aggregate.data.borrow_mut()[..])?;
+    }
+
+    debug_msg!("Closing review: {}", account_data.title);
+
+    close_account(pda_account, rent_destination)?;
+    if pda_counter.owner == program_id {
+        close_account(pda_counter, rent_destination)?;
+    }
+    // The stake goes back with the rent
+    if load_stake_escrow(program_id, pda_stake, &pda)?.is_some() {
+        close_account(pda_stake, rent_destination)?;
+    }
+    remove_from_reviewer_index(
+        program_id,
+        pda_index,
+        rent_destination,
+        &account_data.author,
+        &pda,
+        &load_rent(rent_sysvar)?,
+    )?;
+
+    msg!("Review deleted, rent returned to {}", rent_destination.key);
+
+    ReviewDeleted {
+        review: pda,
+        owner: *initializer.key,
+        title: account_data.title,
+    }
+    .emit()?;
+
+    Ok(())
+}
+
+// Handler for renaming a review
+// The title is part of the review's PDA seeds, so renaming means moving
+// the review: the new PDA is created with a copy of the state, the old
+// review and its comment counter are closed, and the rating moves to the
+// new restaurant's aggregate, all in one instruction. Comments, votes, and
+// revisions stay at the old address; the new review starts a fresh counter
+pub fn migrate_review(program_id: &Pubkey, accounts: &[AccountInfo], new_title: String) -> ProgramResult {
+    msg!("Migrating review...");
+    debug_msg!("New title: {}", new_title);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;              // Review owner (signer, pays for the new accounts)
+    let pda_old = next_account_info(account_info_iter)?;            // Review PDA under the current title
+    let pda_new = next_account_info(account_info_iter)?;            // Review PDA under the new title
+    let pda_old_counter = next_account_info(account_info_iter)?;    // Comment counter of the old review, closed
+    let pda_new_counter = next_account_info(account_info_iter)?;    // Comment counter of the new review, created
+    let pda_old_aggregate = next_account_info(account_info_iter)?;  // Aggregate of the current restaurant
+    let pda_new_aggregate = next_account_info(account_info_iter)?;  // Aggregate of the new restaurant (created if needed)
+    let pda_config = next_account_info(account_info_iter)?;         // Program config PDA (may be uninitialized)
+    let pda_restaurant = next_account_info(account_info_iter)?;     // Registry PDA of the new restaurant (may be uninitialized)
+    let pda_index = next_account_info(account_info_iter)?;          // Author's reviewer index PDA
+    let pda_old_stake = next_account_info(account_info_iter)?;      // Stake escrow of the old review, closed if staked
+    let pda_new_stake = next_account_info(account_info_iter)?;      // Stake escrow of the new review, created if staked
+    let system_program = next_account_info(account_info_iter)?;     // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok();    // Optional Rent sysvar, see load_rent
+    let payer_account = next_account_info(account_info_iter).ok();  // Rent payer, if the review was sponsored
+
+    check_system_program(system_program)?;
+    check_not_paused(program_id, pda_config)?;
+
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut account_data = load_review(program_id, pda_old)?;
+    if account_data.owner != *owner.key {
+        msg!("Signer is not the owner of this review");
+        return Err(ReviewError::NotReviewOwner.into());
+    }
+    if account_data.status == ReviewStatus::Deleted {
+        msg!("Review has been deleted");
+        return Err(ReviewError::ReviewArchived.into());
+    }
+    // A follow-up's address includes its sequence, and it can't be edited
+    if account_data.sequence > 0 {
+        msg!("Follow-up reviews can't be moved to another title");
+        return Err(ReviewError::FollowUpReadOnly.into());
+    }
+
+    // The new title goes through the same checks as a new review's
+    if new_title.len() > MAX_TITLE_LEN {
+        msg!("Title is {} bytes, maximum is {}", new_title.len(), MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    let new_title_graphemes = grapheme_count(&new_title);
+    if new_title_graphemes > MAX_TITLE_GRAPHEMES {
+        msg!("Title is {} characters, maximum is {}", new_title_graphemes, MAX_TITLE_GRAPHEMES);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    validate_text("Title", &new_title)?;
+    if new_title == account_data.title {
+        msg!("Review already has this title");
+        return Err(ProgramError::InvalidArgument);
+    }
+    check_registry(program_id, pda_config, pda_restaurant, &new_title)?;
+
+    // The author stays in the seeds, so the review keeps one address per
+    // (author, title) even if it has been transferred
+    let old_pda = *pda_old.key;
+    let new_title_hash = review_seed(&new_title);
+    let (new_pda, new_bump) = Pubkey::find_program_address(
+        &[account_data.author.as_ref(), &new_title_hash],
+        program_id,
+    );
+    if new_pda != *pda_new.key {
+        msg!("Invalid seeds for PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // A title that normalizes to the same seed (e.g. a capitalization fix)
+    // keeps the review's address, so only the stored title changes
+    if new_pda == old_pda {
+        account_data.title = new_title;
+        AccountState::pack(account_data, &mut pda_old.data.borrow_mut())?;
+        msg!("Review title changed in place");
+        return Ok(());
+    }
+
+    let (old_counter_pda, _old_counter_bump) =
+        Pubkey::find_program_address(&[old_pda.as_ref(), b"comment"], program_id);
+    if old_counter_pda != *pda_old_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // Find where the old rent goes before anything is created
+    let rent_destination = rent_destination(&account_data, owner, payer_account)?;
+    let rent = load_rent(rent_sysvar)?;
+
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            pda_new.key,
+            rent.minimum_balance(AccountState::LEN),
+            AccountState::LEN as u64,
+            program_id,
+        ),
+        &[owner.clone(), pda_new.clone(), system_program.clone()],
+        &[&[account_data.author.as_ref(), &new_title_hash, &[new_bump]]],
+    )?;
+    create_comment_counter(program_id, owner, pda_new_counter, system_program, &rent, &new_pda)?;
+
+    // The owner stakes the same amount for the new review; the old stake
+    // goes back with the old review's rent
+    let old_stake = load_stake_escrow(program_id, pda_old_stake, &old_pda)?;
+    if let Some(stake) = &old_stake {
+        create_stake_escrow(program_id, owner, pda_new_stake, system_program, &rent, &new_pda, stake.amount)?;
+    }
+
+    // Move the rating to the new restaurant, unless both titles normalize
+    // to the same restaurant (e.g. a capitalization fix)
+    if pda_old_aggregate.key != pda_new_aggregate.key {
+        let mut old_aggregate = load_aggregate(program_id, pda_old_aggregate, &account_data.title)?;
+        old_aggregate.remove_rating(account_data.rating, account_data.helpfulness(), account_data.created_at)?;
+        old_aggregate.remove_sub_ratings(account_data.sub_ratings)?;
+        old_aggregate.serialize(&mut &mut pda_old_aggregate.data.borrow_mut()[..])?;
+
+        let mut new_aggregate = load_or_create_aggregate(
+            program_id,
+            owner,
+            pda_new_aggregate,
+            system_program,
+            &rent,
+            &new_title,
+            &load_rating_range(program_id, pda_config)?,
+        )?;
+        new_aggregate.add_rating(account_data.rating, account_data.helpfulness(), account_data.created_at)?;
+        new_aggregate.add_sub_ratings(account_data.sub_ratings)?;
+        new_aggregate.serialize(&mut &mut pda_new_aggregate.data.borrow_mut()[..])?;
+    } else {
+        load_aggregate(program_id, pda_new_aggregate, &new_title)?;
+    }
+
+    let old_title = std::mem::replace(&mut account_data.title, new_title);
+    account_data.bump = new_bump;
+    account_data.payer = *owner.key;
+
+    let added = ReviewAdded {
+        review: new_pda,
+        author: account_data.author,
+        title: account_data.title.clone(),
+        rating: account_data.rating,
+        created_at: account_data.created_at,
+    };
+    AccountState::pack(account_data, &mut pda_new.data.borrow_mut())?;
+
+    close_account(pda_old, rent_destination)?;
+    if pda_old_counter.owner == program_id {
+        close_account(pda_old_counter, rent_destination)?;
+    }
+    if old_stake.is_some() {
+        close_account(pda_old_stake, rent_destination)?;
+    }
+    replace_in_reviewer_index(program_id, pda_index, &added.author, &old_pda, &new_pda)?;
+
+    msg!("Review moved from {} to {}", old_pda, new_pda);
+
+    ReviewDeleted {
+        review: old_pda,
+        owner: *owner.key,
+        title: old_title,
+    }
+    .emit()?;
+    added.emit()?;
+
+    Ok(())
+}
+
+// Handler for upgrading a review to the current layout version
+// Anyone can run it, since it doesn't change what the review says; the
+// signer pays for any extra rent the larger layout needs. Running it on a
+// review that is already current does nothing
+pub fn migrate_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Migrating review account...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let payer = next_account_info(account_info_iter)?;          // Pays for the extra rent (signer)
+    let pda_account = next_account_info(account_info_iter)?;    // Review to upgrade
+    let system_program = next_account_info(account_info_iter)?; // System program for the rent top-up
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let version = AccountState::version_of(&pda_account.data.borrow())?;
+    match version {
+        AccountState::VERSION => {
+            msg!("Review is already at version {}", version);
+            return Ok(());
+        }
+        0..=3 => {
+            // Grow the account to the current layout, topping up the rent first
+            let rent = load_rent(rent_sysvar)?;
+            let shortfall = rent
+                .minimum_balance(AccountState::LEN)
+                .saturating_sub(pda_account.lamports());
+            if shortfall > 0 {
+                invoke(
+                    &system_instruction::transfer(payer.key, pda_account.key, shortfall),
+                    &[payer.clone(), pda_account.clone(), system_program.clone()],
+                )?;
+            }
+            pda_account.resize(AccountState::LEN)?;
+
+            let mut data = pda_account.data.borrow_mut();
+            if version == 0 {
+                // Refill: everything after is_initialized moves up one byte
+                // to make room for the version
+                data.copy_within(AccountState::VERSION_OFFSET..AccountState::LEN_V0, AccountState::RATING_OFFSET);
+            }
+            // Zero the fields this version didn't have yet: sub-ratings
+            // from version 2, visit details from version 3, the sequence
+            // from version 4
+            let old_len = match version {
+                3 => AccountState::LEN_V3,
+                2 => AccountState::LEN_V2,
+                _ => AccountState::LEN_V1,
+            };
+            data[old_len..].fill(0);
+            data[AccountState::VERSION_OFFSET] = AccountState::VERSION;
+        }
+        _ => {
+            msg!("Unknown review version {}", version);
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    // The upgraded review must read back as the review at this address
+    let account_data = load_review(program_id, pda_account)?;
+    msg!("Review upgraded from version {} to {}", version, account_data.version);
+
+    Ok(())
+}
+
+// Create the comment counter of a new review
+// The counter lives at [review_pda, "comment"]
+fn create_comment_counter<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_counter: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+    review: &Pubkey,
+) -> ProgramResult {
+    let (counter_pda, counter_bump) =
+        Pubkey::find_program_address(&[review.as_ref(), b"comment"], program_id);
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_counter.key,
+            rent.minimum_balance(ReviewCommentCounter::SIZE),
+            ReviewCommentCounter::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), pda_counter.clone(), system_program.clone()],
+        &[&[review.as_ref(), b"comment", &[counter_bump]]],
+    )?;
+    msg!("comment counter created");
+
+    let counter_data = ReviewCommentCounter {
+        discriminator: ReviewCommentCounter::DISCRIMINATOR,
+        is_initialized: true,
+        counter: 0,
+    };
+    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// CPIs go to whichever program account is passed in, so make sure it is the
+// real system program
+fn check_system_program(system_program: &AccountInfo) -> ProgramResult {
+    if !solana_system_interface::program::check_id(system_program.key) {
+        msg!("Expected the system program, got {}", system_program.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+// Add a review to its author's index, creating the index with the
+// author's first review and growing it by one entry, paid by `payer`
+#[allow(clippy::too_many_arguments)]
+fn add_to_reviewer_index<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_index: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+    reviewer: &Pubkey,
+    review: &Pubkey,
+) -> ProgramResult {
+    let (index_pda, index_bump) =
+        Pubkey::find_program_address(&[b"reviewer_index", reviewer.as_ref()], program_id);
+    if index_pda != *pda_index.key {
+        msg!("Invalid seeds for reviewer index PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let mut index = if pda_index.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                pda_index.key,
+                rent.minimum_balance(ReviewerIndex::size_for(0)),
+                ReviewerIndex::size_for(0) as u64,
+                program_id,
+            ),
+            &[payer.clone(), pda_index.clone(), system_program.clone()],
+            &[&[b"reviewer_index", reviewer.as_ref(), &[index_bump]]],
+        )?;
+        ReviewerIndex {
+            discriminator: ReviewerIndex::DISCRIMINATOR,
+            is_initialized: true,
+            reviewer: *reviewer,
+            reviews: Vec::new(),
+        }
+    } else {
+        if pda_index.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        ReviewerIndex::unpack_checked(&pda_index.data.borrow())?
+    };
+    index.reviews.push(*review);
+
+    // Grow the account by one entry, topping up the rent first
+    let new_len = ReviewerIndex::size_for(index.reviews.len());
+    let shortfall = rent.minimum_balance(new_len).saturating_sub(pda_index.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, pda_index.key, shortfall),
+            &[payer.clone(), pda_index.clone(), system_program.clone()],
+        )?;
+    }
+    pda_index.resize(new_len)?;
+    index.serialize(&mut &mut pda_index.data.borrow_mut()[..])?;
+    Ok(())
+}
+
+// Take a review out of its author's index, shrinking the account and
+// sending the freed rent to `destination`
+// Reviews added before the index existed aren't in it, so a missing index
+// or entry is not an error
+fn remove_from_reviewer_index(
+    program_id: &Pubkey,
+    pda_index: &AccountInfo,
+    destination: &AccountInfo,
+    reviewer: &Pubkey,
+    review: &Pubkey,
+    rent: &Rent,
+) -> ProgramResult {
+    let (index_pda, _index_bump) =
+        Pubkey::find_program_address(&[b"reviewer_index", reviewer.as_ref()], program_id);
+    if index_pda != *pda_index.key {
+        msg!("Invalid seeds for reviewer index PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_index.data_is_empty() {
+        return Ok(());
+    }
+    if pda_index.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut index = ReviewerIndex::unpack_checked(&pda_index.data.borrow())?;
+    let Some(position) = index.reviews.iter().position(|entry| entry == review) else {
+        return Ok(());
+    };
+    index.reviews.remove(position);
+
+    let new_len = ReviewerIndex::size_for(index.reviews.len());
+    pda_index.resize(new_len)?;
+    index.serialize(&mut &mut pda_index.data.borrow_mut()[..])?;
+
+    let excess = pda_index
+        .lamports()
+        .saturating_sub(rent.minimum_balance(new_len));
+    **pda_index.lamports.borrow_mut() -= excess;
+    **destination.lamports.borrow_mut() = destination
+        .lamports()
+        .checked_add(excess)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Point an index entry at a review's new PDA after a migration
+// The entry keeps its position, so the account size doesn't change
+fn replace_in_reviewer_index(
+    program_id: &Pubkey,
+    pda_index: &AccountInfo,
+    reviewer: &Pubkey,
+    old_review: &Pubkey,
+    new_review: &Pubkey,
+) -> ProgramResult {
+    let (index_pda, _index_bump) =
+        Pubkey::find_program_address(&[b"reviewer_index", reviewer.as_ref()], program_id);
+    if index_pda != *pda_index.key {
+        msg!("Invalid seeds for reviewer index PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_index.data_is_empty() {
+        return Ok(());
+    }
+    if pda_index.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut index = ReviewerIndex::unpack_checked(&pda_index.data.borrow())?;
+    if let Some(entry) = index.reviews.iter_mut().find(|entry| *entry == old_review) {
+        *entry = *new_review;
+        index.serialize(&mut &mut pda_index.data.borrow_mut()[..])?;
+    }
+    Ok(())
+}
+
+// Rent parameters for sizing new accounts
+// Prefers the runtime's Rent sysvar via Rent::get(); where the syscall is
+// unavailable (some program-test setups fail with UnsupportedSysvar) the
+// caller can pass the Rent sysvar account instead
+fn load_rent(rent_sysvar: Option<&AccountInfo>) -> Result<Rent, ProgramError> {
+    match Rent::get() {
+        Ok(rent) => Ok(rent),
+        Err(err) => match rent_sysvar {
+            Some(account) if *account.key == solana_program::sysvar::rent::ID => {
+                Rent::from_account_info(account)
+            }
+            _ => {
+                msg!("Rent sysvar unavailable and no Rent sysvar account passed");
+                Err(err)
+            }
+        },
+    }
+}
+
+// Load the aggregate for a restaurant title, creating it if this is
+// the restaurant's first review
+fn load_or_create_aggregate<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_aggregate: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+    title: &str,
+    rating_range: &RangeInclusive<u8>,
+) -> Result<RestaurantAggregate, ProgramError> {
+    if !pda_aggregate.data_is_empty() {
+        return load_aggregate(program_id, pda_aggregate, title);
+    }
+
+    let normalized = normalize_title(title);
+    let (aggregate_pda, aggregate_bump) =
+        Pubkey::find_program_address(&[b"aggregate", &title_seed(&normalized)], program_id);
+    if aggregate_pda != *pda_aggregate.key {
+        msg!("Invalid seeds for aggregate PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_aggregate.key,
+            rent.minimum_balance(RestaurantAggregate::SIZE),
+            RestaurantAggregate::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), pda_aggregate.clone(), system_program.clone()],
+        &[&[b"aggregate", &title_seed(&normalized), &[aggregate_bump]]],
+    )?;
+    debug_msg!("Created aggregate for: {}", normalized);
+
+    Ok(RestaurantAggregate {
+        discriminator: RestaurantAggregate::DISCRIMINATOR,
+        is_initialized: true,
+        review_count: 0,
+        rating_sum: 0,
+        min_rating: *rating_range.start(),
+        max_rating: *rating_range.end(),
+        histogram: [0; 10],
+        sub_rating_count: 0,
+        food_sum: 0,
+        service_sum: 0,
+        ambiance_sum: 0,
+        weight_sum: 0,
+        weighted_rating_sum: 0,
+        decayed_weight_sum: 0,
+        decayed_rating_sum: 0,
+        decayed_at: 0,
+    })
+}
+
+// Load and validate the existing aggregate for a restaurant title
+fn load_aggregate(
+    program_id: &Pubkey,
+    pda_aggregate: &AccountInfo,
+    title: &str,
+) -> Result<RestaurantAggregate, ProgramError> {
+    let (aggregate_pda, _aggregate_bump) = Pubkey::find_program_address(
+        &[b"aggregate", &title_seed(&normalize_title(title))],
+        program_id,
+    );
+    if aggregate_pda != *pda_aggregate.key {
+        msg!("Invalid seeds for aggregate PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_aggregate.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let aggregate = RestaurantAggregate::unpack_checked(&pda_aggregate.data.borrow())?;
+    if !aggregate.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    Ok(aggregate)
+}
+
+// Handler for the time-decay crank
+// Anyone can decay a restaurant's recency-weighted sums to the current
+// time; reviews already do this as they are added, so the crank only
+// matters for restaurants that haven't had a review in a while
+pub fn recompute_aggregate(program_id: &Pubkey, accounts: &[AccountInfo], title: String) -> ProgramResult {
+    msg!("Recomputing aggregate...");
+    debug_msg!("Title: {}", title);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let pda_aggregate = next_account_info(account_info_iter)?; // Restaurant aggregate PDA
+    let clock_sysvar = next_account_info(account_info_iter)?;  // Clock sysvar for the current time
+
+    let mut aggregate = load_aggregate(program_id, pda_aggregate, &title)?;
+
+    // Note: In tests, Clock::get() may fail with UnsupportedSysvar
+    let now = Clock::from_account_info(clock_sysvar)?.unix_timestamp;
+    aggregate.decay_to(now);
+    aggregate.serialize(&mut &mut pda_aggregate.data.borrow_mut()[..])?;
+
+    msg!(
+        "Effective review count {}.{:06} as of {}",
+        aggregate.decayed_weight_sum / DECAY_SCALE,
+        aggregate.decayed_weight_sum % DECAY_SCALE,
+        now
+    );
+
+    Ok(())
+}
+
+// Close a program-owned account and send its lamports to `destination`
+fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    // Zero the data so the account can't be read as its old type again
+    // if it is revived within the same transaction
+    account.data.borrow_mut().fill(0);
+
+    // Hand the account back to the system program with no data, so a later
+    // instruction in the same transaction sees it as empty and can create
+    // it again (e.g. DeleteReview followed by AddReview of the same title)
+    account.resize(0)?;
+    account.assign(&solana_system_interface::program::ID);
+
+    // Move all lamports to the destination; an account with zero lamports
+    // is removed by the runtime at the end of the transaction
+    let dest_starting_lamports = destination.lamports();
+    **destination.lamports.borrow_mut() = dest_starting_lamports
+        .checked_add(account.lamports())
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.lamports.borrow_mut() = 0;
+
+    Ok(())
+}
+
+// Handler for commenting on a review
+// Creates a comment PDA at the review's next comment index
+// so every comment can be found by counting up from 0
+pub fn add_comment(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    comment: String,       // Comment text
+) -> ProgramResult {
+    msg!("Adding comment...");
+    debug_msg!("Comment: {}", comment);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let commenter = next_account_info(account_info_iter)?;       // User writing the comment (signer, pays rent)
+    let pda_review = next_account_info(account_info_iter)?;      // Review being commented on
+    let pda_counter = next_account_info(account_info_iter)?;     // Comment counter PDA for the review
+    let pda_comment = next_account_info(account_info_iter)?;     // New comment PDA to create
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA (may be uninitialized)
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+    check_not_paused(program_id, pda_config)?;
+
+    if !commenter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Only comments on real reviews owned by this program are accepted
+    if pda_review.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let review_data = AccountState::unpack_checked(&pda_review.data.borrow())?;
+    if !review_data.is_initialized() {
+        msg!("Review is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    let data_len = ReviewComment::get_account_size(&comment);
+    if data_len > MAX_ACCOUNT_LEN {
+        msg!("Comment needs {} bytes, maximum is {}", data_len, MAX_ACCOUNT_LEN);
+        return Err(ReviewError::DataTooLarge.into());
+    }
+
+    let rent = load_rent(rent_sysvar)?;
+
+    // The counter was created with the review at [review_pda, "comment"]
+    let (counter_pda, _counter_bump) =
+        Pubkey::find_program_address(&[pda_review.key.as_ref(), b"comment"], program_id);
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_counter.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut counter_data = ReviewCommentCounter::unpack_checked(&pda_counter.data.borrow())?;
+    if !counter_data.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // Each comment lives at [review_pda, count] with count as big-endian u64
+    let (comment_pda, comment_bump) = Pubkey::find_program_address(
+        &[pda_review.key.as_ref(), counter_data.counter.to_be_bytes().as_ref()],
+        program_id,
+    );
+    if comment_pda != *pda_comment.key {
+        msg!("Invalid seeds for comment PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            commenter.key,
+            pda_comment.key,
+            rent.minimum_balance(data_len),
+            data_len as u64,
+            program_id,
+        ),
+        &[commenter.clone(), pda_comment.clone(), system_program.clone()],
+        &[&[
+            pda_review.key.as_ref(),
+            counter_data.counter.to_be_bytes().as_ref(),
+            &[comment_bump],
+        ]],
+    )?;
+
+    msg!("Created comment account");
+
+    let comment_data = ReviewComment {
+        discriminator: ReviewComment::DISCRIMINATOR,
+        is_initialized: true,
+        review: *pda_review.key,
+        commenter: *commenter.key,
+        comment,
+        count: counter_data.counter,
+    };
+    comment_data.serialize(&mut &mut pda_comment.data.borrow_mut()[..])?;
+
+    // Bump the counter so the next comment gets the next index
+    counter_data.counter = counter_data
+        .counter
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    counter_data.serialize(&mut &mut pda_counter.data.borrow_mut()[..])?;
+
+    msg!("Comment count: {}", counter_data.counter);
+
+    Ok(())
+}
+
+// Handler for creating the program-wide statistics account
+// The stats PDA is a singleton derived from ["stats"], so this can only succeed once
+pub fn initialize_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Initializing program stats...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let payer = next_account_info(account_info_iter)?;          // Pays rent for the stats account (signer)
+    let pda_stats = next_account_info(account_info_iter)?;      // Stats PDA to create
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (stats_pda, stats_bump) = Pubkey::find_program_address(&[b"stats"], program_id);
+    if stats_pda != *pda_stats.key {
+        msg!("Invalid seeds for stats PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_stats.data_is_empty() {
+        return Err(ReviewError::AlreadyInitialized.into());
+    }
+
+    let rent = load_rent(rent_sysvar)?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_stats.key,
+            rent.minimum_balance(ProgramStats::SIZE),
+            ProgramStats::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), pda_stats.clone(), system_program.clone()],
+        &[&[b"stats", &[stats_bump]]],
+    )?;
+
+    let stats = ProgramStats {
+        discriminator: ProgramStats::DISCRIMINATOR,
+        is_initialized: true,
+        total_reviews: 0,
+        total_updates: 0,
+        total_unique_reviewers: 0,
+    };
+    stats.serialize(&mut &mut pda_stats.data.borrow_mut()[..])?;
+
+    msg!("Program stats created: {}", stats_pda);
+
+    Ok(())
+}
+
+// Load and validate the program stats account
+fn load_stats(program_id: &Pubkey, pda_stats: &AccountInfo) -> Result<ProgramStats, ProgramError> {
+    let (stats_pda, _stats_bump) = Pubkey::find_program_address(&[b"stats"], program_id);
+    if stats_pda != *pda_stats.key {
+        msg!("Invalid seeds for stats PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_stats.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let stats = ProgramStats::unpack_checked(&pda_stats.data.borrow())?;
+    if !stats.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    Ok(stats)
+}
+
+// Handler for tipping a review's owner in SOL
+// Transfers lamports from the tipper straight to the owner and adds them
+// to the review's running tip total
+pub fn tip_reviewer(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    msg!("Tipping reviewer {} lamports", lamports);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let tipper = next_account_info(account_info_iter)?;         // Wallet sending the tip (signer)
+    let reviewer = next_account_info(account_info_iter)?;       // Review owner receiving the tip
+    let pda_account = next_account_info(account_info_iter)?;    // Review being tipped
+    let system_program = next_account_info(account_info_iter)?; // System program for the transfer
+
+    check_system_program(system_program)?;
+
+    if !tipper.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if lamports == 0 {
+        return Err(ReviewError::InvalidTipAmount.into());
+    }
+
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut account_data = AccountState::unpack_checked(&pda_account.data.borrow())?;
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // Tips go to whoever owns the review now
+    review_address(program_id, &account_data, pda_account.key)?;
+    if account_data.owner != *reviewer.key {
+        msg!("Reviewer is not the owner of this review");
+        return Err(ReviewError::NotReviewOwner.into());
+    }
+
+    invoke(
+        &system_instruction::transfer(tipper.key, reviewer.key, lamports),
+        &[tipper.clone(), reviewer.clone(), system_program.clone()],
+    )?;
+
+    account_data.tip_total = account_data
+        .tip_total
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    msg!("Total tips: {}", account_data.tip_total);
+    AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Handler for claiming a milestone badge
+// Each badge is its own SPL Token mint at ["badge", reviewer, milestone]
+// with 0 decimals and a supply of 1. The ["badge_authority"] PDA mints it
+// into the reviewer's badge account at ["badge_account", mint], freezes
+// that account so the badge can't be transferred, and gives up the mint
+// authority so no second copy can ever be minted
+pub fn claim_badge(program_id: &Pubkey, accounts: &[AccountInfo], milestone: u64) -> ProgramResult {
+    msg!("Claiming badge for {} reviews", milestone);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let reviewer = next_account_info(account_info_iter)?;        // Reviewer claiming the badge (signer, pays rent)
+    let pda_index = next_account_info(account_info_iter)?;       // Reviewer index PDA, listing their live reviews
+    let badge_mint = next_account_info(account_info_iter)?;      // Badge mint PDA, created here
+    let badge_account = next_account_info(account_info_iter)?;   // Reviewer's badge token account PDA, created here
+    let badge_authority = next_account_info(account_info_iter)?; // Badge mint and freeze authority PDA
+    let token_program = next_account_info(account_info_iter)?;   // SPL Token program
+    let system_program = next_account_info(account_info_iter)?;  // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !spl_token::check_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if !BADGE_MILESTONES.contains(&milestone) {
+        msg!("Badges are awarded at {:?} reviews", BADGE_MILESTONES);
+        return Err(ReviewError::InvalidMilestone.into());
+    }
+
+    // Milestones count live reviews, the ones in the reviewer's index at
+    // ["reviewer_index", reviewer_pubkey]; the reviewer record only ever
+    // counts up, so deleting and re-adding reviews would farm badges
+    let (index_pda, _index_bump) =
+        Pubkey::find_program_address(&[b"reviewer_index", reviewer.key.as_ref()], program_id);
+    if index_pda != *pda_index.key {
+        msg!("Invalid seeds for reviewer index PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_index.owner != program_id {
+        // No index means no reviews yet
+        return Err(ReviewError::MilestoneNotReached.into());
+    }
+    let live_reviews = ReviewerIndex::unpack_checked(&pda_index.data.borrow())?.reviews.len() as u64;
+    if live_reviews < milestone {
+        msg!("{} live reviews, {} needed", live_reviews, milestone);
+        return Err(ReviewError::MilestoneNotReached.into());
+    }
+
+    let milestone_bytes = milestone.to_le_bytes();
+    let (mint_pda, mint_bump) = Pubkey::find_program_address(
+        &[b"badge", reviewer.key.as_ref(), &milestone_bytes],
+        program_id,
+    );
+    if mint_pda != *badge_mint.key {
+        msg!("Invalid seeds for badge mint PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !badge_mint.data_is_empty() {
+        return Err(ReviewError::BadgeAlreadyClaimed.into());
+    }
+    let (account_pda, account_bump) =
+        Pubkey::find_program_address(&[b"badge_account", mint_pda.as_ref()], program_id);
+    if account_pda != *badge_account.key {
+        msg!("Invalid seeds for badge account PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    let (authority_pda, authority_bump) =
+        Pubkey::find_program_address(&[b"badge_authority"], program_id);
+    if authority_pda != *badge_authority.key {
+        msg!("Invalid seeds for badge authority PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    let authority_seeds: &[&[u8]] = &[b"badge_authority", &[authority_bump]];
+
+    let rent = load_rent(rent_sysvar)?;
+
+    // Create the mint, owned by the token program
+    invoke_signed(
+        &system_instruction::create_account(
+            reviewer.key,
+            badge_mint.key,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            token_program.key,
+        ),
+        &[reviewer.clone(), badge_mint.clone(), system_program.clone()],
+        &[&[b"badge", reviewer.key.as_ref(), &milestone_bytes, &[mint_bump]]],
+    )?;
+    invoke(
+        &initialize_mint2(token_program.key, badge_mint.key, &authority_pda, Some(&authority_pda), 0)?,
+        &[badge_mint.clone(), token_program.clone()],
+    )?;
+
+    // Create the reviewer's badge account
+    invoke_signed(
+        &system_instruction::create_account(
+            reviewer.key,
+            badge_account.key,
+            rent.minimum_balance(spl_token::state::Account::LEN),
+            spl_token::state::Account::LEN as u64,
+            token_program.key,
+        ),
+        &[reviewer.clone(), badge_account.clone(), system_program.clone()],
+        &[&[b"badge_account", mint_pda.as_ref(), &[account_bump]]],
+    )?;
+    invoke(
+        &initialize_account3(token_program.key, badge_account.key, badge_mint.key, reviewer.key)?,
+        &[badge_account.clone(), badge_mint.clone(), token_program.clone()],
+    )?;
+
+    // Mint the single badge, lock it in place, and fix the supply at 1
+    invoke_signed(
+        &mint_to(token_program.key, badge_mint.key, badge_account.key, &authority_pda, &[], 1)?,
+        &[badge_mint.clone(), badge_account.clone(), badge_authority.clone(), token_program.clone()],
+        &[authority_seeds],
+    )?;
+    invoke_signed(
+        &freeze_account(token_program.key, badge_account.key, badge_mint.key, &authority_pda, &[])?,
+        &[badge_account.clone(), badge_mint.clone(), badge_authority.clone(), token_program.clone()],
+        &[authority_seeds],
+    )?;
+    invoke_signed(
+        &set_authority(
+            token_program.key,
+            badge_mint.key,
+            None,
+            AuthorityType::MintTokens,
+            &authority_pda,
+            &[],
+        )?,
+        &[badge_mint.clone(), badge_authority.clone(), token_program.clone()],
+        &[authority_seeds],
+    )?;
+
+    msg!("Badge {} minted to {}", mint_pda, reviewer.key);
+
+    Ok(())
+}
+
+// Handler for tipping a review's owner in an SPL token
+// Transfers tokens between the tipper's and owner's token accounts and
+// adds them to the review's tip ledger for that mint
+// Works with both SPL Token and Token-2022: the token accounts and mint
+// are read through the program that owns them, so Token-2022 accounts
+// with extensions are accepted too
+pub fn tip_reviewer_token(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    msg!("Tipping reviewer {} tokens", amount);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let tipper = next_account_info(account_info_iter)?;         // Wallet sending the tip (signer, pays ledger rent)
+    let reviewer = next_account_info(account_info_iter)?;       // Review owner
+    let pda_account = next_account_info(account_info_iter)?;    // Review being tipped
+    let tipper_token = next_account_info(account_info_iter)?;   // Tipper's token account
+    let reviewer_token = next_account_info(account_info_iter)?; // Owner's token account receiving the tip
+    let mint_info = next_account_info(account_info_iter)?;      // Mint of the tipped token
+    let pda_ledger = next_account_info(account_info_iter)?;     // Tip ledger PDA for (review, mint)
+    let token_program = next_account_info(account_info_iter)?;  // SPL Token or Token-2022 program
+    let system_program = next_account_info(account_info_iter)?; // System program for ledger creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !tipper.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if amount == 0 {
+        return Err(ReviewError::InvalidTipAmount.into());
+    }
+    if !spl_generic_token::is_known_spl_token_id(token_program.key) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let account_data = AccountState::unpack_checked(&pda_account.data.borrow())?;
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // Same owner check as SOL tips
+    review_address(program_id, &account_data, pda_account.key)?;
+    if account_data.owner != *reviewer.key {
+        msg!("Reviewer is not the owner of this review");
+        return Err(ReviewError::NotReviewOwner.into());
+    }
+
+    // The destination must be the owner's own account for this mint
+    // Both it and the mint have to belong to the token program being used
+    if reviewer_token.owner != token_program.key || mint_info.owner != token_program.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let reviewer_token_state = Account::unpack(&reviewer_token.try_borrow_data()?, token_program.key)
+        .ok_or(ReviewError::InvalidTokenAccount)?;
+    if reviewer_token_state.owner != *reviewer.key || reviewer_token_state.mint != *mint_info.key {
+        return Err(ReviewError::InvalidTokenAccount.into());
+    }
+    let decimals = Mint::unpack(&mint_info.try_borrow_data()?, token_program.key)
+        .ok_or(ProgramError::InvalidAccountData)?
+        .decimals;
+
+    // The ledger lives at [review_pda, "tips", mint]
+    let (ledger_pda, ledger_bump) = Pubkey::find_program_address(
+        &[pda_account.key.as_ref(), b"tips", mint_info.key.as_ref()],
+        program_id,
+    );
+    if ledger_pda != *pda_ledger.key {
+        msg!("Invalid seeds for tip ledger PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // First tip in this mint: create the ledger
+    let mut ledger = if pda_ledger.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                tipper.key,
+                pda_ledger.key,
+                load_rent(rent_sysvar)?.minimum_balance(TipLedger::SIZE),
+                TipLedger::SIZE as u64,
+                program_id,
+            ),
+            &[tipper.clone(), pda_ledger.clone(), system_program.clone()],
+            &[&[
+                pda_account.key.as_ref(),
+                b"tips",
+                mint_info.key.as_ref(),
+                &[ledger_bump],
+            ]],
+        )?;
+        msg!("Created tip ledger");
+
+        TipLedger {
+            discriminator: TipLedger::DISCRIMINATOR,
+            is_initialized: true,
+            review: *pda_account.key,
+            mint: *mint_info.key,
+            total: 0,
+        }
+    } else {
+        if pda_ledger.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        TipLedger::unpack_checked(&pda_ledger.data.borrow())?
+    };
+
+    // The tipper signs for their own token account
+    // Token-2022 encodes TransferChecked exactly like SPL Token, but the
+    // spl_token builder only accepts its own program id, so the instruction
+    // is built for SPL Token and sent to whichever program was passed
+    let mut transfer = transfer_checked(
+        &spl_token::id(),
+        tipper_token.key,
+        mint_info.key,
+        reviewer_token.key,
+        tipper.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    transfer.program_id = *token_program.key;
+    invoke(
+        &transfer,
+        &[
+            tipper_token.clone(),
+            mint_info.clone(),
+            reviewer_token.clone(),
+            tipper.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    ledger.total = ledger
+        .total
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    ledger.serialize(&mut &mut pda_ledger.data.borrow_mut()[..])?;
+
+    msg!("Total tips in this mint: {}", ledger.total);
+
+    Ok(())
+}
+
+// Handler for voting on a review
+// Creates a vote PDA for (review, voter) so each wallet votes at most once,
+// then bumps the matching counter on the review
+pub fn vote_review(program_id: &Pubkey, accounts: &[AccountInfo], up: bool) -> ProgramResult {
+    msg!("Voting on review, up: {}", up);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let voter = next_account_info(account_info_iter)?;          // Wallet voting (signer, pays vote rent)
+    let pda_account = next_account_info(account_info_iter)?;    // Review being voted on
+    let pda_vote = next_account_info(account_info_iter)?;       // Vote PDA for (review, voter)
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let pda_aggregate = next_account_info(account_info_iter)?;  // Aggregate PDA for the review's restaurant
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !voter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut account_data = AccountState::unpack_checked(&pda_account.data.borrow())?;
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // The vote lives at [review_pda, voter]
+    let (vote_pda, vote_bump) = Pubkey::find_program_address(
+        &[pda_account.key.as_ref(), voter.key.as_ref()],
+        program_id,
+    );
+    if vote_pda != *pda_vote.key {
+        msg!("Invalid seeds for vote PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_vote.data_is_empty() {
+        msg!("Wallet has already voted");
+        return Err(ReviewError::AlreadyVoted.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            voter.key,
+            pda_vote.key,
+            load_rent(rent_sysvar)?.minimum_balance(ReviewVote::SIZE),
+            ReviewVote::SIZE as u64,
+            program_id,
+        ),
+        &[voter.clone(), pda_vote.clone(), system_program.clone()],
+        &[&[pda_account.key.as_ref(), voter.key.as_ref(), &[vote_bump]]],
+    )?;
+
+    let vote = ReviewVote {
+        discriminator: ReviewVote::DISCRIMINATOR,
+        is_initialized: true,
+        review: *pda_account.key,
+        voter: *voter.key,
+        up,
+    };
+    vote.serialize(&mut &mut pda_vote.data.borrow_mut()[..])?;
+
+    let old_helpfulness = account_data.helpfulness();
+    let counter = if up {
+        &mut account_data.upvotes
+    } else {
+        &mut account_data.downvotes
+    };
+    *counter = counter.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+    msg!("Votes: {} up, {} down", account_data.upvotes, account_data.downvotes);
+
+    // The vote moves the review's helpfulness, so recount its rating in the
+    // restaurant's weighted average; an archived review no longer counts
+    if account_data.status != ReviewStatus::Deleted {
+        let mut aggregate = load_aggregate(program_id, pda_aggregate, &account_data.title)?;
+        aggregate.reweight_rating(account_data.rating, old_helpfulness, account_data.helpfulness())?;
+        aggregate.serialize(&mut &mut pda_aggregate.data.borrow_mut()[..])?;
+    }
+    AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Handler for handing a review to another wallet
+// The review keeps its address (derived from the author), but from now on
+// only the new owner can update or delete it and tips go to them
+pub fn transfer_review_ownership(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_owner: Pubkey,
+) -> ProgramResult {
+    msg!("Transferring review ownership to {}", new_owner);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;       // Current owner (signer, or a ReviewMultisig)
+    let pda_account = next_account_info(account_info_iter)?; // Review being transferred
+    let pda_config = next_account_info(account_info_iter)?;  // Program config PDA (may be uninitialized)
+    // Remaining accounts: multisig members signing for a multisig owner
+
+    check_not_paused(program_id, pda_config)?;
+    review_authority(program_id, owner, account_info_iter.as_slice())?;
+
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut account_data = AccountState::unpack_checked(&pda_account.data.borrow())?;
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    review_address(program_id, &account_data, pda_account.key)?;
+    if account_data.owner != *owner.key {
+        msg!("Signer is not the owner of this review");
+        return Err(ReviewError::NotReviewOwner.into());
+    }
+
+    account_data.owner = new_owner;
+    AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
+
+    msg!("Review ownership transferred");
+
+    Ok(())
+}
+
+// Account that approves a change to a review owned by `owner`
+// A wallet owner approves by signing. A ReviewMultisig owner approves when
+// enough of its members are among `signers`; the first member that signed
+// is returned, so it can pay for anything the change creates
+fn review_authority<'a, 'b>(
+    program_id: &Pubkey,
+    owner: &'b AccountInfo<'a>,
+    signers: &'b [AccountInfo<'a>],
+) -> Result<&'b AccountInfo<'a>, ProgramError> {
+    if owner.is_signer {
+        return Ok(owner);
+    }
+    if owner.owner != program_id {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let multisig = ReviewMultisig::unpack_checked(&owner.data.borrow())?;
+    if !multisig.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    if !multisig.is_approved_by(signers) {
+        msg!("Multisig needs {} member signatures", multisig.threshold);
+        return Err(ReviewError::NotEnoughSigners.into());
+    }
+    signers
+        .iter()
+        .find(|account| account.is_signer && multisig.signers.contains(account.key))
+        .ok_or_else(|| ReviewError::NotEnoughSigners.into())
+}
+
+// Handler for creating a review multisig
+// The multisig is a fresh keypair account, like an SPL Token multisig;
+// reviews are handed to it with TransferReviewOwnership
+pub fn create_multisig(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    threshold: u8,
+    signers: Vec<Pubkey>,
+) -> ProgramResult {
+    msg!("Creating {}-of-{} multisig", threshold, signers.len());
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let payer = next_account_info(account_info_iter)?;          // Pays rent (signer)
+    let multisig_account = next_account_info(account_info_iter)?; // New multisig account (signer)
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !payer.is_signer || !multisig_account.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // 1 <= M <= N <= MAX_MULTISIG_SIGNERS, and each member listed once so
+    // no one can count twice towards the threshold
+    let has_duplicates = signers
+        .iter()
+        .enumerate()
+        .any(|(i, signer)| signers[..i].contains(signer));
+    if threshold == 0
+        || threshold as usize > signers.len()
+        || signers.len() > MAX_MULTISIG_SIGNERS
+        || has_duplicates
+    {
+        return Err(ReviewError::InvalidMultisig.into());
+    }
+    if !multisig_account.data_is_empty() {
+        return Err(ReviewError::AlreadyInitialized.into());
+    }
+
+    let rent = load_rent(rent_sysvar)?;
+    invoke(
+        &system_instruction::create_account(
+            payer.key,
+            multisig_account.key,
+            rent.minimum_balance(ReviewMultisig::SIZE),
+            ReviewMultisig::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), multisig_account.clone(), system_program.clone()],
+    )?;
+
+    let multisig = ReviewMultisig {
+        discriminator: ReviewMultisig::DISCRIMINATOR,
+        is_initialized: true,
+        threshold,
+        signers,
+    };
+    multisig.serialize(&mut &mut multisig_account.data.borrow_mut()[..])?;
+
+    msg!("Multisig created: {}", multisig_account.key);
+
+    Ok(())
+}
+
+// Handler for setting up a Merkle tree for compressed reviews
+// The tree account must already be allocated for max_depth/max_buffer_size
+// and owned by the compression program (clients do this in the same
+// transaction); this hands its authority to the program's tree PDA
+pub fn create_review_tree(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> ProgramResult {
+    msg!("Creating review tree (depth {}, buffer {})", max_depth, max_buffer_size);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let payer = next_account_info(account_info_iter)?;          // Creator of the tree (signer)
+    let merkle_tree = next_account_info(account_info_iter)?;    // Allocated, empty tree account
+    let tree_authority = next_account_info(account_info_iter)?; // Tree authority PDA
+    let compression_program = next_account_info(account_info_iter)?; // spl-account-compression
+    let noop_program = next_account_info(account_info_iter)?;   // spl-noop
+
+    if !payer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let bump = check_tree_accounts(program_id, merkle_tree, tree_authority, compression_program, noop_program)?;
+
+    invoke_signed(
+        &compression::init_empty_merkle_tree(merkle_tree.key, tree_authority.key, max_depth, max_buffer_size),
+        &[merkle_tree.clone(), tree_authority.clone(), noop_program.clone()],
+        &[&[b"tree_authority", merkle_tree.key.as_ref(), &[bump]]],
+    )?;
+
+    msg!("Review tree created: {}", merkle_tree.key);
+
+    Ok(())
+}
+
+// Handler for adding a compressed review
+// The review is a leaf of the tree rather than an account, so it costs no
+// rent; it is not counted in the stats, aggregates, or reviewer records
+pub fn add_compressed_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    rating: u8,
+    description: String,
+) -> ProgramResult {
+    msg!("Adding compressed review...");
+    debug_msg!("Title: {}", title);
+    debug_msg!("Rating: {}", rating);
+    debug_msg!("Description: {}", description);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let author = next_account_info(account_info_iter)?;         // Review author (signer)
+    let merkle_tree = next_account_info(account_info_iter)?;    // Review tree
+    let tree_authority = next_account_info(account_info_iter)?; // Tree authority PDA
+    let compression_program = next_account_info(account_info_iter)?; // spl-account-compression
+    let noop_program = next_account_info(account_info_iter)?;   // spl-noop
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA (may be uninitialized)
+
+    if !author.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let rating_range = load_rating_range(program_id, pda_config)?;
+    validate_compressed_review(&title, rating, &description, rating_range)?;
+    let bump = check_tree_accounts(program_id, merkle_tree, tree_authority, compression_program, noop_program)?;
+
+    let leaf = review_leaf(author.key, &title, rating, &description);
+    invoke_signed(
+        &compression::append(merkle_tree.key, tree_authority.key, leaf),
+        &[merkle_tree.clone(), tree_authority.clone(), noop_program.clone()],
+        &[&[b"tree_authority", merkle_tree.key.as_ref(), &[bump]]],
+    )?;
+
+    CompressedReviewAdded {
+        merkle_tree: *merkle_tree.key,
+        author: *author.key,
+        title,
+        rating,
+        description,
+    }
+    .emit()?;
+
+    Ok(())
+}
+
+// Handler for updating a compressed review
+// The author restates the current review; the compression program checks
+// that its leaf, with the proof accounts, hashes up to `root`. Because the
+// leaf includes the author, no one else can produce it
+#[allow(clippy::too_many_arguments)]
+pub fn update_compressed_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    rating: u8,
+    description: String,
+    new_rating: u8,
+    new_description: String,
+    root: [u8; 32],
+    index: u32,
+) -> ProgramResult {
+    msg!("Updating compressed review {}...", index);
+    debug_msg!("Title: {}", title);
+    debug_msg!("Rating: {}", new_rating);
+    debug_msg!("Description: {}", new_description);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let author = next_account_info(account_info_iter)?;         // Review author (signer)
+    let merkle_tree = next_account_info(account_info_iter)?;    // Review tree
+    let tree_authority = next_account_info(account_info_iter)?; // Tree authority PDA
+    let compression_program = next_account_info(account_info_iter)?; // spl-account-compression
+    let noop_program = next_account_info(account_info_iter)?;   // spl-noop
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA (may be uninitialized)
+    let proof = account_info_iter.as_slice();                   // Proof nodes, leaf to root
+
+    if !author.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let rating_range = load_rating_range(program_id, pda_config)?;
+    validate_compressed_review(&title, new_rating, &new_description, rating_range)?;
+    let bump = check_tree_accounts(program_id, merkle_tree, tree_authority, compression_program, noop_program)?;
+
+    let previous_leaf = review_leaf(author.key, &title, rating, &description);
+    let new_leaf = review_leaf(author.key, &title, new_rating, &new_description);
+    let proof_keys: Vec<Pubkey> = proof.iter().map(|node| *node.key).collect();
+
+    let mut cpi_accounts = vec![merkle_tree.clone(), tree_authority.clone(), noop_program.clone()];
+    cpi_accounts.extend(proof.iter().cloned());
+    invoke_signed(
+        &compression::replace_leaf(
+            merkle_tree.key,
+            tree_authority.key,
+            root,
+            previous_leaf,
+            new_leaf,
+            index,
+            &proof_keys,
+        ),
+        &cpi_accounts,
+        &[&[b"tree_authority", merkle_tree.key.as_ref(), &[bump]]],
+    )?;
+
+    CompressedReviewUpdated {
+        merkle_tree: *merkle_tree.key,
+        index,
+        author: *author.key,
+        title,
+        rating: new_rating,
+        description: new_description,
+    }
+    .emit()?;
+
+    Ok(())
+}
+
+// Check the program accounts of a compression CPI and the tree authority
+// PDA ["tree_authority", merkle_tree]; returns the PDA's bump
+fn check_tree_accounts(
+    program_id: &Pubkey,
+    merkle_tree: &AccountInfo,
+    tree_authority: &AccountInfo,
+    compression_program: &AccountInfo,
+    noop_program: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    if *compression_program.key != ACCOUNT_COMPRESSION_ID || *noop_program.key != NOOP_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (authority_pda, bump) =
+        Pubkey::find_program_address(&[b"tree_authority", merkle_tree.key.as_ref()], program_id);
+    if authority_pda != *tree_authority.key {
+        msg!("Invalid seeds for tree authority PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    Ok(bump)
+}
+
+// Same limits as an account-backed review, including the configured
+// rating scale read by load_rating_range
+fn validate_compressed_review(
+    title: &str,
+    rating: u8,
+    description: &str,
+    rating_range: RangeInclusive<u8>,
+) -> ProgramResult {
+    if title.len() > MAX_TITLE_LEN {
+        msg!("Title is {} bytes, maximum is {}", title.len(), MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    let title_graphemes = grapheme_count(title);
+    if title_graphemes > MAX_TITLE_GRAPHEMES {
+        msg!("Title is {} characters, maximum is {}", title_graphemes, MAX_TITLE_GRAPHEMES);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    validate_text("Title", title)?;
+    if description.len() > MAX_DESCRIPTION_LEN {
+        msg!("Description is {} bytes, maximum is {}", description.len(), MAX_DESCRIPTION_LEN);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+    let description_graphemes = grapheme_count(description);
+    if description_graphemes > MAX_DESCRIPTION_GRAPHEMES {
+        msg!("Description is {} characters, maximum is {}", description_graphemes, MAX_DESCRIPTION_GRAPHEMES);
+        return Err(ReviewError::DescriptionTooLong.into());
+    }
+    validate_text("Description", description)?;
+    if !rating_range.contains(&rating) {
+        return Err(ReviewError::InvalidRating.into());
+    }
+    Ok(())
+}
+
+// Handler for creating the program config
+// The signer becomes the admin; like the stats account it can only be
+// created once
+pub fn initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    moderators: Vec<Pubkey>,
+) -> ProgramResult {
+    msg!("Initializing program config...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;          // Admin (signer, pays rent)
+    let pda_config = next_account_info(account_info_iter)?;     // Config PDA to create
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if moderators.len() > MAX_MODERATORS {
+        msg!("{} moderators, maximum is {}", moderators.len(), MAX_MODERATORS);
+        return Err(ReviewError::TooManyModerators.into());
+    }
+
+    let (config_pda, config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_config.data_is_empty() {
+        return Err(ReviewError::AlreadyInitialized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_config.key,
+            load_rent(rent_sysvar)?.minimum_balance(ProgramConfig::SIZE),
+            ProgramConfig::SIZE as u64,
+            program_id,
+        ),
+        &[admin.clone(), pda_config.clone(), system_program.clone()],
+        &[&[b"config", &[config_bump]]],
+    )?;
+
+    let config = ProgramConfig {
+        discriminator: ProgramConfig::DISCRIMINATOR,
+        is_initialized: true,
+        admin: *admin.key,
+        strict_registry: false,
+        update_cooldown: 0,
+        min_rating: DEFAULT_MIN_RATING,
+        max_rating: DEFAULT_MAX_RATING,
+        report_threshold: 0,
+        review_stake: 0,
+        review_fee: 0,
+        pending_admin: None,
+        paused: false,
+        claim_fee: 0,
+        moderators,
+    };
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    msg!("Program config created: {}", config_pda);
+
+    Ok(())
+}
+
+// Load and validate the program config account
+fn load_config(program_id: &Pubkey, pda_config: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_config.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let config = ProgramConfig::unpack_checked(&pda_config.data.borrow())?;
+    if !config.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    Ok(config)
+}
+
+// Load a review for moderation or archiving, checking it belongs to this program
+fn load_review(program_id: &Pubkey, pda_account: &AccountInfo) -> Result<AccountState, ProgramError> {
+    if pda_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let account_data = AccountState::unpack_checked(&pda_account.data.borrow())?;
+    if !account_data.is_initialized() {
+        msg!("Account is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    review_address(program_id, &account_data, pda_account.key)?;
+    Ok(account_data)
+}
+
+// Handler for reporting a review
+// Any wallet can flag a visible review; it stays Flagged until a
+// moderator resolves it
+pub fn flag_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Flagging review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let flagger = next_account_info(account_info_iter)?;     // Wallet reporting the review (signer)
+    let pda_account = next_account_info(account_info_iter)?; // Review being flagged
+
+    if !flagger.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut account_data = load_review(program_id, pda_account)?;
+    if account_data.status != ReviewStatus::Visible {
+        msg!("Review is already {:?}", account_data.status);
+        return Err(ReviewError::ReviewNotVisible.into());
+    }
+
+    account_data.status = ReviewStatus::Flagged;
+    AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
+
+    msg!("Review flagged by {}", flagger.key);
+
+    Ok(())
+}
+
+// Handler for reporting a review as abusive
+// Each wallet can report a review once; when the reports reach the
+// config's threshold the review is hidden until a moderator resolves it
+pub fn report_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Reporting review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let reporter = next_account_info(account_info_iter)?;       // Wallet reporting the review (signer, pays rent)
+    let pda_account = next_account_info(account_info_iter)?;    // Review being reported
+    let pda_report = next_account_info(account_info_iter)?;     // Report PDA for (review, reporter)
+    let pda_count = next_account_info(account_info_iter)?;      // Report count PDA for the review
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA (may be uninitialized)
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !reporter.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut account_data = load_review(program_id, pda_account)?;
+    if account_data.status == ReviewStatus::Deleted {
+        msg!("Review has been deleted");
+        return Err(ReviewError::ReviewArchived.into());
+    }
+
+    // The report lives at ["report", review_pda, reporter]
+    let (report_pda, report_bump) = Pubkey::find_program_address(
+        &[b"report", pda_account.key.as_ref(), reporter.key.as_ref()],
+        program_id,
+    );
+    if report_pda != *pda_report.key {
+        msg!("Invalid seeds for report PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_report.data_is_empty() {
+        return Err(ReviewError::AlreadyReported.into());
+    }
+
+    let rent = load_rent(rent_sysvar)?;
+    invoke_signed(
+        &system_instruction::create_account(
+            reporter.key,
+            pda_report.key,
+            rent.minimum_balance(ReviewReport::SIZE),
+            ReviewReport::SIZE as u64,
+            program_id,
+        ),
+        &[reporter.clone(), pda_report.clone(), system_program.clone()],
+        &[&[b"report", pda_account.key.as_ref(), reporter.key.as_ref(), &[report_bump]]],
+    )?;
+    let report = ReviewReport {
+        discriminator: ReviewReport::DISCRIMINATOR,
+        is_initialized: true,
+        review: *pda_account.key,
+        reporter: *reporter.key,
+    };
+    report.serialize(&mut &mut pda_report.data.borrow_mut()[..])?;
+
+    // The count lives at ["reports", review_pda] and is created with the
+    // first report
+    let (count_pda, count_bump) =
+        Pubkey::find_program_address(&[b"reports", pda_account.key.as_ref()], program_id);
+    if count_pda != *pda_count.key {
+        msg!("Invalid seeds for report count PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    let mut count = if pda_count.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                reporter.key,
+                pda_count.key,
+                rent.minimum_balance(ReviewReportCount::SIZE),
+                ReviewReportCount::SIZE as u64,
+                program_id,
+            ),
+            &[reporter.clone(), pda_count.clone(), system_program.clone()],
+            &[&[b"reports", pda_account.key.as_ref(), &[count_bump]]],
+        )?;
+        ReviewReportCount {
+            discriminator: ReviewReportCount::DISCRIMINATOR,
+            is_initialized: true,
+            review: *pda_account.key,
+            report_count: 0,
+        }
+    } else {
+        if pda_count.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        ReviewReportCount::unpack_checked(&pda_count.data.borrow())?
+    };
+    count.report_count = count
+        .report_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    count.serialize(&mut &mut pda_count.data.borrow_mut()[..])?;
+    msg!("Review has {} reports", count.report_count);
+
+    // Hide the review once the threshold is reached, unless a moderator
+    // has already hidden it
+    let threshold = load_report_threshold(program_id, pda_config)?;
+    if threshold > 0
+        && count.report_count >= threshold as u64
+        && matches!(account_data.status, ReviewStatus::Visible | ReviewStatus::Flagged)
+    {
+        account_data.status = ReviewStatus::Hidden;
+        msg!("Review hidden after {} reports", count.report_count);
+        AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
+    }
+
+    Ok(())
+}
+
+// Reports that hide a review, 0 if there is no config yet
+fn load_report_threshold(program_id: &Pubkey, pda_config: &AccountInfo) -> Result<u32, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_config.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(load_config(program_id, pda_config)?.report_threshold)
+}
+
+// Lamports a new review must stake, 0 while the config doesn't exist
+fn load_review_stake(program_id: &Pubkey, pda_config: &AccountInfo) -> Result<u64, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_config.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(load_config(program_id, pda_config)?.review_stake)
+}
+
+// Create a review's stake escrow holding `lamports` on top of its rent,
+// all paid by `payer`
+fn create_stake_escrow<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_stake: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+    review: &Pubkey,
+    lamports: u64,
+) -> ProgramResult {
+    let (stake_pda, stake_bump) = Pubkey::find_program_address(&[b"stake", review.as_ref()], program_id);
+    if stake_pda != *pda_stake.key {
+        msg!("Invalid seeds for stake PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_stake.key,
+            rent.minimum_balance(ReviewStake::SIZE)
+                .checked_add(lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            ReviewStake::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), pda_stake.clone(), system_program.clone()],
+        &[&[b"stake", review.as_ref(), &[stake_bump]]],
+    )?;
+
+    let stake = ReviewStake {
+        discriminator: ReviewStake::DISCRIMINATOR,
+        is_initialized: true,
+        review: *review,
+        amount: lamports,
+    };
+    stake.serialize(&mut &mut pda_stake.data.borrow_mut()[..])?;
+    msg!("Staked {} lamports", lamports);
+    Ok(())
+}
+
+// Load a review's stake escrow, or None for a review added without a stake
+fn load_stake_escrow(
+    program_id: &Pubkey,
+    pda_stake: &AccountInfo,
+    review: &Pubkey,
+) -> Result<Option<ReviewStake>, ProgramError> {
+    let (stake_pda, _stake_bump) = Pubkey::find_program_address(&[b"stake", review.as_ref()], program_id);
+    if stake_pda != *pda_stake.key {
+        msg!("Invalid seeds for stake PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_stake.data_is_empty() {
+        return Ok(None);
+    }
+    if pda_stake.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(Some(ReviewStake::unpack_checked(&pda_stake.data.borrow())?))
+}
+
+// Handler for setting the review stake
+pub fn set_review_stake(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    msg!("Setting review stake: {} lamports", lamports);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    // Reviews keep the stake they were added with
+    config.review_stake = lamports;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for slashing a review's stake
+// A moderator judging a review to be spam sends its stake, along with the
+// escrow's rent, to the ["treasury"] PDA, where it counts towards the
+// totals like a removed review's rent; hiding the review is a separate decision
+pub fn slash_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Slashing review stake...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let moderator = next_account_info(account_info_iter)?;      // Admin or moderator (signer)
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA
+    let pda_account = next_account_info(account_info_iter)?;    // Review being slashed
+    let pda_stake = next_account_info(account_info_iter)?;      // Review's stake escrow PDA, closed
+    let pda_treasury = next_account_info(account_info_iter)?;   // Program treasury PDA, receives the stake
+    let system_program = next_account_info(account_info_iter)?; // System program, creates the treasury if missing
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    if !moderator.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if !config.is_moderator(moderator.key) {
+        msg!("{} is not a moderator", moderator.key);
+        return Err(ReviewError::NotModerator.into());
+    }
+
+    load_review(program_id, pda_account)?;
+    let Some(stake) = load_stake_escrow(program_id, pda_stake, pda_account.key)? else {
+        msg!("Review {} was added without a stake", pda_account.key);
+        return Err(ReviewError::NoStake.into());
+    };
+
+    // The moderator covers the treasury's rent if no fee was ever set
+    create_treasury_if_missing(program_id, moderator, pda_treasury, system_program, &load_rent(rent_sysvar)?)?;
+    let mut treasury = load_treasury(program_id, pda_treasury)?;
+    let treasury_before = pda_treasury.lamports();
+
+    close_account(pda_stake, pda_treasury)?;
+
+    let slashed = pda_treasury.lamports() - treasury_before;
+    treasury.total_fees = treasury
+        .total_fees
+        .checked_add(slashed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    treasury.serialize(&mut &mut pda_treasury.data.borrow_mut()[..])?;
+    msg!("Slashed {} lamports ({} staked) to the treasury", slashed, stake.amount);
+
+    Ok(())
+}
+
+// Handler for taking down an abusive review
+// Unlike hiding, this closes the review and its comment counter, and the
+// rent (and any stake) goes to the ["treasury"] PDA rather than back to the
+// author, and counts towards its totals so the admin can withdraw it
+pub fn remove_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Removing review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let moderator = next_account_info(account_info_iter)?;     // Admin or moderator (signer)
+    let pda_config = next_account_info(account_info_iter)?;    // Program config PDA
+    let pda_account = next_account_info(account_info_iter)?;   // Review PDA to close
+    let pda_counter = next_account_info(account_info_iter)?;   // Comment counter PDA to close
+    let pda_aggregate = next_account_info(account_info_iter)?; // Restaurant aggregate PDA
+    let pda_index = next_account_info(account_info_iter)?;     // Author's reviewer index PDA
+    let pda_stake = next_account_info(account_info_iter)?;     // Stake escrow PDA, slashed if the review was staked
+    let pda_treasury = next_account_info(account_info_iter)?;  // Program treasury PDA, receives the rent
+    let system_program = next_account_info(account_info_iter)?; // System program, creates the treasury if missing
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    if !moderator.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if !config.is_moderator(moderator.key) {
+        msg!("{} is not a moderator", moderator.key);
+        return Err(ReviewError::NotModerator.into());
+    }
+
+    // The moderator covers the treasury's rent if no fee was ever set
+    let rent = load_rent(rent_sysvar)?;
+    create_treasury_if_missing(program_id, moderator, pda_treasury, system_program, &rent)?;
+    let mut treasury = load_treasury(program_id, pda_treasury)?;
+    let treasury_before = pda_treasury.lamports();
+
+    let account_data = load_review(program_id, pda_account)?;
+    review_address(program_id, &account_data, pda_account.key)?;
+    let pda = *pda_account.key;
+
+    let (counter_pda, _counter_bump) =
+        Pubkey::find_program_address(&[pda.as_ref(), b"comment"], program_id);
+    if counter_pda != *pda_counter.key {
+        msg!("Invalid seeds for counter PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // Hidden reviews still count towards the rating, removed ones don't;
+    // an archived one was already taken out when it was archived
+    if account_data.status != ReviewStatus::Deleted {
+        let mut aggregate = load_aggregate(program_id, pda_aggregate, &account_data.title)?;
+        aggregate.remove_rating(account_data.rating, account_data.helpfulness(), account_data.created_at)?;
+        aggregate.remove_sub_ratings(account_data.sub_ratings)?;
+        aggregate.serialize(&mut &mut pda_aggregate.data.borrow_mut()[..])?;
+    }
+
+    close_account(pda_account, pda_treasury)?;
+    if pda_counter.owner == program_id {
+        close_account(pda_counter, pda_treasury)?;
+    }
+    if load_stake_escrow(program_id, pda_stake, &pda)?.is_some() {
+        close_account(pda_stake, pda_treasury)?;
+    }
+    remove_from_reviewer_index(program_id, pda_index, pda_treasury, &account_data.author, &pda, &rent)?;
+
+    let reclaimed = pda_treasury.lamports() - treasury_before;
+    treasury.total_fees = treasury
+        .total_fees
+        .checked_add(reclaimed)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    treasury.serialize(&mut &mut pda_treasury.data.borrow_mut()[..])?;
+
+    msg!("Review removed by {}, {} lamports sent to the treasury", moderator.key, reclaimed);
+
+    ReviewRemoved {
+        review: pda,
+        author: account_data.author,
+        moderator: *moderator.key,
+        title: account_data.title,
+    }
+    .emit()?;
+
+    Ok(())
+}
+
+// Fail with ProgramPaused while the admin has paused the program
+// Before the config exists nothing can be paused
+fn check_not_paused(program_id: &Pubkey, pda_config: &AccountInfo) -> ProgramResult {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_config.data_is_empty() && load_config(program_id, pda_config)?.paused {
+        msg!("Program is paused");
+        return Err(ReviewError::ProgramPaused.into());
+    }
+    Ok(())
+}
+
+// Lamports a new review pays into the treasury, 0 while the config doesn't exist
+fn load_review_fee(program_id: &Pubkey, pda_config: &AccountInfo) -> Result<u64, ProgramError> {
+    let (config_pda, _config_bump) = Pubkey::find_program_address(&[b"config"], program_id);
+    if config_pda != *pda_config.key {
+        msg!("Invalid seeds for config PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_config.data_is_empty() {
+        return Ok(0);
+    }
+    Ok(load_config(program_id, pda_config)?.review_fee)
+}
+
+// Load the program treasury, checking its address and type
+fn load_treasury(program_id: &Pubkey, pda_treasury: &AccountInfo) -> Result<ProgramTreasury, ProgramError> {
+    let (treasury_pda, _treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+    if treasury_pda != *pda_treasury.key {
+        msg!("Invalid seeds for treasury PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_treasury.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let treasury = ProgramTreasury::unpack_checked(&pda_treasury.data.borrow())?;
+    if !treasury.is_initialized() {
+        msg!("Treasury is not initialized");
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    Ok(treasury)
+}
+
+// Move a review's fee from `payer` into the treasury
+// SetReviewFee creates the treasury before any fee can be charged
+fn collect_review_fee<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_treasury: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    fee: u64,
+) -> ProgramResult {
+    let mut treasury = load_treasury(program_id, pda_treasury)?;
+
+    // The system program can credit an account it doesn't own, so a plain
+    // transfer works even though the treasury belongs to this program
+    invoke(
+        &system_instruction::transfer(payer.key, pda_treasury.key, fee),
+        &[payer.clone(), pda_treasury.clone(), system_program.clone()],
+    )?;
+
+    treasury.total_fees = treasury
+        .total_fees
+        .checked_add(fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    treasury.serialize(&mut &mut pda_treasury.data.borrow_mut()[..])?;
+    msg!("Paid a fee of {} lamports", fee);
+    Ok(())
+}
+
+// Handler for setting the review fee
+// Creates the treasury the first time, with the admin paying its rent
+pub fn set_review_fee(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    msg!("Setting review fee: {} lamports", lamports);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;          // Config admin (signer, pays the treasury's rent)
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA
+    let pda_treasury = next_account_info(account_info_iter)?;   // Program treasury PDA (created if missing)
+    let system_program = next_account_info(account_info_iter)?; // System program for creating the treasury
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    create_treasury_if_missing(program_id, admin, pda_treasury, system_program, &load_rent(rent_sysvar)?)?;
+
+    config.review_fee = lamports;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for setting the restaurant claim fee
+// Creates the treasury the first time, like SetReviewFee, since approved
+// claims pay their fee into it
+pub fn set_claim_fee(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    msg!("Setting claim fee: {} lamports", lamports);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;          // Config admin (signer, pays the treasury's rent)
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA
+    let pda_treasury = next_account_info(account_info_iter)?;   // Program treasury PDA (created if missing)
+    let system_program = next_account_info(account_info_iter)?; // System program for creating the treasury
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    create_treasury_if_missing(program_id, admin, pda_treasury, system_program, &load_rent(rent_sysvar)?)?;
+
+    config.claim_fee = lamports;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Create the program treasury at ["treasury"] unless it already exists,
+// with `payer` covering its rent
+fn create_treasury_if_missing<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_treasury: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    rent: &Rent,
+) -> ProgramResult {
+    let (treasury_pda, treasury_bump) = Pubkey::find_program_address(&[b"treasury"], program_id);
+    if treasury_pda != *pda_treasury.key {
+        msg!("Invalid seeds for treasury PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_treasury.data_is_empty() {
+        return Ok(());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_treasury.key,
+            rent.minimum_balance(ProgramTreasury::SIZE),
+            ProgramTreasury::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), pda_treasury.clone(), system_program.clone()],
+        &[&[b"treasury", &[treasury_bump]]],
+    )?;
+
+    let treasury = ProgramTreasury {
+        discriminator: ProgramTreasury::DISCRIMINATOR,
+        is_initialized: true,
+        total_fees: 0,
+        total_withdrawn: 0,
+    };
+    treasury.serialize(&mut &mut pda_treasury.data.borrow_mut()[..])?;
+    msg!("Treasury created: {}", treasury_pda);
+    Ok(())
+}
+
+// Handler for withdrawing collected fees
+// Only the admin can withdraw, and never below the treasury's rent, so the
+// treasury keeps collecting afterwards
+pub fn withdraw_treasury(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    msg!("Withdrawing {} lamports from the treasury", lamports);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;        // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?;   // Program config PDA
+    let pda_treasury = next_account_info(account_info_iter)?; // Program treasury PDA
+    let destination = next_account_info(account_info_iter)?;  // Receives the withdrawal
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    let mut treasury = load_treasury(program_id, pda_treasury)?;
+    let available = pda_treasury
+        .lamports()
+        .saturating_sub(load_rent(rent_sysvar)?.minimum_balance(ProgramTreasury::SIZE));
+    if lamports > available {
+        msg!("Treasury holds {} lamports above its rent", available);
+        return Err(ReviewError::InsufficientTreasuryFunds.into());
+    }
+
+    **pda_treasury.lamports.borrow_mut() -= lamports;
+    **destination.lamports.borrow_mut() = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    treasury.total_withdrawn = treasury
+        .total_withdrawn
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    treasury.serialize(&mut &mut pda_treasury.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for nominating the next admin
+// The admin role only moves once the nominee signs AcceptAdmin, so a
+// mistyped key can't lock everyone out; nominating again replaces the
+// earlier nominee
+pub fn nominate_admin(program_id: &Pubkey, accounts: &[AccountInfo], new_admin: Pubkey) -> ProgramResult {
+    msg!("Nominating admin: {}", new_admin);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    config.pending_admin = Some(new_admin);
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for accepting the admin role
+// Only the nominated wallet can accept; the old admin loses the role
+pub fn accept_admin(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Accepting admin role...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let new_admin = next_account_info(account_info_iter)?;  // Nominated admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+
+    if !new_admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.pending_admin != Some(*new_admin.key) {
+        msg!("{} was not nominated as admin", new_admin.key);
+        return Err(ReviewError::NotPendingAdmin.into());
+    }
+
+    msg!("Admin changed from {} to {}", config.admin, new_admin.key);
+    config.admin = *new_admin.key;
+    config.pending_admin = None;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for pausing or unpausing the program
+// A circuit breaker for incidents: while paused no review or comment can be
+// added, edited, or deleted; admin and moderation instructions still work
+pub fn set_paused(program_id: &Pubkey, accounts: &[AccountInfo], paused: bool) -> ProgramResult {
+    msg!("Setting paused: {}", paused);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    config.paused = paused;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for setting the report threshold
+pub fn set_report_threshold(program_id: &Pubkey, accounts: &[AccountInfo], reports: u32) -> ProgramResult {
+    msg!("Setting report threshold: {}", reports);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    config.report_threshold = reports;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for a moderator's decision on a review
+// Hides the review or makes it visible again, unless its owner archived it
+pub fn resolve_flag(program_id: &Pubkey, accounts: &[AccountInfo], hide: bool) -> ProgramResult {
+    msg!("Resolving flag, hide: {}", hide);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let moderator = next_account_info(account_info_iter)?;   // Admin or moderator (signer)
+    let pda_config = next_account_info(account_info_iter)?;  // Program config PDA
+    let pda_account = next_account_info(account_info_iter)?; // Review being moderated
+
+    if !moderator.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if !config.is_moderator(moderator.key) {
+        msg!("{} is not a moderator", moderator.key);
+        return Err(ReviewError::NotModerator.into());
+    }
+
+    let mut account_data = load_review(program_id, pda_account)?;
+    // Only the owner decides about an archived review
+    if account_data.status == ReviewStatus::Deleted {
+        msg!("Review has been deleted");
+        return Err(ReviewError::ReviewArchived.into());
+    }
+
+    account_data.status = if hide {
+        ReviewStatus::Hidden
+    } else {
+        ReviewStatus::Visible
+    };
+    msg!("Review is now {:?}", account_data.status);
+    AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Handler for soft-deleting a review
+// The owner marks the review Deleted instead of closing it, so the account
+// (and its history) stays readable; it stops counting towards the
+// restaurant's rating and can no longer be edited or moderated
+pub fn archive_review(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Archiving review...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;         // Review owner (signer)
+    let pda_account = next_account_info(account_info_iter)?;   // Review being archived
+    let pda_aggregate = next_account_info(account_info_iter)?; // Restaurant aggregate PDA
+    let pda_config = next_account_info(account_info_iter)?;    // Program config PDA (may be uninitialized)
+
+    check_not_paused(program_id, pda_config)?;
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut account_data = load_review(program_id, pda_account)?;
+    if account_data.owner != *owner.key {
+        msg!("Signer is not the owner of this review");
+        return Err(ReviewError::NotReviewOwner.into());
+    }
+    if account_data.status == ReviewStatus::Deleted {
+        msg!("Review has already been deleted");
+        return Err(ReviewError::ReviewArchived.into());
+    }
+
+    let mut aggregate = load_aggregate(program_id, pda_aggregate, &account_data.title)?;
+    aggregate.remove_rating(account_data.rating, account_data.helpfulness(), account_data.created_at)?;
+    aggregate.remove_sub_ratings(account_data.sub_ratings)?;
+    aggregate.serialize(&mut &mut pda_aggregate.data.borrow_mut()[..])?;
+
+    account_data.status = ReviewStatus::Deleted;
+    AccountState::pack(account_data, &mut pda_account.data.borrow_mut())?;
+
+    msg!("Review archived by {}", owner.key);
+
+    Ok(())
+}
+
+// Handler for registering a restaurant's verified owner
+// Only the config admin can do this; calling it again for the same
+// restaurant replaces the owner, e.g. after the restaurant is sold
+pub fn verify_restaurant_owner(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    title: String,
+    owner: Pubkey,
+) -> ProgramResult {
+    msg!("Verifying restaurant owner...");
+    debug_msg!("Owner {} of: {}", owner, title);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;          // Config admin (signer, pays rent)
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA
+    let pda_owner = next_account_info(account_info_iter)?;      // Verified owner PDA for the restaurant
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    if title.len() > MAX_TITLE_LEN {
+        msg!("Title is {} bytes, maximum is {}", title.len(), MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    let title_graphemes = grapheme_count(&title);
+    if title_graphemes > MAX_TITLE_GRAPHEMES {
+        msg!("Title is {} characters, maximum is {}", title_graphemes, MAX_TITLE_GRAPHEMES);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    validate_text("Title", &title)?;
+
+    let normalized = normalize_title(&title);
+    let (owner_pda, owner_bump) =
+        Pubkey::find_program_address(&[b"verified_owner", &title_seed(&normalized)], program_id);
+    if owner_pda != *pda_owner.key {
+        msg!("Invalid seeds for verified owner PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if pda_owner.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                admin.key,
+                pda_owner.key,
+                load_rent(rent_sysvar)?.minimum_balance(VerifiedOwner::SIZE),
+                VerifiedOwner::SIZE as u64,
+                program_id,
+            ),
+            &[admin.clone(), pda_owner.clone(), system_program.clone()],
+            &[&[b"verified_owner", &title_seed(&normalized), &[owner_bump]]],
+        )?;
+        debug_msg!("Created verified owner account for: {}", normalized);
+    } else {
+        if pda_owner.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        VerifiedOwner::unpack_checked(&pda_owner.data.borrow())?;
+    }
+
+    let record = VerifiedOwner {
+        discriminator: VerifiedOwner::DISCRIMINATOR,
+        is_initialized: true,
+        owner,
+    };
+    record.serialize(&mut &mut pda_owner.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for claiming a registered restaurant
+// Escrows the configured claim fee in the restaurant's claim PDA until the
+// admin approves or rejects it; a restaurant that already has a verified
+// owner or a pending claim can't be claimed
+pub fn claim_restaurant(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Claiming restaurant...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let claimant = next_account_info(account_info_iter)?;       // Wallet claiming (signer, pays rent and the fee)
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant registry PDA
+    let pda_owner = next_account_info(account_info_iter)?;      // Verified owner PDA for the restaurant
+    let pda_claim = next_account_info(account_info_iter)?;      // Claim PDA for the restaurant
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !claimant.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let fee = load_config(program_id, pda_config)?.claim_fee;
+
+    if pda_restaurant.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let restaurant = Restaurant::unpack_checked(&pda_restaurant.data.borrow())?;
+    if !restaurant.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    let seed = title_seed(&normalize_title(&restaurant.name));
+
+    let (owner_pda, _owner_bump) = Pubkey::find_program_address(&[b"verified_owner", &seed], program_id);
+    if owner_pda != *pda_owner.key {
+        msg!("Invalid seeds for verified owner PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_owner.data_is_empty() {
+        msg!("{} already has a verified owner", restaurant.name);
+        return Err(ReviewError::RestaurantAlreadyVerified.into());
+    }
+
+    let (claim_pda, claim_bump) = Pubkey::find_program_address(&[b"claim", &seed], program_id);
+    if claim_pda != *pda_claim.key {
+        msg!("Invalid seeds for claim PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if pda_claim.data_is_empty() {
+        // The fee rides along with the rent, so one transfer escrows both
+        let lamports = load_rent(rent_sysvar)?
+            .minimum_balance(RestaurantClaim::SIZE)
+            .checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        invoke_signed(
+            &system_instruction::create_account(
+                claimant.key,
+                pda_claim.key,
+                lamports,
+                RestaurantClaim::SIZE as u64,
+                program_id,
+            ),
+            &[claimant.clone(), pda_claim.clone(), system_program.clone()],
+            &[&[b"claim", &seed, &[claim_bump]]],
+        )?;
+    } else {
+        // Only a rejected claim can be replaced
+        if pda_claim.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        let previous = RestaurantClaim::unpack_checked(&pda_claim.data.borrow())?;
+        if previous.status != ClaimStatus::Rejected {
+            msg!("{} already has a {:?} claim", restaurant.name, previous.status);
+            return Err(ReviewError::ClaimPending.into());
+        }
+        if fee > 0 {
+            invoke(
+                &system_instruction::transfer(claimant.key, pda_claim.key, fee),
+                &[claimant.clone(), pda_claim.clone(), system_program.clone()],
+            )?;
+        }
+    }
+
+    let claim = RestaurantClaim {
+        discriminator: RestaurantClaim::DISCRIMINATOR,
+        is_initialized: true,
+        restaurant: *pda_restaurant.key,
+        claimant: *claimant.key,
+        fee,
+        status: ClaimStatus::Pending,
+    };
+    claim.serialize(&mut &mut pda_claim.data.borrow_mut()[..])?;
+
+    msg!("{} claimed {} with a fee of {} lamports", claimant.key, restaurant.name, fee);
+
+    Ok(())
+}
+
+// Load a restaurant's claim and check it is still pending
+fn load_pending_claim(program_id: &Pubkey, pda_claim: &AccountInfo) -> Result<RestaurantClaim, ProgramError> {
+    if pda_claim.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let claim = RestaurantClaim::unpack_checked(&pda_claim.data.borrow())?;
+    if !claim.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    if claim.status != ClaimStatus::Pending {
+        msg!("Claim is {:?}", claim.status);
+        return Err(ReviewError::ClaimNotPending.into());
+    }
+    Ok(claim)
+}
+
+// Handler for approving a restaurant claim
+// The claimant becomes the restaurant's registered and verified owner, and
+// the escrowed fee moves into the treasury
+pub fn approve_claim(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Approving restaurant claim...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;          // Config admin (signer, pays rent)
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant registry PDA
+    let pda_owner = next_account_info(account_info_iter)?;      // Verified owner PDA to create
+    let pda_claim = next_account_info(account_info_iter)?;      // Claim PDA for the restaurant
+    let pda_treasury = next_account_info(account_info_iter)?;   // Program treasury PDA, receives the fee
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    let mut claim = load_pending_claim(program_id, pda_claim)?;
+    if claim.restaurant != *pda_restaurant.key {
+        msg!("Claim is for another restaurant");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_restaurant.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut restaurant = Restaurant::unpack_checked(&pda_restaurant.data.borrow())?;
+    let seed = title_seed(&normalize_title(&restaurant.name));
+
+    let (claim_pda, _claim_bump) = Pubkey::find_program_address(&[b"claim", &seed], program_id);
+    if claim_pda != *pda_claim.key {
+        msg!("Invalid seeds for claim PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    // The owner may have been verified directly since the claim was made
+    let (owner_pda, owner_bump) = Pubkey::find_program_address(&[b"verified_owner", &seed], program_id);
+    if owner_pda != *pda_owner.key {
+        msg!("Invalid seeds for verified owner PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_owner.data_is_empty() {
+        msg!("{} already has a verified owner", restaurant.name);
+        return Err(ReviewError::RestaurantAlreadyVerified.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_owner.key,
+            load_rent(rent_sysvar)?.minimum_balance(VerifiedOwner::SIZE),
+            VerifiedOwner::SIZE as u64,
+            program_id,
+        ),
+        &[admin.clone(), pda_owner.clone(), system_program.clone()],
+        &[&[b"verified_owner", &seed, &[owner_bump]]],
+    )?;
+    let record = VerifiedOwner {
+        discriminator: VerifiedOwner::DISCRIMINATOR,
+        is_initialized: true,
+        owner: claim.claimant,
+    };
+    record.serialize(&mut &mut pda_owner.data.borrow_mut()[..])?;
+
+    restaurant.owner = claim.claimant;
+    restaurant.serialize(&mut &mut pda_restaurant.data.borrow_mut()[..])?;
+
+    // Both accounts belong to this program, so the fee moves directly
+    if claim.fee > 0 {
+        let mut treasury = load_treasury(program_id, pda_treasury)?;
+        **pda_claim.lamports.borrow_mut() -= claim.fee;
+        **pda_treasury.lamports.borrow_mut() = pda_treasury
+            .lamports()
+            .checked_add(claim.fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        treasury.total_fees = treasury
+            .total_fees
+            .checked_add(claim.fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        treasury.serialize(&mut &mut pda_treasury.data.borrow_mut()[..])?;
+    }
+
+    claim.status = ClaimStatus::Approved;
+    claim.serialize(&mut &mut pda_claim.data.borrow_mut()[..])?;
+
+    msg!("{} is now the owner of {}", claim.claimant, restaurant.name);
+
+    Ok(())
+}
+
+// Handler for rejecting a restaurant claim
+// Refunds the escrowed fee to the claimant; the claim account stays as a
+// record and is reused by the restaurant's next claim
+pub fn reject_claim(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Rejecting restaurant claim...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+    let pda_claim = next_account_info(account_info_iter)?;  // Claim PDA for the restaurant
+    let claimant = next_account_info(account_info_iter)?;   // Claimant, receives the refund
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    let mut claim = load_pending_claim(program_id, pda_claim)?;
+    if claim.claimant != *claimant.key {
+        msg!("Refund must go to the claimant {}", claim.claimant);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    **pda_claim.lamports.borrow_mut() -= claim.fee;
+    **claimant.lamports.borrow_mut() = claimant
+        .lamports()
+        .checked_add(claim.fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    claim.status = ClaimStatus::Rejected;
+    claim.serialize(&mut &mut pda_claim.data.borrow_mut()[..])?;
+
+    msg!("Claim by {} rejected, {} lamports refunded", claim.claimant, claim.fee);
+
+    Ok(())
+}
+
+// Handler for a restaurant owner's reply to a review
+// The signer must be the verified owner registered for the review's
+// restaurant; a later reply replaces the earlier one
+pub fn respond_to_review(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    response: String,
+) -> ProgramResult {
+    msg!("Responding to review...");
+    debug_msg!("Response: {}", response);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let responder = next_account_info(account_info_iter)?;      // Restaurant owner (signer, pays rent)
+    let pda_review = next_account_info(account_info_iter)?;     // Review being answered
+    let pda_owner = next_account_info(account_info_iter)?;      // Verified owner PDA for the review's restaurant
+    let pda_response = next_account_info(account_info_iter)?;   // Response PDA for the review
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !responder.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if response.len() > MAX_RESPONSE_LEN {
+        msg!("Response is {} bytes, maximum is {}", response.len(), MAX_RESPONSE_LEN);
+        return Err(ReviewError::ResponseTooLong.into());
+    }
+
+    let review = load_review(program_id, pda_review)?;
+
+    // The registry entry must be the one for this review's restaurant
+    let (owner_pda, _owner_bump) = Pubkey::find_program_address(
+        &[b"verified_owner", &title_seed(&normalize_title(&review.title))],
+        program_id,
+    );
+    if owner_pda != *pda_owner.key {
+        msg!("Invalid seeds for verified owner PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_owner.data_is_empty() {
+        msg!("Restaurant has no verified owner");
+        return Err(ReviewError::NotRestaurantOwner.into());
+    }
+    if pda_owner.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let verified = VerifiedOwner::unpack_checked(&pda_owner.data.borrow())?;
+    if !verified.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    if verified.owner != *responder.key {
+        msg!("{} is not the verified owner of this restaurant", responder.key);
+        return Err(ReviewError::NotRestaurantOwner.into());
+    }
+
+    let (response_pda, response_bump) =
+        Pubkey::find_program_address(&[b"response", pda_review.key.as_ref()], program_id);
+    if response_pda != *pda_response.key {
+        msg!("Invalid seeds for response PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if pda_response.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                responder.key,
+                pda_response.key,
+                load_rent(rent_sysvar)?.minimum_balance(ReviewResponse::SIZE),
+                ReviewResponse::SIZE as u64,
+                program_id,
+            ),
+            &[responder.clone(), pda_response.clone(), system_program.clone()],
+            &[&[b"response", pda_review.key.as_ref(), &[response_bump]]],
+        )?;
+        msg!("Created response account");
+    } else {
+        if pda_response.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        ReviewResponse::unpack_checked(&pda_response.data.borrow())?;
+    }
+
+    let response_data = ReviewResponse {
+        discriminator: ReviewResponse::DISCRIMINATOR,
+        is_initialized: true,
+        review: *pda_review.key,
+        responder: *responder.key,
+        response,
+    };
+    response_data.serialize(&mut &mut pda_response.data.borrow_mut()[..])?;
+
+    msg!("Response saved");
+
+    Ok(())
+}
+
+// Handler for adding a restaurant to the registry
+// Only the config admin can register restaurants, and each normalized name
+// can be registered once
+pub fn register_restaurant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    location: String,
+    owner: Pubkey,
+) -> ProgramResult {
+    msg!("Registering restaurant...");
+    debug_msg!("Name: {}", name);
+    debug_msg!("Location: {}", location);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;          // Config admin (signer, pays rent)
+    let pda_config = next_account_info(account_info_iter)?;     // Program config PDA
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant PDA to create
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    if name.len() > MAX_TITLE_LEN {
+        msg!("Name is {} bytes, maximum is {}", name.len(), MAX_TITLE_LEN);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    let name_graphemes = grapheme_count(&name);
+    if name_graphemes > MAX_TITLE_GRAPHEMES {
+        msg!("Name is {} characters, maximum is {}", name_graphemes, MAX_TITLE_GRAPHEMES);
+        return Err(ReviewError::TitleTooLong.into());
+    }
+    validate_text("Name", &name)?;
+    if location.len() > MAX_LOCATION_LEN {
+        msg!("Location is {} bytes, maximum is {}", location.len(), MAX_LOCATION_LEN);
+        return Err(ReviewError::LocationTooLong.into());
+    }
+
+    let normalized = normalize_title(&name);
+    let (restaurant_pda, restaurant_bump) =
+        Pubkey::find_program_address(&[b"restaurant", &title_seed(&normalized)], program_id);
+    if restaurant_pda != *pda_restaurant.key {
+        msg!("Invalid seeds for restaurant PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_restaurant.data_is_empty() {
+        return Err(ReviewError::AlreadyInitialized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin.key,
+            pda_restaurant.key,
+            load_rent(rent_sysvar)?.minimum_balance(Restaurant::SIZE),
+            Restaurant::SIZE as u64,
+            program_id,
+        ),
+        &[admin.clone(), pda_restaurant.clone(), system_program.clone()],
+        &[&[b"restaurant", &title_seed(&normalized), &[restaurant_bump]]],
+    )?;
+
+    let restaurant = Restaurant {
+        discriminator: Restaurant::DISCRIMINATOR,
+        is_initialized: true,
+        owner,
+        name,
+        location,
+    };
+    restaurant.serialize(&mut &mut pda_restaurant.data.borrow_mut()[..])?;
+
+    msg!("Restaurant registered: {}", restaurant_pda);
+
+    Ok(())
+}
+
+// Handler for creating a restaurant's directory profile
+// Only the wallet the restaurant is registered to can create it, once
+pub fn create_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cuisine: String,
+    address: String,
+    website: String,
+    price_range: u8,
+) -> ProgramResult {
+    msg!("Creating restaurant profile...");
+    debug_msg!("Cuisine: {}", cuisine);
+    debug_msg!("Address: {}", address);
+    debug_msg!("Website: {}", website);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;          // Registered owner (signer, pays rent)
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant registry PDA
+    let pda_profile = next_account_info(account_info_iter)?;    // Profile PDA to create
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    load_owned_restaurant(program_id, pda_restaurant, owner)?;
+    validate_profile(&cuisine, &address, &website, price_range)?;
+
+    // The profile lives at ["profile", restaurant_pda]
+    let (profile_pda, profile_bump) =
+        Pubkey::find_program_address(&[b"profile", pda_restaurant.key.as_ref()], program_id);
+    if profile_pda != *pda_profile.key {
+        msg!("Invalid seeds for profile PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_profile.data_is_empty() {
+        return Err(ReviewError::AlreadyInitialized.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            owner.key,
+            pda_profile.key,
+            load_rent(rent_sysvar)?.minimum_balance(RestaurantProfile::SIZE),
+            RestaurantProfile::SIZE as u64,
+            program_id,
+        ),
+        &[owner.clone(), pda_profile.clone(), system_program.clone()],
+        &[&[b"profile", pda_restaurant.key.as_ref(), &[profile_bump]]],
+    )?;
+
+    let profile = RestaurantProfile {
+        discriminator: RestaurantProfile::DISCRIMINATOR,
+        is_initialized: true,
+        restaurant: *pda_restaurant.key,
+        price_range,
+        cuisine,
+        address,
+        website,
+    };
+    profile.serialize(&mut &mut pda_profile.data.borrow_mut()[..])?;
+
+    msg!("Restaurant profile created: {}", profile_pda);
+
+    Ok(())
+}
+
+// Handler for updating a restaurant's directory profile
+// Every field is replaced; the account is created at full size, so any
+// valid profile fits
+pub fn update_profile(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cuisine: String,
+    address: String,
+    website: String,
+    price_range: u8,
+) -> ProgramResult {
+    msg!("Updating restaurant profile...");
+    debug_msg!("Cuisine: {}", cuisine);
+    debug_msg!("Address: {}", address);
+    debug_msg!("Website: {}", website);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;          // Registered owner (signer)
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant registry PDA
+    let pda_profile = next_account_info(account_info_iter)?;    // Profile PDA to update
+
+    load_owned_restaurant(program_id, pda_restaurant, owner)?;
+    validate_profile(&cuisine, &address, &website, price_range)?;
+
+    let (profile_pda, _profile_bump) =
+        Pubkey::find_program_address(&[b"profile", pda_restaurant.key.as_ref()], program_id);
+    if profile_pda != *pda_profile.key {
+        msg!("Invalid seeds for profile PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_profile.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let mut profile = RestaurantProfile::unpack_checked(&pda_profile.data.borrow())?;
+    if !profile.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    profile.cuisine = cuisine;
+    profile.address = address;
+    profile.website = website;
+    profile.price_range = price_range;
+    profile.serialize(&mut &mut pda_profile.data.borrow_mut()[..])?;
+
+    msg!("Restaurant profile updated");
+
+    Ok(())
+}
+
+// Load a registry entry and check `owner` signed and is who it is
+// registered to
+fn load_owned_restaurant(
+    program_id: &Pubkey,
+    pda_restaurant: &AccountInfo,
+    owner: &AccountInfo,
+) -> Result<Restaurant, ProgramError> {
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let restaurant = load_restaurant(program_id, pda_restaurant)?;
+    if restaurant.owner != *owner.key {
+        msg!("{} is not the registered owner of this restaurant", owner.key);
+        return Err(ReviewError::NotRestaurantOwner.into());
+    }
+    Ok(restaurant)
+}
+
+// Registry PDA of a restaurant: ["restaurant", title_seed(normalize_title(name))]
+fn restaurant_address(program_id: &Pubkey, name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[b"restaurant", &title_seed(&normalize_title(name))], program_id).0
+}
+
+// Load a registry entry, which must exist
+fn load_restaurant(program_id: &Pubkey, pda_restaurant: &AccountInfo) -> Result<Restaurant, ProgramError> {
+    if pda_restaurant.data_is_empty() {
+        msg!("{} is not a registered restaurant", pda_restaurant.key);
+        return Err(ReviewError::RestaurantNotRegistered.into());
+    }
+    if pda_restaurant.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let restaurant = Restaurant::unpack_checked(&pda_restaurant.data.borrow())?;
+    if !restaurant.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // Re-derive the PDA from the stored name
+    if restaurant_address(program_id, &restaurant.name) != *pda_restaurant.key {
+        msg!("Invalid seeds for restaurant PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    Ok(restaurant)
+}
+
+// Profile fields must fit the account, which is sized for the maximums
+fn validate_profile(cuisine: &str, address: &str, website: &str, price_range: u8) -> ProgramResult {
+    if cuisine.len() > MAX_CUISINE_LEN || website.len() > MAX_WEBSITE_LEN {
+        msg!(
+            "Cuisine is at most {} bytes, website at most {}",
+            MAX_CUISINE_LEN,
+            MAX_WEBSITE_LEN
+        );
+        return Err(ReviewError::ProfileFieldTooLong.into());
+    }
+    if address.len() > MAX_LOCATION_LEN {
+        msg!("Address is {} bytes, maximum is {}", address.len(), MAX_LOCATION_LEN);
+        return Err(ReviewError::LocationTooLong.into());
+    }
+    if !(1..=MAX_PRICE_RANGE).contains(&price_range) {
+        return Err(ReviewError::InvalidPriceRange.into());
+    }
+    Ok(())
+}
+
+// Handler for setting a reviewer's display name
+// Creates the reviewer's profile on first use, then overwrites the name;
+// the account is created at full size, so any valid name fits
+pub fn set_display_name(program_id: &Pubkey, accounts: &[AccountInfo], name: String) -> ProgramResult {
+    msg!("Setting display name...");
+    debug_msg!("Display name: {}", name);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let reviewer = next_account_info(account_info_iter)?;       // Reviewer (signer, pays rent on first use)
+    let pda_profile = next_account_info(account_info_iter)?;    // Reviewer profile PDA
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !reviewer.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    validate_display_name(&name)?;
+
+    let rent = load_rent(rent_sysvar)?;
+    let mut profile =
+        load_or_create_reviewer_profile(program_id, reviewer, pda_profile, system_program, reviewer.key, &rent)?;
+    profile.display_name = name;
+    profile.serialize(&mut &mut pda_profile.data.borrow_mut()[..])?;
+
+    msg!("Display name set for {}", reviewer.key);
+
+    Ok(())
+}
+
+// Load `reviewer`'s profile, creating it with no display name and no
+// followers if this is the first time it is needed; `payer` covers the rent
+fn load_or_create_reviewer_profile<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pda_profile: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    reviewer: &Pubkey,
+    rent: &Rent,
+) -> Result<ReviewerProfile, ProgramError> {
+    // The profile lives at ["reviewer_profile", reviewer]
+    let (profile_pda, profile_bump) =
+        Pubkey::find_program_address(&[b"reviewer_profile", reviewer.as_ref()], program_id);
+    if profile_pda != *pda_profile.key {
+        msg!("Invalid seeds for reviewer profile PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    if !pda_profile.data_is_empty() {
+        return load_reviewer_profile(program_id, pda_profile);
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pda_profile.key,
+            rent.minimum_balance(ReviewerProfile::SIZE),
+            ReviewerProfile::SIZE as u64,
+            program_id,
+        ),
+        &[payer.clone(), pda_profile.clone(), system_program.clone()],
+        &[&[b"reviewer_profile", reviewer.as_ref(), &[profile_bump]]],
+    )?;
+    Ok(ReviewerProfile {
+        discriminator: ReviewerProfile::DISCRIMINATOR,
+        is_initialized: true,
+        reviewer: *reviewer,
+        follower_count: 0,
+        following_count: 0,
+        display_name: String::new(),
+    })
+}
+
+// Load an existing reviewer profile
+// The caller checks the address, or that the profile belongs to the
+// wallet it expects
+fn load_reviewer_profile(program_id: &Pubkey, pda_profile: &AccountInfo) -> Result<ReviewerProfile, ProgramError> {
+    if pda_profile.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let profile = ReviewerProfile::unpack_checked(&pda_profile.data.borrow())?;
+    if !profile.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+    Ok(profile)
+}
+
+// Handler for following a reviewer
+// Creates the follow PDA and bumps both wallets' profile counters, creating
+// either profile if it doesn't exist yet; the follower pays for all of it
+pub fn follow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Following reviewer...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let follower = next_account_info(account_info_iter)?;         // Wallet following (signer, pays rent)
+    let followee = next_account_info(account_info_iter)?;         // Reviewer to follow
+    let pda_follow = next_account_info(account_info_iter)?;       // Follow PDA for (follower, followee)
+    let pda_follower_profile = next_account_info(account_info_iter)?; // Follower's reviewer profile PDA
+    let pda_followee_profile = next_account_info(account_info_iter)?; // Followee's reviewer profile PDA
+    let system_program = next_account_info(account_info_iter)?;   // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok();  // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !follower.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if follower.key == followee.key {
+        return Err(ReviewError::CannotFollowSelf.into());
+    }
+
+    // The follow lives at ["follow", follower, followee]
+    let (follow_pda, follow_bump) = Pubkey::find_program_address(
+        &[b"follow", follower.key.as_ref(), followee.key.as_ref()],
+        program_id,
+    );
+    if follow_pda != *pda_follow.key {
+        msg!("Invalid seeds for follow PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if !pda_follow.data_is_empty() {
+        msg!("{} already follows {}", follower.key, followee.key);
+        return Err(ReviewError::AlreadyFollowing.into());
+    }
+
+    let rent = load_rent(rent_sysvar)?;
+    invoke_signed(
+        &system_instruction::create_account(
+            follower.key,
+            pda_follow.key,
+            rent.minimum_balance(ReviewerFollow::SIZE),
+            ReviewerFollow::SIZE as u64,
+            program_id,
+        ),
+        &[follower.clone(), pda_follow.clone(), system_program.clone()],
+        &[&[b"follow", follower.key.as_ref(), followee.key.as_ref(), &[follow_bump]]],
+    )?;
+    let follow = ReviewerFollow {
+        discriminator: ReviewerFollow::DISCRIMINATOR,
+        is_initialized: true,
+        follower: *follower.key,
+        followee: *followee.key,
+    };
+    follow.serialize(&mut &mut pda_follow.data.borrow_mut()[..])?;
+
+    let mut follower_profile =
+        load_or_create_reviewer_profile(program_id, follower, pda_follower_profile, system_program, follower.key, &rent)?;
+    follower_profile.following_count = follower_profile
+        .following_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    follower_profile.serialize(&mut &mut pda_follower_profile.data.borrow_mut()[..])?;
+
+    let mut followee_profile =
+        load_or_create_reviewer_profile(program_id, follower, pda_followee_profile, system_program, followee.key, &rent)?;
+    followee_profile.follower_count = followee_profile
+        .follower_count
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    followee_profile.serialize(&mut &mut pda_followee_profile.data.borrow_mut()[..])?;
+
+    msg!("{} now follows {} ({} followers)", follower.key, followee.key, followee_profile.follower_count);
+
+    Ok(())
+}
+
+// Handler for unfollowing a reviewer
+// Closes the follow PDA, returning its rent to the follower, and drops
+// both profile counters
+pub fn unfollow(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Unfollowing reviewer...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let follower = next_account_info(account_info_iter)?;         // Wallet unfollowing (signer, gets the rent back)
+    let followee = next_account_info(account_info_iter)?;         // Reviewer to unfollow
+    let pda_follow = next_account_info(account_info_iter)?;       // Follow PDA for (follower, followee)
+    let pda_follower_profile = next_account_info(account_info_iter)?; // Follower's reviewer profile PDA
+    let pda_followee_profile = next_account_info(account_info_iter)?; // Followee's reviewer profile PDA
+
+    if !follower.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (follow_pda, _follow_bump) = Pubkey::find_program_address(
+        &[b"follow", follower.key.as_ref(), followee.key.as_ref()],
+        program_id,
+    );
+    if follow_pda != *pda_follow.key {
+        msg!("Invalid seeds for follow PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_follow.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let follow = ReviewerFollow::unpack_checked(&pda_follow.data.borrow())?;
+    if !follow.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // Both profiles were created by the follow
+    let mut follower_profile = load_reviewer_profile(program_id, pda_follower_profile)?;
+    let mut followee_profile = load_reviewer_profile(program_id, pda_followee_profile)?;
+    if follower_profile.reviewer != *follower.key || followee_profile.reviewer != *followee.key {
+        msg!("Reviewer profiles don't belong to the follower and followee");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    follower_profile.following_count = follower_profile
+        .following_count
+        .checked_sub(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    followee_profile.follower_count = followee_profile
+        .follower_count
+        .checked_sub(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    follower_profile.serialize(&mut &mut pda_follower_profile.data.borrow_mut()[..])?;
+    followee_profile.serialize(&mut &mut pda_followee_profile.data.borrow_mut()[..])?;
+
+    close_account(pda_follow, follower)?;
+
+    msg!("{} no longer follows {}", follower.key, followee.key);
+
+    Ok(())
+}
+
+// Handler for bookmarking a restaurant
+// Creates the wallet's bookmark list with its first entry and grows it by
+// one entry per bookmark, with the wallet paying the extra rent
+pub fn bookmark_restaurant(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Bookmarking restaurant...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;          // Wallet bookmarking (signer, pays rent)
+    let pda_restaurant = next_account_info(account_info_iter)?; // Restaurant registry PDA
+    let pda_bookmarks = next_account_info(account_info_iter)?;  // Owner's bookmark list PDA
+    let system_program = next_account_info(account_info_iter)?; // System program for account creation
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    check_system_program(system_program)?;
+
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Only restaurants in the registry can be bookmarked
+    if pda_restaurant.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let restaurant = Restaurant::unpack_checked(&pda_restaurant.data.borrow())?;
+    if !restaurant.is_initialized() {
+        return Err(ReviewError::UninitializedAccount.into());
+    }
+
+    // The list lives at ["bookmarks", owner]
+    let (bookmarks_pda, bookmarks_bump) =
+        Pubkey::find_program_address(&[b"bookmarks", owner.key.as_ref()], program_id);
+    if bookmarks_pda != *pda_bookmarks.key {
+        msg!("Invalid seeds for bookmarks PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+
+    let rent = load_rent(rent_sysvar)?;
+    let mut bookmarks = if pda_bookmarks.data_is_empty() {
+        invoke_signed(
+            &system_instruction::create_account(
+                owner.key,
+                pda_bookmarks.key,
+                rent.minimum_balance(RestaurantBookmarks::size_for(0)),
+                RestaurantBookmarks::size_for(0) as u64,
+                program_id,
+            ),
+            &[owner.clone(), pda_bookmarks.clone(), system_program.clone()],
+            &[&[b"bookmarks", owner.key.as_ref(), &[bookmarks_bump]]],
+        )?;
+        RestaurantBookmarks {
+            discriminator: RestaurantBookmarks::DISCRIMINATOR,
+            is_initialized: true,
+            owner: *owner.key,
+            restaurants: Vec::new(),
+        }
+    } else {
+        if pda_bookmarks.owner != program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        RestaurantBookmarks::unpack_checked(&pda_bookmarks.data.borrow())?
+    };
+
+    if bookmarks.restaurants.contains(pda_restaurant.key) {
+        msg!("{} is already bookmarked", restaurant.name);
+        return Err(ReviewError::AlreadyBookmarked.into());
+    }
+    if bookmarks.restaurants.len() >= MAX_BOOKMARKS {
+        msg!("Bookmark list already holds {} restaurants", MAX_BOOKMARKS);
+        return Err(ReviewError::TooManyBookmarks.into());
+    }
+    bookmarks.restaurants.push(*pda_restaurant.key);
+
+    // Grow the account by one entry, topping up the rent first
+    let new_len = RestaurantBookmarks::size_for(bookmarks.restaurants.len());
+    let shortfall = rent.minimum_balance(new_len).saturating_sub(pda_bookmarks.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(owner.key, pda_bookmarks.key, shortfall),
+            &[owner.clone(), pda_bookmarks.clone(), system_program.clone()],
+        )?;
+    }
+    pda_bookmarks.resize(new_len)?;
+    bookmarks.serialize(&mut &mut pda_bookmarks.data.borrow_mut()[..])?;
+
+    msg!("Bookmarked {} ({} bookmarks)", restaurant.name, bookmarks.restaurants.len());
+
+    Ok(())
+}
+
+// Handler for removing a bookmark
+// Shrinks the list by one entry and returns the freed rent to the wallet;
+// the restaurant doesn't need to still be registered
+pub fn remove_bookmark(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Removing bookmark...");
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let owner = next_account_info(account_info_iter)?;          // Wallet removing the bookmark (signer, gets rent back)
+    let restaurant = next_account_info(account_info_iter)?;     // Bookmarked restaurant registry PDA
+    let pda_bookmarks = next_account_info(account_info_iter)?;  // Owner's bookmark list PDA
+    let rent_sysvar = next_account_info(account_info_iter).ok(); // Optional Rent sysvar, see load_rent
+
+    if !owner.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (bookmarks_pda, _bookmarks_bump) =
+        Pubkey::find_program_address(&[b"bookmarks", owner.key.as_ref()], program_id);
+    if bookmarks_pda != *pda_bookmarks.key {
+        msg!("Invalid seeds for bookmarks PDA");
+        return Err(ReviewError::InvalidPDA.into());
+    }
+    if pda_bookmarks.data_is_empty() {
+        return Err(ReviewError::NotBookmarked.into());
+    }
+    if pda_bookmarks.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let mut bookmarks = RestaurantBookmarks::unpack_checked(&pda_bookmarks.data.borrow())?;
+    let Some(position) = bookmarks.restaurants.iter().position(|entry| entry == restaurant.key) else {
+        return Err(ReviewError::NotBookmarked.into());
+    };
+    bookmarks.restaurants.remove(position);
+
+    let new_len = RestaurantBookmarks::size_for(bookmarks.restaurants.len());
+    pda_bookmarks.resize(new_len)?;
+    bookmarks.serialize(&mut &mut pda_bookmarks.data.borrow_mut()[..])?;
+
+    let excess = pda_bookmarks
+        .lamports()
+        .saturating_sub(load_rent(rent_sysvar)?.minimum_balance(new_len));
+    **pda_bookmarks.lamports.borrow_mut() -= excess;
+    **owner.lamports.borrow_mut() = owner
+        .lamports()
+        .checked_add(excess)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    msg!("Removed bookmark ({} left)", bookmarks.restaurants.len());
+
+    Ok(())
+}
+
+// Display names are short handles of a limited charset, so frontends can
+// show them without escaping and they can't impersonate other text
+fn validate_display_name(name: &str) -> ProgramResult {
+    if name.is_empty()
+        || name.len() > MAX_DISPLAY_NAME_LEN
+        || !name
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'.'))
+    {
+        msg!("Invalid display name: {:?}", name);
+        return Err(ReviewError::InvalidDisplayName.into());
+    }
+    Ok(())
+}
+
+// Handler for switching strict registry mode
+// While it is on, AddReview rejects restaurants that aren't registered
+pub fn set_strict_registry(program_id: &Pubkey, accounts: &[AccountInfo], strict: bool) -> ProgramResult {
+    msg!("Setting strict registry mode: {}", strict);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    config.strict_registry = strict;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for setting the update cooldown
+// While it is non-zero, UpdateReview rejects edits made sooner than
+// `seconds` after a review's last update
+pub fn set_update_cooldown(program_id: &Pubkey, accounts: &[AccountInfo], seconds: u32) -> ProgramResult {
+    msg!("Setting update cooldown: {} seconds", seconds);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    config.update_cooldown = seconds;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+// Handler for changing the rating scale
+// Aggregates bucket their histogram by the scale they were created with, so
+// it can only change before the first review
+pub fn update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_rating: u8,
+    max_rating: u8,
+) -> ProgramResult {
+    msg!("Setting rating scale: {}-{}", min_rating, max_rating);
+
+    let account_info_iter = &mut accounts.iter();
+
+    // Expected accounts in order:
+    let admin = next_account_info(account_info_iter)?;      // Config admin (signer)
+    let pda_config = next_account_info(account_info_iter)?; // Program config PDA
+    let pda_stats = next_account_info(account_info_iter)?;  // Program stats PDA
+
+    if !admin.is_signer {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut config = load_config(program_id, pda_config)?;
+    if config.admin != *admin.key {
+        msg!("{} is not the config admin", admin.key);
+        return Err(ReviewError::NotAdmin.into());
+    }
+
+    if min_rating == 0 || min_rating >= max_rating {
+        return Err(ReviewError::InvalidRatingScale.into());
+    }
+    if load_stats(program_id, pda_stats)?.total_reviews > 0 {
+        return Err(ReviewError::RatingScaleLocked.into());
+    }
+
+    config.min_rating = min_rating;
+    config.max_rating = max_rating;
+    config.serialize(&mut &mut pda_config.data.borrow_mut()[..])?;
 
     Ok(())
 }
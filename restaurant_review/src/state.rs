@@ -1,29 +1,1704 @@
 // Import Borsh traits for serializing/deserializing data to store on-chain
 use borsh::{BorshDeserialize, BorshSerialize};
+use bytemuck::{Pod, Zeroable};
+use solana_program::account_info::AccountInfo;
+use solana_program::hash::hash;
 use solana_program::program_error::ProgramError;
-use solana_program::program_pack::{IsInitialized, Sealed};
+use solana_program::program_pack::{IsInitialized, Pack, Sealed};
+use solana_program::pubkey::Pubkey;
+use std::ops::RangeInclusive;
+use unicode_segmentation::UnicodeSegmentation;
 use thiserror::Error;  // For creating custom error types with descriptions
 
+// Largest account the program will create, in bytes
+// Comments whose serialized size exceeds this are rejected with DataTooLarge
+pub const MAX_ACCOUNT_LEN: usize = 1000;
+
+// Longest title and description accepted, in bytes
+// Checked before any account is created so oversized input gets a clear error
+// They are also the fixed capacities of the strings in a review account
+pub const MAX_TITLE_LEN: usize = 50;
+pub const MAX_DESCRIPTION_LEN: usize = 500;
+
+// Longest title and description accepted, in user-perceived characters
+// (extended grapheme clusters), which is what a reader actually sees
+// They match the byte limits, so ASCII text keeps its full length; the
+// byte limits still apply, since they are the account's storage
+pub const MAX_TITLE_GRAPHEMES: usize = MAX_TITLE_LEN;
+pub const MAX_DESCRIPTION_GRAPHEMES: usize = MAX_DESCRIPTION_LEN;
+
+// Most tags a review can carry, and the longest tag accepted, in bytes
+pub const MAX_TAGS: usize = 5;
+pub const MAX_TAG_LEN: usize = 16;
+
+// Longest off-chain content URI accepted, in bytes, and the schemes allowed
+// A CIDv1 with its "ipfs://" prefix is around 66 bytes
+pub const MAX_CONTENT_URI_LEN: usize = 128;
+pub const CONTENT_URI_PREFIXES: [&str; 2] = ["ipfs://", "ar://"];
+
+// Rating scale used until the admin configures another one
+pub const DEFAULT_MIN_RATING: u8 = 1;
+pub const DEFAULT_MAX_RATING: u8 = 10;
+
+// Scale of the optional food/service/ambiance sub-ratings, which stays
+// 1-10 whatever overall rating scale the admin configures
+pub const MIN_SUB_RATING: u8 = 1;
+pub const MAX_SUB_RATING: u8 = 10;
+
+// Live review counts at which a reviewer can claim a badge NFT
+pub const BADGE_MILESTONES: [u64; 3] = [10, 50, 100];
+
+// Every account this program owns starts with an 8-byte tag naming its type,
+// checked before the rest of the account is read so one kind of account
+// can't be passed where another is expected
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+fn check_discriminator(data: &[u8], expected: &[u8; DISCRIMINATOR_LEN]) -> Result<(), ProgramError> {
+    if data.get(..DISCRIMINATOR_LEN) != Some(&expected[..]) {
+        return Err(ReviewError::InvalidAccountType.into());
+    }
+    Ok(())
+}
+
 // The account state structure that will be stored in the PDA
 // This represents a restaurant review with rating and description
+//
+// Reviews use a fixed layout (see the Pack impl below) rather than Borsh:
+// strings are stored as a u32 length followed by a buffer of their maximum
+// capacity, so every field sits at the same byte offset in every review
+pub struct AccountState {
+    pub discriminator: [u8; 8], // Account type tag, always AccountState::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub version: u8,            // Layout version, always AccountState::VERSION once unpacked
+    pub rating: u8,             // Restaurant rating on the configured scale
+    pub tip_total: u64,         // Lamports tipped to the author over the review's lifetime
+    pub upvotes: u64,           // Readers who found the review useful
+    pub downvotes: u64,         // Readers who didn't
+    pub revision_count: u64,    // Number of earlier versions saved as ReviewRevision accounts
+    pub created_at: i64,        // Unix timestamp the review was added
+    pub updated_at: i64,        // Unix timestamp of the last update (created_at if never updated)
+    pub bump: u8,               // Canonical bump of the review PDA, saved so it is only searched for once
+    pub author: Pubkey,         // Wallet that wrote the review; its key is a PDA seed, so it never changes
+    pub owner: Pubkey,          // Wallet allowed to update or delete the review, the author until transferred
+    pub payer: Pubkey,          // Wallet that paid the review's rent and gets it back when it is deleted
+    pub status: ReviewStatus,   // Lifecycle state, Visible unless flagged, hidden, or archived
+    pub tags: Vec<String>,      // Cuisine/category tags for indexers, at most MAX_TAGS
+    pub content_uri: String,    // IPFS/Arweave URI of long-form text and photos, empty if none
+    pub description: String,    // Review description/comment
+    pub title: String,          // Restaurant name/title
+    pub sub_ratings: Option<SubRatings>, // Food/service/ambiance breakdown, None if not given
+    pub visited_at: i64,        // Unix timestamp of the reviewer's visit, never after the review was written
+    pub price_range: u8,        // 1 ($) to MAX_PRICE_RANGE ($$$$) as paid on the visit
+    pub sequence: u32,          // 0 for a first review, n for the author's n-th follow-up of the same title
+}
+
+impl AccountState {
+    // Tag stored at the start of every review account so indexers can tell
+    // reviews apart from the other account types this program owns
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"review\0\0";
+
+    // Current layout version
+    // Version 0 reviews predate the version byte: they are one byte shorter,
+    // with every field from the rating on one byte further forward.
+    // Version 1 reviews end at the title, before the sub-ratings,
+    // version 2 reviews end at the sub-ratings, before the visit details,
+    // and version 3 reviews end at the visit details, before the sequence.
+    // MigrateAccount upgrades older reviews to this version; fields they
+    // didn't have are zeroed, so a visited_at and price_range of 0 mean
+    // unknown, and every older review is a first review
+    pub const VERSION: u8 = 4;
+    pub const LEN_V3: usize = Self::SEQUENCE_OFFSET;
+    pub const LEN_V2: usize = Self::VISITED_AT_OFFSET;
+    pub const LEN_V1: usize = Self::SUB_RATINGS_OFFSET;
+    pub const LEN_V0: usize = Self::LEN_V1 - 1;
+
+    // Byte offsets of each field within a review account
+    // They never move, so RPC memcmp filters and dataSlice queries can
+    // read any of them without deserializing the review
+    pub const IS_INITIALIZED_OFFSET: usize = DISCRIMINATOR_LEN;
+    pub const VERSION_OFFSET: usize = Self::IS_INITIALIZED_OFFSET + 1;
+    pub const RATING_OFFSET: usize = Self::VERSION_OFFSET + 1;
+    pub const TIP_TOTAL_OFFSET: usize = Self::RATING_OFFSET + 1;
+    pub const UPVOTES_OFFSET: usize = Self::TIP_TOTAL_OFFSET + 8;
+    pub const DOWNVOTES_OFFSET: usize = Self::UPVOTES_OFFSET + 8;
+    pub const REVISION_COUNT_OFFSET: usize = Self::DOWNVOTES_OFFSET + 8;
+    pub const CREATED_AT_OFFSET: usize = Self::REVISION_COUNT_OFFSET + 8;
+    pub const UPDATED_AT_OFFSET: usize = Self::CREATED_AT_OFFSET + 8;
+    pub const BUMP_OFFSET: usize = Self::UPDATED_AT_OFFSET + 8;
+    pub const AUTHOR_OFFSET: usize = Self::BUMP_OFFSET + 1;
+    pub const OWNER_OFFSET: usize = Self::AUTHOR_OFFSET + 32;
+    pub const PAYER_OFFSET: usize = Self::OWNER_OFFSET + 32;
+    pub const STATUS_OFFSET: usize = Self::PAYER_OFFSET + 32;
+    pub const TAGS_OFFSET: usize = Self::STATUS_OFFSET + 1;
+    pub const CONTENT_URI_OFFSET: usize = Self::TAGS_OFFSET + 1 + MAX_TAGS * (4 + MAX_TAG_LEN);
+    pub const DESCRIPTION_OFFSET: usize = Self::CONTENT_URI_OFFSET + 4 + MAX_CONTENT_URI_LEN;
+    pub const TITLE_OFFSET: usize = Self::DESCRIPTION_OFFSET + 4 + MAX_DESCRIPTION_LEN;
+    pub const SUB_RATINGS_OFFSET: usize = Self::TITLE_OFFSET + 4 + MAX_TITLE_LEN;
+    pub const VISITED_AT_OFFSET: usize = Self::SUB_RATINGS_OFFSET + 3;
+    pub const PRICE_RANGE_OFFSET: usize = Self::VISITED_AT_OFFSET + 8;
+    pub const SEQUENCE_OFFSET: usize = Self::PRICE_RANGE_OFFSET + 1;
+
+    // Deserialize a review, rejecting accounts of any other type
+    // The discriminator is checked before Pack's length check, so another
+    // kind of account fails with InvalidAccountType rather than a size error
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_version(data)?;
+        Self::unpack_unchecked(data)
+    }
+
+    // Layout version of a review's raw data, see VERSION
+    pub fn version_of(data: &[u8]) -> Result<u8, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        match data.len() {
+            Self::LEN_V0 => Ok(0),
+            Self::LEN_V1 | Self::LEN_V2 | Self::LEN_V3 | Self::LEN => Ok(data[Self::VERSION_OFFSET]),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    // Helpfulness score from the review's votes, see helpfulness()
+    pub fn helpfulness(&self) -> u64 {
+        helpfulness(self.upvotes, self.downvotes)
+    }
+}
+
+// Sealed trait implementation - required by Solana's Pack trait
+impl Sealed for AccountState {}
+
+// Implement IsInitialized trait to check if account is ready to use
+impl IsInitialized for AccountState {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for AccountState {
+    // discriminator (8) + is_initialized (1) + version (1) + rating (1) + tip_total (8)
+    // + upvotes (8) + downvotes (8) + revision_count (8) + created_at (8) + updated_at (8)
+    // + bump (1) + author (32) + owner (32) + payer (32) + status (1)
+    // + tags (1 + MAX_TAGS * (4 + MAX_TAG_LEN)) + content_uri (4 + MAX_CONTENT_URI_LEN)
+    // + description (4 + MAX_DESCRIPTION_LEN) + title (4 + MAX_TITLE_LEN)
+    // + sub_ratings (3) + visited_at (8) + price_range (1) + sequence (4)
+    const LEN: usize = Self::SEQUENCE_OFFSET + 4;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..DISCRIMINATOR_LEN].copy_from_slice(&self.discriminator);
+        dst[Self::IS_INITIALIZED_OFFSET] = self.is_initialized as u8;
+        dst[Self::VERSION_OFFSET] = self.version;
+        dst[Self::RATING_OFFSET] = self.rating;
+        dst[Self::TIP_TOTAL_OFFSET..Self::UPVOTES_OFFSET].copy_from_slice(&self.tip_total.to_le_bytes());
+        dst[Self::UPVOTES_OFFSET..Self::DOWNVOTES_OFFSET].copy_from_slice(&self.upvotes.to_le_bytes());
+        dst[Self::DOWNVOTES_OFFSET..Self::REVISION_COUNT_OFFSET]
+            .copy_from_slice(&self.downvotes.to_le_bytes());
+        dst[Self::REVISION_COUNT_OFFSET..Self::CREATED_AT_OFFSET]
+            .copy_from_slice(&self.revision_count.to_le_bytes());
+        dst[Self::CREATED_AT_OFFSET..Self::UPDATED_AT_OFFSET]
+            .copy_from_slice(&self.created_at.to_le_bytes());
+        dst[Self::UPDATED_AT_OFFSET..Self::BUMP_OFFSET].copy_from_slice(&self.updated_at.to_le_bytes());
+        dst[Self::BUMP_OFFSET] = self.bump;
+        dst[Self::AUTHOR_OFFSET..Self::OWNER_OFFSET].copy_from_slice(self.author.as_ref());
+        dst[Self::OWNER_OFFSET..Self::PAYER_OFFSET].copy_from_slice(self.owner.as_ref());
+        dst[Self::PAYER_OFFSET..Self::STATUS_OFFSET].copy_from_slice(self.payer.as_ref());
+        dst[Self::STATUS_OFFSET] = self.status as u8;
+        // Tag count, then MAX_TAGS fixed-capacity slots (unused ones zeroed)
+        dst[Self::TAGS_OFFSET] = self.tags.len() as u8;
+        for (i, slot) in dst[Self::TAGS_OFFSET + 1..Self::CONTENT_URI_OFFSET]
+            .chunks_mut(4 + MAX_TAG_LEN)
+            .enumerate()
+        {
+            pack_fixed_str(slot, self.tags.get(i).map_or("", String::as_str));
+        }
+        pack_fixed_str(&mut dst[Self::CONTENT_URI_OFFSET..Self::DESCRIPTION_OFFSET], &self.content_uri);
+        pack_fixed_str(&mut dst[Self::DESCRIPTION_OFFSET..Self::TITLE_OFFSET], &self.description);
+        pack_fixed_str(&mut dst[Self::TITLE_OFFSET..Self::SUB_RATINGS_OFFSET], &self.title);
+        dst[Self::SUB_RATINGS_OFFSET..Self::VISITED_AT_OFFSET]
+            .copy_from_slice(&SubRatings::to_bytes(self.sub_ratings));
+        dst[Self::VISITED_AT_OFFSET..Self::PRICE_RANGE_OFFSET].copy_from_slice(&self.visited_at.to_le_bytes());
+        dst[Self::PRICE_RANGE_OFFSET] = self.price_range;
+        dst[Self::SEQUENCE_OFFSET..].copy_from_slice(&self.sequence.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        check_version(src)?;
+        let read_u64 = |offset: usize| {
+            u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap())
+        };
+        let read_i64 = |offset: usize| {
+            i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap())
+        };
+        let is_initialized = match src[Self::IS_INITIALIZED_OFFSET] {
+            0 => false,
+            1 => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(Self {
+            discriminator: Self::DISCRIMINATOR,
+            is_initialized,
+            version: src[Self::VERSION_OFFSET],
+            rating: src[Self::RATING_OFFSET],
+            tip_total: read_u64(Self::TIP_TOTAL_OFFSET),
+            upvotes: read_u64(Self::UPVOTES_OFFSET),
+            downvotes: read_u64(Self::DOWNVOTES_OFFSET),
+            revision_count: read_u64(Self::REVISION_COUNT_OFFSET),
+            created_at: read_i64(Self::CREATED_AT_OFFSET),
+            updated_at: read_i64(Self::UPDATED_AT_OFFSET),
+            bump: src[Self::BUMP_OFFSET],
+            author: Pubkey::new_from_array(src[Self::AUTHOR_OFFSET..Self::OWNER_OFFSET].try_into().unwrap()),
+            owner: Pubkey::new_from_array(src[Self::OWNER_OFFSET..Self::PAYER_OFFSET].try_into().unwrap()),
+            payer: Pubkey::new_from_array(src[Self::PAYER_OFFSET..Self::STATUS_OFFSET].try_into().unwrap()),
+            status: ReviewStatus::try_from(src[Self::STATUS_OFFSET])?,
+            tags: unpack_tags(&src[Self::TAGS_OFFSET..Self::CONTENT_URI_OFFSET])?,
+            content_uri: unpack_fixed_str(&src[Self::CONTENT_URI_OFFSET..Self::DESCRIPTION_OFFSET])?,
+            description: unpack_fixed_str(&src[Self::DESCRIPTION_OFFSET..Self::TITLE_OFFSET])?,
+            title: unpack_fixed_str(&src[Self::TITLE_OFFSET..Self::SUB_RATINGS_OFFSET])?,
+            sub_ratings: SubRatings::from_bytes(
+                src[Self::SUB_RATINGS_OFFSET..Self::VISITED_AT_OFFSET].try_into().unwrap(),
+            ),
+            visited_at: read_i64(Self::VISITED_AT_OFFSET),
+            price_range: src[Self::PRICE_RANGE_OFFSET],
+            sequence: u32::from_le_bytes(src[Self::SEQUENCE_OFFSET..Self::LEN].try_into().unwrap()),
+        })
+    }
+}
+
+// Reject other account types, and reviews in an older layout, which have
+// to be migrated first
+fn check_version(data: &[u8]) -> Result<(), ProgramError> {
+    if AccountState::version_of(data)? != AccountState::VERSION {
+        return Err(ReviewError::OutdatedAccount.into());
+    }
+    Ok(())
+}
+
+// Write a string into a fixed-capacity field: u32 length, the bytes,
+// then zeros up to the end of the field
+// Callers check lengths against MAX_TITLE_LEN / MAX_DESCRIPTION_LEN first
+fn pack_fixed_str(dst: &mut [u8], value: &str) {
+    let (len, buf) = dst.split_at_mut(4);
+    len.copy_from_slice(&(value.len() as u32).to_le_bytes());
+    buf[..value.len()].copy_from_slice(value.as_bytes());
+    buf[value.len()..].fill(0);
+}
+
+// Read the tag count and that many tags from the tag slots
+fn unpack_tags(src: &[u8]) -> Result<Vec<String>, ProgramError> {
+    let (count, slots) = src.split_first().unwrap();
+    if *count as usize > MAX_TAGS {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    slots
+        .chunks(4 + MAX_TAG_LEN)
+        .take(*count as usize)
+        .map(unpack_fixed_str)
+        .collect()
+}
+
+// Read a string written by pack_fixed_str
+fn unpack_fixed_str(src: &[u8]) -> Result<String, ProgramError> {
+    let (len, buf) = src.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if len > buf.len() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    String::from_utf8(buf[..len].to_vec()).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+// Zero-copy view of a review account, laid out byte for byte like the
+// Pack impl above
+// Every field is a byte array, so the struct has alignment 1 and can be
+// cast straight over account data; hot paths use it to patch single fields
+// instead of unpacking and repacking the whole review
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ReviewZeroCopy {
+    pub discriminator: [u8; 8],                        // Always AccountState::DISCRIMINATOR
+    pub is_initialized: u8,                            // 0 or 1
+    pub version: u8,                                   // Always AccountState::VERSION
+    pub rating: u8,                                    // Restaurant rating on the configured scale
+    pub tip_total: [u8; 8],                            // Little-endian u64
+    pub upvotes: [u8; 8],                              // Little-endian u64
+    pub downvotes: [u8; 8],                            // Little-endian u64
+    pub revision_count: [u8; 8],                       // Little-endian u64
+    pub created_at: [u8; 8],                           // Little-endian i64
+    pub updated_at: [u8; 8],                           // Little-endian i64
+    pub bump: u8,                                      // Canonical bump of the review PDA
+    pub author: [u8; 32],                              // Author pubkey
+    pub owner: [u8; 32],                               // Owner pubkey
+    pub payer: [u8; 32],                               // Rent payer pubkey
+    pub status: u8,                                    // ReviewStatus as u8
+    pub tag_count: u8,                                 // Number of tag slots in use
+    pub tags: [FixedStr<MAX_TAG_LEN>; MAX_TAGS],       // Tag slots, unused ones zeroed
+    pub content_uri: FixedStr<MAX_CONTENT_URI_LEN>,    // Off-chain content URI
+    pub description: FixedStr<MAX_DESCRIPTION_LEN>,    // Review description
+    pub title: FixedStr<MAX_TITLE_LEN>,                // Restaurant name
+    pub sub_ratings: [u8; 3],                          // Food, service, ambiance; all zero if not given
+    pub visited_at: [u8; 8],                           // Little-endian i64
+    pub price_range: u8,                               // 1 ($) to MAX_PRICE_RANGE ($$$$)
+    pub sequence: [u8; 4],                             // Little-endian u32, 0 unless a follow-up
+}
+
+// The zero-copy view must cover exactly the packed layout
+const _: () = assert!(std::mem::size_of::<ReviewZeroCopy>() == AccountState::LEN);
+const _: () = assert!(std::mem::offset_of!(ReviewZeroCopy, title) == AccountState::TITLE_OFFSET);
+const _: () = assert!(std::mem::offset_of!(ReviewZeroCopy, sub_ratings) == AccountState::SUB_RATINGS_OFFSET);
+const _: () = assert!(std::mem::offset_of!(ReviewZeroCopy, visited_at) == AccountState::VISITED_AT_OFFSET);
+const _: () = assert!(std::mem::offset_of!(ReviewZeroCopy, sequence) == AccountState::SEQUENCE_OFFSET);
+
+impl ReviewZeroCopy {
+    // Borrow a review account's data as a zero-copy view
+    pub fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        check_version(data)?;
+        bytemuck::try_from_bytes(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Mutable variant of load, for patching fields in place
+    pub fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        check_version(data)?;
+        bytemuck::try_from_bytes_mut(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.is_initialized == 1
+    }
+
+    pub fn author(&self) -> Pubkey {
+        Pubkey::new_from_array(self.author)
+    }
+
+    pub fn owner(&self) -> Pubkey {
+        Pubkey::new_from_array(self.owner)
+    }
+
+    pub fn revision_count(&self) -> u64 {
+        u64::from_le_bytes(self.revision_count)
+    }
+
+    // Helpfulness score from the review's votes, see helpfulness()
+    pub fn helpfulness(&self) -> u64 {
+        helpfulness(u64::from_le_bytes(self.upvotes), u64::from_le_bytes(self.downvotes))
+    }
+
+    pub fn set_revision_count(&mut self, value: u64) {
+        self.revision_count = value.to_le_bytes();
+    }
+
+    pub fn created_at(&self) -> i64 {
+        i64::from_le_bytes(self.created_at)
+    }
+
+    pub fn updated_at(&self) -> i64 {
+        i64::from_le_bytes(self.updated_at)
+    }
+
+    pub fn set_updated_at(&mut self, value: i64) {
+        self.updated_at = value.to_le_bytes();
+    }
+
+    pub fn sub_ratings(&self) -> Option<SubRatings> {
+        SubRatings::from_bytes(self.sub_ratings)
+    }
+
+    pub fn set_sub_ratings(&mut self, value: Option<SubRatings>) {
+        self.sub_ratings = SubRatings::to_bytes(value);
+    }
+
+    pub fn visited_at(&self) -> i64 {
+        i64::from_le_bytes(self.visited_at)
+    }
+
+    pub fn set_visited_at(&mut self, value: i64) {
+        self.visited_at = value.to_le_bytes();
+    }
+
+    pub fn sequence(&self) -> u32 {
+        u32::from_le_bytes(self.sequence)
+    }
+
+    // Replace the tags, zeroing the slots that are no longer used
+    // Callers check the count and lengths against MAX_TAGS / MAX_TAG_LEN first
+    pub fn set_tags(&mut self, tags: &[String]) {
+        self.tag_count = tags.len() as u8;
+        for (i, slot) in self.tags.iter_mut().enumerate() {
+            slot.set(tags.get(i).map_or("", String::as_str));
+        }
+    }
+}
+
+// Fixed-capacity string inside ReviewZeroCopy, the same bytes
+// pack_fixed_str writes: a little-endian u32 length, then N bytes
+// `packed` only lets bytemuck accept the generic; the fields are all u8
+// arrays, so there is no padding to remove
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct FixedStr<const N: usize> {
+    len: [u8; 4],
+    bytes: [u8; N],
+}
+
+impl<const N: usize> FixedStr<N> {
+    pub fn as_str(&self) -> Result<&str, ProgramError> {
+        let (len, bytes) = bytemuck::bytes_of(self).split_at(4);
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        let bytes = bytes.get(..len).ok_or(ProgramError::InvalidAccountData)?;
+        std::str::from_utf8(bytes).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Overwrite the string; callers check value.len() <= N first
+    pub fn set(&mut self, value: &str) {
+        pack_fixed_str(bytemuck::bytes_of_mut(self), value);
+    }
+}
+
+// Optional breakdown of a review's rating, each part from MIN_SUB_RATING
+// to MAX_SUB_RATING
+// Stored in a review as three bytes after the title; a review without
+// sub-ratings stores zeros, which no valid sub-rating can be
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubRatings {
+    pub food: u8,
+    pub service: u8,
+    pub ambiance: u8,
+}
+
+impl SubRatings {
+    // Whether every part is on the sub-rating scale
+    pub fn is_valid(&self) -> bool {
+        [self.food, self.service, self.ambiance]
+            .iter()
+            .all(|rating| (MIN_SUB_RATING..=MAX_SUB_RATING).contains(rating))
+    }
+
+    fn to_bytes(value: Option<Self>) -> [u8; 3] {
+        value.map_or([0; 3], |ratings| [ratings.food, ratings.service, ratings.ambiance])
+    }
+
+    fn from_bytes(bytes: [u8; 3]) -> Option<Self> {
+        if bytes == [0; 3] {
+            return None;
+        }
+        let [food, service, ambiance] = bytes;
+        Some(Self {
+            food,
+            service,
+            ambiance,
+        })
+    }
+}
+
+// Lifecycle state of a review, stored as a single byte
+// Anyone can flag a visible review; moderators then hide it or restore it.
+// The owner can soft-delete (archive) it: the account stays readable but
+// the review can no longer be edited or moderated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReviewStatus {
+    Visible = 0, // Shown normally
+    Flagged = 1, // Reported by a reader, waiting for a moderator
+    Hidden = 2,  // Hidden by a moderator
+    Deleted = 3, // Archived by its owner, kept for indexers
+}
+
+impl TryFrom<u8> for ReviewStatus {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Visible),
+            1 => Ok(Self::Flagged),
+            2 => Ok(Self::Hidden),
+            3 => Ok(Self::Deleted),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+// Per-review counter that tracks how many comments have been added
+// Stored in a PDA derived from [review_pda, "comment"] so clients can
+// enumerate comments by deriving [review_pda, 0..counter]
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewCommentCounter {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewCommentCounter::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub counter: u64,           // Number of comments on the review
+}
+
+impl ReviewCommentCounter {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"counter\0";
+
+    // discriminator (8) + is_initialized (1) + counter (8)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 8;
+
+    // Deserialize a comment counter, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewCommentCounter {}
+
+impl IsInitialized for ReviewCommentCounter {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A single comment on a review
+// Stored in a PDA derived from [review_pda, count as big-endian u64]
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewComment {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewComment::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA this comment belongs to
+    pub commenter: Pubkey,      // Wallet that wrote the comment
+    pub comment: String,        // Comment text
+    pub count: u64,             // Position of this comment in the review's thread
+}
+
+impl ReviewComment {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"comment\0";
+
+    // Borsh-serialized size of a comment with the given text
+    // discriminator (8) + is_initialized (1) + review (32) + commenter (32)
+    // + comment (4 + len) + count (8)
+    pub fn get_account_size(comment: &str) -> usize {
+        DISCRIMINATOR_LEN + 1 + 32 + 32 + (4 + comment.len()) + 8
+    }
+
+    // Deserialize a comment, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewComment {}
+
+impl IsInitialized for ReviewComment {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Program-wide counters kept in a singleton PDA derived from ["stats"]
+// Lets dashboards show totals without scanning every program account
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProgramStats {
+    pub discriminator: [u8; 8],      // Account type tag, always ProgramStats::DISCRIMINATOR
+    pub is_initialized: bool,        // Whether this account has been initialized
+    pub total_reviews: u64,          // Reviews ever created
+    pub total_updates: u64,          // Review updates ever made
+    pub total_unique_reviewers: u64, // Wallets that have created at least one review
+}
+
+impl ProgramStats {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"stats\0\0\0";
+
+    // discriminator (8) + is_initialized (1) + three u64 counters (8 * 3)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 8 * 3;
+
+    // Deserialize the stats account, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ProgramStats {}
+
+impl IsInitialized for ProgramStats {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Most moderators a config can list besides the admin
+pub const MAX_MODERATORS: usize = 10;
+
+// Program-wide settings kept in a singleton PDA derived from ["config"]
+// The admin and the listed moderators may hide or restore flagged reviews
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProgramConfig {
+    pub discriminator: [u8; 8],  // Account type tag, always ProgramConfig::DISCRIMINATOR
+    pub is_initialized: bool,    // Whether this account has been initialized
+    pub admin: Pubkey,           // Wallet that created the config, always a moderator
+    pub strict_registry: bool,   // Only registered restaurants can be reviewed
+    pub update_cooldown: u32,    // Seconds a review must wait between updates, 0 for none
+    pub min_rating: u8,          // Lowest rating accepted
+    pub max_rating: u8,          // Highest rating accepted
+    pub report_threshold: u32,   // Reports that hide a review until a moderator looks, 0 for never
+    pub review_stake: u64,       // Lamports a new review locks in its stake escrow, 0 for none
+    pub review_fee: u64,         // Lamports each new review pays into the program treasury, 0 for none
+    pub pending_admin: Option<Pubkey>, // Wallet nominated to take over as admin, until it accepts
+    pub paused: bool,            // Reviews and comments are frozen until the admin unpauses
+    pub claim_fee: u64,          // Lamports a restaurant claim escrows until the admin decides, 0 for none
+    pub moderators: Vec<Pubkey>, // Additional moderators, at most MAX_MODERATORS
+}
+
+impl ProgramConfig {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"config\0\0";
+
+    // discriminator (8) + is_initialized (1) + admin (32) + strict_registry (1)
+    // + update_cooldown (4) + min_rating (1) + max_rating (1)
+    // + report_threshold (4) + review_stake (8) + review_fee (8)
+    // + pending_admin (1 + 32) + paused (1) + claim_fee (8)
+    // + moderators (4 + 32 * MAX_MODERATORS)
+    pub const SIZE: usize =
+        DISCRIMINATOR_LEN + 1 + 32 + 1 + 4 + 1 + 1 + 4 + 8 + 8 + (1 + 32) + 1 + 8 + (4 + 32 * MAX_MODERATORS);
+
+    // Deserialize the config, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+
+    // Whether `key` may moderate reviews
+    pub fn is_moderator(&self, key: &Pubkey) -> bool {
+        self.admin == *key || self.moderators.contains(key)
+    }
+
+    // Ratings reviews may give
+    pub fn rating_range(&self) -> RangeInclusive<u8> {
+        self.min_rating..=self.max_rating
+    }
+}
+
+impl Sealed for ProgramConfig {}
+
+impl IsInitialized for ProgramConfig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Most members an M-of-N review multisig can have, as in SPL Token
+pub const MAX_MULTISIG_SIGNERS: usize = 11;
+
+// M-of-N authority that can own reviews, like an SPL Token multisig
+// A review transferred to this account's address can only be updated,
+// deleted, or transferred when at least `threshold` of `signers` sign
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewMultisig {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewMultisig::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub threshold: u8,          // Signatures required (M)
+    pub signers: Vec<Pubkey>,   // Members (N), at most MAX_MULTISIG_SIGNERS, no duplicates
+}
+
+impl ReviewMultisig {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"multisig";
+
+    // discriminator (8) + is_initialized (1) + threshold (1)
+    // + signers (4 + 32 * MAX_MULTISIG_SIGNERS)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 1 + (4 + 32 * MAX_MULTISIG_SIGNERS);
+
+    // Deserialize a multisig, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+
+    // Whether enough members are among the accounts that signed
+    // Each member counts once, however many times it is passed
+    pub fn is_approved_by(&self, accounts: &[AccountInfo]) -> bool {
+        let approvals = self
+            .signers
+            .iter()
+            .filter(|member| accounts.iter().any(|account| account.is_signer && account.key == *member))
+            .count();
+        approvals >= self.threshold as usize
+    }
+}
+
+impl Sealed for ReviewMultisig {}
+
+impl IsInitialized for ReviewMultisig {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Per-wallet record created with a user's first review
+// Stored in a PDA derived from ["reviewer", reviewer_pubkey]; its existence
+// is what marks a wallet as already counted in total_unique_reviewers
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewerRecord {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewerRecord::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub reviewer: Pubkey,       // Wallet this record belongs to
+    pub review_count: u64,      // Reviews this wallet has created
+}
+
+impl ReviewerRecord {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"reviewer";
+
+    // discriminator (8) + is_initialized (1) + reviewer (32) + review_count (8)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 8;
+
+    // Deserialize a reviewer record, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewerRecord {}
+
+impl IsInitialized for ReviewerRecord {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Follow-up reviews a wallet has posted for one restaurant title
+// Stored in a PDA derived from ["followups", author, review_seed(title)];
+// follow-up n lives at [author, review_seed(title), n as u32 LE]
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct FollowUpCounter {
+    pub discriminator: [u8; 8], // Account type tag, always FollowUpCounter::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub author: Pubkey,         // Wallet posting the follow-ups
+    pub count: u32,             // Sequence number of the latest follow-up
+}
+
+impl FollowUpCounter {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"followup";
+
+    // discriminator (8) + is_initialized (1) + author (32) + count (4)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 4;
+
+    // Deserialize a follow-up counter, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for FollowUpCounter {}
+
+impl IsInitialized for FollowUpCounter {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// One wallet following a reviewer
+// Stored in a PDA derived from ["follow", follower, followee]; it exists
+// exactly while the follow does, so clients can list who a wallet follows
+// with a memcmp filter on `follower`
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewerFollow {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewerFollow::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub follower: Pubkey,       // Wallet following
+    pub followee: Pubkey,       // Reviewer being followed
+}
+
+impl ReviewerFollow {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"follow\0\0";
+
+    // discriminator (8) + is_initialized (1) + follower (32) + followee (32)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 32;
+
+    // Deserialize a follow, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewerFollow {}
+
+impl IsInitialized for ReviewerFollow {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Longest display name a reviewer can set
+pub const MAX_DISPLAY_NAME_LEN: usize = 32;
+
+// Public handle and follower counts a reviewer shows next to their reviews
+// Stored in a PDA derived from ["reviewer_profile", reviewer_pubkey],
+// created the first time the reviewer sets a display name, follows someone,
+// or is followed
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewerProfile {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewerProfile::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub reviewer: Pubkey,       // Wallet this profile belongs to
+    pub follower_count: u64,    // Wallets following this reviewer
+    pub following_count: u64,   // Reviewers this wallet follows
+    pub display_name: String,   // ASCII letters, digits, '_', '-' and '.', at most MAX_DISPLAY_NAME_LEN bytes; empty until set
+}
+
+impl ReviewerProfile {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"rvwrprof";
+
+    // discriminator (8) + is_initialized (1) + reviewer (32)
+    // + follower_count (8) + following_count (8)
+    // + display_name (4 + MAX_DISPLAY_NAME_LEN)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 8 + 8 + (4 + MAX_DISPLAY_NAME_LEN);
+
+    // Deserialize a reviewer profile, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewerProfile {}
+
+impl IsInitialized for ReviewerProfile {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Every live review a wallet has written, so "my reviews" is one fetch
+// Stored in a PDA derived from ["reviewer_index", reviewer_pubkey]; the
+// account is resized to fit the list as reviews are added and deleted
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewerIndex {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewerIndex::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub reviewer: Pubkey,       // Wallet the index belongs to (the reviews' author)
+    pub reviews: Vec<Pubkey>,   // Review PDAs, oldest first
+}
+
+impl ReviewerIndex {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"revindex";
+
+    // Account size for an index of `reviews` entries
+    // discriminator (8) + is_initialized (1) + reviewer (32) + reviews (4 + 32 * n)
+    pub fn size_for(reviews: usize) -> usize {
+        DISCRIMINATOR_LEN + 1 + 32 + 4 + 32 * reviews
+    }
+
+    // Deserialize an index, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewerIndex {}
+
+impl IsInitialized for ReviewerIndex {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Most restaurants a wallet can bookmark
+pub const MAX_BOOKMARKS: usize = 256;
+
+// A wallet's favorite restaurants
+// Stored in a PDA derived from ["bookmarks", owner]; like the reviewer
+// index it is created by the first bookmark and grows or shrinks by one
+// entry per instruction
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RestaurantBookmarks {
+    pub discriminator: [u8; 8], // Account type tag, always RestaurantBookmarks::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub owner: Pubkey,          // Wallet the bookmarks belong to
+    pub restaurants: Vec<Pubkey>, // Restaurant registry PDAs, oldest first, at most MAX_BOOKMARKS
+}
+
+impl RestaurantBookmarks {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"bookmark";
+
+    // Account size for a list of `restaurants` entries
+    // discriminator (8) + is_initialized (1) + owner (32) + restaurants (4 + 32 * n)
+    pub fn size_for(restaurants: usize) -> usize {
+        DISCRIMINATOR_LEN + 1 + 32 + 4 + 32 * restaurants
+    }
+
+    // Deserialize a bookmark list, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for RestaurantBookmarks {}
+
+impl IsInitialized for RestaurantBookmarks {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Scale of a review's helpfulness score
+pub const MAX_HELPFULNESS: u64 = 1000;
+
+// Helpfulness score of a review from its votes, 0 to MAX_HELPFULNESS
+// The share of upvotes with one vote each way added, so a review without
+// votes sits in the middle and a few votes can't swing it to either end
+pub fn helpfulness(upvotes: u64, downvotes: u64) -> u64 {
+    let up = upvotes as u128 + 1;
+    let total = upvotes as u128 + downvotes as u128 + 2;
+    (up * MAX_HELPFULNESS as u128 / total) as u64
+}
+
+// Half-life of a rating's weight in the time-decayed average
+pub const DECAY_HALF_LIFE: i64 = 90 * 24 * 60 * 60;
+
+// Weight of a review in the time-decayed sums at the moment it was added
+pub const DECAY_SCALE: u64 = 1_000_000;
+
+// 2^(-k/16) for k = 0..=16, scaled by 10^9
+const DECAY_STEPS: [u128; 17] = [
+    1_000_000_000, 957_603_281, 917_004_043, 878_126_080, 840_896_415, 805_245_166,
+    771_105_413, 738_413_073, 707_106_781, 677_127_773, 648_419_777, 620_928_906,
+    594_603_558, 569_394_317, 545_253_866, 522_136_891, 500_000_000,
+];
+
+// `weight` after `elapsed` seconds of exponential decay, halving every
+// DECAY_HALF_LIFE
+// Whole half-lives are a shift; the rest of the way is interpolated between
+// sixteenths of a half-life, which is within 0.03% of the exact curve and
+// keeps the program off floating point
+pub fn decay(weight: u64, elapsed: i64) -> u64 {
+    if elapsed <= 0 {
+        return weight;
+    }
+    let halvings = elapsed / DECAY_HALF_LIFE;
+    if halvings >= 64 {
+        return 0;
+    }
+    let weight = (weight >> halvings) as u128;
+
+    let half_life = DECAY_HALF_LIFE as u128;
+    let position = (elapsed % DECAY_HALF_LIFE) as u128 * 16;
+    let step = (position / half_life) as usize;
+    let (upper, lower) = (DECAY_STEPS[step], DECAY_STEPS[step + 1]);
+    let factor = upper - (upper - lower) * (position % half_life) / half_life;
+    (weight * factor / DECAY_STEPS[0]) as u64
+}
+
+// Per-restaurant rating summary kept in a PDA derived from
+// ["aggregate", title_seed(normalize_title(title))], so reviews of "Pizza Place" and
+// " pizza  place" land in the same aggregate
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RestaurantAggregate {
+    pub discriminator: [u8; 8], // Account type tag, always RestaurantAggregate::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review_count: u64,      // Live reviews for this restaurant
+    pub rating_sum: u64,        // Sum of their ratings
+    pub min_rating: u8,         // Rating scale when the aggregate was created,
+    pub max_rating: u8,         // which the histogram buckets divide into tenths
+    pub histogram: [u64; 10],   // histogram[i] = number of reviews in the i-th tenth of the scale
+    pub sub_rating_count: u64,  // Live reviews that gave sub-ratings; each average is its sum / this
+    pub food_sum: u64,          // Sum of their food sub-ratings
+    pub service_sum: u64,       // Sum of their service sub-ratings
+    pub ambiance_sum: u64,      // Sum of their ambiance sub-ratings
+    pub weight_sum: u64,        // Sum of the live reviews' helpfulness scores
+    pub weighted_rating_sum: u64, // Sum of rating * helpfulness; the weighted average is this / weight_sum
+    pub decayed_weight_sum: u64, // Sum of the live reviews' time-decayed weights as of decayed_at
+    pub decayed_rating_sum: u64, // Sum of rating * decayed weight; the recency-weighted average is this / decayed_weight_sum
+    pub decayed_at: i64,         // Unix timestamp the decayed sums were last brought up to date
+}
+
+impl RestaurantAggregate {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"aggregat";
+
+    // discriminator (8) + is_initialized (1) + review_count (8)
+    // + rating_sum (8) + min_rating (1) + max_rating (1) + histogram (8 * 10)
+    // + sub_rating_count (8) + food_sum (8) + service_sum (8) + ambiance_sum (8)
+    // + weight_sum (8) + weighted_rating_sum (8)
+    // + decayed_weight_sum (8) + decayed_rating_sum (8) + decayed_at (8)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 8 + 8 + 1 + 1 + 8 * 10 + 8 * 4 + 8 * 2 + 8 * 3;
+
+    // Deserialize an aggregate, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+
+    // Histogram bucket of a rating on the aggregate's scale
+    // On the default 1-10 scale each rating has its own bucket
+    fn bucket(&self, rating: u8) -> Result<usize, ProgramError> {
+        if !(self.min_rating..=self.max_rating).contains(&rating) {
+            return Err(ReviewError::InvalidRating.into());
+        }
+        let span = (self.max_rating - self.min_rating) as usize + 1;
+        Ok((rating - self.min_rating) as usize * self.histogram.len() / span)
+    }
+
+    // Count a new rating from a review with the given helpfulness score,
+    // added at `created_at`
+    pub fn add_rating(&mut self, rating: u8, helpfulness: u64, created_at: i64) -> Result<(), ProgramError> {
+        let bucket = self.bucket(rating)?;
+        let slot = &mut self.histogram[bucket];
+        *slot = slot.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.review_count = self
+            .review_count
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.rating_sum = self
+            .rating_sum
+            .checked_add(rating as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.add_decayed(rating, created_at)?;
+        self.add_weight(rating, helpfulness)
+    }
+
+    // Remove a previously counted rating, with the helpfulness it was counted at
+    pub fn remove_rating(&mut self, rating: u8, helpfulness: u64, created_at: i64) -> Result<(), ProgramError> {
+        let bucket = self.bucket(rating)?;
+        let slot = &mut self.histogram[bucket];
+        *slot = slot.checked_sub(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.review_count = self
+            .review_count
+            .checked_sub(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.rating_sum = self
+            .rating_sum
+            .checked_sub(rating as u64)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.remove_decayed(rating, created_at);
+        self.remove_weight(rating, helpfulness)
+    }
+
+    // Decay the time-weighted sums up to `now`
+    // Decaying every weight by the same factor leaves the recency-weighted
+    // average unchanged; it keeps the sums (and decayed_weight_sum as an
+    // "effective review count") current and bounded, and is what lets a
+    // review's weight be derived from its created_at instead of stored
+    pub fn decay_to(&mut self, now: i64) {
+        if now <= self.decayed_at {
+            return;
+        }
+        let elapsed = now - self.decayed_at;
+        self.decayed_weight_sum = decay(self.decayed_weight_sum, elapsed);
+        self.decayed_rating_sum = decay(self.decayed_rating_sum, elapsed);
+        self.decayed_at = now;
+    }
+
+    // Weight of a review added at `created_at`, as of decayed_at
+    fn decayed_weight(&self, created_at: i64) -> u64 {
+        decay(DECAY_SCALE, self.decayed_at - created_at)
+    }
+
+    // A new review starts at DECAY_SCALE; an existing one moving here (on
+    // update or rename) keeps the weight its age gives it
+    fn add_decayed(&mut self, rating: u8, created_at: i64) -> Result<(), ProgramError> {
+        self.decay_to(created_at);
+        let weight = self.decayed_weight(created_at);
+        self.decayed_weight_sum = self
+            .decayed_weight_sum
+            .checked_add(weight)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.decayed_rating_sum = (rating as u64)
+            .checked_mul(weight)
+            .and_then(|weighted| self.decayed_rating_sum.checked_add(weighted))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    // Rounding means the decayed sums and a review's recomputed weight can
+    // disagree by a few units, so removal saturates, and the sums are reset
+    // once the last review is gone
+    fn remove_decayed(&mut self, rating: u8, created_at: i64) {
+        let weight = self.decayed_weight(created_at);
+        self.decayed_weight_sum = self.decayed_weight_sum.saturating_sub(weight);
+        self.decayed_rating_sum = self
+            .decayed_rating_sum
+            .saturating_sub((rating as u64).saturating_mul(weight));
+        if self.review_count == 0 {
+            self.decayed_weight_sum = 0;
+            self.decayed_rating_sum = 0;
+        }
+    }
+
+    // Recount a rating after a vote moved its review's helpfulness score
+    pub fn reweight_rating(&mut self, rating: u8, old_helpfulness: u64, helpfulness: u64) -> Result<(), ProgramError> {
+        self.remove_weight(rating, old_helpfulness)?;
+        self.add_weight(rating, helpfulness)
+    }
+
+    fn add_weight(&mut self, rating: u8, helpfulness: u64) -> Result<(), ProgramError> {
+        self.weight_sum = self
+            .weight_sum
+            .checked_add(helpfulness)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.weighted_rating_sum = (rating as u64)
+            .checked_mul(helpfulness)
+            .and_then(|weighted| self.weighted_rating_sum.checked_add(weighted))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    fn remove_weight(&mut self, rating: u8, helpfulness: u64) -> Result<(), ProgramError> {
+        self.weight_sum = self
+            .weight_sum
+            .checked_sub(helpfulness)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        self.weighted_rating_sum = (rating as u64)
+            .checked_mul(helpfulness)
+            .and_then(|weighted| self.weighted_rating_sum.checked_sub(weighted))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    // Count a review's sub-ratings, if it gave any
+    pub fn add_sub_ratings(&mut self, sub_ratings: Option<SubRatings>) -> Result<(), ProgramError> {
+        let Some(ratings) = sub_ratings else {
+            return Ok(());
+        };
+        let add = |sum: u64, value: u64| sum.checked_add(value).ok_or(ProgramError::ArithmeticOverflow);
+        self.sub_rating_count = add(self.sub_rating_count, 1)?;
+        self.food_sum = add(self.food_sum, ratings.food as u64)?;
+        self.service_sum = add(self.service_sum, ratings.service as u64)?;
+        self.ambiance_sum = add(self.ambiance_sum, ratings.ambiance as u64)?;
+        Ok(())
+    }
+
+    // Remove previously counted sub-ratings
+    pub fn remove_sub_ratings(&mut self, sub_ratings: Option<SubRatings>) -> Result<(), ProgramError> {
+        let Some(ratings) = sub_ratings else {
+            return Ok(());
+        };
+        let sub = |sum: u64, value: u64| sum.checked_sub(value).ok_or(ProgramError::ArithmeticOverflow);
+        self.sub_rating_count = sub(self.sub_rating_count, 1)?;
+        self.food_sum = sub(self.food_sum, ratings.food as u64)?;
+        self.service_sum = sub(self.service_sum, ratings.service as u64)?;
+        self.ambiance_sum = sub(self.ambiance_sum, ratings.ambiance as u64)?;
+        Ok(())
+    }
+}
+
+impl Sealed for RestaurantAggregate {}
+
+impl IsInitialized for RestaurantAggregate {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Running total of SPL token tips a review has received in one mint
+// Stored in a PDA derived from [review_pda, "tips", mint]
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct TipLedger {
+    pub discriminator: [u8; 8], // Account type tag, always TipLedger::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA the tips were sent to
+    pub mint: Pubkey,           // Token mint of the tips
+    pub total: u64,             // Tokens tipped so far, in base units
+}
+
+impl TipLedger {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"tips\0\0\0\0";
+
+    // discriminator (8) + is_initialized (1) + review (32) + mint (32) + total (8)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 8;
+
+    // Deserialize a tip ledger, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for TipLedger {}
+
+impl IsInitialized for TipLedger {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Snapshot of a review as it was before an update
+// Stored in a PDA derived from [review_pda, "revision", index as little-endian u64];
+// the "revision" seed keeps these apart from comment PDAs
 #[derive(BorshSerialize, BorshDeserialize)]
-pub struct AccountState {
-    pub is_initialized: bool,  // Whether this account has been initialized
-    pub rating: u8,            // Restaurant rating (1-10)
-    pub description: String,   // Review description/comment
-    pub title: String,         // Restaurant name/title
+pub struct ReviewRevision {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewRevision::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA this revision belongs to
+    pub revision: u64,          // Index of this revision, starting at 0
+    pub rating: u8,             // Rating before the update
+    pub description: String,    // Description before the update
 }
 
-// Sealed trait implementation - required by Solana's Pack trait
-impl Sealed for AccountState {}
+impl ReviewRevision {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"revision";
 
-// Implement IsInitialized trait to check if account is ready to use
-impl IsInitialized for AccountState {
+    // Borsh-serialized size of a revision holding the given description
+    // discriminator (8) + is_initialized (1) + review (32) + revision (8)
+    // + rating (1) + description (4 + len)
+    pub fn get_account_size(description: &str) -> usize {
+        DISCRIMINATOR_LEN + 1 + 32 + 8 + 1 + (4 + description.len())
+    }
+
+    // Deserialize a revision, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewRevision {}
+
+impl IsInitialized for ReviewRevision {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A single reader's vote on a review
+// Stored in a PDA derived from [review_pda, voter]; the account existing
+// is what stops the same wallet from voting twice
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewVote {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewVote::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA that was voted on
+    pub voter: Pubkey,          // Wallet that voted
+    pub up: bool,               // true = upvote, false = downvote
+}
+
+impl ReviewVote {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"vote\0\0\0\0";
+
+    // discriminator (8) + is_initialized (1) + review (32) + voter (32) + up (1)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 1;
+
+    // Deserialize a vote, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewVote {}
+
+impl IsInitialized for ReviewVote {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// A single wallet's abuse report on a review
+// Stored in a PDA derived from ["report", review_pda, reporter]; like a
+// vote, the account existing stops the same wallet reporting twice
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewReport {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewReport::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA that was reported
+    pub reporter: Pubkey,       // Wallet that reported it
+}
+
+impl ReviewReport {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"report\0\0";
+
+    // discriminator (8) + is_initialized (1) + review (32) + reporter (32)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 32;
+
+    // Deserialize a report, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewReport {}
+
+impl IsInitialized for ReviewReport {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Number of abuse reports filed on a review
+// Stored in a PDA derived from ["reports", review_pda], created with the
+// first report
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewReportCount {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewReportCount::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA the reports are about
+    pub report_count: u64,      // Wallets that have reported it
+}
+
+impl ReviewReportCount {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"reports\0";
+
+    // discriminator (8) + is_initialized (1) + review (32) + report_count (8)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 8;
+
+    // Deserialize a report count, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewReportCount {}
+
+impl IsInitialized for ReviewReportCount {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// SOL a reviewer locked when adding a review, as a deterrent to spam
+// Stored in a PDA derived from ["stake", review_pda], which holds the stake
+// on top of its own rent; DeleteReview returns both to whoever paid the
+// review's rent, SlashReview sends them to the config's treasury
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewStake {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewStake::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA the stake is for
+    pub amount: u64,            // Lamports staked, on top of the rent
+}
+
+impl ReviewStake {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"stake\0\0\0";
+
+    // discriminator (8) + is_initialized (1) + review (32) + amount (8)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 8;
+
+    // Deserialize a stake escrow, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewStake {}
+
+impl IsInitialized for ReviewStake {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Fees paid by new reviews and claims, plus the rent of removed reviews,
+// collected until the admin withdraws them
+// Stored in a PDA derived from ["treasury"], created by SetReviewFee (or the
+// first RemoveReview or SlashReview); the fees sit on top of its rent, which
+// WithdrawTreasury never touches
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ProgramTreasury {
+    pub discriminator: [u8; 8], // Account type tag, always ProgramTreasury::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub total_fees: u64,        // Lamports collected over the treasury's lifetime
+    pub total_withdrawn: u64,   // Lamports the admin has withdrawn
+}
+
+impl ProgramTreasury {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"treasury";
+
+    // discriminator (8) + is_initialized (1) + total_fees (8) + total_withdrawn (8)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 8 + 8;
+
+    // Deserialize the treasury, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ProgramTreasury {}
+
+impl IsInitialized for ProgramTreasury {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Longest restaurant location accepted, in bytes
+pub const MAX_LOCATION_LEN: usize = 100;
+
+// A restaurant in the registry, created by the config admin
+// Stored in a PDA derived from ["restaurant", title_seed(normalize_title(name))]; when
+// the config is in strict mode only restaurants listed here can be reviewed
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Restaurant {
+    pub discriminator: [u8; 8], // Account type tag, always Restaurant::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub owner: Pubkey,          // Wallet the restaurant is registered to
+    pub name: String,           // Canonical name as registered, at most MAX_TITLE_LEN bytes
+    pub location: String,       // Free-form address or city, at most MAX_LOCATION_LEN bytes
+}
+
+impl Restaurant {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"restaurn";
+
+    // discriminator (8) + is_initialized (1) + owner (32)
+    // + name (4 + MAX_TITLE_LEN) + location (4 + MAX_LOCATION_LEN)
+    pub const SIZE: usize =
+        DISCRIMINATOR_LEN + 1 + 32 + (4 + MAX_TITLE_LEN) + (4 + MAX_LOCATION_LEN);
+
+    // Deserialize a registry entry, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for Restaurant {}
+
+impl IsInitialized for Restaurant {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Longest cuisine and website accepted in a restaurant profile, in bytes
+// The profile's address shares MAX_LOCATION_LEN with the registry
+pub const MAX_CUISINE_LEN: usize = 32;
+pub const MAX_WEBSITE_LEN: usize = 128;
+
+// Price ranges run from 1 ($) to MAX_PRICE_RANGE ($$$$)
+pub const MAX_PRICE_RANGE: u8 = 4;
+
+// Directory details for a registered restaurant, kept up to date by its owner
+// Stored in a PDA derived from ["profile", restaurant_pda]
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RestaurantProfile {
+    pub discriminator: [u8; 8], // Account type tag, always RestaurantProfile::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub restaurant: Pubkey,     // Registry PDA this profile describes
+    pub price_range: u8,        // 1 ($) to MAX_PRICE_RANGE ($$$$)
+    pub cuisine: String,        // e.g. "Neapolitan pizza", at most MAX_CUISINE_LEN bytes
+    pub address: String,        // Street address, at most MAX_LOCATION_LEN bytes
+    pub website: String,        // Website URL, empty if none, at most MAX_WEBSITE_LEN bytes
+}
+
+impl RestaurantProfile {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"profile\0";
+
+    // discriminator (8) + is_initialized (1) + restaurant (32) + price_range (1)
+    // + cuisine (4 + MAX_CUISINE_LEN) + address (4 + MAX_LOCATION_LEN)
+    // + website (4 + MAX_WEBSITE_LEN)
+    pub const SIZE: usize = DISCRIMINATOR_LEN
+        + 1
+        + 32
+        + 1
+        + (4 + MAX_CUISINE_LEN)
+        + (4 + MAX_LOCATION_LEN)
+        + (4 + MAX_WEBSITE_LEN);
+
+    // Deserialize a profile, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for RestaurantProfile {}
+
+impl IsInitialized for RestaurantProfile {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Hash of a review that hasn't been revealed yet
+// Stored in a PDA derived from ["commitment", author, commitment]; the
+// restaurant can't see what the review says, or which restaurant it is
+// about, until the author reveals it with RevealReview
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewCommitment {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewCommitment::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub author: Pubkey,         // Wallet that committed and alone can reveal
+    pub commitment: [u8; 32],   // review_commitment() of the hidden review
+}
+
+impl ReviewCommitment {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"commitmt";
+
+    // discriminator (8) + is_initialized (1) + author (32) + commitment (32)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 32;
+
+    // Deserialize a commitment, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewCommitment {}
+
+impl IsInitialized for ReviewCommitment {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Commitment to a review: SHA-256 of the Borsh-encoded
+// (author, title, rating, description, salt)
+// The random salt keeps short reviews from being guessed from the hash
+pub fn review_commitment(
+    author: &Pubkey,
+    title: &str,
+    rating: u8,
+    description: &str,
+    salt: &[u8; 32],
+) -> [u8; 32] {
+    let data = borsh::to_vec(&(author, title, rating, description, salt)).unwrap();
+    hash(&data).to_bytes()
+}
+
+// Verified owner of a restaurant, registered by the config admin
+// Stored in a PDA derived from ["verified_owner", title_seed(normalize_title(title))],
+// so it covers every review of the restaurant however its title is spelled
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct VerifiedOwner {
+    pub discriminator: [u8; 8], // Account type tag, always VerifiedOwner::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub owner: Pubkey,          // Wallet allowed to respond to the restaurant's reviews
+}
+
+impl VerifiedOwner {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"vowner\0\0";
+
+    // discriminator (8) + is_initialized (1) + owner (32)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32;
+
+    // Deserialize a verified owner entry, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for VerifiedOwner {}
+
+impl IsInitialized for VerifiedOwner {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Where a restaurant claim stands
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClaimStatus {
+    Pending,  // Fee escrowed, waiting for the admin
+    Approved, // Claimant became the verified owner, fee moved to the treasury
+    Rejected, // Fee refunded; the restaurant can be claimed again
+}
+
+// A wallet's request to become the verified owner of a registered restaurant
+// Stored in a PDA derived from ["claim", title_seed(normalize_title(name))],
+// so a restaurant has at most one claim at a time; the escrowed fee sits
+// on top of the account's rent until the admin approves or rejects it
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RestaurantClaim {
+    pub discriminator: [u8; 8], // Account type tag, always RestaurantClaim::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub restaurant: Pubkey,     // Restaurant registry PDA being claimed
+    pub claimant: Pubkey,       // Wallet asking to be the owner
+    pub fee: u64,               // Lamports escrowed by the claim
+    pub status: ClaimStatus,    // Pending until the admin decides
+}
+
+impl RestaurantClaim {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"rstclaim";
+
+    // discriminator (8) + is_initialized (1) + restaurant (32) + claimant (32)
+    // + fee (8) + status (1)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + 8 + 1;
+
+    // Deserialize a claim, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for RestaurantClaim {}
+
+impl IsInitialized for RestaurantClaim {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+// Longest owner response accepted, in bytes
+pub const MAX_RESPONSE_LEN: usize = 500;
+
+// The restaurant owner's reply to a review
+// Stored in a PDA derived from ["response", review_pda]; it is created at
+// full size so later replies overwrite it in place
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReviewResponse {
+    pub discriminator: [u8; 8], // Account type tag, always ReviewResponse::DISCRIMINATOR
+    pub is_initialized: bool,   // Whether this account has been initialized
+    pub review: Pubkey,         // Review PDA being answered
+    pub responder: Pubkey,      // Verified owner who wrote the reply
+    pub response: String,       // Reply text, at most MAX_RESPONSE_LEN bytes
+}
+
+impl ReviewResponse {
+    pub const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = *b"response";
+
+    // discriminator (8) + is_initialized (1) + review (32) + responder (32)
+    // + response (4 + MAX_RESPONSE_LEN)
+    pub const SIZE: usize = DISCRIMINATOR_LEN + 1 + 32 + 32 + (4 + MAX_RESPONSE_LEN);
+
+    // Deserialize a response, rejecting accounts of any other type
+    pub fn unpack_checked(data: &[u8]) -> Result<Self, ProgramError> {
+        check_discriminator(data, &Self::DISCRIMINATOR)?;
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+impl Sealed for ReviewResponse {}
+
+impl IsInitialized for ReviewResponse {
     fn is_initialized(&self) -> bool {
         self.is_initialized
     }
 }
 
+// Whether text is free of characters that break logs and rendering:
+// control characters (newlines, escapes, NUL) and the bidirectional
+// overrides and isolates that can make text display in a different order
+// than it is stored. Strings are already valid UTF-8 by the time they get
+// here, since Borsh rejects invalid byte sequences when decoding them
+pub fn is_printable_text(text: &str) -> bool {
+    !text
+        .chars()
+        .any(|c| c.is_control() || matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'))
+}
+
+// Number of user-perceived characters in text: "e" plus a combining
+// accent, or a flag made of two regional indicators, counts once
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+// Canonical form of a restaurant title used for aggregate seeds:
+// trimmed, lowercased, with runs of whitespace collapsed to one space
+pub fn normalize_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// PDA seed for a title: the SHA-256 hash of its bytes
+// A seed can be at most 32 bytes but titles can be up to MAX_TITLE_LEN,
+// so every title-based address uses the hash; the full title stays in state
+pub fn title_seed(title: &str) -> [u8; 32] {
+    hash(title.as_bytes()).to_bytes()
+}
+
+// PDA seed for a review's title: the title is normalized first, so
+// "Pizza Place" and "pizza place " are the same review; the title as
+// typed is what's stored
+pub fn review_seed(title: &str) -> [u8; 32] {
+    title_seed(&normalize_title(title))
+}
+
 // Custom error types for this program
 // The #[error(...)] attributes provide user-friendly error messages
 #[derive(Debug, Error)]
@@ -34,8 +1709,189 @@ pub enum ReviewError {
     #[error("PDA derived does not equal PDA passed in")]
     InvalidPDA,
 
-    #[error("Rating greater than 10 or less than 1")]
+    #[error("Rating outside the configured scale")]
     InvalidRating,
+
+    #[error("Review data exceeds the maximum account size")]
+    DataTooLarge,
+
+    // No longer returned, see InvalidAccountType; kept so later error codes don't shift
+    #[error("Account discriminator does not match the expected account type")]
+    InvalidDiscriminator,
+
+    #[error("Account is already initialized")]
+    AlreadyInitialized,
+
+    #[error("Tip amount must be greater than zero")]
+    InvalidTipAmount,
+
+    #[error("Token account does not belong to the expected owner or mint")]
+    InvalidTokenAccount,
+
+    #[error("Wallet has already voted on this review")]
+    AlreadyVoted,
+
+    #[error("Title is longer than the maximum allowed length")]
+    TitleTooLong,
+
+    #[error("Description is longer than the maximum allowed length")]
+    DescriptionTooLong,
+
+    #[error("Account is not rent exempt")]
+    NotRentExempt,
+
+    #[error("Account is not of the expected type")]
+    InvalidAccountType,
+
+    #[error("Signer is not the owner of this review")]
+    NotReviewOwner,
+
+    #[error("Signer is not a moderator")]
+    NotModerator,
+
+    #[error("Too many moderators")]
+    TooManyModerators,
+
+    #[error("Only visible reviews can be flagged")]
+    ReviewNotVisible,
+
+    #[error("Review has more than the maximum number of tags")]
+    TooManyTags,
+
+    #[error("Tag is empty or longer than the maximum allowed length")]
+    InvalidTag,
+
+    #[error("Content URI is longer than the maximum allowed length")]
+    ContentUriTooLong,
+
+    #[error("Content URI must start with an allowed prefix")]
+    InvalidContentUri,
+
+    #[error("Signer is not the config admin")]
+    NotAdmin,
+
+    #[error("Signer is not the verified owner of this restaurant")]
+    NotRestaurantOwner,
+
+    #[error("Response is longer than the maximum allowed length")]
+    ResponseTooLong,
+
+    #[error("Location is longer than the maximum allowed length")]
+    LocationTooLong,
+
+    #[error("Restaurant is not in the registry")]
+    RestaurantNotRegistered,
+
+    #[error("Rent payer account does not match the review")]
+    InvalidRentPayer,
+
+    #[error("Review was updated too recently")]
+    UpdateTooSoon,
+
+    #[error("Review has been deleted by its owner")]
+    ReviewArchived,
+
+    #[error("Account uses an older layout and must be migrated")]
+    OutdatedAccount,
+
+    #[error("No badge is awarded at this review count")]
+    InvalidMilestone,
+
+    #[error("Reviewer does not have enough live reviews for this badge")]
+    MilestoneNotReached,
+
+    #[error("Badge has already been claimed")]
+    BadgeAlreadyClaimed,
+
+    #[error("Multisig threshold or signer list is invalid")]
+    InvalidMultisig,
+
+    #[error("Not enough multisig members signed")]
+    NotEnoughSigners,
+
+    #[error("Profile field is longer than the maximum allowed length")]
+    ProfileFieldTooLong,
+
+    #[error("Price range must be between 1 and 4")]
+    InvalidPriceRange,
+
+    #[error("A review for this restaurant already exists; update it instead")]
+    AccountAlreadyInitialized,
+
+    #[error("Review account must be passed as writable")]
+    ReviewNotWritable,
+
+    #[error("Review account is too small to hold a review")]
+    ReviewAccountTooSmall,
+
+    #[error("Review owner account cannot be an executable program")]
+    ExecutableOwner,
+
+    #[error("Rating scale minimum must be at least 1 and below the maximum")]
+    InvalidRatingScale,
+
+    #[error("Rating scale can't change once reviews exist")]
+    RatingScaleLocked,
+
+    #[error("Revealed review does not match the commitment")]
+    CommitmentMismatch,
+
+    #[error("Wallet has already reported this review")]
+    AlreadyReported,
+
+    #[error("Review has no stake to slash")]
+    NoStake,
+
+    #[error("Account is not the configured treasury")]
+    InvalidTreasury,
+
+    #[error("Sub-ratings must be between 1 and 10")]
+    InvalidSubRating,
+
+    #[error("Visit date is in the future")]
+    VisitInFuture,
+
+    #[error("Treasury holds less than the amount requested")]
+    InsufficientTreasuryFunds,
+
+    #[error("Signer is not the nominated admin")]
+    NotPendingAdmin,
+
+    #[error("Program is paused")]
+    ProgramPaused,
+
+    #[error("Display name is empty, too long, or has characters other than letters, digits, '_', '-' and '.'")]
+    InvalidDisplayName,
+
+    #[error("Text contains control or bidirectional formatting characters")]
+    InvalidCharacters,
+
+    #[error("Follow-up reviews can't be edited")]
+    FollowUpReadOnly,
+
+    #[error("A wallet can't follow itself")]
+    CannotFollowSelf,
+
+    #[error("Wallet already follows this reviewer")]
+    AlreadyFollowing,
+
+    #[error("Restaurant is already bookmarked")]
+    AlreadyBookmarked,
+
+    #[error("Restaurant is not bookmarked")]
+    NotBookmarked,
+
+    #[error("Bookmark list is full")]
+    TooManyBookmarks,
+
+    #[error("Restaurant already has a verified owner")]
+    RestaurantAlreadyVerified,
+
+    #[error("Restaurant already has a pending claim")]
+    ClaimPending,
+
+    #[error("Claim is not pending")]
+    ClaimNotPending,
 }
 
 // Convert our custom errors into Solana's ProgramError type
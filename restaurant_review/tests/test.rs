@@ -1,20 +1,45 @@
 // Integration tests for the restaurant review Solana program
 // These tests use solana-program-test to simulate on-chain behavior
 
+use base64::{prelude::BASE64_STANDARD, Engine};
 use borsh::BorshDeserialize;
-use borsh::BorshSerialize;
-use review::state::AccountState;
-use solana_program::instruction::{AccountMeta, Instruction};
+use review::client::{
+    accept_admin_ix, add_anonymous_review_ix, bookmark_restaurant_ix, bookmarks_pda, add_follow_up_review_ix, add_review_for_restaurant_ix, add_review_if_not_exists_ix, add_review_ix, aggregate_pda, approve_claim_ix, archive_review_ix, badge_account_pda, badge_mint_pda, claim_badge_ix, claim_pda, claim_restaurant_ix,
+    add_comment_ix, add_compressed_review_ix, comment_counter_pda, commitment_pda, config_pda, create_multisig_ix, create_profile_ix, decode_event_log,
+    delete_review_ix, delete_sponsored_review_ix, flag_review_ix, follow_ix, follow_pda, follow_up_review_pda, followups_pda, sponsored_add_review_ix,
+    initialize_config_ix, initialize_stats_ix, migrate_account_ix, migrate_review_ix, nominate_admin_ix, register_restaurant_ix, resolve_flag_ix,
+    recompute_aggregate_ix, reject_claim_ix, remove_bookmark_ix, remove_review_ix, report_count_pda, report_review_ix, respond_to_review_ix, response_pda, reveal_review_ix, restaurant_pda, restaurant_profile_pda, restaurant_review_pda, review_stake_pda, reviewer_index_pda, reviewer_profile_pda, reviewer_record_pda, revision_pda,
+    set_claim_fee_ix, set_display_name_ix, set_paused_ix, set_report_threshold_ix, set_review_fee_ix, set_review_stake_ix, slash_review_ix, set_strict_registry_ix, set_update_cooldown_ix, signed_by_multisig, stats_pda, tip_ledger_pda, tip_reviewer_token_ix, treasury_pda,
+    transfer_review_ownership_ix, unfollow_ix, update_config_ix, update_profile_ix, update_review_ix, upsert_review_ix,
+    verified_owner_pda, verify_restaurant_owner_ix, vote_review_ix, withdraw_treasury_ix,
+};
+use review::instruction::ReviewReturnData;
+use review::events::{Event, ReviewAdded, ReviewDeleted, ReviewEvent, ReviewRemoved, ReviewUpdated};
+use review::state::{
+    AccountState, ClaimStatus, FollowUpCounter, ProgramConfig, ProgramStats, ProgramTreasury, Restaurant, RestaurantAggregate, RestaurantBookmarks, RestaurantClaim, RestaurantProfile, ReviewComment, ReviewCommentCounter,
+    ReviewCommitment, ReviewError, ReviewReportCount, ReviewResponse, ReviewRevision, ReviewStake, ReviewStatus, ReviewZeroCopy, ReviewerFollow, ReviewerIndex, ReviewerProfile, ReviewerRecord,
+    SubRatings, TipLedger, VerifiedOwner, decay, grapheme_count, helpfulness, review_commitment, review_seed, MAX_DESCRIPTION_LEN, MAX_CONTENT_URI_LEN, MAX_RESPONSE_LEN, MAX_TAGS,
+    DECAY_HALF_LIFE, DECAY_SCALE, MAX_DISPLAY_NAME_LEN, MAX_TAG_LEN, MAX_TITLE_LEN, MAX_DESCRIPTION_GRAPHEMES, MAX_TITLE_GRAPHEMES,
+};
+use solana_program::rent::Rent;
+use solana_program::clock::Clock;
+use solana_program::hash::Hash;
+use solana_program::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_program::program_pack::Pack;
 use solana_program::pubkey::Pubkey;
-use solana_program_test::{processor, tokio, ProgramTest};
+use solana_program_test::{processor, tokio, BanksClient, ProgramTest};
 use solana_sdk::signature::{Keypair, Signer};
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, TransactionError};
 use solana_system_interface::instruction as system_instruction;
+use spl_token::state::{Account as TokenAccount, Mint};
 use std::str::FromStr;
 
 // System program ID constant - used for account creation and transfers
 const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
 
+// Visit date for test reviews (November 2023), safely before the test clock
+const VISITED_AT: i64 = 1_700_000_000;
+
 /// Payload structure for AddReview instruction
 /// Must match the structure expected by ReviewInstruction::unpack in instruction.rs
 #[derive(borsh::BorshSerialize)]
@@ -22,6 +47,11 @@ struct AddReviewPayload {
     title: String,
     rating: u8,
     description: String,
+    tags: Vec<String>,
+    content_uri: String,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
 }
 
 /// Payload structure for UpdateReview instruction
@@ -31,6 +61,11 @@ struct UpdateReviewPayload {
     title: String,
     rating: u8,
     description: String,
+    tags: Vec<String>,
+    content_uri: String,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
 }
 
 /// Helper function to create instruction data for AddReview
@@ -43,6 +78,11 @@ fn create_add_review_instruction_data(title: &str, rating: u8, description: &str
         title: title.to_string(),
         rating,
         description: description.to_string(),
+        tags: Vec::new(),
+        content_uri: String::new(),
+        sub_ratings: None,
+        visited_at: VISITED_AT,
+        price_range: 2,
     };
     
     // Append the Borsh-serialized payload after the variant byte
@@ -60,6 +100,11 @@ fn create_update_review_instruction_data(title: &str, rating: u8, description: &
         title: title.to_string(),
         rating,
         description: description.to_string(),
+        tags: Vec::new(),
+        content_uri: String::new(),
+        sub_ratings: None,
+        visited_at: VISITED_AT,
+        price_range: 2,
     };
     
     // Append the Borsh-serialized payload after the variant byte
@@ -67,6 +112,68 @@ fn create_update_review_instruction_data(title: &str, rating: u8, description: &
     data
 }
 
+/// Payload structure for DeleteReview instruction
+/// Must match the structure expected by ReviewInstruction::unpack in instruction.rs
+#[derive(borsh::BorshSerialize)]
+struct DeleteReviewPayload {
+    title: String,
+}
+
+/// Helper function to create instruction data for DeleteReview
+/// Format: [variant_byte: 2][borsh_serialized_payload]
+/// The variant byte (2) indicates this is a DeleteReview instruction
+fn create_delete_review_instruction_data(title: &str) -> Vec<u8> {
+    let mut data = vec![2u8]; // Variant 2 for DeleteReview
+
+    let payload = DeleteReviewPayload {
+        title: title.to_string(),
+    };
+
+    // Append the Borsh-serialized payload after the variant byte
+    data.extend_from_slice(&borsh::to_vec(&payload).unwrap());
+    data
+}
+
+/// Helper function to create the program stats account
+/// AddReview and UpdateReview both require it, so every test runs this first
+async fn initialize_stats(
+    banks_client: &BanksClient,
+    payer: &Keypair,
+    program_id: Pubkey,
+    recent_blockhash: Hash,
+) {
+    let instruction = initialize_stats_ix(&program_id, &payer.pubkey());
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+/// Payload structure for AddComment instruction
+/// Must match the structure expected by ReviewInstruction::unpack in instruction.rs
+#[derive(borsh::BorshSerialize)]
+struct CommentPayload {
+    comment: String,
+}
+
+/// Helper function to create instruction data for AddComment
+/// Format: [variant_byte: 3][borsh_serialized_payload]
+/// The variant byte (3) indicates this is an AddComment instruction
+fn create_add_comment_instruction_data(comment: &str) -> Vec<u8> {
+    let mut data = vec![3u8]; // Variant 3 for AddComment
+
+    let payload = CommentPayload {
+        comment: comment.to_string(),
+    };
+
+    // Append the Borsh-serialized payload after the variant byte
+    data.extend_from_slice(&borsh::to_vec(&payload).unwrap());
+    data
+}
+
 /// TEST 1: Successfully add a restaurant review
 /// 
 /// This test verifies the complete happy path for adding a review:
@@ -83,7 +190,7 @@ async fn test_add_review_success() {
     // Setup program and accounts
     // The program ID must be a valid base58-encoded public key
     let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
-    let mut program_test = ProgramTest::new(
+    let program_test = ProgramTest::new(
         "review",                              // Program name
         program_id,                            // Program ID
         processor!(review::process_instruction), // Entry point function
@@ -91,7 +198,8 @@ async fn test_add_review_success() {
     
     // Start test environment - creates a local validator with our program deployed
     // Returns: banks_client (for transactions), payer (funded account), recent_blockhash
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
     
     // Create a new keypair for the user who will write the review
     let reviewer = Keypair::new();
@@ -102,7 +210,7 @@ async fn test_add_review_success() {
         &[system_instruction::transfer(
             &payer.pubkey(),
             &reviewer.pubkey(),
-            10_000_000, // 0.01 SOL (lamports)
+            100_000_000, // 0.1 SOL (lamports)
         )],
         Some(&payer.pubkey()),  // Fee payer
         &[&payer],              // Signers
@@ -118,8 +226,8 @@ async fn test_add_review_success() {
     // Derive PDA for this review
     // The PDA is derived from [reviewer_pubkey, title] ensuring each user
     // can only have one review per restaurant title
-    let (pda, _bump) = Pubkey::find_program_address(
-        &[reviewer.pubkey().as_ref(), title.as_bytes()],
+    let (pda, bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
         &program_id,
     );
     
@@ -134,6 +242,17 @@ async fn test_add_review_success() {
             AccountMeta::new(reviewer.pubkey(), true),  // Reviewer (signer, pays rent)
             AccountMeta::new(pda, false),                // PDA account to create (writable)
             AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false), // System program
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
         ],
     );
     
@@ -161,12 +280,13 @@ async fn test_add_review_success() {
     
     // Deserialize and verify the stored data
     // Use deserialize() instead of try_from_slice() to handle accounts
-    // larger than the serialized data (account is 1000 bytes, data is smaller)
-    let account_state = AccountState::deserialize(&mut &account.data[..]).unwrap();
-    assert_eq!(account_state.is_initialized, true);
+    // larger than the serialized data (the account includes spare headroom)
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert!(account_state.is_initialized);
     assert_eq!(account_state.title, title);
     assert_eq!(account_state.rating, rating);
     assert_eq!(account_state.description, description);
+    assert_eq!(account_state.bump, bump); // Canonical bump saved for later checks
 }
 
 /// TEST 2: Reject review with rating too high
@@ -179,13 +299,14 @@ async fn test_add_review_invalid_rating_too_high() {
     solana_logger::setup_with_default("solana_runtime::message=debug");
     
     let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
-    let mut program_test = ProgramTest::new(
+    let program_test = ProgramTest::new(
         "review",
         program_id,
         processor!(review::process_instruction),
     );
     
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
     let reviewer = Keypair::new();
     
     // Fund the reviewer account
@@ -193,7 +314,7 @@ async fn test_add_review_invalid_rating_too_high() {
         &[system_instruction::transfer(
             &payer.pubkey(),
             &reviewer.pubkey(),
-            10_000_000,
+            100_000_000,
         )],
         Some(&payer.pubkey()),
         &[&payer],
@@ -206,7 +327,7 @@ async fn test_add_review_invalid_rating_too_high() {
     let description = "Test description";
     
     let (pda, _bump) = Pubkey::find_program_address(
-        &[reviewer.pubkey().as_ref(), title.as_bytes()],
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
         &program_id,
     );
     
@@ -219,6 +340,17 @@ async fn test_add_review_invalid_rating_too_high() {
             AccountMeta::new(reviewer.pubkey(), true),
             AccountMeta::new(pda, false),
             AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
         ],
     );
     
@@ -244,13 +376,14 @@ async fn test_add_review_invalid_rating_too_low() {
     solana_logger::setup_with_default("solana_runtime::message=debug");
     
     let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
-    let mut program_test = ProgramTest::new(
+    let program_test = ProgramTest::new(
         "review",
         program_id,
         processor!(review::process_instruction),
     );
     
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
     let reviewer = Keypair::new();
     
     // Fund the reviewer account
@@ -258,7 +391,7 @@ async fn test_add_review_invalid_rating_too_low() {
         &[system_instruction::transfer(
             &payer.pubkey(),
             &reviewer.pubkey(),
-            10_000_000,
+            100_000_000,
         )],
         Some(&payer.pubkey()),
         &[&payer],
@@ -271,7 +404,7 @@ async fn test_add_review_invalid_rating_too_low() {
     let description = "Test description";
     
     let (pda, _bump) = Pubkey::find_program_address(
-        &[reviewer.pubkey().as_ref(), title.as_bytes()],
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
         &program_id,
     );
     
@@ -284,6 +417,17 @@ async fn test_add_review_invalid_rating_too_low() {
             AccountMeta::new(reviewer.pubkey(), true),
             AccountMeta::new(pda, false),
             AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
         ],
     );
     
@@ -307,18 +451,20 @@ async fn test_add_review_invalid_rating_too_low() {
 /// 2. User later updates the review with new rating and description
 /// 3. The title cannot be changed (it's part of the PDA derivation)
 /// 4. Only the original reviewer can update (verified via PDA seeds)
+/// 5. The previous version is kept as a ReviewRevision account
 #[tokio::test]
 async fn test_update_review_success() {
     solana_logger::setup_with_default("solana_runtime::message=debug");
     
     let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
-    let mut program_test = ProgramTest::new(
+    let program_test = ProgramTest::new(
         "review",
         program_id,
         processor!(review::process_instruction),
     );
     
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
     let reviewer = Keypair::new();
     
     // Fund the reviewer account
@@ -326,7 +472,7 @@ async fn test_update_review_success() {
         &[system_instruction::transfer(
             &payer.pubkey(),
             &reviewer.pubkey(),
-            10_000_000,
+            100_000_000,
         )],
         Some(&payer.pubkey()),
         &[&payer],
@@ -340,7 +486,7 @@ async fn test_update_review_success() {
     
     // Derive the same PDA for both add and update operations
     let (pda, _bump) = Pubkey::find_program_address(
-        &[reviewer.pubkey().as_ref(), title.as_bytes()],
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
         &program_id,
     );
     
@@ -354,6 +500,17 @@ async fn test_update_review_success() {
             AccountMeta::new(reviewer.pubkey(), true),
             AccountMeta::new(pda, false),
             AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
         ],
     );
     
@@ -372,14 +529,20 @@ async fn test_update_review_success() {
     
     let update_instruction_data = create_update_review_instruction_data(title, updated_rating, updated_description);
     
-    // Note: Update instruction doesn't need System Program account
-    // The PDA already exists, we're just modifying its data
+    // Note: Update instruction needs the System Program account
+    // in case the new description grows the PDA and more rent is owed
     let update_instruction = Instruction::new_with_bytes(
         program_id,
         &update_instruction_data,
         vec![
             AccountMeta::new(reviewer.pubkey(), true),  // Original reviewer (must sign)
             AccountMeta::new(pda, false),                // Existing PDA account (writable)
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false), // System program
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new(revision_pda(&pda, 0, &program_id), false), // Revision PDA for the old version
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
         ],
     );
     
@@ -399,11 +562,24 @@ async fn test_update_review_success() {
         .unwrap()
         .expect("PDA account should exist");
     
-    let account_state = AccountState::deserialize(&mut &account.data[..]).unwrap();
-    assert_eq!(account_state.is_initialized, true);
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert!(account_state.is_initialized);
     assert_eq!(account_state.title, title); // Title doesn't change (part of PDA seeds)
     assert_eq!(account_state.rating, updated_rating);
     assert_eq!(account_state.description, updated_description);
+    assert_eq!(account_state.revision_count, 1);
+
+    // STEP 4: The previous version was saved as revision 0
+    let account = banks_client
+        .get_account(revision_pda(&pda, 0, &program_id))
+        .await
+        .unwrap()
+        .expect("Revision account should exist");
+    let revision = ReviewRevision::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(revision.review, pda);
+    assert_eq!(revision.revision, 0);
+    assert_eq!(revision.rating, initial_rating);
+    assert_eq!(revision.description, initial_description);
 }
 
 /// TEST 5: Prevent unauthorized updates
@@ -417,13 +593,14 @@ async fn test_update_review_wrong_reviewer_fails() {
     solana_logger::setup_with_default("solana_runtime::message=debug");
     
     let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
-    let mut program_test = ProgramTest::new(
+    let program_test = ProgramTest::new(
         "review",
         program_id,
         processor!(review::process_instruction),
     );
     
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
     
     let original_reviewer = Keypair::new();
     let malicious_user = Keypair::new(); // Different user attempting unauthorized update
@@ -433,7 +610,7 @@ async fn test_update_review_wrong_reviewer_fails() {
         &[system_instruction::transfer(
             &payer.pubkey(),
             &original_reviewer.pubkey(),
-            10_000_000,
+            100_000_000,
         )],
         Some(&payer.pubkey()),
         &[&payer],
@@ -448,7 +625,7 @@ async fn test_update_review_wrong_reviewer_fails() {
     // PDA derived from ORIGINAL reviewer's pubkey
     // This creates a unique address owned by the original reviewer
     let (pda, _bump) = Pubkey::find_program_address(
-        &[original_reviewer.pubkey().as_ref(), title.as_bytes()],
+        &[original_reviewer.pubkey().as_ref(), &review_seed(title)],
         &program_id,
     );
     
@@ -462,6 +639,17 @@ async fn test_update_review_wrong_reviewer_fails() {
             AccountMeta::new(original_reviewer.pubkey(), true),
             AccountMeta::new(pda, false),
             AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&original_reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&original_reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
         ],
     );
     
@@ -485,6 +673,12 @@ async fn test_update_review_wrong_reviewer_fails() {
         vec![
             AccountMeta::new(malicious_user.pubkey(), true), // Wrong signer!
             AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new(revision_pda(&pda, 0, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
         ],
     );
     
@@ -508,7 +702,7 @@ async fn test_update_review_wrong_reviewer_fails() {
         .unwrap()
         .expect("PDA account should exist");
     
-    let account_state = AccountState::deserialize(&mut &account.data[..]).unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
     assert_eq!(account_state.description, description); // Still original description
 }
 
@@ -523,13 +717,14 @@ async fn test_multiple_reviews_same_user_different_restaurants() {
     solana_logger::setup_with_default("solana_runtime::message=debug");
     
     let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
-    let mut program_test = ProgramTest::new(
+    let program_test = ProgramTest::new(
         "review",
         program_id,
         processor!(review::process_instruction),
     );
     
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
     let reviewer = Keypair::new();
     
     // Fund the reviewer account (needs sufficient lamports for multiple PDA creations)
@@ -537,7 +732,7 @@ async fn test_multiple_reviews_same_user_different_restaurants() {
         &[system_instruction::transfer(
             &payer.pubkey(),
             &reviewer.pubkey(),
-            30_000_000, // More funds for multiple transactions
+            300_000_000, // More funds for multiple transactions
         )],
         Some(&payer.pubkey()),
         &[&payer],
@@ -547,7 +742,7 @@ async fn test_multiple_reviews_same_user_different_restaurants() {
     
     // Three different restaurants to review
     // Each title is a seed component, so each generates a unique PDA
-    let reviews = vec![
+    let reviews = [
         ("Pizza Place", 9u8, "Great pizza"),
         ("Burger Joint", 7u8, "Good burgers"),
         ("Sushi Bar", 10u8, "Best sushi ever"),
@@ -556,7 +751,7 @@ async fn test_multiple_reviews_same_user_different_restaurants() {
     // Create a review for each restaurant (same user, different titles = different PDAs)
     for (title, rating, description) in reviews.iter() {
         let (pda, _bump) = Pubkey::find_program_address(
-            &[reviewer.pubkey().as_ref(), title.as_bytes()],
+            &[reviewer.pubkey().as_ref(), &review_seed(title)],
             &program_id,
         );
         
@@ -569,6 +764,17 @@ async fn test_multiple_reviews_same_user_different_restaurants() {
                 AccountMeta::new(reviewer.pubkey(), true),
                 AccountMeta::new(pda, false),
                 AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+                AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+                AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+                AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+                AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+                AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+                AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+                AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+                AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+                AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+                AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
             ],
         );
         
@@ -583,7 +789,7 @@ async fn test_multiple_reviews_same_user_different_restaurants() {
         
         // Verify each review is stored correctly with unique PDA
         let account = banks_client.get_account(pda).await.unwrap().unwrap();
-        let account_state = AccountState::deserialize(&mut &account.data[..]).unwrap();
+        let account_state = AccountState::unpack(&account.data).unwrap();
         assert_eq!(account_state.title, *title);
         assert_eq!(account_state.rating, *rating);
         assert_eq!(account_state.description, *description);
@@ -602,13 +808,14 @@ async fn test_cannot_add_duplicate_review() {
     solana_logger::setup_with_default("solana_runtime::message=debug");
     
     let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
-    let mut program_test = ProgramTest::new(
+    let program_test = ProgramTest::new(
         "review",
         program_id,
         processor!(review::process_instruction),
     );
     
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
     let reviewer = Keypair::new();
     
     // Fund the reviewer account with enough lamports for multiple attempts
@@ -630,7 +837,7 @@ async fn test_cannot_add_duplicate_review() {
     
     // Same PDA will be derived for both attempts since same user + same title
     let (pda, _bump) = Pubkey::find_program_address(
-        &[reviewer.pubkey().as_ref(), title.as_bytes()],
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
         &program_id,
     );
     
@@ -644,6 +851,17 @@ async fn test_cannot_add_duplicate_review() {
             AccountMeta::new(reviewer.pubkey(), true),
             AccountMeta::new(pda, false),
             AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
         ],
     );
     
@@ -668,6 +886,17 @@ async fn test_cannot_add_duplicate_review() {
             AccountMeta::new(reviewer.pubkey(), true),
             AccountMeta::new(pda, false), // Same PDA as before
             AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
         ],
     );
     
@@ -681,8 +910,6138 @@ async fn test_cannot_add_duplicate_review() {
     // Should fail because:
     // 1. Same reviewer + same title = same PDA
     // 2. That PDA account is already initialized
-    // 3. Solana prevents re-initializing an account
+    // 3. add_review refuses to re-initialize it
     // Result: Duplicate reviews are impossible; users must use update instead
     let result = banks_client.process_transaction(transaction2).await;
-    assert!(result.is_err(), "Should not allow duplicate review for same restaurant");
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::AccountAlreadyInitialized as u32)
+        ),
+        "Should not allow duplicate review for same restaurant"
+    );
+}
+
+/// TEST 8: Delete a review and reclaim its rent
+///
+/// The reviewer closes their review PDA:
+/// 1. The PDA account no longer exists after the transaction
+/// 2. The rent lamports held by the PDA are returned to the reviewer
+#[tokio::test]
+async fn test_delete_review_success() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "Closing Soon Diner";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+
+    // STEP 1: Add the review
+    let add_instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 6, "Decent breakfast"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let pda_lamports = banks_client.get_balance(pda).await.unwrap();
+    let counter_lamports = banks_client
+        .get_balance(comment_counter_pda(&pda, &program_id))
+        .await
+        .unwrap();
+    let index_pda = reviewer_index_pda(&reviewer.pubkey(), &program_id);
+    let index_before = banks_client.get_balance(index_pda).await.unwrap();
+    let reviewer_before = banks_client.get_balance(reviewer.pubkey()).await.unwrap();
+
+    // STEP 2: Delete the review
+    let delete_instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_delete_review_instruction_data(title),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),  // Original reviewer (signer, receives rent)
+            AccountMeta::new(pda, false),                // Review PDA to close
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA to close
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 3: The PDA and its comment counter are gone and the reviewer
+    // got the rent back, including what the shrunk reviewer index freed
+    // (the transaction fee was paid by `payer`, not the reviewer)
+    let account = banks_client.get_account(pda).await.unwrap();
+    assert!(account.is_none(), "Review PDA should be closed");
+    let counter = banks_client.get_account(comment_counter_pda(&pda, &program_id)).await.unwrap();
+    assert!(counter.is_none(), "Comment counter should be closed");
+
+    let index_refund = index_before - banks_client.get_balance(index_pda).await.unwrap();
+    let reviewer_after = banks_client.get_balance(reviewer.pubkey()).await.unwrap();
+    assert_eq!(reviewer_after, reviewer_before + pda_lamports + counter_lamports + index_refund);
+}
+
+/// TEST 9: Prevent unauthorized deletes
+///
+/// Like updates, deletes re-derive the PDA from the signer's pubkey, so a
+/// different user can't close someone else's review and take its rent.
+#[tokio::test]
+async fn test_delete_review_wrong_reviewer_fails() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+
+    let original_reviewer = Keypair::new();
+    let malicious_user = Keypair::new();
+
+    // Fund the original reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &original_reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "Protected Bistro";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[original_reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+
+    // STEP 1: Original reviewer creates the review
+    let add_instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 8, "Lovely"),
+        vec![
+            AccountMeta::new(original_reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&original_reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&original_reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &original_reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Malicious user tries to delete it (should fail)
+    let delete_instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_delete_review_instruction_data(title),
+        vec![
+            AccountMeta::new(malicious_user.pubkey(), true), // Wrong signer!
+            AccountMeta::new(pda, false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new(reviewer_index_pda(&original_reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &malicious_user],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Should not allow different user to delete review");
+
+    // The review is still there
+    let account = banks_client.get_account(pda).await.unwrap();
+    assert!(account.is_some(), "Review PDA should still exist");
+}
+
+/// TEST 10: Review accounts use a fixed layout
+///
+/// Every review is AccountState::LEN bytes regardless of its content, with
+/// is_initialized and rating at stable offsets for memcmp filters.
+#[tokio::test]
+async fn test_add_review_fixed_layout() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "Tiny Taqueria";
+    let description = "Short and sweet";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 8, description),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), AccountState::LEN);
+    assert_eq!(account.data[AccountState::IS_INITIALIZED_OFFSET], 1);
+    assert_eq!(
+        account.data[AccountState::RATING_OFFSET],
+        AccountState::unpack(&account.data).unwrap().rating
+    );
+}
+
+/// TEST 11: Reject reviews with an oversized description
+///
+/// A description over MAX_DESCRIPTION_LEN is rejected up front with
+/// DescriptionTooLong instead of failing during serialization.
+#[tokio::test]
+async fn test_add_review_too_large_fails() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "Verbose Bistro";
+    let description = "a".repeat(MAX_DESCRIPTION_LEN + 1);
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 5, &description),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+
+    let result = banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Transaction should fail when the review is too large");
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::DescriptionTooLong as u32)
+        )
+    );
+}
+
+/// TEST 12: Updating a review never resizes its account
+///
+/// The description has a fixed capacity, so growing it to the maximum and
+/// shrinking it again leaves the PDA's size and rent untouched.
+#[tokio::test]
+async fn test_update_review_keeps_fixed_size() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let rent = Rent::default();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "Growing Grill";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    // Each update saves the previous version at the next revision index
+    let update_accounts = |revision: u64| {
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new(revision_pda(&pda, revision, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ]
+    };
+
+    // STEP 1: Add a short review
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 6, "Fine"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+            AccountMeta::new(treasury_pda(&program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Grow the description to its full capacity
+    let long_description = "b".repeat(MAX_DESCRIPTION_LEN);
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_update_review_instruction_data(title, 8, &long_description),
+        update_accounts(0),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), AccountState::LEN);
+    assert_eq!(account.lamports, rent.minimum_balance(AccountState::LEN));
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.description, long_description);
+
+    // STEP 3: Shrink it back down
+    let reviewer_before = banks_client.get_balance(reviewer.pubkey()).await.unwrap();
+    let pda_before = banks_client.get_balance(pda).await.unwrap();
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_update_review_instruction_data(title, 7, "Short again"),
+        update_accounts(1),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), AccountState::LEN);
+    assert_eq!(account.lamports, pda_before);
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.description, "Short again");
+
+    // The only rent the reviewer paid is for the revision that now holds
+    // the long description
+    let revision_lamports = banks_client
+        .get_balance(revision_pda(&pda, 1, &program_id))
+        .await
+        .unwrap();
+    let reviewer_after = banks_client.get_balance(reviewer.pubkey()).await.unwrap();
+    assert_eq!(
+        reviewer_after,
+        reviewer_before - revision_lamports,
+        "Reviewer should only pay for the revision"
+    );
+}
+
+/// TEST 13: Comment on a review
+///
+/// Two different users comment on the same review. Each comment lands at
+/// the next index so clients can enumerate them by deriving [review_pda, 0..counter].
+#[tokio::test]
+async fn test_add_comments_to_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let commenter = Keypair::new();
+
+    // Fund the reviewer and the commenter
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &commenter.pubkey(), 100_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Create the review being commented on
+    let title = "Chatty Cafe";
+    let (review_pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 7, "Good coffee"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(review_pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&review_pda, &program_id), false), // Comment counter PDA
+            AccountMeta::new(stats_pda(&program_id), false), // Program stats PDA
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false), // Reviewer record PDA
+            AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false), // Clock sysvar
+            AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false), // Restaurant registry PDA
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false), // Reviewer index PDA
+            AccountMeta::new(review_stake_pda(&review_pda, &program_id), false), // Stake escrow PDA
+            AccountMeta::new(treasury_pda(&program_id), false), // Program treasury PDA
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false), // Rent sysvar
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Add two comments from different users
+    let counter_pda = comment_counter_pda(&review_pda, &program_id);
+    let comments = [(&commenter, "Agreed, the latte is great"), (&reviewer, "Thanks!")];
+
+    for (count, (author, text)) in comments.iter().enumerate() {
+        let (comment_pda, _bump) = Pubkey::find_program_address(
+            &[review_pda.as_ref(), (count as u64).to_be_bytes().as_ref()],
+            &program_id,
+        );
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &create_add_comment_instruction_data(text),
+            vec![
+                AccountMeta::new(author.pubkey(), true),      // Commenter (signer, pays rent)
+                AccountMeta::new_readonly(review_pda, false), // Review being commented on
+                AccountMeta::new(counter_pda, false),         // Comment counter PDA
+                AccountMeta::new(comment_pda, false),         // New comment PDA
+                AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+                AccountMeta::new_readonly(config_pda(&program_id), false), // Program config PDA
+            ],
+        );
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, *author],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let account = banks_client.get_account(comment_pda).await.unwrap().unwrap();
+        let comment = ReviewComment::deserialize(&mut &account.data[..]).unwrap();
+        assert_eq!(comment.review, review_pda);
+        assert_eq!(comment.commenter, author.pubkey());
+        assert_eq!(comment.comment, *text);
+        assert_eq!(comment.count, count as u64);
+    }
+
+    // STEP 3: The counter reflects both comments
+    let account = banks_client.get_account(counter_pda).await.unwrap().unwrap();
+    let counter = ReviewCommentCounter::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(counter.counter, 2);
+}
+
+/// TEST 14: Reviews carry discriminators and get a comment counter
+///
+/// AddReview creates the comment counter next to the review, both tagged
+/// with their own discriminator, and a counter account can't be passed
+/// off as a review.
+#[tokio::test]
+async fn test_review_and_counter_discriminators() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "Tagged Tavern";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    let counter_pda = comment_counter_pda(&pda, &program_id);
+
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 9, "Great ales"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(counter_pda, false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+            AccountMeta::new(treasury_pda(&program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.discriminator, AccountState::DISCRIMINATOR);
+
+    let account = banks_client.get_account(counter_pda).await.unwrap().unwrap();
+    let counter = ReviewCommentCounter::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(counter.discriminator, ReviewCommentCounter::DISCRIMINATOR);
+    assert_eq!(counter.counter, 0);
+
+    // Passing the counter where a review is expected is rejected
+    let (comment_pda, _bump) = Pubkey::find_program_address(
+        &[counter_pda.as_ref(), 0u64.to_be_bytes().as_ref()],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_comment_instruction_data("Not a review"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new_readonly(counter_pda, false), // Counter posing as a review
+            AccountMeta::new(comment_counter_pda(&counter_pda, &program_id), false),
+            AccountMeta::new(comment_pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::InvalidAccountType as u32)
+        )
+    );
+}
+
+/// TEST 15: Program stats track reviews, updates, and unique reviewers
+///
+/// Two reviews from one wallet and one from another give three reviews
+/// but only two unique reviewers; a single update bumps total_updates.
+/// The stats account can only be initialized once.
+#[tokio::test]
+async fn test_program_stats() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+
+    // Fund both reviewers
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &alice.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &bob.pubkey(), 100_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Alice reviews two restaurants, Bob reviews one
+    let reviews = [(&alice, "Stats Steakhouse"), (&alice, "Stats Sushi"), (&bob, "Stats Steakhouse")];
+    for (reviewer, title) in reviews.iter() {
+        let (pda, _bump) = Pubkey::find_program_address(
+            &[reviewer.pubkey().as_ref(), &review_seed(title)],
+            &program_id,
+        );
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &create_add_review_instruction_data(title, 7, "Counted"),
+            vec![
+                AccountMeta::new(reviewer.pubkey(), true),
+                AccountMeta::new(pda, false),
+                AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+                AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+                AccountMeta::new(stats_pda(&program_id), false),
+                AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+                AccountMeta::new(aggregate_pda(title, &program_id), false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new_readonly(config_pda(&program_id), false),
+                AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+                AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+                AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+                AccountMeta::new(treasury_pda(&program_id), false),
+                AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+            ],
+        );
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, *reviewer],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+
+    // STEP 2: Bob updates his review
+    let (bob_pda, _bump) = Pubkey::find_program_address(
+        &[bob.pubkey().as_ref(), &review_seed("Stats Steakhouse")],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_update_review_instruction_data("Stats Steakhouse", 9, "Better second time"),
+        vec![
+            AccountMeta::new(bob.pubkey(), true),
+            AccountMeta::new(bob_pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(aggregate_pda("Stats Steakhouse", &program_id), false),
+            AccountMeta::new(revision_pda(&bob_pda, 0, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &bob],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 3: Check the totals
+    let account = banks_client.get_account(stats_pda(&program_id)).await.unwrap().unwrap();
+    let stats = ProgramStats::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(stats.total_reviews, 3);
+    assert_eq!(stats.total_updates, 1);
+    assert_eq!(stats.total_unique_reviewers, 2);
+
+    let account = banks_client
+        .get_account(reviewer_record_pda(&alice.pubkey(), &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let record = ReviewerRecord::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(record.reviewer, alice.pubkey());
+    assert_eq!(record.review_count, 2);
+
+    // STEP 4: The stats singleton can't be initialized twice
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[4u8],
+        vec![
+            AccountMeta::new(alice.pubkey(), true),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &alice],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Stats should only be initialized once");
+}
+
+/// TEST 16: Restaurant aggregates follow adds, updates, and deletes
+///
+/// Reviews of "Pizza Place" and "  pizza   PLACE " share one aggregate
+/// because titles are normalized before deriving its PDA. The review
+/// count, rating sum, and histogram move with every change.
+#[tokio::test]
+async fn test_restaurant_aggregate() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+
+    // Fund both reviewers
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &alice.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &bob.pubkey(), 100_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let alice_title = "Pizza Place";
+    let bob_title = "  pizza   PLACE ";
+    let aggregate = aggregate_pda(alice_title, &program_id);
+    assert_eq!(aggregate, aggregate_pda(bob_title, &program_id));
+
+    // STEP 1: Alice rates it 8, Bob rates it 4
+    for (reviewer, title, rating) in [(&alice, alice_title, 8u8), (&bob, bob_title, 4u8)] {
+        let (pda, _bump) = Pubkey::find_program_address(
+            &[reviewer.pubkey().as_ref(), &review_seed(title)],
+            &program_id,
+        );
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &create_add_review_instruction_data(title, rating, "Slice of life"),
+            vec![
+                AccountMeta::new(reviewer.pubkey(), true),
+                AccountMeta::new(pda, false),
+                AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+                AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+                AccountMeta::new(stats_pda(&program_id), false),
+                AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+                AccountMeta::new(aggregate, false),
+                AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+                AccountMeta::new_readonly(config_pda(&program_id), false),
+                AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+                AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+                AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+                AccountMeta::new(treasury_pda(&program_id), false),
+                AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+            ],
+        );
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, reviewer],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+
+    let account = banks_client.get_account(aggregate).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.review_count, 2);
+    assert_eq!(summary.rating_sum, 12);
+    assert_eq!(summary.histogram[7], 1);
+    assert_eq!(summary.histogram[3], 1);
+
+    // STEP 2: Bob changes his rating from 4 to 6
+    let (bob_pda, _bump) = Pubkey::find_program_address(
+        &[bob.pubkey().as_ref(), &review_seed(bob_title)],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_update_review_instruction_data(bob_title, 6, "Grew on me"),
+        vec![
+            AccountMeta::new(bob.pubkey(), true),
+            AccountMeta::new(bob_pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(aggregate, false),
+            AccountMeta::new(revision_pda(&bob_pda, 0, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &bob],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(aggregate).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.review_count, 2);
+    assert_eq!(summary.rating_sum, 14);
+    assert_eq!(summary.histogram[3], 0);
+    assert_eq!(summary.histogram[5], 1);
+
+    // STEP 3: Alice deletes her review
+    let (alice_pda, _bump) = Pubkey::find_program_address(
+        &[alice.pubkey().as_ref(), &review_seed(alice_title)],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_delete_review_instruction_data(alice_title),
+        vec![
+            AccountMeta::new(alice.pubkey(), true),
+            AccountMeta::new(alice_pda, false),
+            AccountMeta::new(comment_counter_pda(&alice_pda, &program_id), false),
+            AccountMeta::new(aggregate, false),
+            AccountMeta::new(reviewer_index_pda(&alice.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&alice_pda, &program_id), false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &alice],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(aggregate).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.review_count, 1);
+    assert_eq!(summary.rating_sum, 6);
+    assert_eq!(summary.histogram[7], 0);
+}
+
+/// TEST 17: Tip a reviewer in SOL
+///
+/// The tip moves straight from the tipper to the review author and is
+/// added to the review's tip_total. Tipping with someone other than the
+/// author as recipient is rejected.
+#[tokio::test]
+async fn test_tip_reviewer_sol() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let tipper = Keypair::new();
+
+    // Fund the reviewer and the tipper
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &tipper.pubkey(), 100_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Create the review
+    let title = "Tip Top Thai";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 10, "Best curry in town"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+            AccountMeta::new(treasury_pda(&program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Tip the author twice
+    let reviewer_before = banks_client.get_balance(reviewer.pubkey()).await.unwrap();
+    for tip in [1_000_000u64, 500_000u64] {
+        let mut data = vec![5u8]; // Variant 5 for TipReviewer
+        data.extend_from_slice(&tip.to_le_bytes());
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(tipper.pubkey(), true),   // Tipper (signer)
+                AccountMeta::new(reviewer.pubkey(), false), // Review author receives the tip
+                AccountMeta::new(pda, false),               // Review being tipped
+                AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            ],
+        );
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, &tipper],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+
+    let reviewer_after = banks_client.get_balance(reviewer.pubkey()).await.unwrap();
+    assert_eq!(reviewer_after - reviewer_before, 1_500_000);
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.tip_total, 1_500_000);
+
+    // STEP 3: Redirecting the tip to the tipper's own wallet fails
+    let mut data = vec![5u8];
+    data.extend_from_slice(&1_000u64.to_le_bytes());
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &data,
+        vec![
+            AccountMeta::new(tipper.pubkey(), true),
+            AccountMeta::new(tipper.pubkey(), false), // Not the author!
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &tipper],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Tips should only go to the review author");
+}
+
+/// TEST 18: Tip a reviewer in an SPL token
+///
+/// 1. Creates a mint plus token accounts for the tipper and the author
+/// 2. Tips twice and checks the author's balance and the per-mint ledger
+/// 3. Verifies a destination account owned by someone else is rejected
+#[tokio::test]
+async fn test_tip_reviewer_token() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let tipper = Keypair::new();
+    let rent = Rent::default();
+
+    // Fund the reviewer and the tipper
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &tipper.pubkey(), 100_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Create the mint and both token accounts, then fund the tipper
+    let mint = Keypair::new();
+    let tipper_token = Keypair::new();
+    let reviewer_token = Keypair::new();
+    let decimals = 6;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &payer.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &tipper_token.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &tipper_token.pubkey(),
+                &mint.pubkey(),
+                &tipper.pubkey(),
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &reviewer_token.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &reviewer_token.pubkey(),
+                &mint.pubkey(),
+                &reviewer.pubkey(),
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &tipper_token.pubkey(),
+                &payer.pubkey(),
+                &[],
+                1_000_000,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &mint, &tipper_token, &reviewer_token],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Create the review
+    let title = "Token Tavern";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 9, "Worth every token"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+            AccountMeta::new(treasury_pda(&program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 3: Tip the author twice
+    let (ledger_pda, _bump) = Pubkey::find_program_address(
+        &[pda.as_ref(), b"tips", mint.pubkey().as_ref()],
+        &program_id,
+    );
+    for tip in [250_000u64, 100_000u64] {
+        let mut data = vec![6u8]; // Variant 6 for TipReviewerToken
+        data.extend_from_slice(&tip.to_le_bytes());
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &data,
+            vec![
+                AccountMeta::new(tipper.pubkey(), true),             // Tipper (signer)
+                AccountMeta::new_readonly(reviewer.pubkey(), false), // Review author
+                AccountMeta::new_readonly(pda, false),               // Review being tipped
+                AccountMeta::new(tipper_token.pubkey(), false),      // Tipper's token account
+                AccountMeta::new(reviewer_token.pubkey(), false),    // Author's token account
+                AccountMeta::new_readonly(mint.pubkey(), false),     // Mint
+                AccountMeta::new(ledger_pda, false),                 // Tip ledger PDA
+                AccountMeta::new_readonly(spl_token::id(), false),   // Token program
+                AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            ],
+        );
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, &tipper],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+
+    let account = banks_client.get_account(reviewer_token.pubkey()).await.unwrap().unwrap();
+    assert_eq!(TokenAccount::unpack(&account.data).unwrap().amount, 350_000);
+
+    let account = banks_client.get_account(ledger_pda).await.unwrap().unwrap();
+    let ledger = TipLedger::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(ledger.review, pda);
+    assert_eq!(ledger.mint, mint.pubkey());
+    assert_eq!(ledger.total, 350_000);
+
+    // STEP 4: Sending the tip to the tipper's own token account fails
+    let mut data = vec![6u8];
+    data.extend_from_slice(&1_000u64.to_le_bytes());
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &data,
+        vec![
+            AccountMeta::new(tipper.pubkey(), true),
+            AccountMeta::new_readonly(reviewer.pubkey(), false),
+            AccountMeta::new_readonly(pda, false),
+            AccountMeta::new(tipper_token.pubkey(), false),
+            AccountMeta::new(tipper_token.pubkey(), false), // Not the author's account!
+            AccountMeta::new_readonly(mint.pubkey(), false),
+            AccountMeta::new(ledger_pda, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &tipper],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "Token tips should only go to the author's token account");
+}
+
+/// TEST 19: Vote on a review, once per wallet
+///
+/// One reader upvotes, another downvotes, and the first reader's second
+/// vote is rejected because their vote PDA already exists.
+#[tokio::test]
+async fn test_vote_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let fan = Keypair::new();
+    let critic = Keypair::new();
+
+    // Fund the reviewer and both voters
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &fan.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &critic.pubkey(), 100_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Create the review
+    let title = "Vote Vineyard";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 6, "Decent wine list"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+            AccountMeta::new(treasury_pda(&program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // Builds a VoteReview instruction: [variant_byte: 7][up: bool]
+    let vote_instruction = |voter: &Keypair, up: bool| {
+        let (vote_pda, _bump) = Pubkey::find_program_address(
+            &[pda.as_ref(), voter.pubkey().as_ref()],
+            &program_id,
+        );
+        Instruction::new_with_bytes(
+            program_id,
+            &[7u8, up as u8], // Variant 7 for VoteReview
+            vec![
+                AccountMeta::new(voter.pubkey(), true), // Voter (signer, pays rent)
+                AccountMeta::new(pda, false),           // Review being voted on
+                AccountMeta::new(vote_pda, false),      // Vote PDA for (review, voter)
+                AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+                AccountMeta::new(aggregate_pda(title, &program_id), false), // Restaurant aggregate PDA
+            ],
+        )
+    };
+
+    // STEP 2: One upvote and one downvote
+    for (voter, up) in [(&fan, true), (&critic, false)] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[vote_instruction(voter, up)],
+            Some(&payer.pubkey()),
+            &[&payer, voter],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.upvotes, 1);
+    assert_eq!(account_state.downvotes, 1);
+
+    // STEP 3: The fan can't vote again, even the other way
+    let transaction = Transaction::new_signed_with_payer(
+        &[vote_instruction(&fan, false)],
+        Some(&payer.pubkey()),
+        &[&payer, &fan],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert!(result.is_err(), "A wallet should only be able to vote once");
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.downvotes, 1);
+}
+
+/// TEST 20: Reviews record when they were created and last updated
+///
+/// Both timestamps are set on creation; an update after the clock moves
+/// forward only changes updated_at. The raw account bytes at the exported
+/// offsets match the deserialized values, which is what RPC filters rely on.
+#[tokio::test]
+async fn test_review_timestamps() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    initialize_stats(&context.banks_client, &payer, program_id, context.last_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Add the review
+    let title = "Timely Tapas";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(title, 7, "Quick service"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(comment_counter_pda(&pda, &program_id), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+            AccountMeta::new_readonly(restaurant_pda(title, &program_id), false),
+            AccountMeta::new(reviewer_index_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(review_stake_pda(&pda, &program_id), false),
+            AccountMeta::new(treasury_pda(&program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    let created_at = account_state.created_at;
+    assert_eq!(account_state.updated_at, created_at);
+
+    // STEP 2: Move the clock forward a day and update the review
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = created_at + 86_400;
+    context.set_sysvar(&clock);
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_update_review_instruction_data(title, 8, "Quick and tasty"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new(revision_pda(&pda, 0, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.created_at, created_at);
+    assert_eq!(account_state.updated_at, created_at + 86_400);
+
+    // STEP 3: The timestamps sit at their fixed offsets
+    let read_i64 = |offset: usize| {
+        i64::from_le_bytes(account.data[offset..offset + 8].try_into().unwrap())
+    };
+    assert_eq!(read_i64(AccountState::CREATED_AT_OFFSET), account_state.created_at);
+    assert_eq!(read_i64(AccountState::UPDATED_AT_OFFSET), account_state.updated_at);
+}
+
+/// TEST 21: Reject reviews with an oversized title
+///
+/// Titles over MAX_TITLE_LEN fail with TitleTooLong before the program
+/// tries to derive a PDA from them.
+#[tokio::test]
+async fn test_add_review_title_too_long_fails() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // The title is too long to be a seed, so the accounts can't be real PDAs;
+    // the length check has to fire before they are looked at
+    let title = "t".repeat(MAX_TITLE_LEN + 1);
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_add_review_instruction_data(&title, 5, "Fine"),
+        vec![
+            AccountMeta::new(reviewer.pubkey(), true),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(reviewer_record_pda(&reviewer.pubkey(), &program_id), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(solana_program::sysvar::rent::ID, false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::TitleTooLong as u32)
+        )
+    );
+}
+
+/// TEST 22: Add and update a review with the client instruction builders
+///
+/// add_review_ix and update_review_ix derive every PDA and account meta,
+/// so the instructions they build must be accepted as-is.
+#[tokio::test]
+async fn test_client_instruction_builders() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Add the review
+    let title = "Client Cafe";
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 7, "Built off-chain", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Update it, saving the first version as revision 0
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Even better", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.rating, 9);
+    assert_eq!(account_state.description, "Even better");
+
+    let revision = banks_client
+        .get_account(revision_pda(&pda, 0, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let revision = ReviewRevision::deserialize(&mut &revision.data[..]).unwrap();
+    assert_eq!(revision.rating, 7);
+    assert_eq!(revision.description, "Built off-chain");
+}
+
+/// TEST 23: Transfer a review to another wallet
+///
+/// After TransferReviewOwnership the review keeps its address, the old
+/// author can no longer update it, and the new owner can.
+#[tokio::test]
+async fn test_transfer_review_ownership() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+
+    // Fund both wallets
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &alice.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &bob.pubkey(), 100_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Alice reviews and hands the review to Bob
+    let title = "Handover House";
+    let pda = review::client::review_pda(&alice.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            add_review_ix(&program_id, &alice.pubkey(), title, 6, "Decent", &[], "", None, VISITED_AT, 2),
+            transfer_review_ownership_ix(&program_id, &alice.pubkey(), &pda, &bob.pubkey()),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &alice],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.author, alice.pubkey());
+    assert_eq!(account_state.owner, bob.pubkey());
+
+    // STEP 2: Alice can no longer update it
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &alice.pubkey(), title, 2, "Changed my mind", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &alice],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotReviewOwner as u32)
+        )
+    );
+
+    // STEP 3: Bob updates the review at Alice's address
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &create_update_review_instruction_data(title, 9, "Under new management"),
+        vec![
+            AccountMeta::new(bob.pubkey(), true),
+            AccountMeta::new(pda, false),
+            AccountMeta::new_readonly(Pubkey::from_str(SYSTEM_PROGRAM_ID).unwrap(), false),
+            AccountMeta::new(stats_pda(&program_id), false),
+            AccountMeta::new(aggregate_pda(title, &program_id), false),
+            AccountMeta::new(revision_pda(&pda, 0, &program_id), false),
+            AccountMeta::new_readonly(solana_program::sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(&program_id), false),
+        ],
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &bob],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.rating, 9);
+    assert_eq!(account_state.description, "Under new management");
+}
+
+/// TEST 24: Flag a review and have moderators resolve it
+///
+/// Anyone can flag a visible review once; only the admin or a listed
+/// moderator can hide it or make it visible again.
+#[tokio::test]
+async fn test_flag_and_moderate_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let reader = Keypair::new();
+    let moderator = Keypair::new();
+
+    // Fund the reviewer; the payer is the admin and pays for the config
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[moderator.pubkey()]),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "Questionable Diner";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 1, "Spam spam spam", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let status = |data: &[u8]| AccountState::unpack(data).unwrap().status;
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(status(&account.data), ReviewStatus::Visible);
+
+    // STEP 1: A reader flags the review
+    let transaction = Transaction::new_signed_with_payer(
+        &[flag_review_ix(&program_id, &reader.pubkey(), &pda)],
+        Some(&payer.pubkey()),
+        &[&payer, &reader],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(status(&account.data), ReviewStatus::Flagged);
+
+    // STEP 2: It can't be flagged again while waiting for a moderator
+    let transaction = Transaction::new_signed_with_payer(
+        &[flag_review_ix(&program_id, &payer.pubkey(), &pda)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::ReviewNotVisible as u32)
+        )
+    );
+
+    // STEP 3: The reader is not a moderator
+    let transaction = Transaction::new_signed_with_payer(
+        &[resolve_flag_ix(&program_id, &reader.pubkey(), &pda, true)],
+        Some(&payer.pubkey()),
+        &[&payer, &reader],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotModerator as u32)
+        )
+    );
+
+    // STEP 4: The moderator hides it and the admin restores it
+    let transaction = Transaction::new_signed_with_payer(
+        &[resolve_flag_ix(&program_id, &moderator.pubkey(), &pda, true)],
+        Some(&payer.pubkey()),
+        &[&payer, &moderator],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(status(&account.data), ReviewStatus::Hidden);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[resolve_flag_ix(&program_id, &payer.pubkey(), &pda, false)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(status(&account.data), ReviewStatus::Visible);
+}
+
+/// TEST 25: Reviews carry validated tags
+///
+/// Tags are stored with the review and replaced on update; more than
+/// MAX_TAGS tags or a tag over MAX_TAG_LEN bytes is rejected.
+#[tokio::test]
+async fn test_review_tags() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Add a tagged review
+    let title = "Tagged Trattoria";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great pasta", &["italian", "pasta"], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().tags, vec!["italian", "pasta"]);
+
+    // STEP 2: Updating replaces the tags
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great pizza too", &["pizza"], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().tags, vec!["pizza"]);
+
+    // STEP 3: Too many tags, or a tag that is too long, is rejected
+    let too_many = vec!["tag"; MAX_TAGS + 1];
+    let too_long = "t".repeat(MAX_TAG_LEN + 1);
+    for (tags, error) in [
+        (too_many, ReviewError::TooManyTags),
+        (vec![too_long.as_str()], ReviewError::InvalidTag),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Nope", &tags, "", None, VISITED_AT, 2, 1)],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+/// TEST 26: Reviews can point at off-chain content
+///
+/// An ipfs:// or ar:// content URI is stored with the review and can be
+/// changed on update; other schemes and oversized URIs are rejected.
+#[tokio::test]
+async fn test_review_content_uri() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // Fund the reviewer account
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            &reviewer.pubkey(),
+            100_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Add a review whose long form lives on IPFS
+    let title = "Photo Bistro";
+    let cid = "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 9, "See photos", &[], cid, None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().content_uri, cid);
+
+    // STEP 2: Move it to Arweave
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 9, "See photos", &[], "ar://abc123", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().content_uri, "ar://abc123");
+
+    // STEP 3: Other schemes and oversized URIs are rejected
+    let too_long = format!("ipfs://{}", "a".repeat(MAX_CONTENT_URI_LEN));
+    for (uri, error) in [
+        ("https://example.com/review", ReviewError::InvalidContentUri),
+        (too_long.as_str(), ReviewError::ContentUriTooLong),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[update_review_ix(&program_id, &reviewer.pubkey(), title, 9, "See photos", &[], uri, None, VISITED_AT, 2, 1)],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+/// TEST 27: Verified restaurant owners can respond to reviews
+///
+/// Only the config admin can register a restaurant's owner, and only that
+/// owner can respond; a second response replaces the first.
+#[tokio::test]
+async fn test_respond_to_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let restaurant_owner = Keypair::new();
+    let stranger = Keypair::new();
+
+    // Fund the reviewer and the owner; the payer is the admin
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &restaurant_owner.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let title = "The Bistro";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 4, "Cold soup", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: The restaurant owner can't verify themselves
+    let transaction = Transaction::new_signed_with_payer(
+        &[verify_restaurant_owner_ix(
+            &program_id,
+            &restaurant_owner.pubkey(),
+            title,
+            &restaurant_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &restaurant_owner],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    // STEP 2: Responding before anyone is verified fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[respond_to_review_ix(&program_id, &restaurant_owner.pubkey(), &pda, title, "Sorry!")],
+        Some(&payer.pubkey()),
+        &[&payer, &restaurant_owner],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotRestaurantOwner as u32)
+        )
+    );
+
+    // STEP 3: The admin registers the owner under a differently spelled title
+    let transaction = Transaction::new_signed_with_payer(
+        &[verify_restaurant_owner_ix(
+            &program_id,
+            &payer.pubkey(),
+            "  the   BISTRO ",
+            &restaurant_owner.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 4: Someone else can't respond on the owner's behalf
+    let transaction = Transaction::new_signed_with_payer(
+        &[respond_to_review_ix(&program_id, &stranger.pubkey(), &pda, title, "Fake reply")],
+        Some(&payer.pubkey()),
+        &[&payer, &stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotRestaurantOwner as u32)
+        )
+    );
+
+    // STEP 5: The owner responds, then edits the response
+    let response_address = response_pda(&pda, &program_id);
+    for text in ["Sorry, we fixed the heater", "Come back for a free dessert"] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[respond_to_review_ix(&program_id, &restaurant_owner.pubkey(), &pda, title, text)],
+            Some(&payer.pubkey()),
+            &[&payer, &restaurant_owner],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+
+        let account = banks_client.get_account(response_address).await.unwrap().unwrap();
+        assert_eq!(account.data.len(), ReviewResponse::SIZE);
+        let response = ReviewResponse::unpack_checked(&account.data).unwrap();
+        assert_eq!(response.review, pda);
+        assert_eq!(response.responder, restaurant_owner.pubkey());
+        assert_eq!(response.response, text);
+    }
+
+    // STEP 6: Oversized responses are rejected
+    let long_response = "x".repeat(MAX_RESPONSE_LEN + 1);
+    let transaction = Transaction::new_signed_with_payer(
+        &[respond_to_review_ix(
+            &program_id,
+            &restaurant_owner.pubkey(),
+            &pda,
+            title,
+            &long_response,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &restaurant_owner],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::ResponseTooLong as u32)
+        )
+    );
+}
+
+/// TEST 28: Strict registry mode only allows reviews of registered restaurants
+///
+/// The admin registers restaurants; once strict mode is on, AddReview for an
+/// unregistered name fails and a registered name (however it is spelled) works.
+#[tokio::test]
+async fn test_restaurant_registry_strict_mode() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let restaurant_owner = Keypair::new();
+
+    // Fund the reviewer; the payer is the admin
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Only the admin can register restaurants or turn on strict mode
+    let transaction = Transaction::new_signed_with_payer(
+        &[register_restaurant_ix(
+            &program_id,
+            &reviewer.pubkey(),
+            "Noodle Bar",
+            "Main St",
+            &reviewer.pubkey(),
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_strict_registry_ix(&program_id, &reviewer.pubkey(), true)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    // STEP 2: The admin registers a restaurant and turns on strict mode
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            register_restaurant_ix(
+                &program_id,
+                &payer.pubkey(),
+                "Noodle Bar",
+                "12 Main St, Springfield",
+                &restaurant_owner.pubkey(),
+            ),
+            set_strict_registry_ix(&program_id, &payer.pubkey(), true),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client
+        .get_account(restaurant_pda("Noodle Bar", &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let restaurant = Restaurant::unpack_checked(&account.data).unwrap();
+    assert_eq!(restaurant.name, "Noodle Bar");
+    assert_eq!(restaurant.location, "12 Main St, Springfield");
+    assert_eq!(restaurant.owner, restaurant_owner.pubkey());
+
+    // STEP 3: Reviewing an unregistered restaurant fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), "Taco Truck", 7, "Tasty", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::RestaurantNotRegistered as u32)
+        )
+    );
+
+    // STEP 4: A registered restaurant can be reviewed under any spelling
+    let title = "noodle  BAR";
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Great broth", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    assert!(banks_client.get_account(pda).await.unwrap().is_some());
+
+    // STEP 5: With strict mode off again, unregistered restaurants are accepted
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            set_strict_registry_ix(&program_id, &payer.pubkey(), false),
+            add_review_ix(&program_id, &reviewer.pubkey(), "Taco Truck", 7, "Tasty", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+/// TEST 29: Updates patch the review in place
+///
+/// After an update that shortens the description and drops tags, the
+/// zero-copy view and Pack agree on every field and no stale bytes remain.
+#[tokio::test]
+async fn test_update_review_zero_copy() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Patch Kitchen";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(
+                &program_id,
+                &reviewer.pubkey(),
+                title,
+                3,
+                "A very long first description that will be shortened",
+                &["thai", "spicy"],
+                "ipfs://first",
+                None,
+                VISITED_AT,
+                2,
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Short", &["thai"], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let view = ReviewZeroCopy::load(&account.data).unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+
+    assert_eq!(view.rating, 8);
+    assert_eq!(review.rating, 8);
+    assert_eq!(view.description.as_str().unwrap(), "Short");
+    assert_eq!(review.description, "Short");
+    assert_eq!(view.title.as_str().unwrap(), title);
+    assert_eq!(view.tag_count, 1);
+    assert_eq!(review.tags, vec!["thai".to_string()]);
+    assert_eq!(view.content_uri.as_str().unwrap(), "");
+    assert_eq!(view.revision_count(), 1);
+    assert_eq!(view.author(), reviewer.pubkey());
+    assert_eq!(view.owner(), reviewer.pubkey());
+
+    // The bytes after the shorter description and the dropped tag are zeroed
+    let description_end = AccountState::DESCRIPTION_OFFSET + 4 + "Short".len();
+    assert!(account.data[description_end..AccountState::TITLE_OFFSET].iter().all(|&b| b == 0));
+    let second_tag = AccountState::TAGS_OFFSET + 1 + (4 + MAX_TAG_LEN);
+    assert!(account.data[second_tag..AccountState::CONTENT_URI_OFFSET].iter().all(|&b| b == 0));
+}
+
+/// TEST 30: Events decode from their program log lines
+///
+/// The native test processor prints sol_log_data to stdout rather than the
+/// transaction logs, so this builds the "Program data:" lines an on-chain
+/// run produces and checks the client decodes them.
+#[test]
+fn test_decode_event_logs() {
+    let log_line = |discriminator: [u8; 8], data: Vec<u8>| {
+        format!(
+            "Program data: {} {}",
+            BASE64_STANDARD.encode(discriminator),
+            BASE64_STANDARD.encode(data)
+        )
+    };
+
+    let added = ReviewAdded {
+        review: Pubkey::new_unique(),
+        author: Pubkey::new_unique(),
+        title: "Event Diner".to_string(),
+        rating: 7,
+        created_at: 1_700_000_000,
+    };
+    let updated = ReviewUpdated {
+        review: added.review,
+        owner: added.author,
+        rating: 9,
+        revision_count: 1,
+        updated_at: 1_700_000_100,
+    };
+    let deleted = ReviewDeleted {
+        review: added.review,
+        owner: added.author,
+        title: added.title.clone(),
+    };
+
+    let removed = ReviewRemoved {
+        review: added.review,
+        author: added.author,
+        moderator: Pubkey::new_unique(),
+        title: added.title.clone(),
+    };
+    let line = log_line(ReviewAdded::DISCRIMINATOR, borsh::to_vec(&added).unwrap());
+    assert_eq!(decode_event_log(&line), Some(ReviewEvent::Added(added)));
+    let line = log_line(ReviewUpdated::DISCRIMINATOR, borsh::to_vec(&updated).unwrap());
+    assert_eq!(decode_event_log(&line), Some(ReviewEvent::Updated(updated)));
+    let line = log_line(ReviewDeleted::DISCRIMINATOR, borsh::to_vec(&deleted).unwrap());
+    assert_eq!(decode_event_log(&line), Some(ReviewEvent::Deleted(deleted)));
+    let line = log_line(ReviewRemoved::DISCRIMINATOR, borsh::to_vec(&removed).unwrap());
+    assert_eq!(decode_event_log(&line), Some(ReviewEvent::Removed(removed)));
+
+    // Ordinary logs, unknown discriminators, and bad base64 are not events
+    assert_eq!(decode_event_log("Program log: Adding  review..."), None);
+    assert_eq!(decode_event_log(&log_line(*b"unknown!", vec![1, 2, 3])), None);
+    assert_eq!(decode_event_log("Program data: !!! ???"), None);
+}
+
+/// TEST 31: Titles longer than a PDA seed
+///
+/// A title of MAX_TITLE_LEN bytes is longer than the 32-byte seed limit;
+/// the review is addressed by the title's hash and stores the full title.
+#[tokio::test]
+async fn test_long_title_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "The Extremely Long Named Family Restaurant & Grill";
+    assert_eq!(title.len(), MAX_TITLE_LEN);
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 6, "Long name, short menu", &[], "", None, VISITED_AT, 2),
+            update_review_ix(&program_id, &reviewer.pubkey(), title, 7, "Menu grew", &[], "", None, VISITED_AT, 2, 0),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.title, title);
+    assert_eq!(review.rating, 7);
+
+    let account = banks_client
+        .get_account(aggregate_pda(title, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.review_count, 1);
+}
+
+/// TEST 32: A sponsor can pay for someone else's review
+///
+/// The sponsor funds every account AddReview creates while the reviewer,
+/// who holds no SOL, stays the author and owner. Deleting the review sends
+/// the rent back to the sponsor, who has to be passed in.
+#[tokio::test]
+async fn test_sponsored_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let sponsor = Keypair::new();
+
+    let title = "Sponsored Sandwiches";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &sponsor.pubkey(), 100_000_000),
+            sponsored_add_review_ix(
+                &program_id,
+                &sponsor.pubkey(),
+                &reviewer.pubkey(),
+                title,
+                8,
+                "Paid for by the app",
+                &[],
+                "",
+                None,
+                VISITED_AT,
+                2,
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &sponsor, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: The reviewer spent nothing and is recorded as author and owner
+    assert_eq!(banks_client.get_balance(reviewer.pubkey()).await.unwrap(), 0);
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.author, reviewer.pubkey());
+    assert_eq!(review.owner, reviewer.pubkey());
+    assert_eq!(review.payer, sponsor.pubkey());
+
+    // STEP 2: Deleting without the sponsor's account fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_review_ix(&program_id, &reviewer.pubkey(), title)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::InvalidRentPayer as u32)
+        )
+    );
+
+    // STEP 3: With it, the review's and counter's rent go to the sponsor
+    let counter = comment_counter_pda(&pda, &program_id);
+    let refund = account.lamports
+        + banks_client.get_account(counter).await.unwrap().unwrap().lamports;
+    let index_pda = reviewer_index_pda(&reviewer.pubkey(), &program_id);
+    let index_before = banks_client.get_balance(index_pda).await.unwrap();
+    let sponsor_before = banks_client.get_balance(sponsor.pubkey()).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_sponsored_review_ix(&program_id, &reviewer.pubkey(), title, &sponsor.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert!(banks_client.get_account(pda).await.unwrap().is_none());
+    let refund = refund + index_before - banks_client.get_balance(index_pda).await.unwrap();
+    assert_eq!(banks_client.get_balance(sponsor.pubkey()).await.unwrap(), sponsor_before + refund);
+    assert_eq!(banks_client.get_balance(reviewer.pubkey()).await.unwrap(), 0);
+}
+
+/// TEST 33: Updates respect the configured cooldown
+///
+/// With a one hour cooldown, an update right after adding the review fails
+/// with UpdateTooSoon and succeeds once the clock has moved an hour on.
+#[tokio::test]
+async fn test_update_cooldown() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    initialize_stats(&context.banks_client, &payer, program_id, context.last_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // STEP 1: Only the admin sets the cooldown
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            set_update_cooldown_ix(&program_id, &reviewer.pubkey(), 0),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(2, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    let title = "Cooldown Cafe";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            set_update_cooldown_ix(&program_id, &payer.pubkey(), 3_600),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 5, "Okay", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: An immediate update is too soon
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 10, "Best ever", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::UpdateTooSoon as u32)
+        )
+    );
+
+    // STEP 3: An hour later the update goes through
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let account = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    let updated_at = AccountState::unpack(&account.data).unwrap().updated_at;
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = updated_at + 3_600;
+    context.set_sysvar(&clock);
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 10, "Best ever", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    let account = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().rating, 10);
+}
+
+/// TEST 34: Owners can soft-delete a review
+///
+/// Archiving marks the review Deleted and takes it out of the restaurant's
+/// rating, but the account stays readable. Updates, moderation, and a second
+/// archive are refused, and the owner can still close it for the rent.
+#[tokio::test]
+async fn test_archive_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Closing Down Bistro";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 7, "Good while it lasted", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Only the owner can archive it
+    let transaction = Transaction::new_signed_with_payer(
+        &[archive_review_ix(&program_id, &payer.pubkey(), &pda, title)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotReviewOwner as u32)
+        )
+    );
+
+    // STEP 2: The owner archives it; the review is still readable
+    let transaction = Transaction::new_signed_with_payer(
+        &[archive_review_ix(&program_id, &reviewer.pubkey(), &pda, title)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.status, ReviewStatus::Deleted);
+    assert_eq!(review.description, "Good while it lasted");
+
+    let account = banks_client
+        .get_account(aggregate_pda(title, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.review_count, 0);
+
+    // STEP 3: Updating, moderating, or archiving it again is refused
+    // (each signer pays its own fee, so the repeated archive isn't a duplicate)
+    for (instruction, signer) in [
+        (update_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Reopened", &[], "", None, VISITED_AT, 2, 0), &reviewer),
+        (resolve_flag_ix(&program_id, &payer.pubkey(), &pda, false), &payer),
+        (archive_review_ix(&program_id, &reviewer.pubkey(), &pda, title), &reviewer),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&signer.pubkey()),
+            &[signer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(ReviewError::ReviewArchived as u32)
+            )
+        );
+    }
+
+    // STEP 4: The owner can still close it without touching the aggregate again
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_review_ix(&program_id, &reviewer.pubkey(), title)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert!(banks_client.get_account(pda).await.unwrap().is_none());
+}
+
+/// TEST 35: Rename a review by migrating it to a new title
+///
+/// The review moves to the PDA for the new title with its content intact,
+/// the old review and comment counter are closed, and the rating moves to
+/// the new restaurant's aggregate.
+#[tokio::test]
+async fn test_migrate_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Pizza Plaec";
+    let new_title = "Pizza Place";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great crust", &["pizza"], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Only the owner can migrate the review
+    let transaction = Transaction::new_signed_with_payer(
+        &[migrate_review_ix(&program_id, &payer.pubkey(), &reviewer.pubkey(), title, new_title)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotReviewOwner as u32)
+        )
+    );
+
+    // STEP 2: The owner moves it to the corrected title
+    let transaction = Transaction::new_signed_with_payer(
+        &[migrate_review_ix(&program_id, &reviewer.pubkey(), &reviewer.pubkey(), title, new_title)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let old_pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let new_pda = review::client::review_pda(&reviewer.pubkey(), new_title, &program_id);
+    assert!(banks_client.get_account(old_pda).await.unwrap().is_none());
+    assert!(banks_client
+        .get_account(comment_counter_pda(&old_pda, &program_id))
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client
+        .get_account(comment_counter_pda(&new_pda, &program_id))
+        .await
+        .unwrap()
+        .is_some());
+
+    let account = banks_client.get_account(new_pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.title, new_title);
+    assert_eq!(review.rating, 8);
+    assert_eq!(review.description, "Great crust");
+    assert_eq!(review.tags, vec!["pizza".to_string()]);
+    assert_eq!(review.author, reviewer.pubkey());
+
+    // STEP 3: The rating now counts for the new restaurant only
+    for (name, count) in [(title, 0), (new_title, 1)] {
+        let account = banks_client
+            .get_account(aggregate_pda(name, &program_id))
+            .await
+            .unwrap()
+            .unwrap();
+        let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+        assert_eq!(summary.review_count, count);
+    }
+
+    // STEP 4: The migrated review can be updated under its new title
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), new_title, 9, "Even better", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+/// TEST 36: Upgrade a review written before the layout had a version byte
+///
+/// The review is rewritten in the version 0 layout, which every instruction
+/// rejects with OutdatedAccount. MigrateAccount grows it back to the current
+/// layout with the same contents and the newer fields zeroed, after which it
+/// can be updated again.
+#[tokio::test]
+async fn test_migrate_account_version() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    initialize_stats(&context.banks_client, &payer, program_id, context.last_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Vintage Grill";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 6, "Classic", &["grill"], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // Rewrite the review in the version 0 layout: no version byte and
+    // nothing after the title
+    let current = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(current.data[AccountState::VERSION_OFFSET], AccountState::VERSION);
+    let mut old = current.clone();
+    old.data.truncate(AccountState::LEN_V1);
+    old.data.remove(AccountState::VERSION_OFFSET);
+    assert_eq!(old.data.len(), AccountState::LEN_V0);
+    old.lamports = Rent::default().minimum_balance(AccountState::LEN_V0);
+    context.set_account(&pda, &old.into());
+
+    // STEP 1: An outdated review can't be updated
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Still classic", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::OutdatedAccount as u32)
+        )
+    );
+
+    // STEP 2: Anyone can migrate it; the payer covers the extra rent
+    let transaction = Transaction::new_signed_with_payer(
+        &[migrate_account_ix(&program_id, &payer.pubkey(), &pda)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let migrated = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(migrated.data[..AccountState::LEN_V1], current.data[..AccountState::LEN_V1]);
+    // Fields the old layout didn't have come back zeroed
+    let review = AccountState::unpack(&migrated.data).unwrap();
+    assert_eq!(
+        (review.sub_ratings, review.visited_at, review.price_range, review.sequence),
+        (None, 0, 0, 0)
+    );
+    assert!(migrated.lamports >= Rent::default().minimum_balance(AccountState::LEN));
+
+    // STEP 3: Migrating again is a no-op, and the review can be updated
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            migrate_account_ix(&program_id, &payer.pubkey(), &pda),
+            update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Still classic", &[], "", None, VISITED_AT, 2, 0),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    let account = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.version, AccountState::VERSION);
+    assert_eq!(review.rating, 8);
+}
+
+/// TEST 37: Tip a reviewer in a Token-2022 token
+///
+/// The author's token account uses the ImmutableOwner extension, so it is
+/// longer than a plain SPL Token account. The tip goes through Token-2022,
+/// and naming the SPL Token program for a Token-2022 mint is rejected.
+#[tokio::test]
+async fn test_tip_reviewer_token_2022() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let tipper = Keypair::new();
+    let rent = Rent::default();
+    let token_2022 = spl_generic_token::token_2022::id();
+
+    // Token-2022 takes the same instructions as SPL Token for these calls;
+    // the spl_token builders just need pointing at the other program
+    let via_token_2022 = |mut instruction: Instruction| {
+        instruction.program_id = token_2022;
+        instruction
+    };
+
+    // STEP 1: Create the mint and both token accounts, then fund the tipper
+    // ImmutableOwner adds a type byte and a 4-byte TLV header to the account
+    let mint = Keypair::new();
+    let tipper_token = Keypair::new();
+    let reviewer_token = Keypair::new();
+    let reviewer_token_len = TokenAccount::LEN + 1 + 4;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &tipper.pubkey(), 100_000_000),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &token_2022,
+            ),
+            via_token_2022(
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &payer.pubkey(),
+                    None,
+                    2,
+                )
+                .unwrap(),
+            ),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &tipper_token.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &token_2022,
+            ),
+            via_token_2022(
+                spl_token::instruction::initialize_account(
+                    &spl_token::id(),
+                    &tipper_token.pubkey(),
+                    &mint.pubkey(),
+                    &tipper.pubkey(),
+                )
+                .unwrap(),
+            ),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &reviewer_token.pubkey(),
+                rent.minimum_balance(reviewer_token_len),
+                reviewer_token_len as u64,
+                &token_2022,
+            ),
+            // InitializeImmutableOwner (instruction 22) has no data
+            Instruction::new_with_bytes(
+                token_2022,
+                &[22],
+                vec![AccountMeta::new(reviewer_token.pubkey(), false)],
+            ),
+            via_token_2022(
+                spl_token::instruction::initialize_account(
+                    &spl_token::id(),
+                    &reviewer_token.pubkey(),
+                    &mint.pubkey(),
+                    &reviewer.pubkey(),
+                )
+                .unwrap(),
+            ),
+            via_token_2022(
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &tipper_token.pubkey(),
+                    &payer.pubkey(),
+                    &[],
+                    10_000,
+                )
+                .unwrap(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &mint, &tipper_token, &reviewer_token],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Create the review
+    let title = "Extension Eatery";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Modern tokens", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 3: The SPL Token program can't move a Token-2022 mint
+    let tip = |token_program: &Pubkey, amount: u64| {
+        tip_reviewer_token_ix(
+            &program_id,
+            &tipper.pubkey(),
+            &reviewer.pubkey(),
+            &pda,
+            &tipper_token.pubkey(),
+            &reviewer_token.pubkey(),
+            &mint.pubkey(),
+            token_program,
+            amount,
+        )
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[tip(&spl_token::id(), 1_000)],
+        Some(&payer.pubkey()),
+        &[&payer, &tipper],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+    );
+
+    // STEP 4: Tipping through Token-2022 works
+    let transaction = Transaction::new_signed_with_payer(
+        &[tip(&token_2022, 2_500)],
+        Some(&payer.pubkey()),
+        &[&payer, &tipper],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(reviewer_token.pubkey()).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), reviewer_token_len);
+    let balance = spl_generic_token::generic_token::Account::unpack(&account.data, &token_2022)
+        .unwrap()
+        .amount;
+    assert_eq!(balance, 2_500);
+
+    let account = banks_client
+        .get_account(tip_ledger_pda(&pda, &mint.pubkey(), &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let ledger = TipLedger::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(ledger.total, 2_500);
+}
+
+/// TEST 38: Claim a badge NFT at a review milestone
+///
+/// The 10-review badge can't be claimed after nine reviews. After the tenth
+/// it mints a single, frozen token to the reviewer, whose mint can't issue
+/// more. It can only be claimed once, and only at a listed milestone.
+#[tokio::test]
+async fn test_claim_badge() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    let recent_blockhash = context.last_blockhash;
+    initialize_stats(&context.banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // The payer sponsors the reviews so the reviewer only pays for the badge
+    let add_review = |index: u64| {
+        Transaction::new_signed_with_payer(
+            &[sponsored_add_review_ix(
+                &program_id,
+                &payer.pubkey(),
+                &reviewer.pubkey(),
+                &format!("Badge Bistro {}", index),
+                7,
+                "Regular here",
+                &[],
+                "",
+                None,
+                VISITED_AT,
+                2,
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        )
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    for index in 1..=9 {
+        context.banks_client.process_transaction(add_review(index)).await.unwrap();
+    }
+
+    let expect_error = |result: Result<(), solana_program_test::BanksClientError>, error: ReviewError| {
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    };
+
+    // STEP 1: Nine reviews aren't enough, and 7 isn't a milestone
+    // (the reviewer pays the fee so the later, successful claim isn't a
+    // duplicate of this transaction)
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_badge_ix(&program_id, &reviewer.pubkey(), 10)],
+        Some(&reviewer.pubkey()),
+        &[&reviewer],
+        recent_blockhash,
+    );
+    expect_error(context.banks_client.process_transaction(transaction).await, ReviewError::MilestoneNotReached);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_badge_ix(&program_id, &reviewer.pubkey(), 7)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    expect_error(context.banks_client.process_transaction(transaction).await, ReviewError::InvalidMilestone);
+
+    // STEP 2: The tenth review unlocks the badge
+    context.banks_client.process_transaction(add_review(10)).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_badge_ix(&program_id, &reviewer.pubkey(), 10)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let mint = badge_mint_pda(&reviewer.pubkey(), 10, &program_id);
+    let account = context.banks_client
+        .get_account(badge_account_pda(&mint, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let badge = TokenAccount::unpack(&account.data).unwrap();
+    assert_eq!(badge.owner, reviewer.pubkey());
+    assert_eq!(badge.amount, 1);
+    assert_eq!(badge.state, spl_token::state::AccountState::Frozen);
+
+    let account = context.banks_client.get_account(mint).await.unwrap().unwrap();
+    let mint_state = Mint::unpack(&account.data).unwrap();
+    assert_eq!(mint_state.supply, 1);
+    assert_eq!(mint_state.decimals, 0);
+    assert!(mint_state.mint_authority.is_none());
+
+    // STEP 3: It can't be claimed twice, and the 50-review badge is still locked
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_badge_ix(&program_id, &reviewer.pubkey(), 10)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        blockhash,
+    );
+    expect_error(context.banks_client.process_transaction(transaction).await, ReviewError::BadgeAlreadyClaimed);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_badge_ix(&program_id, &reviewer.pubkey(), 50)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    expect_error(context.banks_client.process_transaction(transaction).await, ReviewError::MilestoneNotReached);
+}
+
+/// TEST 39: Reviews owned by a 2-of-3 multisig
+///
+/// After the author hands the review to a multisig, neither the author nor a
+/// single member can change it; two members together can update and then
+/// delete it, with the rent going back to the author who paid it.
+#[tokio::test]
+async fn test_multisig_owned_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let members = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let member_keys: Vec<Pubkey> = members.iter().map(|member| member.pubkey()).collect();
+    let multisig = Keypair::new();
+
+    let expect_error = |result: Result<(), solana_program_test::BanksClientError>, error: ReviewError| {
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    };
+
+    // STEP 1: A threshold above the member count is rejected
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_multisig_ix(&program_id, &payer.pubkey(), &multisig.pubkey(), 4, &member_keys)],
+        Some(&payer.pubkey()),
+        &[&payer, &multisig],
+        recent_blockhash,
+    );
+    expect_error(banks_client.process_transaction(transaction).await, ReviewError::InvalidMultisig);
+
+    // STEP 2: Create the 2-of-3 multisig and hand it a review
+    let title = "Committee Kitchen";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &member_keys[0], 100_000_000),
+            create_multisig_ix(&program_id, &payer.pubkey(), &multisig.pubkey(), 2, &member_keys),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 6, "Decent", &[], "", None, VISITED_AT, 2),
+            transfer_review_ownership_ix(&program_id, &reviewer.pubkey(), &pda, &multisig.pubkey()),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &multisig, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let update = || update_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Agreed: great", &[], "", None, VISITED_AT, 2, 0);
+
+    // STEP 3: The author no longer owns it, and one member isn't enough
+    let transaction = Transaction::new_signed_with_payer(
+        &[update()],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    expect_error(banks_client.process_transaction(transaction).await, ReviewError::NotReviewOwner);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[signed_by_multisig(update(), &multisig.pubkey(), &member_keys[..1])],
+        Some(&payer.pubkey()),
+        &[&payer, &members[0]],
+        recent_blockhash,
+    );
+    expect_error(banks_client.process_transaction(transaction).await, ReviewError::NotEnoughSigners);
+
+    // STEP 4: Two members update it; the first one pays for the revision
+    let transaction = Transaction::new_signed_with_payer(
+        &[signed_by_multisig(update(), &multisig.pubkey(), &member_keys[..2])],
+        Some(&payer.pubkey()),
+        &[&payer, &members[0], &members[1]],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.rating, 9);
+    assert_eq!(review.owner, multisig.pubkey());
+
+    // STEP 5: Two other members delete it; the author gets the rent back
+    let author_balance = banks_client.get_balance(reviewer.pubkey()).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[signed_by_multisig(
+            delete_sponsored_review_ix(&program_id, &reviewer.pubkey(), title, &reviewer.pubkey()),
+            &multisig.pubkey(),
+            &member_keys[1..],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &members[1], &members[2]],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert!(banks_client.get_account(pda).await.unwrap().is_none());
+    assert!(banks_client.get_balance(reviewer.pubkey()).await.unwrap() > author_balance);
+}
+
+/// TEST 40: Registered owners manage their restaurant's profile
+///
+/// Only the wallet a restaurant is registered to can create or update its
+/// profile, and the price range must be between 1 and 4.
+#[tokio::test]
+async fn test_restaurant_profile() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let restaurant_owner = Keypair::new();
+    let stranger = Keypair::new();
+
+    // The payer is the admin and registers the restaurant to its owner
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &restaurant_owner.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &stranger.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            register_restaurant_ix(
+                &program_id,
+                &payer.pubkey(),
+                "Noodle Bar",
+                "Main St",
+                &restaurant_owner.pubkey(),
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Someone else cannot create the profile
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_profile_ix(
+            &program_id,
+            &stranger.pubkey(),
+            "Noodle Bar",
+            "Ramen",
+            "1 Main St",
+            "https://noodle.bar",
+            2,
+        )],
+        Some(&stranger.pubkey()),
+        &[&stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotRestaurantOwner as u32))
+    );
+
+    // STEP 2: The owner creates it
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_profile_ix(
+            &program_id,
+            &restaurant_owner.pubkey(),
+            "Noodle Bar",
+            "Ramen",
+            "1 Main St",
+            "https://noodle.bar",
+            2,
+        )],
+        Some(&restaurant_owner.pubkey()),
+        &[&restaurant_owner],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let profile_pda = restaurant_profile_pda("Noodle Bar", &program_id);
+    let account = banks_client.get_account(profile_pda).await.unwrap().unwrap();
+    let profile = RestaurantProfile::unpack_checked(&account.data).unwrap();
+    assert_eq!(profile.restaurant, restaurant_pda("Noodle Bar", &program_id));
+    assert_eq!(profile.cuisine, "Ramen");
+    assert_eq!(profile.address, "1 Main St");
+    assert_eq!(profile.website, "https://noodle.bar");
+    assert_eq!(profile.price_range, 2);
+
+    // STEP 3: Price ranges outside 1..=4 are rejected
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_profile_ix(
+            &program_id,
+            &restaurant_owner.pubkey(),
+            "Noodle Bar",
+            "Ramen",
+            "1 Main St",
+            "https://noodle.bar",
+            5,
+        )],
+        Some(&restaurant_owner.pubkey()),
+        &[&restaurant_owner],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::InvalidPriceRange as u32))
+    );
+
+    // STEP 4: The owner updates the profile
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_profile_ix(
+            &program_id,
+            &restaurant_owner.pubkey(),
+            "Noodle Bar",
+            "Ramen & Udon",
+            "2 Main St",
+            "",
+            3,
+        )],
+        Some(&restaurant_owner.pubkey()),
+        &[&restaurant_owner],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(profile_pda).await.unwrap().unwrap();
+    let profile = RestaurantProfile::unpack_checked(&account.data).unwrap();
+    assert_eq!(profile.cuisine, "Ramen & Udon");
+    assert_eq!(profile.address, "2 Main St");
+    assert_eq!(profile.website, "");
+    assert_eq!(profile.price_range, 3);
+}
+
+/// TEST 41: Compressed reviews are validated before the tree CPI
+///
+/// The compression program isn't loaded here, so this covers the checks
+/// AddCompressedReview makes before appending: the review limits and the
+/// compression program account.
+#[tokio::test]
+async fn test_add_compressed_review_validation() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let merkle_tree = Pubkey::new_unique();
+
+    // STEP 1: Ratings are 1-10 until the admin configures another scale,
+    // as for account-backed reviews
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_compressed_review_ix(
+            &program_id,
+            &payer.pubkey(),
+            &merkle_tree,
+            "Noodle Bar",
+            11,
+            "Too good",
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::InvalidRating as u32))
+    );
+
+    // STEP 2: Any other program in place of spl-account-compression is rejected
+    let mut instruction = add_compressed_review_ix(
+        &program_id,
+        &payer.pubkey(),
+        &merkle_tree,
+        "Noodle Bar",
+        8,
+        "Great broth",
+    );
+    instruction.accounts[3].pubkey = program_id;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+}
+
+/// TEST 42: CPIs only go to the real system program
+///
+/// AddReview with another program in the system program slot fails with
+/// IncorrectProgramId before anything is created.
+#[tokio::test]
+async fn test_rejects_fake_system_program() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+
+    let mut instruction = add_review_ix(&program_id, &payer.pubkey(), "Noodle Bar", 8, "Great broth", &[], "", None, VISITED_AT, 2);
+    instruction.accounts[2].pubkey = program_id;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+    let (review, _bump) = Pubkey::find_program_address(
+        &[payer.pubkey().as_ref(), &review_seed("Noodle Bar")],
+        &program_id,
+    );
+    assert!(banks_client.get_account(review).await.unwrap().is_none());
+}
+
+/// TEST 43: UpdateReview reports malformed accounts precisely
+///
+/// A read-only review account, a program in the owner slot, and a truncated
+/// review account each fail with their own error.
+#[tokio::test]
+async fn test_update_review_account_checks() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    initialize_stats(&context.banks_client, &payer, program_id, context.last_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Corner Deli";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 6, "Good pastrami", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: The review must be writable
+    let mut instruction = update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great pastrami", &[], "", None, VISITED_AT, 2, 0);
+    instruction.accounts[1].is_writable = false;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::ReviewNotWritable as u32))
+    );
+
+    // STEP 2: A program can't stand in for the owner
+    let mut instruction = update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great pastrami", &[], "", None, VISITED_AT, 2, 0);
+    instruction.accounts[0] = AccountMeta::new_readonly(program_id, false);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::ExecutableOwner as u32))
+    );
+
+    // STEP 3: A truncated review account is rejected before it is read
+    let mut truncated = context.banks_client.get_account(pda).await.unwrap().unwrap();
+    truncated.data.truncate(16);
+    context.set_account(&pda, &truncated.into());
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great pastrami", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::ReviewAccountTooSmall as u32))
+    );
+}
+
+/// TEST 44: AddReview and UpdateReview set return data
+///
+/// The transaction metadata and a simulation both carry the Borsh-encoded
+/// ReviewReturnData: the review PDA, its rating, and its revision count.
+#[tokio::test]
+async fn test_review_return_data() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+
+    let title = "Taco Stand";
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[payer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+
+    // STEP 1: AddReview returns the new review
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &payer.pubkey(), title, 7, "Fresh tortillas", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, program_id);
+    assert_eq!(
+        ReviewReturnData::try_from_slice(&return_data.data).unwrap(),
+        ReviewReturnData { review: pda, rating: 7, revision_count: 0 }
+    );
+
+    // STEP 2: A simulated update reports the revision it would save
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &payer.pubkey(), title, 9, "Best in town", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let simulation = banks_client.simulate_transaction(transaction).await.unwrap();
+    simulation.result.unwrap().unwrap();
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(
+        ReviewReturnData::try_from_slice(&return_data.data).unwrap(),
+        ReviewReturnData { review: pda, rating: 9, revision_count: 1 }
+    );
+}
+
+/// TEST 45: The admin can switch to a 1-5 rating scale before the first review
+///
+/// Ratings outside the configured scale are rejected, the aggregate buckets
+/// ratings by tenths of the scale, and the scale is locked once a review exists.
+#[tokio::test]
+async fn test_configurable_rating_scale() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // The payer is the admin
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Only the admin can change the scale, and it must be valid
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_config_ix(&program_id, &reviewer.pubkey(), 1, 5)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_config_ix(&program_id, &payer.pubkey(), 5, 5)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::InvalidRatingScale as u32))
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_config_ix(&program_id, &payer.pubkey(), 1, 5)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: A 6 is now out of range, a 5 is the top of the scale
+    let title = "Dumpling House";
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 6, "Juicy", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::InvalidRating as u32))
+    );
+
+    // Compressed reviews use the same scale
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_compressed_review_ix(&program_id, &reviewer.pubkey(), &Pubkey::new_unique(), title, 6, "Juicy")],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::InvalidRating as u32))
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), title, 5, "Juicy", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!((summary.min_rating, summary.max_rating), (1, 5));
+    assert_eq!(summary.rating_sum, 5);
+    assert_eq!(summary.histogram[8], 1);
+
+    // STEP 3: With a review counted, the scale can't change
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_config_ix(&program_id, &payer.pubkey(), 1, 100)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::RatingScaleLocked as u32))
+    );
+}
+
+/// TEST 46: Commit to a review and reveal it later
+///
+/// Only the hash is on chain until the reveal; revealing with different
+/// contents fails, and the matching reveal creates the review and closes
+/// the commitment.
+#[tokio::test]
+async fn test_anonymous_review_reveal() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Sushi Counter";
+    let description = "Rice was cold";
+    let salt = [7u8; 32];
+    let commitment = review_commitment(&reviewer.pubkey(), title, 3, description, &salt);
+
+    // STEP 1: Commit
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_anonymous_review_ix(&program_id, &reviewer.pubkey(), title, 3, description, &salt),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let commitment_address = commitment_pda(&reviewer.pubkey(), &commitment, &program_id);
+    let account = banks_client.get_account(commitment_address).await.unwrap().unwrap();
+    let stored = ReviewCommitment::unpack_checked(&account.data).unwrap();
+    assert_eq!(stored.author, reviewer.pubkey());
+    assert_eq!(stored.commitment, commitment);
+
+    // STEP 2: A reveal that doesn't match the commitment fails
+    let mut instruction = reveal_review_ix(&program_id, &reviewer.pubkey(), title, 3, description, &salt, VISITED_AT, 2);
+    instruction.data = reveal_review_ix(&program_id, &reviewer.pubkey(), title, 9, description, &salt, VISITED_AT, 2).data;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::CommitmentMismatch as u32))
+    );
+
+    // STEP 3: The matching reveal creates the review and closes the commitment
+    let transaction = Transaction::new_signed_with_payer(
+        &[reveal_review_ix(&program_id, &reviewer.pubkey(), title, 3, description, &salt, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.title, title);
+    assert_eq!(review.rating, 3);
+    assert_eq!(review.description, description);
+    assert!(banks_client.get_account(commitment_address).await.unwrap().is_none());
+}
+
+/// TEST 47: Enough abuse reports hide a review until a moderator looks
+///
+/// With a threshold of 2, one report leaves the review visible, a repeat
+/// report from the same wallet fails, and a second wallet's report hides it.
+/// The moderator can then restore it.
+#[tokio::test]
+async fn test_report_review_threshold() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let reporter_a = Keypair::new();
+    let reporter_b = Keypair::new();
+
+    // The payer is the admin and sets a threshold of two reports
+    let title = "Burger Joint";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &reporter_a.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &reporter_b.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            set_report_threshold_ix(&program_id, &payer.pubkey(), 2),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 1, "Spam spam spam", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let (pda, _bump) = Pubkey::find_program_address(
+        &[reviewer.pubkey().as_ref(), &review_seed(title)],
+        &program_id,
+    );
+
+    // STEP 1: One report isn't enough
+    let transaction = Transaction::new_signed_with_payer(
+        &[report_review_ix(&program_id, &reporter_a.pubkey(), &pda)],
+        Some(&reporter_a.pubkey()),
+        &[&reporter_a],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().status, ReviewStatus::Visible);
+
+    // STEP 2: The same wallet can't report twice
+    let transaction = Transaction::new_signed_with_payer(
+        &[report_review_ix(&program_id, &reporter_a.pubkey(), &pda)],
+        Some(&payer.pubkey()),
+        &[&payer, &reporter_a],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::AlreadyReported as u32))
+    );
+
+    // STEP 3: A second wallet's report hides the review
+    let transaction = Transaction::new_signed_with_payer(
+        &[report_review_ix(&program_id, &reporter_b.pubkey(), &pda)],
+        Some(&reporter_b.pubkey()),
+        &[&reporter_b],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().status, ReviewStatus::Hidden);
+    let account = banks_client.get_account(report_count_pda(&pda, &program_id)).await.unwrap().unwrap();
+    assert_eq!(ReviewReportCount::unpack_checked(&account.data).unwrap().report_count, 2);
+
+    // STEP 4: A moderator can restore it
+    let transaction = Transaction::new_signed_with_payer(
+        &[resolve_flag_ix(&program_id, &payer.pubkey(), &pda, false)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().status, ReviewStatus::Visible);
+}
+
+/// TEST 48: The reviewer index tracks a wallet's live reviews
+///
+/// Two reviews from the same wallet both land in its index, which grows to
+/// fit them. Deleting one takes it out again and shrinks the account.
+#[tokio::test]
+async fn test_reviewer_index() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // STEP 1: Add two reviews
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), "Noodle Bar", 8, "Rich broth", &[], "", None, VISITED_AT, 2),
+            add_review_ix(&program_id, &reviewer.pubkey(), "Taco Stand", 6, "Decent", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let noodle = review::client::review_pda(&reviewer.pubkey(), "Noodle Bar", &program_id);
+    let taco = review::client::review_pda(&reviewer.pubkey(), "Taco Stand", &program_id);
+    let index_pda = reviewer_index_pda(&reviewer.pubkey(), &program_id);
+    let account = banks_client.get_account(index_pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ReviewerIndex::size_for(2));
+    let index = ReviewerIndex::unpack_checked(&account.data).unwrap();
+    assert_eq!(index.reviewer, reviewer.pubkey());
+    assert_eq!(index.reviews, vec![noodle, taco]);
+
+    // STEP 2: Delete the first one
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_review_ix(&program_id, &reviewer.pubkey(), "Noodle Bar")],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(index_pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ReviewerIndex::size_for(1));
+    let index = ReviewerIndex::unpack_checked(&account.data).unwrap();
+    assert_eq!(index.reviews, vec![taco]);
+}
+
+/// TEST 49: Titles are normalized before deriving the review PDA
+///
+/// "Pizza Place" and "  pizza   PLACE " are the same review: adding the
+/// second fails, updating through it edits the first, and the stored title
+/// stays as first typed. Renaming to another spelling of the same title
+/// changes the stored title without moving the review.
+#[tokio::test]
+async fn test_title_normalization() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Pizza Place";
+    let other_spelling = "  pizza   PLACE ";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    assert_eq!(pda, review::client::review_pda(&reviewer.pubkey(), other_spelling, &program_id));
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great crust", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: The other spelling is the same review, so it can't be added again
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), other_spelling, 3, "Again", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::AccountAlreadyInitialized as u32)
+        )
+    );
+
+    // STEP 2: Updating through the other spelling edits the original review
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), other_spelling, 9, "Even better", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.title, title);
+    assert_eq!(review.rating, 9);
+
+    // STEP 3: A capitalization fix keeps the address and changes the stored title
+    let transaction = Transaction::new_signed_with_payer(
+        &[migrate_review_ix(&program_id, &reviewer.pubkey(), &reviewer.pubkey(), title, "PIZZA PLACE")],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.title, "PIZZA PLACE");
+    assert_eq!(review.rating, 9);
+}
+
+/// TEST 50: Review a registered restaurant by its registry account
+///
+/// An unregistered restaurant can't be reviewed this way. A registered one
+/// can: the review lives at [reviewer, restaurant], takes the registered
+/// name as its title, and shares the restaurant's aggregate with reviews
+/// added by title. Deleting it works like any other review.
+#[tokio::test]
+async fn test_add_review_for_restaurant() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let other = Keypair::new();
+    let name = "Noodle Bar";
+
+    // STEP 1: Without a registry entry the instruction fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &other.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_for_restaurant_ix(&program_id, &reviewer.pubkey(), name, 8, "Rich broth", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::RestaurantNotRegistered as u32)
+        )
+    );
+
+    // STEP 2: Once registered, one review by registry account and one by title
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            register_restaurant_ix(&program_id, &payer.pubkey(), name, "Main St", &payer.pubkey()),
+            add_review_for_restaurant_ix(&program_id, &reviewer.pubkey(), name, 8, "Rich broth", &[], "", None, VISITED_AT, 2),
+            add_review_ix(&program_id, &other.pubkey(), "noodle bar", 6, "Fine", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer, &other],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let pda = restaurant_review_pda(&reviewer.pubkey(), name, &program_id);
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.title, name);
+    assert_eq!(review.rating, 8);
+    assert_eq!(review.author, reviewer.pubkey());
+
+    let account = banks_client.get_account(aggregate_pda(name, &program_id)).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.review_count, 2);
+    assert_eq!(summary.rating_sum, 14);
+
+    // STEP 3: Delete it, passing its address in place of the title-based one
+    let mut instruction = delete_review_ix(&program_id, &reviewer.pubkey(), name);
+    instruction.accounts[1].pubkey = pda;
+    instruction.accounts[2].pubkey = comment_counter_pda(&pda, &program_id);
+    instruction.accounts[5].pubkey = review_stake_pda(&pda, &program_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert!(banks_client.get_account(pda).await.unwrap().is_none());
+
+    let account = banks_client.get_account(aggregate_pda(name, &program_id)).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.review_count, 1);
+}
+
+/// TEST 51: Reviews lock the configured stake until deleted or slashed
+///
+/// With a stake configured, each new review funds an escrow holding the
+/// stake on top of its rent. Only a moderator can slash it, once, into the
+/// treasury PDA, where it counts towards the fees. Deleting a review that
+/// wasn't slashed returns the stake with the rent.
+#[tokio::test]
+async fn test_review_stake() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let honest = Keypair::new();
+    let spammer = Keypair::new();
+    let stake = 1_000_000;
+
+    // The payer is the admin and asks for a stake on every review
+    let title = "Burger Joint";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &honest.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &spammer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            set_review_stake_ix(&program_id, &payer.pubkey(), stake),
+            add_review_ix(&program_id, &honest.pubkey(), title, 7, "Juicy", &[], "", None, VISITED_AT, 2),
+            add_review_ix(&program_id, &spammer.pubkey(), title, 1, "Buy my stuff", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &honest, &spammer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let honest_pda = review::client::review_pda(&honest.pubkey(), title, &program_id);
+    let spam_pda = review::client::review_pda(&spammer.pubkey(), title, &program_id);
+    let escrow_rent = banks_client.get_rent().await.unwrap().minimum_balance(ReviewStake::SIZE);
+    let account = banks_client
+        .get_account(review_stake_pda(&spam_pda, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.lamports, escrow_rent + stake);
+    let escrow = ReviewStake::unpack_checked(&account.data).unwrap();
+    assert_eq!(escrow.review, spam_pda);
+    assert_eq!(escrow.amount, stake);
+
+    // STEP 1: Only a moderator can slash a stake
+    let transaction = Transaction::new_signed_with_payer(
+        &[slash_review_ix(&program_id, &honest.pubkey(), &spam_pda)],
+        Some(&payer.pubkey()),
+        &[&payer, &honest],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotModerator as u32))
+    );
+
+    // STEP 2: The moderator slashes the spam review's stake into the treasury PDA
+    let transaction = Transaction::new_signed_with_payer(
+        &[slash_review_ix(&program_id, &payer.pubkey(), &spam_pda)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    // No fee was ever set, so the slash created the treasury
+    let treasury_rent = banks_client.get_rent().await.unwrap().minimum_balance(ProgramTreasury::SIZE);
+    let account = banks_client.get_account(treasury_pda(&program_id)).await.unwrap().unwrap();
+    assert_eq!(account.lamports, treasury_rent + escrow_rent + stake);
+    let treasury = ProgramTreasury::unpack_checked(&account.data).unwrap();
+    assert_eq!(treasury.total_fees, escrow_rent + stake);
+    assert!(banks_client
+        .get_account(review_stake_pda(&spam_pda, &program_id))
+        .await
+        .unwrap()
+        .is_none());
+
+    // STEP 3: There is nothing left to slash
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &honest.pubkey(), 1),
+            slash_review_ix(&program_id, &payer.pubkey(), &spam_pda),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(1, InstructionError::Custom(ReviewError::NoStake as u32))
+    );
+
+    // STEP 4: Deleting the honest review returns its stake with the rent
+    let honest_before = banks_client.get_balance(honest.pubkey()).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_review_ix(&program_id, &honest.pubkey(), title)],
+        Some(&payer.pubkey()),
+        &[&payer, &honest],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert!(banks_client
+        .get_account(review_stake_pda(&honest_pda, &program_id))
+        .await
+        .unwrap()
+        .is_none());
+    let honest_after = banks_client.get_balance(honest.pubkey()).await.unwrap();
+    assert!(honest_after >= honest_before + escrow_rent + stake);
+}
+
+/// TEST 52: Optional sub-ratings are validated and summed per restaurant
+///
+/// A review can break its rating down into food, service and ambiance on a
+/// 1-10 scale. The aggregate keeps running sums over the reviews that gave
+/// them; updating a review swaps its sub-ratings and can clear them.
+#[tokio::test]
+async fn test_review_sub_ratings() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+
+    let title = "Corner Bistro";
+    let alice_ratings = SubRatings { food: 9, service: 6, ambiance: 7 };
+    let bob_ratings = SubRatings { food: 5, service: 8, ambiance: 3 };
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &alice.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &bob.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &alice.pubkey(), title, 8, "Great steak", &[], "", Some(alice_ratings), VISITED_AT, 2),
+            add_review_ix(&program_id, &bob.pubkey(), title, 6, "Loud", &[], "", Some(bob_ratings), VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &alice, &bob],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let alice_pda = review::client::review_pda(&alice.pubkey(), title, &program_id);
+    let account = banks_client.get_account(alice_pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(review.sub_ratings, Some(alice_ratings));
+
+    let account = banks_client
+        .get_account(aggregate_pda(title, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let summary = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!(summary.sub_rating_count, 2);
+    assert_eq!((summary.food_sum, summary.service_sum, summary.ambiance_sum), (14, 14, 10));
+
+    // STEP 1: Sub-ratings outside 1-10 are rejected
+    for food in [0, 11] {
+        let carol = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::transfer(&payer.pubkey(), &carol.pubkey(), 100_000_000),
+                add_review_ix(
+                    &program_id,
+                    &carol.pubkey(),
+                    title,
+                    7,
+                    "Fine",
+                    &[],
+                    "",
+                    Some(SubRatings { food, service: 5, ambiance: 5 }),
+                    VISITED_AT,
+                    2,
+                ),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &carol],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(ReviewError::InvalidSubRating as u32)
+            )
+        );
+    }
+
+    // STEP 2: Updating swaps the sub-ratings in the sums
+    let new_ratings = SubRatings { food: 10, service: 10, ambiance: 10 };
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &alice.pubkey(), title, 9, "Even better", &[], "", Some(new_ratings), VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &alice],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client
+        .get_account(aggregate_pda(title, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let summary = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!(summary.sub_rating_count, 2);
+    assert_eq!((summary.food_sum, summary.service_sum, summary.ambiance_sum), (15, 18, 13));
+
+    // STEP 3: Updating without sub-ratings clears them
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &bob.pubkey(), title, 6, "Still loud", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &bob],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let bob_pda = review::client::review_pda(&bob.pubkey(), title, &program_id);
+    let account = banks_client.get_account(bob_pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().sub_ratings, None);
+    let account = banks_client
+        .get_account(aggregate_pda(title, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let summary = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!(summary.sub_rating_count, 1);
+    assert_eq!((summary.food_sum, summary.service_sum, summary.ambiance_sum), (10, 10, 10));
+}
+
+/// TEST 53: Reviews record when the visit was and what it cost
+///
+/// The visit date can't be after the current time and the price range runs
+/// from 1 to 4. An update can correct both.
+#[tokio::test]
+async fn test_review_visit_details() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let now = banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+
+    // STEP 1: A visit in the future is rejected
+    let title = "Sushi Counter";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Fresh fish", &[], "", None, now + 86_400, 4),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(ReviewError::VisitInFuture as u32)
+        )
+    );
+
+    // STEP 2: So is a price range outside 1-4
+    for price_range in [0, 5] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[add_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Fresh fish", &[], "", None, now, price_range)],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(ReviewError::InvalidPriceRange as u32)
+            )
+        );
+    }
+
+    // STEP 3: A visit up to now is stored with the review
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Fresh fish", &[], "", None, now, 4),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!((review.visited_at, review.price_range), (now, 4));
+
+    // STEP 4: An update corrects the visit details
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Fresh fish", &[], "", None, VISITED_AT, 3, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!((review.visited_at, review.price_range), (VISITED_AT, 3));
+}
+
+/// TEST 54: New reviews pay the configured fee into the program treasury
+///
+/// Setting a fee creates the treasury. Each new review then transfers the
+/// fee to it, and only the admin can withdraw what was collected, never
+/// more than the fees held.
+#[tokio::test]
+async fn test_review_fee() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let destination = Keypair::new();
+    let fee = 1_000_000;
+
+    // The payer is the admin and charges a fee on every review
+    let title = "Dim Sum Palace";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            set_review_fee_ix(&program_id, &payer.pubkey(), fee),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great dumplings", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let treasury_rent = banks_client.get_rent().await.unwrap().minimum_balance(ProgramTreasury::SIZE);
+    let account = banks_client.get_account(treasury_pda(&program_id)).await.unwrap().unwrap();
+    assert_eq!(account.lamports, treasury_rent + fee);
+    let treasury = ProgramTreasury::unpack_checked(&account.data).unwrap();
+    assert_eq!(treasury.total_fees, fee);
+
+    // STEP 1: Only the admin can withdraw
+    let transaction = Transaction::new_signed_with_payer(
+        &[withdraw_treasury_ix(&program_id, &reviewer.pubkey(), &reviewer.pubkey(), fee)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    // STEP 2: The treasury's rent can't be withdrawn
+    let transaction = Transaction::new_signed_with_payer(
+        &[withdraw_treasury_ix(&program_id, &payer.pubkey(), &destination.pubkey(), fee + 1)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::InsufficientTreasuryFunds as u32)
+        )
+    );
+
+    // STEP 3: The admin withdraws the collected fee
+    let transaction = Transaction::new_signed_with_payer(
+        &[withdraw_treasury_ix(&program_id, &payer.pubkey(), &destination.pubkey(), fee)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(banks_client.get_balance(destination.pubkey()).await.unwrap(), fee);
+    let account = banks_client.get_account(treasury_pda(&program_id)).await.unwrap().unwrap();
+    assert_eq!(account.lamports, treasury_rent);
+    let treasury = ProgramTreasury::unpack_checked(&account.data).unwrap();
+    assert_eq!((treasury.total_fees, treasury.total_withdrawn), (fee, fee));
+}
+
+/// TEST 55: The admin role moves only when the nominee accepts it
+///
+/// The admin nominates a successor, but keeps the role until the nominee
+/// signs AcceptAdmin. Nobody else can nominate or accept, and once the
+/// nominee accepts the old admin loses its admin rights.
+#[tokio::test]
+async fn test_admin_transfer() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let new_admin = Keypair::new();
+    let stranger = Keypair::new();
+
+    // The payer creates the config and nominates new_admin
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            nominate_admin_ix(&program_id, &payer.pubkey(), &new_admin.pubkey()),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(config_pda(&program_id)).await.unwrap().unwrap();
+    let config = ProgramConfig::unpack_checked(&account.data).unwrap();
+    assert_eq!(config.admin, payer.pubkey());
+    assert_eq!(config.pending_admin, Some(new_admin.pubkey()));
+
+    // STEP 1: Only the admin can nominate
+    let transaction = Transaction::new_signed_with_payer(
+        &[nominate_admin_ix(&program_id, &stranger.pubkey(), &stranger.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    // STEP 2: Only the nominee can accept
+    let transaction = Transaction::new_signed_with_payer(
+        &[accept_admin_ix(&program_id, &stranger.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotPendingAdmin as u32)
+        )
+    );
+
+    // STEP 3: The nominee accepts and can act as admin
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            accept_admin_ix(&program_id, &new_admin.pubkey()),
+            set_report_threshold_ix(&program_id, &new_admin.pubkey(), 3),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &new_admin],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(config_pda(&program_id)).await.unwrap().unwrap();
+    let config = ProgramConfig::unpack_checked(&account.data).unwrap();
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, None);
+    assert_eq!(config.report_threshold, 3);
+
+    // STEP 4: The old admin no longer can
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_report_threshold_ix(&program_id, &payer.pubkey(), 1)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+}
+
+/// TEST 56: While paused, reviews and comments are frozen
+///
+/// The admin pauses the program: adding, updating, deleting, archiving,
+/// transferring, and commenting all fail with ProgramPaused. Only the admin can pause, and unpausing
+/// restores everything.
+#[tokio::test]
+async fn test_pause() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Ramen Shop";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 7, "Good broth", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+
+    // STEP 1: Only the admin can pause
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_paused_ix(&program_id, &reviewer.pubkey(), true)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::NotAdmin as u32))
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_paused_ix(&program_id, &payer.pubkey(), true)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Every write fails while paused
+    let writes = [
+        add_review_ix(&program_id, &reviewer.pubkey(), "Taco Stand", 6, "Decent", &[], "", None, VISITED_AT, 2),
+        update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great broth", &[], "", None, VISITED_AT, 2, 0),
+        delete_review_ix(&program_id, &reviewer.pubkey(), title),
+        archive_review_ix(&program_id, &reviewer.pubkey(), &pda, title),
+        transfer_review_ownership_ix(&program_id, &reviewer.pubkey(), &pda, &payer.pubkey()),
+        add_comment_ix(&program_id, &reviewer.pubkey(), &pda, 0, "Try the gyoza"),
+    ];
+    for instruction in writes {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(ReviewError::ProgramPaused as u32)
+            )
+        );
+    }
+
+    // STEP 3: Unpausing lets the review be updated again
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            set_paused_ix(&program_id, &payer.pubkey(), false),
+            update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Great broth", &[], "", None, VISITED_AT, 2, 0),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().rating, 8);
+}
+
+/// TEST 57: Votes weight each review in the restaurant's average
+///
+/// Every review starts with a middling helpfulness score. Upvotes raise it
+/// and downvotes lower it, and the aggregate's weighted sums follow, so a
+/// heavily downvoted review counts for less than a well-received one. The
+/// weight carries through an update and leaves with a delete.
+#[tokio::test]
+async fn test_weighted_rating() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let fan = Keypair::new();
+    let hater = Keypair::new();
+    let voters = [Keypair::new(), Keypair::new(), Keypair::new()];
+
+    // STEP 1: A glowing and a scathing review of the same restaurant
+    let title = "Dim Sum Palace";
+    let mut setup = vec![initialize_config_ix(&program_id, &payer.pubkey(), &[])];
+    for wallet in [&fan, &hater].into_iter().chain(voters.iter()) {
+        setup.push(system_instruction::transfer(&payer.pubkey(), &wallet.pubkey(), 100_000_000));
+    }
+    setup.push(add_review_ix(&program_id, &fan.pubkey(), title, 8, "Best har gow", &[], "", None, VISITED_AT, 2));
+    setup.push(add_review_ix(&program_id, &hater.pubkey(), title, 2, "Cold dumplings", &[], "", None, VISITED_AT, 2));
+    let transaction = Transaction::new_signed_with_payer(
+        &setup,
+        Some(&payer.pubkey()),
+        &[&payer, &fan, &hater],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let fan_review = review::client::review_pda(&fan.pubkey(), title, &program_id);
+    let hater_review = review::client::review_pda(&hater.pubkey(), title, &program_id);
+
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.weight_sum, 2 * helpfulness(0, 0));
+    assert_eq!(summary.weighted_rating_sum, 10 * helpfulness(0, 0));
+
+    // STEP 2: One upvote for the fan, three downvotes for the hater
+    let mut votes = vec![vote_review_ix(&program_id, &voters[0].pubkey(), &fan_review, title, true)];
+    for voter in &voters {
+        votes.push(vote_review_ix(&program_id, &voter.pubkey(), &hater_review, title, false));
+    }
+    let transaction = Transaction::new_signed_with_payer(
+        &votes,
+        Some(&payer.pubkey()),
+        &[&payer, &voters[0], &voters[1], &voters[2]],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(hater_review).await.unwrap().unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().helpfulness(), helpfulness(0, 3));
+    assert!(helpfulness(0, 3) < helpfulness(0, 0) && helpfulness(0, 0) < helpfulness(1, 0));
+
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.rating_sum, 10);
+    assert_eq!(summary.weight_sum, helpfulness(1, 0) + helpfulness(0, 3));
+    assert_eq!(summary.weighted_rating_sum, 8 * helpfulness(1, 0) + 2 * helpfulness(0, 3));
+    // The weighted average leans towards the helpful review
+    assert!(summary.weighted_rating_sum / summary.weight_sum > summary.rating_sum / summary.review_count);
+
+    // STEP 3: An update keeps the review's weight, a delete removes it
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            update_review_ix(&program_id, &fan.pubkey(), title, 9, "Best har gow in town", &[], "", None, VISITED_AT, 2, 0),
+            delete_review_ix(&program_id, &hater.pubkey(), title),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &fan, &hater],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(summary.weight_sum, helpfulness(1, 0));
+    assert_eq!(summary.weighted_rating_sum, 9 * helpfulness(1, 0));
+}
+
+/// TEST 58: Reviewers can set a display name
+///
+/// The first SetDisplayName creates the reviewer's profile and later ones
+/// overwrite the name. Names that are empty, too long, or use characters
+/// outside [A-Za-z0-9_.-] are rejected.
+#[tokio::test]
+async fn test_display_name() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let reviewer = Keypair::new();
+
+    // STEP 1: The first name creates the profile
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            set_display_name_ix(&program_id, &reviewer.pubkey(), "food_critic"),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let profile_pda = reviewer_profile_pda(&reviewer.pubkey(), &program_id);
+    let account = banks_client.get_account(profile_pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ReviewerProfile::SIZE);
+    let profile = ReviewerProfile::unpack_checked(&account.data).unwrap();
+    assert_eq!(profile.reviewer, reviewer.pubkey());
+    assert_eq!(profile.display_name, "food_critic");
+
+    // STEP 2: Invalid names are rejected
+    for name in ["", "has space", "emoji\u{1F35C}", "<b>bold</b>", &"x".repeat(MAX_DISPLAY_NAME_LEN + 1)] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[set_display_name_ix(&program_id, &reviewer.pubkey(), name)],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(ReviewError::InvalidDisplayName as u32)
+            ),
+            "{:?} should be rejected",
+            name
+        );
+    }
+
+    // STEP 3: A longer name overwrites the first
+    let name = "x".repeat(MAX_DISPLAY_NAME_LEN - 4) + ".eth";
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_display_name_ix(&program_id, &reviewer.pubkey(), &name)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(profile_pda).await.unwrap().unwrap();
+    assert_eq!(ReviewerProfile::unpack_checked(&account.data).unwrap().display_name, name);
+}
+
+/// TEST 59: Titles and descriptions can't carry control characters
+///
+/// Newlines, escape sequences, and bidirectional overrides in a title or
+/// description fail with InvalidCharacters, on add and on update. Strings
+/// that aren't valid UTF-8 never decode. Ordinary non-ASCII text is fine,
+/// and lengths are also capped in graphemes, not just bytes.
+#[tokio::test]
+async fn test_reject_invalid_characters() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // STEP 1: Accented and non-Latin text is accepted
+    let title = "Café Zoë 東京";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 7, "Très bon — 美味しい", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: Control and bidi characters are rejected
+    let rejected = [
+        add_review_ix(&program_id, &reviewer.pubkey(), "Fake\nLog Line", 7, "Fine", &[], "", None, VISITED_AT, 2),
+        add_review_ix(&program_id, &reviewer.pubkey(), "Diner", 7, "Nice\u{1b}[31m red", &[], "", None, VISITED_AT, 2),
+        add_review_ix(&program_id, &reviewer.pubkey(), "Diner", 7, "evil\u{202E}txt.exe", &[], "", None, VISITED_AT, 2),
+        update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Null\0byte", &[], "", None, VISITED_AT, 2, 0),
+    ];
+    for instruction in rejected {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(ReviewError::InvalidCharacters as u32)
+            )
+        );
+    }
+
+    // STEP 3: A description that isn't valid UTF-8 fails to decode
+    let mut instruction = add_review_ix(&program_id, &reviewer.pubkey(), "Diner", 7, "abc", &[], "", None, VISITED_AT, 2);
+    let at = instruction.data.windows(3).position(|bytes| bytes == b"abc").unwrap();
+    instruction.data[at..at + 3].copy_from_slice(&[0xff, 0xfe, 0xfd]);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    );
+
+    // STEP 4: An accent written as a combining mark is part of its letter's
+    // grapheme, so this title is 45 chars but only 40 graphemes
+    let combined = format!("{}{}", "a".repeat(35), "e\u{301}".repeat(5));
+    assert_eq!(combined.len(), MAX_TITLE_LEN);
+    assert_eq!(combined.chars().count(), 45);
+    assert_eq!(grapheme_count(&combined), 40);
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &reviewer.pubkey(), &combined, 7, "Fine", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 5: Text over the grapheme limits is rejected as too long
+    let rejected = [
+        (
+            add_review_ix(&program_id, &reviewer.pubkey(), &"t".repeat(MAX_TITLE_GRAPHEMES + 1), 7, "Fine", &[], "", None, VISITED_AT, 2),
+            ReviewError::TitleTooLong,
+        ),
+        (
+            update_review_ix(&program_id, &reviewer.pubkey(), title, 8, &"d".repeat(MAX_DESCRIPTION_GRAPHEMES + 1), &[], "", None, VISITED_AT, 2, 0),
+            ReviewError::DescriptionTooLong,
+        ),
+    ];
+    for (instruction, error) in rejected {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        let result = banks_client.process_transaction(transaction).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+/// TEST 60: AddReviewIfNotExists is safe to resend
+///
+/// The first one creates the review like AddReview. Resending it with the
+/// same content succeeds, returns the same ReviewReturnData, and changes
+/// nothing; resending it with different content fails like AddReview does.
+#[tokio::test]
+async fn test_add_review_if_not_exists() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let title = "Noodle Bar";
+    let add = |description: &str| {
+        add_review_if_not_exists_ix(&program_id, &reviewer.pubkey(), title, 8, description, &["ramen"], "", None, VISITED_AT, 2)
+    };
+
+    // STEP 1: The first attempt creates the review
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add("Rich broth"),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let review_before = banks_client.get_account(pda).await.unwrap().unwrap();
+
+    // STEP 2: A retry with the same content is a no-op
+    // (the reviewer pays this time, so the transaction isn't a duplicate)
+    let transaction = Transaction::new_signed_with_payer(
+        &[add("Rich broth")],
+        Some(&reviewer.pubkey()),
+        &[&reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(
+        ReviewReturnData::try_from_slice(&return_data.data).unwrap(),
+        ReviewReturnData { review: pda, rating: 8, revision_count: 0 }
+    );
+
+    let review_after = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(review_after.data, review_before.data);
+    let account = banks_client
+        .get_account(reviewer_record_pda(&reviewer.pubkey(), &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ReviewerRecord::unpack_checked(&account.data).unwrap().review_count, 1);
+
+    // STEP 3: Different content means a different review, which can't be added
+    let transaction = Transaction::new_signed_with_payer(
+        &[add("Thin broth")],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::AccountAlreadyInitialized as u32)
+        )
+    );
+}
+
+/// TEST 61: UpsertReview adds a missing review and updates an existing one
+///
+/// The first upsert creates the review, the second updates it and saves a
+/// revision. Once the review is transferred, the author's upsert fails
+/// the ownership check instead of creating a second review.
+#[tokio::test]
+async fn test_upsert_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let new_owner = Keypair::new();
+
+    // STEP 1: Upserting a review that doesn't exist adds it
+    let title = "Crepe Corner";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            upsert_review_ix(&program_id, &reviewer.pubkey(), title, 6, "Good crepes", &[], "", None, VISITED_AT, 2, 0),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.rating, 6);
+    assert_eq!(account_state.revision_count, 0);
+
+    // STEP 2: Upserting it again updates it
+    let transaction = Transaction::new_signed_with_payer(
+        &[upsert_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Great crepes", &[], "", None, VISITED_AT, 2, 0)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!(account_state.rating, 9);
+    assert_eq!(account_state.description, "Great crepes");
+    assert_eq!(account_state.revision_count, 1);
+    let revision = banks_client.get_account(revision_pda(&pda, 0, &program_id)).await.unwrap().unwrap();
+    assert_eq!(ReviewRevision::unpack_checked(&revision.data).unwrap().rating, 6);
+
+    // STEP 3: After a transfer the author no longer owns the review
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            transfer_review_ownership_ix(&program_id, &reviewer.pubkey(), &pda, &new_owner.pubkey()),
+            upsert_review_ix(&program_id, &reviewer.pubkey(), title, 2, "Went downhill", &[], "", None, VISITED_AT, 2, 1),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(ReviewError::NotReviewOwner as u32)
+        )
+    );
+}
+
+/// TEST 62: Follow-up reviews after a repeat visit
+///
+/// Each follow-up is a new review at the next sequence number, so the
+/// original stays as written and both count towards the restaurant.
+/// Follow-ups can't be edited, and a follow-up at the wrong sequence
+/// number is rejected.
+#[tokio::test]
+async fn test_follow_up_reviews() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    // STEP 1: The first visit, then a follow-up
+    let title = "Harbor Oysters";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 5, "Gritty oysters", &[], "", None, VISITED_AT, 2),
+            add_follow_up_review_ix(&program_id, &reviewer.pubkey(), title, 9, "New chef, much better", &[], "", None, VISITED_AT + 86_400, 3, 1),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let original = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+    let account = banks_client.get_account(original).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!((account_state.rating, account_state.sequence), (5, 0));
+
+    let follow_up = follow_up_review_pda(&reviewer.pubkey(), title, 1, &program_id);
+    let account = banks_client.get_account(follow_up).await.unwrap().unwrap();
+    let account_state = AccountState::unpack(&account.data).unwrap();
+    assert_eq!((account_state.rating, account_state.sequence), (9, 1));
+    assert_eq!(account_state.title, title);
+
+    let account = banks_client
+        .get_account(followups_pda(&reviewer.pubkey(), title, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(FollowUpCounter::unpack_checked(&account.data).unwrap().count, 1);
+
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let summary = RestaurantAggregate::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!((summary.review_count, summary.rating_sum), (2, 14));
+
+    // STEP 2: A follow-up can't be edited
+    let mut instruction = update_review_ix(&program_id, &reviewer.pubkey(), title, 10, "Edited", &[], "", None, VISITED_AT, 2, 0);
+    instruction.accounts[1] = AccountMeta::new(follow_up, false);
+    instruction.accounts[5] = AccountMeta::new(revision_pda(&follow_up, 0, &program_id), false);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::FollowUpReadOnly as u32)
+        )
+    );
+
+    // STEP 3: The next follow-up must take sequence 2
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_follow_up_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Still good", &[], "", None, VISITED_AT, 3, 1)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_err());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_follow_up_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Still good", &[], "", None, VISITED_AT, 3, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client
+        .get_account(follow_up_review_pda(&reviewer.pubkey(), title, 2, &program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(AccountState::unpack(&account.data).unwrap().sequence, 2);
+}
+
+/// TEST 63: Following and unfollowing reviewers
+///
+/// Follow creates the follow PDA and both reviewer profiles, bumping the
+/// follower's following count and the followee's follower count. Following
+/// twice or following yourself fails; Unfollow closes the PDA and drops
+/// both counts.
+#[tokio::test]
+async fn test_follow_reviewer() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let follower = Keypair::new();
+    let followee = Keypair::new();
+
+    // STEP 1: The first follow creates the follow and both profiles
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &follower.pubkey(), 100_000_000),
+            follow_ix(&program_id, &follower.pubkey(), &followee.pubkey()),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &follower],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let follow = follow_pda(&follower.pubkey(), &followee.pubkey(), &program_id);
+    let account = banks_client.get_account(follow).await.unwrap().unwrap();
+    let record = ReviewerFollow::unpack_checked(&account.data).unwrap();
+    assert_eq!((record.follower, record.followee), (follower.pubkey(), followee.pubkey()));
+
+    let follower_profile = reviewer_profile_pda(&follower.pubkey(), &program_id);
+    let followee_profile = reviewer_profile_pda(&followee.pubkey(), &program_id);
+    let account = banks_client.get_account(follower_profile).await.unwrap().unwrap();
+    let profile = ReviewerProfile::unpack_checked(&account.data).unwrap();
+    assert_eq!((profile.follower_count, profile.following_count), (0, 1));
+    assert_eq!(profile.display_name, "");
+    let account = banks_client.get_account(followee_profile).await.unwrap().unwrap();
+    let profile = ReviewerProfile::unpack_checked(&account.data).unwrap();
+    assert_eq!((profile.follower_count, profile.following_count), (1, 0));
+
+    // STEP 2: Following twice fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[follow_ix(&program_id, &follower.pubkey(), &followee.pubkey())],
+        Some(&follower.pubkey()),
+        &[&follower],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::AlreadyFollowing as u32)
+        )
+    );
+
+    // STEP 3: Following yourself fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[follow_ix(&program_id, &follower.pubkey(), &follower.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &follower],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::CannotFollowSelf as u32)
+        )
+    );
+
+    // STEP 4: Setting a display name keeps the counts
+    let transaction = Transaction::new_signed_with_payer(
+        &[set_display_name_ix(&program_id, &follower.pubkey(), "regular")],
+        Some(&payer.pubkey()),
+        &[&payer, &follower],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let account = banks_client.get_account(follower_profile).await.unwrap().unwrap();
+    let profile = ReviewerProfile::unpack_checked(&account.data).unwrap();
+    assert_eq!((profile.following_count, profile.display_name.as_str()), (1, "regular"));
+
+    // STEP 5: Unfollow closes the follow and drops both counts
+    let transaction = Transaction::new_signed_with_payer(
+        &[unfollow_ix(&program_id, &follower.pubkey(), &followee.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &follower],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert!(banks_client.get_account(follow).await.unwrap().is_none());
+    let account = banks_client.get_account(follower_profile).await.unwrap().unwrap();
+    assert_eq!(ReviewerProfile::unpack_checked(&account.data).unwrap().following_count, 0);
+    let account = banks_client.get_account(followee_profile).await.unwrap().unwrap();
+    assert_eq!(ReviewerProfile::unpack_checked(&account.data).unwrap().follower_count, 0);
+}
+
+/// TEST 64: Wallets can bookmark restaurants
+///
+/// The first bookmark creates the wallet's list and each one grows it by
+/// an entry. Unregistered restaurants and duplicates are rejected, and
+/// removing a bookmark shrinks the list again.
+#[tokio::test]
+async fn test_bookmarks() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let user = Keypair::new();
+
+    // The payer is the admin and registers two restaurants
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &user.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            register_restaurant_ix(&program_id, &payer.pubkey(), "Noodle Bar", "Main St", &payer.pubkey()),
+            register_restaurant_ix(&program_id, &payer.pubkey(), "Taco Stand", "Harbor Rd", &payer.pubkey()),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Bookmark both restaurants
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            bookmark_restaurant_ix(&program_id, &user.pubkey(), "Noodle Bar"),
+            bookmark_restaurant_ix(&program_id, &user.pubkey(), "taco stand"),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &user],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let bookmarks = bookmarks_pda(&user.pubkey(), &program_id);
+    let account = banks_client.get_account(bookmarks).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), RestaurantBookmarks::size_for(2));
+    let list = RestaurantBookmarks::unpack_checked(&account.data).unwrap();
+    assert_eq!(list.owner, user.pubkey());
+    assert_eq!(
+        list.restaurants,
+        vec![restaurant_pda("Noodle Bar", &program_id), restaurant_pda("Taco Stand", &program_id)]
+    );
+
+    // STEP 2: Duplicates and unregistered restaurants are rejected
+    let transaction = Transaction::new_signed_with_payer(
+        &[bookmark_restaurant_ix(&program_id, &user.pubkey(), "noodle bar")],
+        Some(&payer.pubkey()),
+        &[&payer, &user],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::AlreadyBookmarked as u32)
+        )
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[bookmark_restaurant_ix(&program_id, &user.pubkey(), "Burger Hut")],
+        Some(&payer.pubkey()),
+        &[&payer, &user],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(transaction).await.is_err());
+
+    // STEP 3: Removing a bookmark shrinks the list and refunds the rent
+    let balance = banks_client.get_balance(user.pubkey()).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[remove_bookmark_ix(&program_id, &user.pubkey(), "Noodle Bar")],
+        Some(&payer.pubkey()),
+        &[&payer, &user],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert!(banks_client.get_balance(user.pubkey()).await.unwrap() > balance);
+
+    let account = banks_client.get_account(bookmarks).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), RestaurantBookmarks::size_for(1));
+    let list = RestaurantBookmarks::unpack_checked(&account.data).unwrap();
+    assert_eq!(list.restaurants, vec![restaurant_pda("Taco Stand", &program_id)]);
+
+    // STEP 4: Removing it again fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[remove_bookmark_ix(&program_id, &user.pubkey(), "Noodle Bar")],
+        Some(&user.pubkey()),
+        &[&user],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotBookmarked as u32)
+        )
+    );
+}
+
+/// TEST 65: Restaurant owners claim their listing for a fee
+///
+/// A claim escrows the configured fee in the restaurant's claim PDA and
+/// blocks other claims. Rejecting it refunds the fee and lets the
+/// restaurant be claimed again; approving it makes the claimant the
+/// registered and verified owner and moves the fee into the treasury.
+#[tokio::test]
+async fn test_claim_restaurant() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    let claimant = Keypair::new();
+    let rival = Keypair::new();
+    let fee = 50_000_000;
+    let name = "Noodle Bar";
+
+    // The payer is the admin, sets the fee, and lists the restaurant
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &claimant.pubkey(), 500_000_000),
+            system_instruction::transfer(&payer.pubkey(), &rival.pubkey(), 500_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            set_claim_fee_ix(&program_id, &payer.pubkey(), fee),
+            register_restaurant_ix(&program_id, &payer.pubkey(), name, "Main St", &payer.pubkey()),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: The claim escrows the fee on top of the claim's rent
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_restaurant_ix(&program_id, &claimant.pubkey(), name)],
+        Some(&payer.pubkey()),
+        &[&payer, &claimant],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let claim = claim_pda(name, &program_id);
+    let account = banks_client.get_account(claim).await.unwrap().unwrap();
+    assert_eq!(account.lamports, Rent::default().minimum_balance(RestaurantClaim::SIZE) + fee);
+    let record = RestaurantClaim::unpack_checked(&account.data).unwrap();
+    assert_eq!(record.restaurant, restaurant_pda(name, &program_id));
+    assert_eq!((record.claimant, record.fee, record.status), (claimant.pubkey(), fee, ClaimStatus::Pending));
+
+    // STEP 2: Nobody else can claim it while the claim is pending
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_restaurant_ix(&program_id, &rival.pubkey(), name)],
+        Some(&payer.pubkey()),
+        &[&payer, &rival],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::ClaimPending as u32)
+        )
+    );
+
+    // STEP 3: Only the admin can settle a claim
+    let transaction = Transaction::new_signed_with_payer(
+        &[reject_claim_ix(&program_id, &rival.pubkey(), name, &claimant.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer, &rival],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotAdmin as u32)
+        )
+    );
+
+    // STEP 4: Rejecting refunds the fee and frees the restaurant
+    let balance = banks_client.get_balance(claimant.pubkey()).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[reject_claim_ix(&program_id, &payer.pubkey(), name, &claimant.pubkey())],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(banks_client.get_balance(claimant.pubkey()).await.unwrap(), balance + fee);
+    let account = banks_client.get_account(claim).await.unwrap().unwrap();
+    assert_eq!(RestaurantClaim::unpack_checked(&account.data).unwrap().status, ClaimStatus::Rejected);
+
+    // STEP 5: The claimant tries again and the admin approves
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            claim_restaurant_ix(&program_id, &claimant.pubkey(), name),
+            approve_claim_ix(&program_id, &payer.pubkey(), name),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &claimant],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(claim).await.unwrap().unwrap();
+    assert_eq!(RestaurantClaim::unpack_checked(&account.data).unwrap().status, ClaimStatus::Approved);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(RestaurantClaim::SIZE));
+    let account = banks_client.get_account(verified_owner_pda(name, &program_id)).await.unwrap().unwrap();
+    assert_eq!(VerifiedOwner::unpack_checked(&account.data).unwrap().owner, claimant.pubkey());
+    let account = banks_client.get_account(restaurant_pda(name, &program_id)).await.unwrap().unwrap();
+    assert_eq!(Restaurant::unpack_checked(&account.data).unwrap().owner, claimant.pubkey());
+    let account = banks_client.get_account(treasury_pda(&program_id)).await.unwrap().unwrap();
+    assert_eq!(ProgramTreasury::unpack_checked(&account.data).unwrap().total_fees, fee);
+
+    // STEP 6: A verified restaurant can't be claimed
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_restaurant_ix(&program_id, &rival.pubkey(), name)],
+        Some(&rival.pubkey()),
+        &[&rival],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::RestaurantAlreadyVerified as u32)
+        )
+    );
+}
+
+/// TEST 66: Moderators remove abusive reviews
+///
+/// RemoveReview closes the review, slashing its stake, and sends the rent
+/// to the treasury PDA instead of the author, counting it in the totals. The rating leaves the
+/// restaurant's aggregate, and only moderators can remove a review.
+#[tokio::test]
+async fn test_remove_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let honest = Keypair::new();
+    let abuser = Keypair::new();
+    let stake = 1_000_000;
+
+    // The payer is the admin and asks for a stake on every review
+    let title = "Burger Joint";
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &honest.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &abuser.pubkey(), 100_000_000),
+            initialize_config_ix(&program_id, &payer.pubkey(), &[]),
+            set_review_stake_ix(&program_id, &payer.pubkey(), stake),
+            add_review_ix(&program_id, &honest.pubkey(), title, 7, "Juicy", &[], "", None, VISITED_AT, 2),
+            add_review_ix(&program_id, &abuser.pubkey(), title, 1, "Abusive rant", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &honest, &abuser],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let abuse_pda = review::client::review_pda(&abuser.pubkey(), title, &program_id);
+    let review_rent = banks_client.get_account(abuse_pda).await.unwrap().unwrap().lamports;
+    let escrow_rent = banks_client.get_rent().await.unwrap().minimum_balance(ReviewStake::SIZE);
+
+    // STEP 1: Other reviewers can't remove a review
+    let transaction = Transaction::new_signed_with_payer(
+        &[remove_review_ix(&program_id, &honest.pubkey(), &abuse_pda, &abuser.pubkey(), title)],
+        Some(&payer.pubkey()),
+        &[&payer, &honest],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(ReviewError::NotModerator as u32)
+        )
+    );
+
+    // STEP 2: The moderator removes it, and the treasury gets the rent and stake
+    let abuser_balance = banks_client.get_balance(abuser.pubkey()).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[remove_review_ix(&program_id, &payer.pubkey(), &abuse_pda, &abuser.pubkey(), title)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert!(banks_client.get_account(abuse_pda).await.unwrap().is_none());
+    assert!(banks_client
+        .get_account(review_stake_pda(&abuse_pda, &program_id))
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(banks_client.get_balance(abuser.pubkey()).await.unwrap(), abuser_balance);
+
+    // No fee was ever set, so the removal created the treasury
+    let account = banks_client.get_account(treasury_pda(&program_id)).await.unwrap().unwrap();
+    let treasury_rent = banks_client.get_rent().await.unwrap().minimum_balance(ProgramTreasury::SIZE);
+    let total_fees = ProgramTreasury::unpack_checked(&account.data).unwrap().total_fees;
+    assert!(total_fees >= review_rent + escrow_rent + stake);
+    assert_eq!(account.lamports, treasury_rent + total_fees);
+
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let aggregate = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!((aggregate.review_count, aggregate.rating_sum), (1, 7));
+}
+
+/// TEST 67: Time-decayed ratings favor recent reviews
+///
+/// A review's weight in the aggregate's decayed sums halves every
+/// DECAY_HALF_LIFE. RecomputeAggregate decays the sums to the current time,
+/// and deleting a review takes out the weight its age gives it.
+#[tokio::test]
+async fn test_time_decayed_rating() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    assert_eq!(decay(DECAY_SCALE, 0), DECAY_SCALE);
+    assert_eq!(decay(DECAY_SCALE, DECAY_HALF_LIFE), DECAY_SCALE / 2);
+    assert_eq!(decay(DECAY_SCALE, DECAY_HALF_LIFE / 2), 707_106);
+    assert_eq!(decay(DECAY_SCALE, DECAY_HALF_LIFE * 64), 0);
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+    initialize_stats(&context.banks_client, &payer, program_id, context.last_blockhash).await;
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    let title = "Slow Cooker";
+
+    // STEP 1: Alice rates it 2
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &alice.pubkey(), 100_000_000),
+            system_instruction::transfer(&payer.pubkey(), &bob.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &alice.pubkey(), title, 2, "Cold food", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &alice],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    let account = context.banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let aggregate = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    let start = aggregate.decayed_at;
+    assert_eq!((aggregate.decayed_weight_sum, aggregate.decayed_rating_sum), (DECAY_SCALE, 2 * DECAY_SCALE));
+
+    // STEP 2: A half-life later Bob rates it 10 and counts twice as much
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = start + DECAY_HALF_LIFE;
+    context.set_sysvar(&clock);
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&program_id, &bob.pubkey(), title, 10, "Much better now", &[], "", None, VISITED_AT, 2)],
+        Some(&payer.pubkey()),
+        &[&payer, &bob],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    let account = context.banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let aggregate = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!(aggregate.decayed_at, start + DECAY_HALF_LIFE);
+    assert_eq!(aggregate.decayed_weight_sum, DECAY_SCALE * 3 / 2);
+    assert_eq!(aggregate.decayed_rating_sum, 2 * DECAY_SCALE / 2 + 10 * DECAY_SCALE);
+    // The plain average is 6, the recency-weighted one 7.33
+    assert_eq!(aggregate.rating_sum / aggregate.review_count, 6);
+    assert_eq!(aggregate.decayed_rating_sum * 100 / aggregate.decayed_weight_sum, 733);
+
+    // STEP 3: The crank decays both sums, leaving the average alone
+    clock.unix_timestamp = start + 2 * DECAY_HALF_LIFE;
+    context.set_sysvar(&clock);
+
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[recompute_aggregate_ix(&program_id, title)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    let account = context.banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let aggregate = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!(aggregate.decayed_at, start + 2 * DECAY_HALF_LIFE);
+    assert_eq!(aggregate.decayed_weight_sum, DECAY_SCALE * 3 / 4);
+    assert_eq!(aggregate.decayed_rating_sum, 11 * DECAY_SCALE / 2);
+
+    // STEP 4: Deleting Alice's review takes out her quarter weight
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_review_ix(&program_id, &alice.pubkey(), title)],
+        Some(&payer.pubkey()),
+        &[&payer, &alice],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    let account = context.banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let aggregate = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!(aggregate.decayed_weight_sum, DECAY_SCALE / 2);
+    assert_eq!(aggregate.decayed_rating_sum, 10 * DECAY_SCALE / 2);
+}
+
+/// TEST 68: Several instructions on the same review in one transaction
+///
+/// Each instruction sees what the ones before it wrote: a review can be
+/// added, updated twice, and commented on in one transaction, then deleted
+/// and added again under the same title in another.
+#[tokio::test]
+async fn test_multiple_instructions_same_review() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+    let title = "Busy Bistro";
+    let pda = review::client::review_pda(&reviewer.pubkey(), title, &program_id);
+
+    // STEP 1: Add, update twice, and comment
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 100_000_000),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 7, "Good", &[], "", None, VISITED_AT, 2),
+            update_review_ix(&program_id, &reviewer.pubkey(), title, 8, "Better", &[], "", None, VISITED_AT, 2, 0),
+            update_review_ix(&program_id, &reviewer.pubkey(), title, 9, "Best", &[], "", None, VISITED_AT, 2, 1),
+            add_comment_ix(&program_id, &reviewer.pubkey(), &pda, 0, "Came back twice"),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!((review.rating, review.description.as_str(), review.revision_count), (9, "Best", 2));
+    for (index, rating) in [(0, 7), (1, 8)] {
+        let account = banks_client.get_account(revision_pda(&pda, index, &program_id)).await.unwrap().unwrap();
+        assert_eq!(ReviewRevision::unpack_checked(&account.data).unwrap().rating, rating);
+    }
+    let account = banks_client.get_account(comment_counter_pda(&pda, &program_id)).await.unwrap().unwrap();
+    assert_eq!(ReviewCommentCounter::unpack_checked(&account.data).unwrap().counter, 1);
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let aggregate = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!((aggregate.review_count, aggregate.rating_sum), (1, 9));
+
+    // STEP 2: Delete and re-add under the same title
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            delete_review_ix(&program_id, &reviewer.pubkey(), title),
+            add_review_ix(&program_id, &reviewer.pubkey(), title, 5, "Starting over", &[], "", None, VISITED_AT, 2),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    let review = AccountState::unpack(&account.data).unwrap();
+    assert_eq!((review.rating, review.description.as_str(), review.revision_count), (5, "Starting over", 0));
+    let account = banks_client.get_account(comment_counter_pda(&pda, &program_id)).await.unwrap().unwrap();
+    assert_eq!(ReviewCommentCounter::unpack_checked(&account.data).unwrap().counter, 0);
+    let account = banks_client.get_account(aggregate_pda(title, &program_id)).await.unwrap().unwrap();
+    let aggregate = RestaurantAggregate::unpack_checked(&account.data).unwrap();
+    assert_eq!((aggregate.review_count, aggregate.rating_sum), (1, 5));
+    let account = banks_client.get_account(reviewer_index_pda(&reviewer.pubkey(), &program_id)).await.unwrap().unwrap();
+    assert_eq!(ReviewerIndex::unpack_checked(&account.data).unwrap().reviews, vec![pda]);
+}
+
+/// TEST 69: Deleted reviews don't count towards badges
+///
+/// Adding and deleting reviews in a loop bumps the reviewer record's
+/// lifetime count, but badge milestones count the reviews still live, so
+/// the loop doesn't unlock the 10-review badge.
+#[tokio::test]
+async fn test_badge_ignores_deleted_reviews() {
+    solana_logger::setup_with_default("solana_runtime::message=debug");
+
+    let program_id = Pubkey::from_str("Fm4FXYj8mbBzHnwq1V7Yh5cP9TqrGJSqYdHZ3u2KLxRV").unwrap();
+    let program_test = ProgramTest::new(
+        "review",
+        program_id,
+        processor!(review::process_instruction),
+    );
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+    initialize_stats(&banks_client, &payer, program_id, recent_blockhash).await;
+    let reviewer = Keypair::new();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(&payer.pubkey(), &reviewer.pubkey(), 1_000_000_000)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 1: Add and delete ten reviews
+    for index in 1..=10 {
+        let title = format!("Farm Stand {}", index);
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                add_review_ix(&program_id, &reviewer.pubkey(), &title, 7, "Fresh", &[], "", None, VISITED_AT, 2),
+                delete_review_ix(&program_id, &reviewer.pubkey(), &title),
+            ],
+            Some(&payer.pubkey()),
+            &[&payer, &reviewer],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(transaction).await.unwrap();
+    }
+
+    let account = banks_client.get_account(reviewer_record_pda(&reviewer.pubkey(), &program_id)).await.unwrap().unwrap();
+    assert_eq!(ReviewerRecord::unpack_checked(&account.data).unwrap().review_count, 10);
+
+    // STEP 2: None of them are live, so the badge stays locked
+    let transaction = Transaction::new_signed_with_payer(
+        &[claim_badge_ix(&program_id, &reviewer.pubkey(), 10)],
+        Some(&payer.pubkey()),
+        &[&payer, &reviewer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(ReviewError::MilestoneNotReached as u32))
+    );
+    assert!(banks_client.get_account(badge_mint_pda(&reviewer.pubkey(), 10, &program_id)).await.unwrap().is_none());
 }
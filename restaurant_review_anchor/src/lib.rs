@@ -0,0 +1,377 @@
+// Anchor port of the restaurant review program's core review lifecycle
+// Only AddReview, UpdateReview, DeleteReview, and the InitializeConfig and
+// UpdateConfig that set their rating scale are ported. Reviews and the config
+// live at the same PDAs and in the same layouts as the native program's, and
+// the instructions keep the native encoding ([variant_byte][borsh payload])
+// and account order, so a native client can target either program by
+// swapping the program ID. The native side accounts (comment counters,
+// aggregates, stats, reviewer records, stakes, fees, revisions) are accepted
+// in their native positions but not created or updated; see README.md
+
+use anchor_lang::prelude::*;
+
+pub mod state; // Review account layout, seeds, and errors
+
+use crate::state::{
+    grapheme_count, is_printable_text, review_seed, write_fixed_str, ProgramConfig, Review, ReviewError, ReviewStatus,
+    SubRatings, CONTENT_URI_PREFIXES, DEFAULT_MAX_RATING, DEFAULT_MIN_RATING, MAX_CONTENT_URI_LEN,
+    MAX_DESCRIPTION_GRAPHEMES, MAX_DESCRIPTION_LEN, MAX_MODERATORS, MAX_PRICE_RANGE, MAX_TAGS, MAX_TAG_LEN,
+    MAX_TITLE_GRAPHEMES, MAX_TITLE_LEN,
+};
+use std::ops::RangeInclusive;
+
+declare_id!("Av3cVdbRASkAfK8scSn8aEyY1pjbwk4JnDWiMxSWsdVJ");
+
+#[program]
+pub mod restaurant_review_anchor {
+    use super::*;
+
+    // Create the signer's review of `title`
+    // Same variant byte and payload as the native AddReview
+    #[allow(clippy::too_many_arguments)]
+    #[instruction(discriminator = [0])]
+    pub fn add_review(
+        ctx: Context<AddReview>,
+        title: String,         // Restaurant name
+        rating: u8,            // Rating on the configured scale
+        description: String,   // Review text
+        tags: Vec<String>,     // Cuisine/category tags
+        content_uri: String,   // Off-chain content URI, empty if none
+        sub_ratings: Option<SubRatings>, // Food/service/ambiance breakdown
+        visited_at: i64,       // When the reviewer visited
+        price_range: u8,       // What the visit cost, 1 ($) to 4 ($$$$)
+    ) -> Result<()> {
+        msg!("Adding review...");
+
+        require!(title.len() <= MAX_TITLE_LEN, ReviewError::TitleTooLong);
+        require!(grapheme_count(&title) <= MAX_TITLE_GRAPHEMES, ReviewError::TitleTooLong);
+        validate_text("Title", &title)?;
+        let now = Clock::get()?.unix_timestamp;
+        let rating_range = load_rating_range(&ctx.accounts.config)?;
+        validate_content(rating, rating_range, &description, &tags, &content_uri, sub_ratings, visited_at, price_range, now)?;
+
+        let initializer = ctx.accounts.initializer.key();
+        let mut review = ctx.accounts.review.load_init()?;
+        review.is_initialized = 1;
+        review.version = Review::VERSION;
+        review.created_at = now.to_le_bytes();
+        review.updated_at = now.to_le_bytes();
+        review.bump = ctx.bumps.review;
+        review.author = initializer;
+        review.owner = initializer;
+        review.payer = initializer;
+        review.status = ReviewStatus::Visible as u8;
+        write_fixed_str(&mut review.title, &title);
+        review.set_content(rating, &description, &tags, &content_uri, sub_ratings, visited_at, price_range);
+
+        msg!("Review created: {}", ctx.accounts.review.key());
+        Ok(())
+    }
+
+    // Replace everything but the title of a review; only its owner can
+    // Same variant byte and payload as the native UpdateReview
+    #[allow(clippy::too_many_arguments)]
+    #[instruction(discriminator = [1])]
+    pub fn update_review(
+        ctx: Context<UpdateReview>,
+        _title: String,        // Restaurant name, only used to find the PDA
+        rating: u8,            // New rating
+        description: String,   // New review text
+        tags: Vec<String>,     // New tags, replacing the old ones
+        content_uri: String,   // New off-chain content URI, empty to clear it
+        sub_ratings: Option<SubRatings>, // New sub-ratings, None to clear them
+        visited_at: i64,       // New visit timestamp, not in the future
+        price_range: u8,       // New price range, 1 ($) to 4 ($$$$)
+    ) -> Result<()> {
+        msg!("Updating review...");
+
+        let now = Clock::get()?.unix_timestamp;
+        let rating_range = load_rating_range(&ctx.accounts.config)?;
+        validate_content(rating, rating_range, &description, &tags, &content_uri, sub_ratings, visited_at, price_range, now)?;
+
+        let mut review = ctx.accounts.review.load_mut()?;
+        require!(review.status != ReviewStatus::Deleted as u8, ReviewError::ReviewArchived);
+        review.set_content(rating, &description, &tags, &content_uri, sub_ratings, visited_at, price_range);
+        review.updated_at = now.to_le_bytes();
+
+        Ok(())
+    }
+
+    // Close a review, returning its rent to its owner
+    // Same variant byte and payload as the native DeleteReview
+    #[instruction(discriminator = [2])]
+    pub fn delete_review(ctx: Context<DeleteReview>, _title: String) -> Result<()> {
+        msg!("Review deleted, rent returned to {}", ctx.accounts.owner.key());
+        Ok(())
+    }
+
+    // Create the config with the default rating scale; the signer becomes the admin
+    // Same variant byte and payload as the native InitializeConfig
+    #[instruction(discriminator = [9])]
+    pub fn initialize_config(ctx: Context<InitializeConfig>, moderators: Vec<Pubkey>) -> Result<()> {
+        msg!("Initializing program config...");
+
+        require!(moderators.len() <= MAX_MODERATORS, ReviewError::TooManyModerators);
+
+        let config = &mut ctx.accounts.config;
+        config.is_initialized = true;
+        config.admin = ctx.accounts.admin.key();
+        config.min_rating = DEFAULT_MIN_RATING;
+        config.max_rating = DEFAULT_MAX_RATING;
+        config.moderators = moderators;
+
+        msg!("Program config created: {}", config.key());
+        Ok(())
+    }
+
+    // Change the rating scale; only the admin can
+    // Same variant byte and payload as the native UpdateConfig. The native
+    // program locks the scale once the first review is counted, because its
+    // aggregates bucket ratings by scale; the port keeps no aggregates or
+    // stats, so the scale can always change
+    #[instruction(discriminator = [27])]
+    pub fn update_config(ctx: Context<UpdateConfig>, min_rating: u8, max_rating: u8) -> Result<()> {
+        msg!("Setting rating scale: {}-{}", min_rating, max_rating);
+
+        require!(min_rating != 0 && min_rating < max_rating, ReviewError::InvalidRatingScale);
+
+        let config = &mut ctx.accounts.config;
+        config.min_rating = min_rating;
+        config.max_rating = max_rating;
+        Ok(())
+    }
+}
+
+// Accounts for AddReview, in the native order
+#[derive(Accounts)]
+#[instruction(title: String)]
+pub struct AddReview<'info> {
+    // Reviewer, pays the rent
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    // Review PDA at [author, review_seed(title)], like the native program's
+    #[account(
+        init,
+        payer = initializer,
+        space = Review::LEN,
+        seeds = [initializer.key().as_ref(), &review_seed(&title)],
+        bump,
+    )]
+    pub review: AccountLoader<'info, Review>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: native comment counter PDA, not kept by the port
+    pub comment_counter: UncheckedAccount<'info>,
+
+    /// CHECK: native stats PDA, not kept by the port
+    pub stats: UncheckedAccount<'info>,
+
+    /// CHECK: native reviewer record PDA, not kept by the port
+    pub reviewer_record: UncheckedAccount<'info>,
+
+    /// CHECK: native restaurant aggregate PDA, not kept by the port
+    pub aggregate: UncheckedAccount<'info>,
+
+    /// CHECK: Clock sysvar, read through Clock::get instead
+    pub clock: UncheckedAccount<'info>,
+
+    /// CHECK: config PDA, may be uninitialized; read by load_rating_range
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: native restaurant registry PDA, not kept by the port
+    pub restaurant: UncheckedAccount<'info>,
+
+    /// CHECK: native reviewer index PDA, not kept by the port
+    pub reviewer_index: UncheckedAccount<'info>,
+
+    /// CHECK: native stake escrow PDA, not kept by the port
+    pub stake: UncheckedAccount<'info>,
+
+    /// CHECK: native treasury PDA, not kept by the port
+    pub treasury: UncheckedAccount<'info>,
+
+    /// CHECK: Rent sysvar, read through Rent::get by `init` instead
+    pub rent: UncheckedAccount<'info>,
+}
+
+// Accounts for UpdateReview, in the native order
+#[derive(Accounts)]
+#[instruction(title: String)]
+pub struct UpdateReview<'info> {
+    // Current owner of the review
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Review PDA, re-derived from the stored author and bump
+    #[account(
+        mut,
+        seeds = [review.load()?.author.as_ref(), &review_seed(&title)],
+        bump = review.load()?.bump,
+        has_one = owner @ ReviewError::NotReviewOwner,
+    )]
+    pub review: AccountLoader<'info, Review>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: native stats PDA, not kept by the port
+    pub stats: UncheckedAccount<'info>,
+
+    /// CHECK: native restaurant aggregate PDA, not kept by the port
+    pub aggregate: UncheckedAccount<'info>,
+
+    /// CHECK: native revision PDA, not kept by the port
+    pub revision: UncheckedAccount<'info>,
+
+    /// CHECK: Clock sysvar, read through Clock::get instead
+    pub clock: UncheckedAccount<'info>,
+
+    /// CHECK: config PDA, may be uninitialized; read by load_rating_range
+    #[account(seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Rent sysvar, unused since no revision is saved
+    pub rent: UncheckedAccount<'info>,
+}
+
+// Accounts for DeleteReview, in the native order
+// The port only writes reviews its author paid for and still owns, so the
+// rent always goes back to the owner and there is no trailing sponsor account
+#[derive(Accounts)]
+#[instruction(title: String)]
+pub struct DeleteReview<'info> {
+    // Current owner of the review, receives the rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    // Review PDA to close
+    #[account(
+        mut,
+        seeds = [review.load()?.author.as_ref(), &review_seed(&title)],
+        bump = review.load()?.bump,
+        has_one = owner @ ReviewError::NotReviewOwner,
+        constraint = review.load()?.payer == owner.key() @ ReviewError::InvalidRentPayer,
+        close = owner,
+    )]
+    pub review: AccountLoader<'info, Review>,
+
+    /// CHECK: native comment counter PDA, not kept by the port
+    pub comment_counter: UncheckedAccount<'info>,
+
+    /// CHECK: native restaurant aggregate PDA, not kept by the port
+    pub aggregate: UncheckedAccount<'info>,
+
+    /// CHECK: native reviewer index PDA, not kept by the port
+    pub reviewer_index: UncheckedAccount<'info>,
+
+    /// CHECK: native stake escrow PDA, not kept by the port
+    pub stake: UncheckedAccount<'info>,
+
+    /// CHECK: config PDA, not read since the port doesn't pause
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Rent sysvar, unused when closing
+    pub rent: UncheckedAccount<'info>,
+}
+
+// Accounts for InitializeConfig, in the native order
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    // Admin, pays the rent
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    // Config PDA at ["config"], like the native program's
+    #[account(init, payer = admin, space = ProgramConfig::SIZE, seeds = [b"config"], bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Rent sysvar, read through Rent::get by `init` instead
+    pub rent: UncheckedAccount<'info>,
+}
+
+// Accounts for UpdateConfig, in the native order
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    // Config admin
+    pub admin: Signer<'info>,
+
+    // Config PDA
+    #[account(mut, seeds = [b"config"], bump, has_one = admin @ ReviewError::NotAdmin)]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: native stats PDA, not kept by the port
+    pub stats: UncheckedAccount<'info>,
+}
+
+// Rating scale from the config PDA, or the default scale until it is created
+fn load_rating_range(config: &AccountInfo) -> Result<RangeInclusive<u8>> {
+    if config.data_is_empty() {
+        return Ok(DEFAULT_MIN_RATING..=DEFAULT_MAX_RATING);
+    }
+    if config.owner != &crate::ID {
+        return Err(ProgramError::IllegalOwner.into());
+    }
+    let config = ProgramConfig::try_deserialize(&mut &config.try_borrow_data()?[..])?;
+    require!(config.is_initialized, ReviewError::UninitializedAccount);
+    Ok(config.rating_range())
+}
+
+// Titles and descriptions end up in logs and frontends, so they can't carry
+// characters that would forge log lines or reorder the display
+fn validate_text(field: &str, text: &str) -> Result<()> {
+    if !is_printable_text(text) {
+        msg!("{} contains control or bidirectional formatting characters", field);
+        return err!(ReviewError::InvalidCharacters);
+    }
+    Ok(())
+}
+
+// The checks the native program runs on everything but the title
+#[allow(clippy::too_many_arguments)]
+fn validate_content(
+    rating: u8,
+    rating_range: RangeInclusive<u8>,
+    description: &str,
+    tags: &[String],
+    content_uri: &str,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+    now: i64,
+) -> Result<()> {
+    require!(rating_range.contains(&rating), ReviewError::InvalidRating);
+    require!(description.len() <= MAX_DESCRIPTION_LEN, ReviewError::DescriptionTooLong);
+    require!(
+        grapheme_count(description) <= MAX_DESCRIPTION_GRAPHEMES,
+        ReviewError::DescriptionTooLong
+    );
+    validate_text("Description", description)?;
+
+    require!(tags.len() <= MAX_TAGS, ReviewError::TooManyTags);
+    require!(
+        tags.iter().all(|tag| !tag.is_empty() && tag.len() <= MAX_TAG_LEN),
+        ReviewError::InvalidTag
+    );
+
+    if !content_uri.is_empty() {
+        require!(content_uri.len() <= MAX_CONTENT_URI_LEN, ReviewError::ContentUriTooLong);
+        require!(
+            CONTENT_URI_PREFIXES
+                .iter()
+                .any(|prefix| content_uri.len() > prefix.len() && content_uri.starts_with(prefix)),
+            ReviewError::InvalidContentUri
+        );
+    }
+
+    require!(
+        !sub_ratings.is_some_and(|ratings| !ratings.is_valid()),
+        ReviewError::InvalidSubRating
+    );
+    require!(visited_at <= now, ReviewError::VisitInFuture);
+    require!((1..=MAX_PRICE_RANGE).contains(&price_range), ReviewError::InvalidPriceRange);
+    Ok(())
+}
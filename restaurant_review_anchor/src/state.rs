@@ -0,0 +1,290 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use std::ops::RangeInclusive;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Limits, seeds, and the review layout below are copied from the native
+// program's state.rs and must be kept in sync with it, so a review written by
+// either program decodes the same way
+
+// Maximum length of a restaurant title in bytes
+pub const MAX_TITLE_LEN: usize = 50;
+// Maximum length of a review description in bytes
+pub const MAX_DESCRIPTION_LEN: usize = 500;
+// Longest title and description accepted, in user-perceived characters
+// (extended grapheme clusters), which is what a reader actually sees
+// They match the byte limits, so ASCII text keeps its full length; the
+// byte limits still apply, since they are the account's storage
+pub const MAX_TITLE_GRAPHEMES: usize = MAX_TITLE_LEN;
+pub const MAX_DESCRIPTION_GRAPHEMES: usize = MAX_DESCRIPTION_LEN;
+// Maximum number of tags per review, and of bytes per tag
+pub const MAX_TAGS: usize = 5;
+pub const MAX_TAG_LEN: usize = 16;
+// Maximum length of the off-chain content URI, and the storage it can point at
+pub const MAX_CONTENT_URI_LEN: usize = 128;
+pub const CONTENT_URI_PREFIXES: [&str; 2] = ["ipfs://", "ar://"];
+// Rating scale used until the admin configures another one
+pub const DEFAULT_MIN_RATING: u8 = 1;
+pub const DEFAULT_MAX_RATING: u8 = 10;
+// Scale of each sub-rating
+pub const MIN_SUB_RATING: u8 = 1;
+pub const MAX_SUB_RATING: u8 = 10;
+// Most expensive price range, 4 = $$$$
+pub const MAX_PRICE_RANGE: u8 = 4;
+
+// Fixed-capacity string fields: a little-endian u32 length, then the bytes,
+// zero-padded to capacity
+pub const TAG_SLOT_LEN: usize = 4 + MAX_TAG_LEN;
+pub const CONTENT_URI_SLOT_LEN: usize = 4 + MAX_CONTENT_URI_LEN;
+pub const DESCRIPTION_SLOT_LEN: usize = 4 + MAX_DESCRIPTION_LEN;
+pub const TITLE_SLOT_LEN: usize = 4 + MAX_TITLE_LEN;
+
+// A review, laid out byte for byte like the native program's AccountState
+// (layout version 4)
+// The 8-byte `review\0\0` tag replaces Anchor's generated discriminator, and
+// every field is a byte array so the struct has alignment 1 and no padding
+#[account(zero_copy, discriminator = b"review\0\0")]
+pub struct Review {
+    pub is_initialized: u8,                    // 0 or 1
+    pub version: u8,                           // Always Review::VERSION
+    pub rating: u8,                            // Restaurant rating on the configured scale
+    pub tip_total: [u8; 8],                    // Little-endian u64, lamports tipped to the author
+    pub upvotes: [u8; 8],                      // Little-endian u64
+    pub downvotes: [u8; 8],                    // Little-endian u64
+    pub revision_count: [u8; 8],               // Little-endian u64, always 0 here (no revisions are kept)
+    pub created_at: [u8; 8],                   // Little-endian i64
+    pub updated_at: [u8; 8],                   // Little-endian i64
+    pub bump: u8,                              // Canonical bump of the review PDA
+    pub author: Pubkey,                        // Wallet that wrote the review, a PDA seed
+    pub owner: Pubkey,                         // Wallet allowed to update or delete the review
+    pub payer: Pubkey,                         // Wallet that paid the rent and gets it back
+    pub status: u8,                            // ReviewStatus as u8
+    pub tag_count: u8,                         // Number of tag slots in use
+    pub tags: [[u8; TAG_SLOT_LEN]; MAX_TAGS],  // Tag slots, unused ones zeroed
+    pub content_uri: [u8; CONTENT_URI_SLOT_LEN], // Off-chain content URI
+    pub description: [u8; DESCRIPTION_SLOT_LEN], // Review description
+    pub title: [u8; TITLE_SLOT_LEN],           // Restaurant name as typed
+    pub sub_ratings: [u8; 3],                  // Food, service, ambiance; all zero if not given
+    pub visited_at: [u8; 8],                   // Little-endian i64
+    pub price_range: u8,                       // 1 ($) to MAX_PRICE_RANGE ($$$$)
+    pub sequence: [u8; 4],                     // Little-endian u32, always 0 here (no follow-ups)
+}
+
+impl Review {
+    // Layout version of the native program's current AccountState
+    pub const VERSION: u8 = 4;
+
+    // Size of a review account, tag included: AccountState::LEN
+    pub const LEN: usize = 8 + std::mem::size_of::<Self>();
+
+    // Offsets of the fields RPC filters use most, tag included, matching
+    // the native AccountState offsets
+    pub const RATING_OFFSET: usize = 10;
+    pub const AUTHOR_OFFSET: usize = 60;
+    pub const TITLE_OFFSET: usize = 894;
+
+    pub fn title(&self) -> Result<String> {
+        read_fixed_str(&self.title)
+    }
+
+    // Write every field a review's author chooses, as AddReview and
+    // UpdateReview both do
+    // Callers validate the input against the limits above first
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_content(
+        &mut self,
+        rating: u8,
+        description: &str,
+        tags: &[String],
+        content_uri: &str,
+        sub_ratings: Option<SubRatings>,
+        visited_at: i64,
+        price_range: u8,
+    ) {
+        self.rating = rating;
+        write_fixed_str(&mut self.description, description);
+        self.tag_count = tags.len() as u8;
+        for (i, slot) in self.tags.iter_mut().enumerate() {
+            write_fixed_str(slot, tags.get(i).map_or("", String::as_str));
+        }
+        write_fixed_str(&mut self.content_uri, content_uri);
+        self.sub_ratings = sub_ratings.map_or([0; 3], |ratings| [ratings.food, ratings.service, ratings.ambiance]);
+        self.visited_at = visited_at.to_le_bytes();
+        self.price_range = price_range;
+    }
+}
+
+// The layout must match the native AccountState exactly
+const _: () = assert!(Review::LEN == 964);
+const _: () = assert!(8 + std::mem::offset_of!(Review, rating) == Review::RATING_OFFSET);
+const _: () = assert!(8 + std::mem::offset_of!(Review, author) == Review::AUTHOR_OFFSET);
+const _: () = assert!(8 + std::mem::offset_of!(Review, title) == Review::TITLE_OFFSET);
+
+// Write a string into a fixed-capacity field: u32 length, the bytes,
+// then zeros up to the end of the field
+pub fn write_fixed_str(dst: &mut [u8], value: &str) {
+    let (len, buf) = dst.split_at_mut(4);
+    len.copy_from_slice(&(value.len() as u32).to_le_bytes());
+    buf[..value.len()].copy_from_slice(value.as_bytes());
+    buf[value.len()..].fill(0);
+}
+
+// Read a string written by write_fixed_str
+pub fn read_fixed_str(src: &[u8]) -> Result<String> {
+    let (len, buf) = src.split_at(4);
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    if len > buf.len() {
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+    String::from_utf8(buf[..len].to_vec()).map_err(|_| ProgramError::InvalidAccountData.into())
+}
+
+// Most moderators the config can list besides the admin
+pub const MAX_MODERATORS: usize = 10;
+
+// Program-wide settings in the singleton ["config"] PDA, laid out like the
+// native program's ProgramConfig: the `config\0\0` tag, then the Borsh fields
+// The port only reads the rating scale; the other settings are kept so the
+// account decodes the same way in either program
+#[account(discriminator = b"config\0\0")]
+pub struct ProgramConfig {
+    pub is_initialized: bool,    // Whether this account has been initialized
+    pub admin: Pubkey,           // Wallet that created the config
+    pub strict_registry: bool,   // Native registry mode, not enforced here
+    pub update_cooldown: u32,    // Native update cooldown, not enforced here
+    pub min_rating: u8,          // Lowest rating accepted
+    pub max_rating: u8,          // Highest rating accepted
+    pub report_threshold: u32,   // Native report threshold, not enforced here
+    pub review_stake: u64,       // Native review stake, not enforced here
+    pub review_fee: u64,         // Native review fee, not enforced here
+    pub pending_admin: Option<Pubkey>, // Wallet nominated to take over as admin
+    pub paused: bool,            // Native pause flag, not enforced here
+    pub claim_fee: u64,          // Native claim fee, not enforced here
+    pub moderators: Vec<Pubkey>, // Additional moderators, at most MAX_MODERATORS
+}
+
+impl ProgramConfig {
+    // Size of the config account, tag included: the native ProgramConfig::SIZE
+    pub const SIZE: usize = 8 + 1 + 32 + 1 + 4 + 1 + 1 + 4 + 8 + 8 + (1 + 32) + 1 + 8 + (4 + 32 * MAX_MODERATORS);
+
+    // Ratings reviews may give
+    pub fn rating_range(&self) -> RangeInclusive<u8> {
+        self.min_rating..=self.max_rating
+    }
+}
+
+// Optional breakdown of a review's rating, each part from MIN_SUB_RATING
+// to MAX_SUB_RATING
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SubRatings {
+    pub food: u8,
+    pub service: u8,
+    pub ambiance: u8,
+}
+
+impl SubRatings {
+    // Whether every part is on the sub-rating scale
+    pub fn is_valid(&self) -> bool {
+        [self.food, self.service, self.ambiance]
+            .iter()
+            .all(|rating| (MIN_SUB_RATING..=MAX_SUB_RATING).contains(rating))
+    }
+}
+
+// A review's lifecycle state, stored as one byte
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReviewStatus {
+    Visible = 0, // Shown normally
+    Flagged = 1, // Reported by a reader, waiting for a moderator
+    Hidden = 2,  // Hidden by a moderator
+    Deleted = 3, // Archived by its owner, kept for indexers
+}
+
+// Whether text is free of control characters and the bidirectional
+// overrides and isolates that can reorder how it is displayed
+pub fn is_printable_text(text: &str) -> bool {
+    !text
+        .chars()
+        .any(|c| c.is_control() || matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'))
+}
+
+// Number of user-perceived characters (extended grapheme clusters) in text
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+// Canonical form of a restaurant title: trimmed, lowercased, with runs of
+// whitespace collapsed to one space
+pub fn normalize_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// PDA seed for a review's title: the SHA-256 hash of the normalized title,
+// the same seed the native program derives reviews from
+pub fn review_seed(title: &str) -> [u8; 32] {
+    hash(normalize_title(title).as_bytes()).to_bytes()
+}
+
+// Errors, named like their native counterparts
+// Anchor numbers custom errors from 6000, so the codes themselves differ
+#[error_code]
+pub enum ReviewError {
+    #[msg("Rating outside the configured scale")]
+    InvalidRating,
+
+    #[msg("Title is longer than the maximum allowed length")]
+    TitleTooLong,
+
+    #[msg("Description is longer than the maximum allowed length")]
+    DescriptionTooLong,
+
+    #[msg("Text contains control or bidirectional formatting characters")]
+    InvalidCharacters,
+
+    #[msg("Review has more than the maximum number of tags")]
+    TooManyTags,
+
+    #[msg("Tag is empty or longer than the maximum allowed length")]
+    InvalidTag,
+
+    #[msg("Content URI is longer than the maximum allowed length")]
+    ContentUriTooLong,
+
+    #[msg("Content URI must start with an allowed prefix")]
+    InvalidContentUri,
+
+    #[msg("Sub-ratings must be between 1 and 10")]
+    InvalidSubRating,
+
+    #[msg("Visit date is in the future")]
+    VisitInFuture,
+
+    #[msg("Price range must be between 1 and 4")]
+    InvalidPriceRange,
+
+    #[msg("Signer is not the owner of this review")]
+    NotReviewOwner,
+
+    #[msg("Review has been deleted by its owner")]
+    ReviewArchived,
+
+    #[msg("Rent payer account does not match the review")]
+    InvalidRentPayer,
+
+    #[msg("Account not initialized yet")]
+    UninitializedAccount,
+
+    #[msg("Too many moderators")]
+    TooManyModerators,
+
+    #[msg("Signer is not the config admin")]
+    NotAdmin,
+
+    #[msg("Rating scale minimum must be at least 1 and below the maximum")]
+    InvalidRatingScale,
+}
@@ -0,0 +1,347 @@
+// Integration tests for the Anchor port of the restaurant review program
+// These use solana-program-test, like the native program's tests, and build
+// instructions by hand in the native encoding
+
+use anchor_lang::prelude::borsh;
+use anchor_lang::{AnchorSerialize, InstructionData};
+use restaurant_review_anchor::state::{read_fixed_str, review_seed, ProgramConfig, Review, ReviewError, SubRatings};
+use solana_program_test::{tokio, BanksClient, ProgramTest};
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::system_program;
+use solana_sdk::sysvar;
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+// Visit date for test reviews (November 2023), safely before the test clock
+const VISITED_AT: i64 = 1_700_000_000;
+
+// Payload of AddReview and UpdateReview, serialized exactly like the
+// native program's ReviewPayload
+#[derive(AnchorSerialize)]
+struct ReviewPayload {
+    title: String,
+    rating: u8,
+    description: String,
+    tags: Vec<String>,
+    content_uri: String,
+    sub_ratings: Option<SubRatings>,
+    visited_at: i64,
+    price_range: u8,
+}
+
+// Native encoding: the variant byte, then the Borsh payload
+fn review_data(variant: u8, title: &str, rating: u8, description: &str) -> Vec<u8> {
+    let mut data = vec![variant];
+    data.extend(
+        borsh::to_vec(&ReviewPayload {
+            title: title.to_string(),
+            rating,
+            description: description.to_string(),
+            tags: vec!["burgers".to_string()],
+            content_uri: String::new(),
+            sub_ratings: Some(SubRatings { food: 8, service: 6, ambiance: 5 }),
+            visited_at: VISITED_AT,
+            price_range: 2,
+        })
+        .unwrap(),
+    );
+    data
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &restaurant_review_anchor::ID).0
+}
+
+fn review_pda(author: &Pubkey, title: &str) -> Pubkey {
+    pda(&[author.as_ref(), &review_seed(title)])
+}
+
+fn config_pda() -> Pubkey {
+    pda(&[b"config"])
+}
+
+// The native AddReview accounts, derived under the port's program ID
+fn add_review_ix(author: &Pubkey, title: &str, rating: u8, description: &str) -> Instruction {
+    let review = review_pda(author, title);
+    Instruction::new_with_bytes(
+        restaurant_review_anchor::ID,
+        &review_data(0, title, rating, description),
+        vec![
+            AccountMeta::new(*author, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(pda(&[review.as_ref(), b"comment"]), false),
+            AccountMeta::new(pda(&[b"stats"]), false),
+            AccountMeta::new(pda(&[b"reviewer", author.as_ref()]), false),
+            AccountMeta::new(pda(&[b"aggregate", &review_seed(title)]), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(), false),
+            AccountMeta::new_readonly(pda(&[b"restaurant", &review_seed(title)]), false),
+            AccountMeta::new(pda(&[b"reviewer_index", author.as_ref()]), false),
+            AccountMeta::new(pda(&[b"stake", review.as_ref()]), false),
+            AccountMeta::new(pda(&[b"treasury"]), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// The native UpdateReview accounts, for the review's first revision
+fn update_review_ix(owner: &Pubkey, author: &Pubkey, title: &str, rating: u8, description: &str) -> Instruction {
+    let review = review_pda(author, title);
+    Instruction::new_with_bytes(
+        restaurant_review_anchor::ID,
+        &review_data(1, title, rating, description),
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(pda(&[b"stats"]), false),
+            AccountMeta::new(pda(&[b"aggregate", &review_seed(title)]), false),
+            AccountMeta::new(pda(&[review.as_ref(), b"revision", &0u64.to_le_bytes()]), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(config_pda(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// The native DeleteReview accounts
+fn delete_review_ix(owner: &Pubkey, author: &Pubkey, title: &str) -> Instruction {
+    let review = review_pda(author, title);
+    let mut data = vec![2];
+    data.extend(borsh::to_vec(&title.to_string()).unwrap());
+    Instruction::new_with_bytes(
+        restaurant_review_anchor::ID,
+        &data,
+        vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(review, false),
+            AccountMeta::new(pda(&[review.as_ref(), b"comment"]), false),
+            AccountMeta::new(pda(&[b"aggregate", &review_seed(title)]), false),
+            AccountMeta::new(pda(&[b"reviewer_index", author.as_ref()]), false),
+            AccountMeta::new(pda(&[b"stake", review.as_ref()]), false),
+            AccountMeta::new_readonly(config_pda(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// The native InitializeConfig accounts
+fn initialize_config_ix(admin: &Pubkey) -> Instruction {
+    let mut data = vec![9];
+    data.extend(borsh::to_vec(&Vec::<Pubkey>::new()).unwrap());
+    Instruction::new_with_bytes(
+        restaurant_review_anchor::ID,
+        &data,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(config_pda(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+    )
+}
+
+// The native UpdateConfig accounts
+fn update_config_ix(admin: &Pubkey, min_rating: u8, max_rating: u8) -> Instruction {
+    Instruction::new_with_bytes(
+        restaurant_review_anchor::ID,
+        &[27, min_rating, max_rating],
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(config_pda(), false),
+            AccountMeta::new_readonly(pda(&[b"stats"]), false),
+        ],
+    )
+}
+
+// Anchor's entry point borrows the accounts for 'info, which
+// solana-program-test's processor! can't express, so the tests run the
+// program built by `cargo test-sbf` instead of a native processor
+async fn start() -> (BanksClient, Keypair, Hash) {
+    ProgramTest::new("restaurant_review_anchor", restaurant_review_anchor::ID, None)
+        .start()
+        .await
+}
+
+fn custom_error(error: ReviewError) -> InstructionError {
+    InstructionError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + error as u32)
+}
+
+// TEST 1: Add, update, and delete a review in the native layout
+//
+// The review lands at [author, review_seed(title)], starts with the native
+// `review\0\0` tag, and keeps the native field offsets. Only the owner can
+// update it, and deleting it returns the rent to the payer.
+#[tokio::test]
+async fn test_review_lifecycle() {
+    let (banks_client, payer, recent_blockhash) = start().await;
+    let stranger = Keypair::new();
+    let title = "Burger Joint";
+    let pda = review_pda(&payer.pubkey(), title);
+
+    // STEP 1: Add the review
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &stranger.pubkey(), 100_000_000),
+            add_review_ix(&payer.pubkey(), title, 7, "Juicy"),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // The same title in another case and spacing is the same review
+    assert_eq!(review_pda(&payer.pubkey(), "  burger   JOINT "), pda);
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), Review::LEN);
+    assert_eq!(&account.data[..8], b"review\0\0");
+    assert_eq!(account.data[Review::RATING_OFFSET], 7);
+    assert_eq!(&account.data[Review::AUTHOR_OFFSET..Review::AUTHOR_OFFSET + 32], payer.pubkey().as_ref());
+    assert_eq!(read_fixed_str(&account.data[Review::TITLE_OFFSET..]).unwrap(), title);
+
+    // STEP 2: A stranger can't update it
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&stranger.pubkey(), &payer.pubkey(), title, 1, "Awful")],
+        Some(&stranger.pubkey()),
+        &[&stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, custom_error(ReviewError::NotReviewOwner))
+    );
+
+    // STEP 3: The owner updates it
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&payer.pubkey(), &payer.pubkey(), title, 9, "Even better")],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(pda).await.unwrap().unwrap();
+    assert_eq!(account.data[Review::RATING_OFFSET], 9);
+
+    // STEP 4: Out-of-scale ratings are rejected
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_review_ix(&payer.pubkey(), &payer.pubkey(), title, 11, "Off the scale")],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, custom_error(ReviewError::InvalidRating))
+    );
+
+    // STEP 5: The owner deletes it and gets the rent back
+    let balance = banks_client.get_balance(payer.pubkey()).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[delete_review_ix(&payer.pubkey(), &payer.pubkey(), title)],
+        Some(&stranger.pubkey()),
+        &[&stranger, &payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    assert!(banks_client.get_account(pda).await.unwrap().is_none());
+    assert_eq!(
+        banks_client.get_balance(payer.pubkey()).await.unwrap(),
+        balance + account.lamports
+    );
+}
+
+// TEST 2: The rating scale comes from the config PDA
+//
+// Until the config exists reviews use the default 1-10 scale. Once the admin
+// creates it and narrows the scale, ratings outside it are rejected, and only
+// the admin can change it.
+#[tokio::test]
+async fn test_configured_rating_scale() {
+    let (banks_client, payer, recent_blockhash) = start().await;
+    let stranger = Keypair::new();
+
+    // STEP 1: Without a config, 10 is on the scale
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&payer.pubkey(), &stranger.pubkey(), 100_000_000),
+            add_review_ix(&payer.pubkey(), "Taco Stand", 10, "Perfect"),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // STEP 2: The payer creates the config in the native layout and narrows the scale to 1-5
+    let transaction = Transaction::new_signed_with_payer(
+        &[initialize_config_ix(&payer.pubkey()), update_config_ix(&payer.pubkey(), 1, 5)],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = banks_client.get_account(config_pda()).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), ProgramConfig::SIZE);
+    assert_eq!(&account.data[..8], b"config\0\0");
+    // Native offsets: tag (8) + is_initialized (1) + admin (32) + strict_registry (1) + update_cooldown (4)
+    assert_eq!(&account.data[46..48], &[1, 5]);
+
+    // STEP 3: A rating of 6 is now off the scale
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&payer.pubkey(), "Burger Joint", 6, "Good")],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, custom_error(ReviewError::InvalidRating))
+    );
+
+    // STEP 4: Only the admin can change the scale
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_config_ix(&stranger.pubkey(), 1, 10)],
+        Some(&stranger.pubkey()),
+        &[&stranger],
+        recent_blockhash,
+    );
+    let result = banks_client.process_transaction(transaction).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, custom_error(ReviewError::NotAdmin))
+    );
+
+    // STEP 5: A 5 is still accepted
+    let transaction = Transaction::new_signed_with_payer(
+        &[add_review_ix(&payer.pubkey(), "Burger Joint", 5, "Good")],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+}
+
+// The instruction data Anchor's generated client builds must be the native
+// encoding, so either client can drive either program
+#[test]
+fn test_native_encoding() {
+    let data = restaurant_review_anchor::instruction::DeleteReview {
+        _title: "Burger Joint".to_string(),
+    }
+    .data();
+    let mut native = vec![2];
+    native.extend(borsh::to_vec(&"Burger Joint".to_string()).unwrap());
+    assert_eq!(data, native);
+}
@@ -1,14 +1,26 @@
 import * as borsh from "@project-serum/borsh";
+import BN from "bn.js";
+
+// Capacities of the fixed-size string fields in a review account
+const MAX_DESCRIPTION_LEN = 500;
+const MAX_TITLE_LEN = 50;
+// Offset of the description in the native AccountState layout (version 4),
+// tag included; the title follows it at 894
+const DESCRIPTION_OFFSET = 390;
 
 export class Review {
     title: string;
     rating: number;
     description: string;
+    visitedAt: number; // Unix timestamp of the visit, defaults to now
+    priceRange: number; // 1 ($) to 4 ($$$$)
 
-    constructor(title: string, rating: number, description: string) {
+    constructor(title: string, rating: number, description: string, visitedAt?: number, priceRange = 2) {
         this.title = title;
         this.rating = rating;
         this.description = description;
+        this.visitedAt = visitedAt ?? Math.floor(Date.now() / 1000);
+        this.priceRange = priceRange;
     }
 
     borshInstructionSchema = borsh.struct([
@@ -16,18 +28,53 @@ export class Review {
         borsh.str("title"),
         borsh.u8("rating"),
         borsh.str("description"),
+        borsh.vec(borsh.str(), "tags"),
+        borsh.str("content_uri"),
+        borsh.option(
+            borsh.struct([borsh.u8("food"), borsh.u8("service"), borsh.u8("ambiance")]),
+            "sub_ratings"
+        ),
+        borsh.i64("visited_at"),
+        borsh.u8("price_range"),
     ]);
 
+    // Tag at the start of every review account, and the size of one
+    static DISCRIMINATOR = Buffer.from("review\0\0");
+    static ACCOUNT_LEN = 964;
+
+    // The native fixed layout: strings are a u32 length followed by a buffer
+    // of their maximum capacity, so every field sits at the same offset
     static borshAccountSchema = borsh.struct([
+        borsh.blob(8, "discriminator"),
         borsh.bool("initialized"),
+        borsh.u8("version"),
         borsh.u8("rating"),
-        borsh.str("description"),
-        borsh.str("title"),
+        // tip_total through content_uri, which the frontend doesn't show
+        borsh.blob(DESCRIPTION_OFFSET - 11, "unused"),
+        borsh.u32("description_len"),
+        borsh.blob(MAX_DESCRIPTION_LEN, "description"),
+        borsh.u32("title_len"),
+        borsh.blob(MAX_TITLE_LEN, "title"),
+        borsh.blob(3, "sub_ratings"),
+        borsh.i64("visited_at"),
+        borsh.u8("price_range"),
+        borsh.u32("sequence"),
     ]);
 
     serialize(): Buffer {
         const buffer = Buffer.alloc(1000);
-        this.borshInstructionSchema.encode({ ...this, variant: 0 }, buffer);
+        this.borshInstructionSchema.encode(
+            {
+                ...this,
+                variant: 0,
+                tags: [],
+                content_uri: "",
+                sub_ratings: null,
+                visited_at: new BN(this.visitedAt),
+                price_range: this.priceRange,
+            },
+            buffer
+        );
         return buffer.slice(0, this.borshInstructionSchema.getSpan(buffer));
     }
 
@@ -36,10 +83,30 @@ export class Review {
             return null;
         }
 
+        // Other account types and older layouts aren't reviews to show
+        if (
+            buffer.length !== this.ACCOUNT_LEN ||
+            !buffer.subarray(0, 8).equals(this.DISCRIMINATOR)
+        ) {
+            return null;
+        }
+
         try {
-            const { title, rating, description } =
-                this.borshAccountSchema.decode(buffer);
-            return new Review(title, rating, description);
+            const account = this.borshAccountSchema.decode(buffer);
+            if (!account.initialized) {
+                return null;
+            }
+            const title = account.title.subarray(0, account.title_len).toString("utf8");
+            const description = account.description
+                .subarray(0, account.description_len)
+                .toString("utf8");
+            return new Review(
+                title,
+                account.rating,
+                description,
+                account.visited_at.toNumber(),
+                account.price_range
+            );
         } catch (e) {
             console.log("Deserialization error:", e);
             console.log(buffer);
@@ -60,10 +60,16 @@ export default function Home() {
             console.log("Serialized buffer length:", buffer.length);
             console.log("Serialized buffer:", buffer);
 
-            const [pda] = await web3.PublicKey.findProgramAddressSync(
-                [publicKey.toBuffer(), Buffer.from(review.title)],
-                new web3.PublicKey(REVIEW_PROGRAM_ID)
+            // The program seeds the PDA with the SHA-256 hash of the normalized
+            // title (whitespace collapsed, lowercased), since titles can be
+            // longer than the 32-byte seed limit
+            const normalizedTitle = review.title.split(/\s+/).filter(Boolean).join(" ").toLowerCase();
+            const titleSeed = Buffer.from(
+                await crypto.subtle.digest("SHA-256", Buffer.from(normalizedTitle))
             );
+            const programId = new web3.PublicKey(REVIEW_PROGRAM_ID);
+            const findPda = (seeds: Buffer[]) => web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+            const pda = findPda([publicKey.toBuffer(), titleSeed]);
             console.log("PDA address:", pda.toBase58());
 
             // Check wallet balance
@@ -75,26 +81,32 @@ export default function Home() {
                 return;
             }
 
+            // AddReview reads its accounts in this order; the aggregate and
+            // registry entry are seeded like the review, by the title hash
+            const account = (pubkey: web3.PublicKey, isWritable: boolean) => ({
+                pubkey,
+                isSigner: false,
+                isWritable,
+            });
             const instruction = new web3.TransactionInstruction({
                 keys: [
-                    {
-                        pubkey: publicKey,
-                        isSigner: true,
-                        isWritable: false,
-                    },
-                    {
-                        pubkey: pda,
-                        isSigner: false,
-                        isWritable: true,
-                    },
-                    {
-                        pubkey: web3.SystemProgram.programId,
-                        isSigner: false,
-                        isWritable: false,
-                    },
+                    { pubkey: publicKey, isSigner: true, isWritable: true }, // Reviewer, pays the rent
+                    account(pda, true), // Review PDA
+                    account(web3.SystemProgram.programId, false),
+                    account(findPda([pda.toBuffer(), Buffer.from("comment")]), true), // Comment counter
+                    account(findPda([Buffer.from("stats")]), true), // Program stats
+                    account(findPda([Buffer.from("reviewer"), publicKey.toBuffer()]), true), // Reviewer record
+                    account(findPda([Buffer.from("aggregate"), titleSeed]), true), // Restaurant aggregate
+                    account(web3.SYSVAR_CLOCK_PUBKEY, false),
+                    account(findPda([Buffer.from("config")]), false), // Program config
+                    account(findPda([Buffer.from("restaurant"), titleSeed]), false), // Restaurant registry entry
+                    account(findPda([Buffer.from("reviewer_index"), publicKey.toBuffer()]), true), // Reviewer index
+                    account(findPda([Buffer.from("stake"), pda.toBuffer()]), true), // Stake escrow
+                    account(findPda([Buffer.from("treasury")]), true), // Program treasury
+                    account(web3.SYSVAR_RENT_PUBKEY, false),
                 ],
                 data: buffer,
-                programId: new web3.PublicKey(REVIEW_PROGRAM_ID),
+                programId,
             });
 
             console.log("Instruction created:", {
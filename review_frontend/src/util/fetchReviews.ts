@@ -10,8 +10,8 @@ export const fetchReviews = async (
     const accounts = await connection.getProgramAccounts(
         new web3.PublicKey(REVIEW_PROGRAM_ID),
         {
-            dataSlice: { offset: 2, length: 18 },
-            filters: [],
+            dataSlice: { offset: 0, length: 0 },
+            filters: [{ dataSize: Review.ACCOUNT_LEN }],
         }
     );
     account_list = accounts.map((account) => account.pubkey);